@@ -571,10 +571,13 @@ pub extern "C" fn resvg_parse_tree_from_data(
     resvg_error::OK as i32
 }
 
-/// @brief Checks that tree has any nodes.
+/// @brief Checks that tree has nothing to render.
+///
+/// Invisible or fully transparent elements, elements without a fill and a stroke
+/// and elements with a zero-sized bounding box are ignored.
 ///
 /// @param tree Render tree.
-/// @return Returns `true` if tree has no nodes.
+/// @return Returns `true` if tree has nothing to render.
 #[no_mangle]
 pub extern "C" fn resvg_is_image_empty(tree: *const resvg_render_tree) -> bool {
     let tree = unsafe {
@@ -582,7 +585,7 @@ pub extern "C" fn resvg_is_image_empty(tree: *const resvg_render_tree) -> bool {
         &*tree
     };
 
-    !tree.0.root().has_children()
+    tree.0.is_empty()
 }
 
 /// @brief Returns an image size.
@@ -868,6 +871,9 @@ fn convert_error(e: usvg::Error) -> resvg_error {
 /// @param height Pixmap height.
 /// @param pixmap Pixmap data. Should have width*height*4 size and contain
 ///               premultiplied RGBA8888 pixels.
+///               Pixels are stored row by row without padding,
+///               as R, G, B and A bytes, regardless of the platform endianness.
+///               Use #resvg_demultiply_alpha to get straight alpha.
 #[no_mangle]
 pub extern "C" fn resvg_render(
     tree: *const resvg_render_tree,
@@ -886,7 +892,7 @@ pub extern "C" fn resvg_render(
         unsafe { std::slice::from_raw_parts_mut(pixmap as *mut u8, pixmap_len) };
     let mut pixmap = tiny_skia::PixmapMut::from_bytes(pixmap, width, height).unwrap();
 
-    resvg::render(&tree.0, transform.to_tiny_skia(), &mut pixmap)
+    resvg::render(&tree.0, transform.to_tiny_skia(), &mut pixmap);
 }
 
 /// @brief Renders a Node by ID onto the image.
@@ -938,6 +944,33 @@ pub extern "C" fn resvg_render_node(
     }
 }
 
+/// @brief Converts premultiplied RGBA8888 pixels into straight alpha RGBA8888 in place.
+///
+/// Pixmaps rendered by #resvg_render and #resvg_render_node are premultiplied,
+/// while most image formats and libraries expect straight alpha.
+///
+/// @param width Pixmap width.
+/// @param height Pixmap height.
+/// @param pixmap Pixmap data. Should have width*height*4 size.
+#[no_mangle]
+pub extern "C" fn resvg_demultiply_alpha(width: u32, height: u32, pixmap: *mut c_char) {
+    assert!(!pixmap.is_null());
+
+    let pixmap_len = width as usize * height as usize * tiny_skia::BYTES_PER_PIXEL;
+    let pixmap: &mut [u8] =
+        unsafe { std::slice::from_raw_parts_mut(pixmap as *mut u8, pixmap_len) };
+
+    for pixel in pixmap.chunks_exact_mut(tiny_skia::BYTES_PER_PIXEL) {
+        // Invalid premultiplied colors are left as is.
+        if let Some(c) =
+            tiny_skia::PremultipliedColorU8::from_rgba(pixel[0], pixel[1], pixel[2], pixel[3])
+        {
+            let c = c.demultiply();
+            pixel.copy_from_slice(&[c.red(), c.green(), c.blue(), c.alpha()]);
+        }
+    }
+}
+
 /// A simple stderr logger.
 static LOGGER: SimpleLogger = SimpleLogger;
 struct SimpleLogger;
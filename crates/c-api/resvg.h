@@ -371,10 +371,13 @@ int32_t resvg_parse_tree_from_data(const char *data,
                                    resvg_render_tree **tree);
 
 /**
- * @brief Checks that tree has any nodes.
+ * @brief Checks that tree has nothing to render.
+ *
+ * Invisible or fully transparent elements, elements without a fill and a stroke
+ * and elements with a zero-sized bounding box are ignored.
  *
  * @param tree Render tree.
- * @return Returns `true` if tree has no nodes.
+ * @return Returns `true` if tree has nothing to render.
  */
 bool resvg_is_image_empty(const resvg_render_tree *tree);
 
@@ -481,6 +484,9 @@ void resvg_tree_destroy(resvg_render_tree *tree);
  * @param height Pixmap height.
  * @param pixmap Pixmap data. Should have width*height*4 size and contain
  *               premultiplied RGBA8888 pixels.
+ *               Pixels are stored row by row without padding,
+ *               as R, G, B and A bytes, regardless of the platform endianness.
+ *               Use #resvg_demultiply_alpha to get straight alpha.
  */
 void resvg_render(const resvg_render_tree *tree,
                   resvg_transform transform,
@@ -509,6 +515,18 @@ bool resvg_render_node(const resvg_render_tree *tree,
                        uint32_t height,
                        char *pixmap);
 
+/**
+ * @brief Converts premultiplied RGBA8888 pixels into straight alpha RGBA8888 in place.
+ *
+ * Pixmaps rendered by #resvg_render and #resvg_render_node are premultiplied,
+ * while most image formats and libraries expect straight alpha.
+ *
+ * @param width Pixmap width.
+ * @param height Pixmap height.
+ * @param pixmap Pixmap data. Should have width*height*4 size.
+ */
+void resvg_demultiply_alpha(uint32_t width, uint32_t height, char *pixmap);
+
 #ifdef __cplusplus
 } // extern "C"
 #endif // __cplusplus
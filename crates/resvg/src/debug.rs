@@ -0,0 +1,98 @@
+// Copyright 2024 the Resvg Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+/*!
+Rendering diagnostics.
+
+Available only with the `debug-hooks` build feature.
+*/
+
+use std::cell::RefCell;
+
+/// A kind of a [`Layer`].
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum LayerKind {
+    /// A `clipPath` element.
+    ClipPath,
+    /// A `mask` element.
+    Mask,
+}
+
+/// An intermediate clip path or mask layer.
+#[derive(Debug)]
+pub struct Layer<'a> {
+    /// Layer kind.
+    pub kind: LayerKind,
+    /// ID of the `clipPath` or `mask` element.
+    pub id: &'a str,
+    /// ID of the node the layer is applied to.
+    ///
+    /// Can be empty, since IDs are not generated.
+    pub node_id: &'a str,
+    /// The layer's alpha, which is multiplied with the node's content.
+    ///
+    /// Has the same size as the node's layer, which covers its
+    /// [`usvg::Group::abs_layer_bounding_box`] clipped to the canvas.
+    pub mask: &'a tiny_skia::Mask,
+}
+
+type Hook = Box<dyn FnMut(&Layer)>;
+
+thread_local! {
+    static HOOK: RefCell<Option<Hook>> = RefCell::new(None);
+}
+
+/// Calls `hook` with each clip path and mask layer generated while `f` is running.
+///
+/// Applies only to rendering on the current thread.
+/// The previous hook is restored afterwards, even when `f` panics.
+/// Layers are reported in the rendering order, and a layer of a nested clip path or mask
+/// is reported before the one that references it.
+///
+/// # Example
+///
+/// ```
+/// # let svg = "<svg width='100' height='100' xmlns='http://www.w3.org/2000/svg'/>";
+/// # use resvg::usvg;
+/// let tree = usvg::Tree::from_str(svg, &usvg::Options::default()).unwrap();
+/// let mut pixmap = resvg::tiny_skia::Pixmap::new(100, 100).unwrap();
+/// resvg::debug::with_layer_hook(
+///     |layer| {
+///         let path = format!("{}-{}.png", layer.node_id, layer.id);
+///         layer.mask.save_png(path).unwrap();
+///     },
+///     || resvg::render(&tree, usvg::Transform::default(), &mut pixmap.as_mut()),
+/// );
+/// ```
+pub fn with_layer_hook<R>(hook: impl FnMut(&Layer) + 'static, f: impl FnOnce() -> R) -> R {
+    let prev = HOOK.with(|h| h.borrow_mut().replace(Box::new(hook)));
+    let _guard = RestoreHook(prev);
+    f()
+}
+
+/// Restores a hook on drop, including during unwinding.
+struct RestoreHook(Option<Hook>);
+
+impl Drop for RestoreHook {
+    fn drop(&mut self) {
+        let prev = self.0.take();
+        let _ = HOOK.try_with(|h| *h.borrow_mut() = prev);
+    }
+}
+
+pub(crate) fn report(kind: LayerKind, id: &str, node_id: &str, mask: &tiny_skia::Mask) {
+    // The hook is taken out for the duration of the call,
+    // so rendering from inside of it wouldn't report anything.
+    let Some(mut hook) = HOOK.with(|h| h.borrow_mut().take()) else {
+        return;
+    };
+
+    hook(&Layer {
+        kind,
+        id,
+        node_id,
+        mask,
+    });
+
+    HOOK.with(|h| *h.borrow_mut() = Some(hook));
+}
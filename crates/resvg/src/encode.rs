@@ -0,0 +1,203 @@
+// Copyright 2024 the Resvg Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+/// PNG encoding options.
+#[derive(Clone, Default, Debug)]
+pub struct PngOptions {
+    /// Encodes 16 bits per channel instead of 8.
+    ///
+    /// Colors are demultiplied into 16 bits, which preserves the precision
+    /// of semi-transparent pixels, like soft shadows and faded gradients.
+    /// Opaque pixels are simply widened.
+    ///
+    /// Note that rendering is still done in 8 bits per channel, since this is the only format
+    /// supported by `tiny-skia`. So this option doesn't reduce banding of opaque gradients.
+    ///
+    /// The encoding is slower and the resulting data is roughly twice as large.
+    ///
+    /// Default: `false`
+    pub high_bit_depth: bool,
+
+    /// A color profile to tag the image with.
+    ///
+    /// Default: `PngColorProfile::None`
+    pub color_profile: PngColorProfile,
+}
+
+/// A PNG color profile.
+///
+/// `resvg` doesn't perform any color management. All colors are treated as sRGB,
+/// gradients are interpolated in sRGB, unless `color-interpolation` says otherwise,
+/// and filters operate in linearRGB by default, as required by `color-interpolation-filters`.
+/// The produced pixels are always sRGB values, so the profile only tells viewers
+/// how to interpret them.
+#[derive(Clone, PartialEq, Default, Debug)]
+pub enum PngColorProfile {
+    /// No color profile. Viewers usually assume sRGB.
+    #[default]
+    None,
+    /// An `sRGB` chunk with a perceptual rendering intent,
+    /// along with the fallback `gAMA` and `cHRM` chunks.
+    SRGB,
+    /// An `iCCP` chunk with the provided ICC profile data.
+    ///
+    /// Useful when SVG colors are known to be in a different color space, like Display P3.
+    ICC(Vec<u8>),
+}
+
+/// A PNG encoding error.
+#[derive(Debug)]
+pub enum PngError {
+    /// Failed to write the data.
+    Io(std::io::Error),
+    /// The image cannot be stored as a PNG.
+    Format(String),
+}
+
+impl PngError {
+    fn from_png(e: png::EncodingError) -> Self {
+        match e {
+            png::EncodingError::IoError(e) => PngError::Io(e),
+            e => PngError::Format(e.to_string()),
+        }
+    }
+}
+
+impl std::fmt::Display for PngError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            PngError::Io(ref e) => write!(f, "PNG writing failed cause {}", e),
+            PngError::Format(ref e) => write!(f, "PNG encoding failed cause {}", e),
+        }
+    }
+}
+
+impl std::error::Error for PngError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match *self {
+            PngError::Io(ref e) => Some(e),
+            PngError::Format(_) => None,
+        }
+    }
+}
+
+/// Encodes a pixmap into a PNG.
+///
+/// Unlike [`tiny_skia::Pixmap::encode_png`], allows to set a bit depth and a color profile.
+/// With default options, produces the same image.
+///
+/// The whole PNG is stored in memory. Use [`write_png`] to avoid this.
+pub fn encode_png(pixmap: tiny_skia::PixmapRef, opt: &PngOptions) -> Result<Vec<u8>, PngError> {
+    let mut buf = Vec::new();
+    write_png(pixmap, opt, &mut buf)?;
+    Ok(buf)
+}
+
+/// Writes a pixmap as a PNG into the writer.
+///
+/// Same as [`encode_png`], but the image is demultiplied, compressed and written
+/// row by row, so no additional image-sized buffers are allocated.
+/// Useful for very large images.
+///
+/// The writer is not buffered, so wrapping a file into [`std::io::BufWriter`] is recommended.
+/// In this case, pass it by reference and flush it afterwards to catch write errors.
+pub fn write_png<W: std::io::Write>(
+    pixmap: tiny_skia::PixmapRef,
+    opt: &PngOptions,
+    w: W,
+) -> Result<(), PngError> {
+    write_png_impl(pixmap, opt, w).map_err(PngError::from_png)
+}
+
+fn write_png_impl<W: std::io::Write>(
+    pixmap: tiny_skia::PixmapRef,
+    opt: &PngOptions,
+    w: W,
+) -> Result<(), png::EncodingError> {
+    use std::io::Write;
+
+    let mut encoder = png::Encoder::new(w, pixmap.width(), pixmap.height());
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(if opt.high_bit_depth {
+        png::BitDepth::Sixteen
+    } else {
+        png::BitDepth::Eight
+    });
+
+    if opt.color_profile == PngColorProfile::SRGB {
+        encoder.set_source_srgb(png::SrgbRenderingIntent::Perceptual);
+        // The fallback values from https://www.w3.org/TR/png-3/#sRGB-gAMA-cHRM
+        encoder.set_source_gamma(png::ScaledFloat::from_scaled(45455));
+        encoder.set_source_chromaticities(png::SourceChromaticities {
+            white: (
+                png::ScaledFloat::from_scaled(31270),
+                png::ScaledFloat::from_scaled(32900),
+            ),
+            red: (
+                png::ScaledFloat::from_scaled(64000),
+                png::ScaledFloat::from_scaled(33000),
+            ),
+            green: (
+                png::ScaledFloat::from_scaled(30000),
+                png::ScaledFloat::from_scaled(60000),
+            ),
+            blue: (
+                png::ScaledFloat::from_scaled(15000),
+                png::ScaledFloat::from_scaled(6000),
+            ),
+        });
+    }
+
+    let mut writer = encoder.write_header()?;
+    if let PngColorProfile::ICC(ref icc) = opt.color_profile {
+        writer.write_chunk(png::chunk::iCCP, &iccp_chunk_data(icc))?;
+    }
+
+    let mut stream = writer.stream_writer()?;
+    let mut row = Vec::new();
+    for pixels in pixmap.pixels().chunks(pixmap.width() as usize) {
+        row.clear();
+        if opt.high_bit_depth {
+            demultiply16(pixels, &mut row);
+        } else {
+            demultiply8(pixels, &mut row);
+        }
+
+        stream.write_all(&row)?;
+    }
+
+    stream.finish()?;
+    writer.finish()
+}
+
+fn demultiply8(pixels: &[tiny_skia::PremultipliedColorU8], data: &mut Vec<u8>) {
+    for pixel in pixels {
+        let c = pixel.demultiply();
+        data.extend_from_slice(&[c.red(), c.green(), c.blue(), c.alpha()]);
+    }
+}
+
+fn demultiply16(pixels: &[tiny_skia::PremultipliedColorU8], data: &mut Vec<u8>) {
+    for pixel in pixels {
+        let a = pixel.alpha() as u32;
+        // Premultiplied channels are never larger than alpha, so the result fits into `u16`.
+        let demultiply = |c: u8| (c as u32 * 65535 + a / 2).checked_div(a).unwrap_or(0) as u16;
+
+        let rgba = [
+            demultiply(pixel.red()),
+            demultiply(pixel.green()),
+            demultiply(pixel.blue()),
+            (a * 257) as u16,
+        ];
+        for c in rgba {
+            data.extend_from_slice(&c.to_be_bytes());
+        }
+    }
+}
+
+// A profile name, a null separator, the zlib compression method and the compressed profile.
+fn iccp_chunk_data(icc: &[u8]) -> Vec<u8> {
+    let mut data = b"ICC profile\0\0".to_vec();
+    data.extend_from_slice(&miniz_oxide::deflate::compress_to_vec_zlib(icc, 6));
+    data
+}
@@ -145,71 +145,20 @@ fn demultiply_alpha(data: &mut [RGBA8]) {
     }
 }
 
-/// Precomputed sRGB to LinearRGB table.
+/// Builds a table that maps each 8-bit channel value using `f`.
 ///
-/// Since we are storing the result in `u8`, there is no need to compute those
-/// values each time. Mainly because it's very expensive.
-///
-/// ```text
-/// if (C_srgb <= 0.04045)
-///     C_lin = C_srgb / 12.92;
-///  else
-///     C_lin = pow((C_srgb + 0.055) / 1.055, 2.4);
-/// ```
+/// Since we are storing the result in `u8`, there is no need to convert
+/// each pixel separately. Mainly because it's very expensive.
 ///
 /// Thanks to librsvg for the idea.
-#[rustfmt::skip]
-const SRGB_TO_LINEAR_RGB_TABLE: &[u8; 256] = &[
-    0,   0,   0,   0,   0,   0,  0,    1,   1,   1,   1,   1,   1,   1,   1,   1,
-    1,   1,   2,   2,   2,   2,  2,    2,   2,   2,   3,   3,   3,   3,   3,   3,
-    4,   4,   4,   4,   4,   5,  5,    5,   5,   6,   6,   6,   6,   7,   7,   7,
-    8,   8,   8,   8,   9,   9,  9,   10,  10,  10,  11,  11,  12,  12,  12,  13,
-    13,  13,  14,  14,  15,  15,  16,  16,  17,  17,  17,  18,  18,  19,  19,  20,
-    20,  21,  22,  22,  23,  23,  24,  24,  25,  25,  26,  27,  27,  28,  29,  29,
-    30,  30,  31,  32,  32,  33,  34,  35,  35,  36,  37,  37,  38,  39,  40,  41,
-    41,  42,  43,  44,  45,  45,  46,  47,  48,  49,  50,  51,  51,  52,  53,  54,
-    55,  56,  57,  58,  59,  60,  61,  62,  63,  64,  65,  66,  67,  68,  69,  70,
-    71,  72,  73,  74,  76,  77,  78,  79,  80,  81,  82,  84,  85,  86,  87,  88,
-    90,  91,  92,  93,  95,  96,  97,  99, 100, 101, 103, 104, 105, 107, 108, 109,
-    111, 112, 114, 115, 116, 118, 119, 121, 122, 124, 125, 127, 128, 130, 131, 133,
-    134, 136, 138, 139, 141, 142, 144, 146, 147, 149, 151, 152, 154, 156, 157, 159,
-    161, 163, 164, 166, 168, 170, 171, 173, 175, 177, 179, 181, 183, 184, 186, 188,
-    190, 192, 194, 196, 198, 200, 202, 204, 206, 208, 210, 212, 214, 216, 218, 220,
-    222, 224, 226, 229, 231, 233, 235, 237, 239, 242, 244, 246, 248, 250, 253, 255,
-];
-
-/// Precomputed LinearRGB to sRGB table.
-///
-/// Since we are storing the result in `u8`, there is no need to compute those
-/// values each time. Mainly because it's very expensive.
-///
-/// ```text
-/// if (C_lin <= 0.0031308)
-///     C_srgb = C_lin * 12.92;
-/// else
-///     C_srgb = 1.055 * pow(C_lin, 1.0 / 2.4) - 0.055;
-/// ```
-///
-/// Thanks to librsvg for the idea.
-#[rustfmt::skip]
-const LINEAR_RGB_TO_SRGB_TABLE: &[u8; 256] = &[
-    0,  13,  22,  28,  34,  38,  42,  46,  50,  53,  56,  59,  61,  64,  66,  69,
-    71,  73,  75,  77,  79,  81,  83,  85,  86,  88,  90,  92,  93,  95,  96,  98,
-    99, 101, 102, 104, 105, 106, 108, 109, 110, 112, 113, 114, 115, 117, 118, 119,
-    120, 121, 122, 124, 125, 126, 127, 128, 129, 130, 131, 132, 133, 134, 135, 136,
-    137, 138, 139, 140, 141, 142, 143, 144, 145, 146, 147, 148, 148, 149, 150, 151,
-    152, 153, 154, 155, 155, 156, 157, 158, 159, 159, 160, 161, 162, 163, 163, 164,
-    165, 166, 167, 167, 168, 169, 170, 170, 171, 172, 173, 173, 174, 175, 175, 176,
-    177, 178, 178, 179, 180, 180, 181, 182, 182, 183, 184, 185, 185, 186, 187, 187,
-    188, 189, 189, 190, 190, 191, 192, 192, 193, 194, 194, 195, 196, 196, 197, 197,
-    198, 199, 199, 200, 200, 201, 202, 202, 203, 203, 204, 205, 205, 206, 206, 207,
-    208, 208, 209, 209, 210, 210, 211, 212, 212, 213, 213, 214, 214, 215, 215, 216,
-    216, 217, 218, 218, 219, 219, 220, 220, 221, 221, 222, 222, 223, 223, 224, 224,
-    225, 226, 226, 227, 227, 228, 228, 229, 229, 230, 230, 231, 231, 232, 232, 233,
-    233, 234, 234, 235, 235, 236, 236, 237, 237, 238, 238, 238, 239, 239, 240, 240,
-    241, 241, 242, 242, 243, 243, 244, 244, 245, 245, 246, 246, 246, 247, 247, 248,
-    248, 249, 249, 250, 250, 251, 251, 251, 252, 252, 253, 253, 254, 254, 255, 255,
-];
+fn channel_table(f: impl Fn(u8) -> u8) -> [u8; 256] {
+    let mut table = [0; 256];
+    for (i, v) in table.iter_mut().enumerate() {
+        *v = f(i as u8);
+    }
+
+    table
+}
 
 /// Converts input pixel from sRGB into LinearRGB.
 ///
@@ -217,10 +166,15 @@ const LINEAR_RGB_TO_SRGB_TABLE: &[u8; 256] = &[
 ///
 /// RGB channels order of the input image doesn't matter, but alpha channel must be the last one.
 fn into_linear_rgb(data: &mut [RGBA8]) {
+    let table = channel_table(|c| {
+        let c = usvg::Color::new_rgb(c, 0, 0).to_linear_rgb()[0];
+        (c * 255.0).round() as u8
+    });
+
     for p in data {
-        p.r = SRGB_TO_LINEAR_RGB_TABLE[p.r as usize];
-        p.g = SRGB_TO_LINEAR_RGB_TABLE[p.g as usize];
-        p.b = SRGB_TO_LINEAR_RGB_TABLE[p.b as usize];
+        p.r = table[p.r as usize];
+        p.g = table[p.g as usize];
+        p.b = table[p.b as usize];
     }
 }
 
@@ -230,10 +184,12 @@ fn into_linear_rgb(data: &mut [RGBA8]) {
 ///
 /// RGB channels order of the input image doesn't matter, but alpha channel must be the last one.
 fn from_linear_rgb(data: &mut [RGBA8]) {
+    let table = channel_table(|c| usvg::Color::from_linear_rgb([c as f32 / 255.0, 0.0, 0.0]).red);
+
     for p in data {
-        p.r = LINEAR_RGB_TO_SRGB_TABLE[p.r as usize];
-        p.g = LINEAR_RGB_TO_SRGB_TABLE[p.g as usize];
-        p.b = LINEAR_RGB_TO_SRGB_TABLE[p.b as usize];
+        p.r = table[p.r as usize];
+        p.g = table[p.g as usize];
+        p.b = table[p.b as usize];
     }
 }
 
@@ -332,12 +288,62 @@ struct FilterResult {
     image: Image,
 }
 
+/// Returns the maximum distance in pixels a filter can move pixels by.
+///
+/// Returns `None` when the result depends on the whole filter region, like for `feTile`
+/// or `feConvolveMatrix` with `edgeMode="wrap"`.
+pub fn margin(filter: &usvg::filter::Filter, ts: tiny_skia::Transform) -> Option<f32> {
+    use usvg::filter::Kind;
+
+    let (sx, sy) = ts.get_scale();
+    let scale = sx.max(sy);
+    let max_abs = |x: f32, y: f32| x.abs().max(y.abs());
+
+    let mut margin = 0.0;
+    for primitive in filter.primitives() {
+        margin += match primitive.kind() {
+            // A blur is practically invisible after three standard deviations.
+            Kind::GaussianBlur(ref fe) => {
+                3.0 * max_abs(fe.std_dev_x().get(), fe.std_dev_y().get()) * scale
+            }
+            Kind::DropShadow(ref fe) => {
+                let blur = 3.0 * max_abs(fe.std_dev_x().get(), fe.std_dev_y().get());
+                (max_abs(fe.dx(), fe.dy()) + blur) * scale
+            }
+            Kind::Offset(ref fe) => max_abs(fe.dx(), fe.dy()) * scale,
+            Kind::Morphology(ref fe) => max_abs(fe.radius_x().get(), fe.radius_y().get()) * scale,
+            Kind::DisplacementMap(ref fe) => fe.scale().abs() * 0.5 * scale,
+            // Wraps around the whole filter region.
+            Kind::ConvolveMatrix(ref fe) if fe.edge_mode() == usvg::filter::EdgeMode::Wrap => {
+                return None
+            }
+            // Kernels are applied in pixels.
+            Kind::ConvolveMatrix(ref fe) => fe.matrix().columns().max(fe.matrix().rows()) as f32,
+            // Surface normals are calculated using neighbor pixels.
+            Kind::DiffuseLighting(_) | Kind::SpecularLighting(_) => 1.0,
+            Kind::Tile(_) => return None,
+            Kind::Turbulence(ref fe) if fe.stitch_tiles() => return None,
+            Kind::Blend(_)
+            | Kind::ColorMatrix(_)
+            | Kind::ComponentTransfer(_)
+            | Kind::Composite(_)
+            | Kind::Flood(_)
+            | Kind::Image(_)
+            | Kind::Merge(_)
+            | Kind::Turbulence(_) => 0.0,
+        };
+    }
+
+    Some(margin)
+}
+
 pub fn apply(
     filter: &usvg::filter::Filter,
+    ctx: &crate::render::Context,
     ts: tiny_skia::Transform,
     source: &mut tiny_skia::Pixmap,
 ) {
-    let result = apply_inner(filter, ts, source);
+    let result = apply_inner(filter, ctx, ts, source);
     let result = result.and_then(|image| apply_to_canvas(image, source));
 
     // Clear on error.
@@ -356,6 +362,7 @@ pub fn apply(
 
 fn apply_inner(
     filter: &usvg::filter::Filter,
+    ctx: &crate::render::Context,
     ts: usvg::Transform,
     source: &mut tiny_skia::Pixmap,
 ) -> Result<Image, Error> {
@@ -414,7 +421,7 @@ fn apply_inner(
                 let input = get_input(fe.input(), region, source, &results)?;
                 apply_tile(input, region)
             }
-            usvg::filter::Kind::Image(ref fe) => apply_image(fe, region, subregion, ts),
+            usvg::filter::Kind::Image(ref fe) => apply_image(fe, ctx, region, subregion, ts),
             usvg::filter::Kind::ComponentTransfer(ref fe) => {
                 let input = get_input(fe.input(), region, source, &results)?;
                 apply_component_transfer(fe, cs, input)
@@ -853,6 +860,7 @@ fn apply_tile(input: Image, region: IntRect) -> Result<Image, Error> {
 
 fn apply_image(
     fe: &usvg::filter::Image,
+    ctx: &crate::render::Context,
     region: IntRect,
     subregion: IntRect,
     ts: usvg::Transform,
@@ -869,8 +877,14 @@ fn apply_image(
         subregion.y() as f32,
     );
 
+    let max_bbox = tiny_skia::IntRect::from_xywh(0, 0, region.width(), region.height()).unwrap();
     let ctx = crate::render::Context {
-        max_bbox: tiny_skia::IntRect::from_xywh(0, 0, region.width(), region.height()).unwrap(),
+        max_bbox,
+        max_filter_bbox: max_bbox,
+        curve_tolerance: ctx.curve_tolerance,
+        linear_compositing: ctx.linear_compositing,
+        anti_alias: ctx.anti_alias,
+        supersampling: ctx.supersampling,
     };
 
     crate::render::render_nodes(fe.root(), &ctx, transform, &mut pixmap.as_mut());
@@ -956,15 +970,17 @@ fn apply_displacement_map(
     input1: Image,
     input2: Image,
 ) -> Result<Image, Error> {
-    let pixmap1 = input1.into_color_space(cs)?.take()?;
-    let pixmap2 = input2.into_color_space(cs)?.take()?;
+    // `color-interpolation-filters` applies only to the displacement map.
+    // The displaced image must remain in its current color space.
+    let cs1 = input1.color_space;
+    let pixmap1 = input1.take()?;
+    let mut pixmap2 = input2.into_color_space(cs)?.take()?;
+    demultiply_alpha(pixmap2.data_mut().as_rgba_mut());
 
     let mut pixmap = tiny_skia::Pixmap::try_create(region.width(), region.height())?;
 
-    let (sx, sy) = match scale_coordinates(fe.scale(), fe.scale(), ts) {
-        Some(v) => v,
-        None => return Ok(Image::from_image(pixmap1, cs)),
-    };
+    // `scale` is applied by `displacement_map::apply` itself.
+    let (sx, sy) = ts.get_scale();
 
     displacement_map::apply(
         fe,
@@ -975,7 +991,7 @@ fn apply_displacement_map(
         pixmap.as_image_ref_mut(),
     );
 
-    Ok(Image::from_image(pixmap, cs))
+    Ok(Image::from_image(pixmap, cs1))
 }
 
 fn apply_turbulence(
@@ -30,6 +30,10 @@ struct StitchInfo {
 ///
 /// - `offset_x` and `offset_y` indicate filter region offset.
 /// - `sx` and `sy` indicate canvas scale.
+///
+/// Only basic arithmetic, `sqrt`, `floor` and `ceil` are used, which are exactly rounded
+/// according to IEEE 754. Therefore the output is identical on all platforms.
+/// Do not introduce `powf`, `sin`, `mul_add` and similar functions here.
 pub fn apply(
     offset_x: f64,
     offset_y: f64,
@@ -17,7 +17,12 @@
 pub use tiny_skia;
 pub use usvg;
 
+pub use encode::{encode_png, write_png, PngColorProfile, PngError, PngOptions};
+
 mod clip;
+#[cfg(feature = "debug-hooks")]
+pub mod debug;
+mod encode;
 mod filter;
 mod geom;
 mod image;
@@ -25,18 +30,216 @@ mod mask;
 mod path;
 mod render;
 
+/// Rendering options.
+#[derive(Clone, Copy, Default, Debug)]
+pub struct RenderOptions {
+    /// The maximum distance in pixels between a curve and the lines approximating it.
+    ///
+    /// When set, curves of fills, strokes and clip paths are flattened before rasterization
+    /// using this tolerance. A larger value, like `1.0`, produces fewer line segments
+    /// and trades accuracy for speed, while a smaller one, like `0.05`,
+    /// produces smoother curves for high-resolution output.
+    ///
+    /// The tolerance is in the output pixels, so it's independent from the `transform`.
+    /// Same as the arc length accuracy used to lay out text on a path,
+    /// which is `0.5` units divided by the element's scale. But unlike it,
+    /// this option is applied at render time and doesn't affect the tree.
+    ///
+    /// When `None`, curves are passed to the rasterizer as is.
+    ///
+    /// Default: `None`
+    pub curve_tolerance: Option<f32>,
+
+    /// Composites group layers in linear RGB instead of sRGB.
+    ///
+    /// Affects only layers with a normal blend mode, i.e. groups with opacity,
+    /// clip paths, masks or filters. Other blend modes, as well as shapes themselves,
+    /// including their anti-aliasing, are still composited in sRGB.
+    ///
+    /// Overlapping semi-transparent layers become lighter and more saturated.
+    /// Note that browsers composite in sRGB as well, since this is what SVG and CSS require.
+    /// So this is an opt-in stylistic choice, which is also slower.
+    ///
+    /// This is unrelated to `color-interpolation-filters`, which is always respected.
+    ///
+    /// Default: `false`
+    pub linear_compositing: bool,
+
+    /// Anti-aliasing quality.
+    ///
+    /// Default: [`AntialiasingQuality::Default`]
+    pub antialiasing_quality: AntialiasingQuality,
+
+    /// A checkerboard backdrop.
+    ///
+    /// When set, the whole pixmap is covered by a checkerboard before rendering,
+    /// which makes transparent areas visible in previews.
+    ///
+    /// Default: `None`
+    pub checkerboard: Option<Checkerboard>,
+}
+
+/// A checkerboard backdrop.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Checkerboard {
+    /// Cell size in pixels.
+    ///
+    /// Cells are aligned with the pixmap and are not affected by the render transform.
+    ///
+    /// Default: 8
+    pub cell_size: u32,
+
+    /// Cells colors.
+    ///
+    /// The top-left cell uses the first one.
+    ///
+    /// Default: white and light gray
+    pub colors: [tiny_skia::Color; 2],
+}
+
+impl Default for Checkerboard {
+    fn default() -> Self {
+        Checkerboard {
+            cell_size: 8,
+            colors: [
+                tiny_skia::Color::WHITE,
+                tiny_skia::Color::from_rgba8(204, 204, 204, 255),
+            ],
+        }
+    }
+}
+
+impl Checkerboard {
+    /// Draws the checkerboard over the whole pixmap.
+    pub fn draw(&self, pixmap: &mut tiny_skia::PixmapMut) {
+        let Some(mut cells) = tiny_skia::Pixmap::new(2, 2) else {
+            return;
+        };
+
+        for (i, pixel) in cells.pixels_mut().iter_mut().enumerate() {
+            // Cells on the diagonal use the first color.
+            let color = self.colors[(i % 2) ^ (i / 2)];
+            *pixel = color.premultiply().to_color_u8();
+        }
+
+        let cell_size = self.cell_size.max(1) as f32;
+        let mut paint = tiny_skia::Paint::default();
+        paint.shader = tiny_skia::Pattern::new(
+            cells.as_ref(),
+            tiny_skia::SpreadMode::Repeat,
+            tiny_skia::FilterQuality::Nearest,
+            1.0,
+            tiny_skia::Transform::from_scale(cell_size, cell_size),
+        );
+
+        let rect =
+            tiny_skia::Rect::from_xywh(0.0, 0.0, pixmap.width() as f32, pixmap.height() as f32);
+        if let Some(rect) = rect {
+            pixmap.fill_rect(rect, &paint, tiny_skia::Transform::default(), None);
+        }
+    }
+}
+
+/// Anti-aliasing quality.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum AntialiasingQuality {
+    /// Disables anti-aliasing of shapes.
+    ///
+    /// The same as `shape-rendering="crispEdges"` on all shapes.
+    /// The fastest one, useful for thumbnails.
+    Disabled,
+    /// The anti-aliasing of `tiny-skia`.
+    #[default]
+    Default,
+    /// Renders at twice the resolution and downsamples the result.
+    ///
+    /// Requires an additional pixmap four times larger than the target one.
+    /// Shapes with anti-aliasing disabled by `shape-rendering` are still rendered
+    /// at the target resolution, so they stay aliased.
+    Supersample2x,
+    /// Renders at four times the resolution and downsamples the result.
+    ///
+    /// Requires an additional pixmap sixteen times larger than the target one.
+    Supersample4x,
+}
+
+impl AntialiasingQuality {
+    fn supersampling_factor(self) -> u32 {
+        match self {
+            Self::Disabled | Self::Default => 1,
+            Self::Supersample2x => 2,
+            Self::Supersample4x => 4,
+        }
+    }
+}
+
 /// Renders a tree onto the pixmap.
 ///
 /// `transform` will be used as a root transform.
 /// Can be used to position SVG inside the `pixmap`.
 ///
+/// Returns `false` and draws nothing when [`usvg::Tree::is_empty`] returns `true`.
+///
 /// The produced content is in the sRGB color space.
 pub fn render(
     tree: &usvg::Tree,
     transform: tiny_skia::Transform,
     pixmap: &mut tiny_skia::PixmapMut,
+) -> bool {
+    render_with_options(tree, transform, &RenderOptions::default(), pixmap)
+}
+
+/// Renders a tree onto the pixmap using the specified options.
+///
+/// Same as [`render`], but allows to control the rendering quality.
+///
+/// With supersampling, the tree is rendered onto a separate pixmap,
+/// which is then drawn onto `pixmap`. Therefore, blend modes of the root children
+/// are not applied to the existing `pixmap` content.
+/// Falls back to the default anti-aliasing when a larger pixmap cannot be allocated.
+///
+/// Returns `false` when [`usvg::Tree::is_empty`] returns `true`.
+/// In this case, only the checkerboard is drawn, if set.
+pub fn render_with_options(
+    tree: &usvg::Tree,
+    transform: tiny_skia::Transform,
+    opt: &RenderOptions,
+    pixmap: &mut tiny_skia::PixmapMut,
+) -> bool {
+    if let Some(ref checkerboard) = opt.checkerboard {
+        checkerboard.draw(pixmap);
+    }
+
+    if tree.is_empty() {
+        return false;
+    }
+
+    let factor = opt.antialiasing_quality.supersampling_factor();
+    if factor == 1 || render_supersampled(tree, transform, opt, factor, pixmap).is_none() {
+        render_with_supersampling(tree, transform, opt, 1, pixmap);
+    }
+
+    true
+}
+
+/// Renders a tree onto a pixmap that is `supersampling` times larger than the output.
+fn render_with_supersampling(
+    tree: &usvg::Tree,
+    transform: tiny_skia::Transform,
+    opt: &RenderOptions,
+    supersampling: u32,
+    pixmap: &mut tiny_skia::PixmapMut,
 ) {
     let target_size = tiny_skia::IntSize::from_wh(pixmap.width(), pixmap.height()).unwrap();
+    let ctx = render::Context {
+        supersampling,
+        ..render_context(target_size, opt)
+    };
+    render::render_nodes(tree.root(), &ctx, transform, pixmap);
+}
+
+/// Creates a context for rendering onto a pixmap of the specified size without supersampling.
+fn render_context(target_size: tiny_skia::IntSize, opt: &RenderOptions) -> render::Context {
     let max_bbox = tiny_skia::IntRect::from_xywh(
         -(target_size.width() as i32) * 2,
         -(target_size.height() as i32) * 2,
@@ -45,8 +248,285 @@ pub fn render(
     )
     .unwrap();
 
-    let ctx = render::Context { max_bbox };
-    render::render_nodes(tree.root(), &ctx, transform, pixmap);
+    render::Context {
+        max_bbox,
+        max_filter_bbox: max_bbox,
+        curve_tolerance: opt.curve_tolerance,
+        linear_compositing: opt.linear_compositing,
+        anti_alias: opt.antialiasing_quality != AntialiasingQuality::Disabled,
+        supersampling: 1,
+    }
+}
+
+fn render_supersampled(
+    tree: &usvg::Tree,
+    transform: tiny_skia::Transform,
+    opt: &RenderOptions,
+    factor: u32,
+    pixmap: &mut tiny_skia::PixmapMut,
+) -> Option<()> {
+    let mut large_pixmap = tiny_skia::Pixmap::new(
+        pixmap.width().checked_mul(factor)?,
+        pixmap.height().checked_mul(factor)?,
+    )?;
+
+    let opt = RenderOptions {
+        // The tolerance is in the output pixels.
+        curve_tolerance: opt.curve_tolerance.map(|t| t * factor as f32),
+        antialiasing_quality: AntialiasingQuality::Default,
+        ..*opt
+    };
+    let transform = transform.post_scale(factor as f32, factor as f32);
+    render_with_supersampling(tree, transform, &opt, factor, &mut large_pixmap.as_mut());
+
+    let small_pixmap = downsample(&large_pixmap, factor)?;
+    pixmap.draw_pixmap(
+        0,
+        0,
+        small_pixmap.as_ref(),
+        &tiny_skia::PixmapPaint::default(),
+        tiny_skia::Transform::default(),
+        None,
+    );
+
+    Some(())
+}
+
+/// Downsamples a pixmap by averaging each `factor`x`factor` block of pixels.
+fn downsample(pixmap: &tiny_skia::Pixmap, factor: u32) -> Option<tiny_skia::Pixmap> {
+    let width = pixmap.width() / factor;
+    let height = pixmap.height() / factor;
+    let mut small_pixmap = tiny_skia::Pixmap::new(width, height)?;
+
+    let count = factor * factor;
+    let src = pixmap.pixels();
+    let dst = small_pixmap.pixels_mut();
+    for y in 0..height {
+        for x in 0..width {
+            let mut sum = [0u32; 4];
+            for sy in 0..factor {
+                let row = ((y * factor + sy) * pixmap.width()) as usize;
+                for sx in 0..factor {
+                    let c = src[row + (x * factor + sx) as usize];
+                    sum[0] += c.red() as u32;
+                    sum[1] += c.green() as u32;
+                    sum[2] += c.blue() as u32;
+                    sum[3] += c.alpha() as u32;
+                }
+            }
+
+            // Since colors are premultiplied, the averaged ones are still valid.
+            let avg = |n: u32| ((n + count / 2) / count) as u8;
+            dst[(y * width + x) as usize] = tiny_skia::PremultipliedColorU8::from_rgba(
+                avg(sum[0]),
+                avg(sum[1]),
+                avg(sum[2]),
+                avg(sum[3]),
+            )?;
+        }
+    }
+
+    Some(small_pixmap)
+}
+
+/// Renders a tree onto a new pixmap with an additional transform.
+///
+/// Unlike [`render`], `transform` is applied to the whole canvas and the pixmap is sized
+/// to fit the transformed canvas bounds.
+/// Can be used to rotate or flip the image without modifying the tree.
+///
+/// The canvas is [`usvg::Tree::render_area`], therefore content outside the viewport
+/// is rendered as well when the root element has a visible overflow.
+///
+/// The pixmap size is the transformed canvas bounds rounded outwards.
+///
+/// Returns `None` when the transformed canvas has a zero size.
+///
+/// The produced content is in the sRGB color space.
+pub fn render_transformed(
+    tree: &usvg::Tree,
+    transform: tiny_skia::Transform,
+) -> Option<tiny_skia::Pixmap> {
+    let bbox = tree.render_area().transform(transform)?;
+    // Round outwards, so fractional bounds are not cut off,
+    // ignoring the float noise left by rotations like 90 degrees.
+    let snap = |n: f32| {
+        if (n - n.round()).abs() < 0.001 {
+            n.round()
+        } else {
+            n
+        }
+    };
+    let bbox = tiny_skia::IntRect::from_ltrb(
+        snap(bbox.left()).floor() as i32,
+        snap(bbox.top()).floor() as i32,
+        snap(bbox.right()).ceil() as i32,
+        snap(bbox.bottom()).ceil() as i32,
+    )?;
+    let mut pixmap = tiny_skia::Pixmap::new(bbox.width(), bbox.height())?;
+
+    // Move the transformed canvas to the origin.
+    let transform = transform.post_translate(-bbox.x() as f32, -bbox.y() as f32);
+    render(tree, transform, &mut pixmap.as_mut());
+
+    Some(pixmap)
+}
+
+/// Renders a tree onto a new pixmap cropped to the tree content.
+///
+/// The content area is the root layer bounding box, i.e. including strokes and filter regions,
+/// transformed by `transform` and rounded outwards.
+/// Unlike [`render`], content outside the canvas is rendered as well.
+///
+/// Returns the pixmap and the content area, which can be used to position the pixmap
+/// on the uncropped canvas.
+///
+/// Returns `None` when the tree has no content.
+///
+/// The produced content is in the sRGB color space.
+pub fn render_cropped(
+    tree: &usvg::Tree,
+    transform: tiny_skia::Transform,
+) -> Option<(tiny_skia::Pixmap, tiny_skia::IntRect)> {
+    if tree.is_empty() {
+        return None;
+    }
+
+    let area = tree
+        .root()
+        .layer_bounding_box()
+        .transform(transform)?
+        .to_int_rect();
+    let mut pixmap = tiny_skia::Pixmap::new(area.width(), area.height())?;
+
+    let transform = transform.post_translate(-area.x() as f32, -area.y() as f32);
+    render(tree, transform, &mut pixmap.as_mut());
+
+    Some((pixmap, area))
+}
+
+/// Renders a tree as a sequence of tiles.
+///
+/// Splits a canvas of the specified `size` into tiles of at most `tile_size`, row by row,
+/// and renders each one onto a separate pixmap. Returns tile pixmaps along with their
+/// positions on the canvas. Stitching the tiles together produces the same image
+/// as [`render`] with the same `transform` onto a single pixmap of `size`,
+/// except for minor anti-aliasing differences of curves crossing tile edges.
+///
+/// Tiles are rendered lazily, so only one tile is kept in memory at a time,
+/// unless the caller stores them. This allows rendering images that are too large
+/// to be allocated at once.
+///
+/// Nodes outside a tile are skipped using precomputed bounding boxes.
+/// Group layers, like ones for opacity or masks, are limited to the tile as well.
+/// Filter layers are limited to the tile expanded by the maximum distance filters
+/// can move pixels by, like a blur radius. Unless the tree has filters that depend
+/// on the whole filter region, like `feTile`. Then their layers are limited
+/// to the canvas, like during a regular rendering.
+///
+/// Yields `None` in place of a tile that cannot be rendered, like when it cannot be allocated,
+/// so the caller can either stop or continue, knowing that the image will have a hole.
+///
+/// Supersampling and the checkerboard of `opt` are not supported and are ignored.
+pub fn render_tiles<'a>(
+    tree: &'a usvg::Tree,
+    transform: tiny_skia::Transform,
+    opt: &RenderOptions,
+    size: tiny_skia::IntSize,
+    tile_size: tiny_skia::IntSize,
+) -> impl Iterator<Item = Option<(tiny_skia::Pixmap, tiny_skia::IntRect)>> + 'a {
+    let opt = *opt;
+    let columns = (size.width() + tile_size.width() - 1) / tile_size.width();
+    let rows = (size.height() + tile_size.height() - 1) / tile_size.height();
+    let inv_transform = transform.invert();
+    // A filter layer is clipped at its edges, which changes anti-aliasing of shapes
+    // crossing them. Filters can spread this change, so it must stay away from the tile.
+    let filters_margin =
+        filters_margin(tree.root(), transform).map(|m| (m * 3.0).ceil() as i32 + 8);
+
+    (0..rows)
+        .flat_map(move |row| (0..columns).map(move |column| (column, row)))
+        .map(move |(column, row)| {
+            let x = column * tile_size.width();
+            let y = row * tile_size.height();
+            let rect = tiny_skia::IntRect::from_xywh(
+                x as i32,
+                y as i32,
+                tile_size.width().min(size.width() - x),
+                tile_size.height().min(size.height() - y),
+            )?;
+
+            let mut pixmap = tiny_skia::Pixmap::new(rect.width(), rect.height())
+                .log_none(|| log::warn!("Failed to allocate a tile: {:?}.", rect))?;
+
+            // Filter layers must include pixels that filters can move into the tile.
+            let canvas_filter_bbox = tiny_skia::IntRect::from_xywh(
+                -(size.width() as i32) * 2 - rect.x(),
+                -(size.height() as i32) * 2 - rect.y(),
+                size.width() * 5,
+                size.height() * 5,
+            )?;
+            let max_filter_bbox = match filters_margin {
+                Some(margin) => tiny_skia::IntRect::from_xywh(
+                    -margin,
+                    -margin,
+                    rect.width() + margin as u32 * 2,
+                    rect.height() + margin as u32 * 2,
+                )
+                .and_then(|bbox| geom::fit_to_rect(bbox, canvas_filter_bbox))?,
+                None => canvas_filter_bbox,
+            };
+
+            let ctx = render::Context {
+                max_filter_bbox,
+                ..render_context(rect.size(), &opt)
+            };
+
+            let tile_transform = transform.post_translate(-(x as f32), -(y as f32));
+
+            // Expand the area by 2px to include anti-aliased pixels.
+            let area = rect
+                .to_rect()
+                .outset(2.0, 2.0)
+                .and_then(|area| area.transform(inv_transform?));
+            match area {
+                Some(area) => render::render_nodes_in_area(
+                    tree.root(),
+                    area,
+                    &ctx,
+                    tile_transform,
+                    &mut pixmap.as_mut(),
+                ),
+                None => {
+                    render::render_nodes(tree.root(), &ctx, tile_transform, &mut pixmap.as_mut())
+                }
+            }
+
+            Some((pixmap, rect))
+        })
+}
+
+/// Returns the maximum distance filters of `parent` descendants can move pixels by.
+///
+/// Returns `None` when any filter depends on the whole filter region.
+fn filters_margin(parent: &usvg::Group, transform: tiny_skia::Transform) -> Option<f32> {
+    let mut margin = Some(0.0f32);
+    let mut update = |m: Option<f32>| margin = margin.and_then(|v| Some(v.max(m?)));
+
+    for node in parent.children() {
+        if let usvg::Node::Group(ref group) = node {
+            let ts = transform.pre_concat(group.abs_transform());
+            for filter in group.filters() {
+                update(filter::margin(filter, ts));
+            }
+
+            update(filters_margin(group, transform));
+        }
+
+        node.subroots(|subroot| update(filters_margin(subroot, transform)));
+    }
+
+    margin
 }
 
 /// Renders a node onto the pixmap.
@@ -60,29 +540,183 @@ pub fn render(
 ///
 /// The produced content is in the sRGB color space.
 pub fn render_node(
+    node: &usvg::Node,
+    transform: tiny_skia::Transform,
+    pixmap: &mut tiny_skia::PixmapMut,
+) -> Option<()> {
+    render_node_with_options(node, transform, &RenderOptions::default(), pixmap)
+}
+
+/// Renders a node onto the pixmap using the specified options.
+///
+/// Same as [`render_node`], but allows to control the rendering quality.
+///
+/// Supersampling and the checkerboard of `opt` are not supported and are ignored.
+pub fn render_node_with_options(
     node: &usvg::Node,
     mut transform: tiny_skia::Transform,
+    opt: &RenderOptions,
     pixmap: &mut tiny_skia::PixmapMut,
 ) -> Option<()> {
     let bbox = node.abs_layer_bounding_box()?;
 
     let target_size = tiny_skia::IntSize::from_wh(pixmap.width(), pixmap.height()).unwrap();
-    let max_bbox = tiny_skia::IntRect::from_xywh(
-        -(target_size.width() as i32) * 2,
-        -(target_size.height() as i32) * 2,
-        target_size.width() * 5,
-        target_size.height() * 5,
-    )
-    .unwrap();
-
     transform = transform.pre_translate(-bbox.x(), -bbox.y());
 
-    let ctx = render::Context { max_bbox };
+    let ctx = render_context(target_size, opt);
     render::render_node(node, &ctx, transform, pixmap);
 
     Some(())
 }
 
+/// Renders a node by ID onto the pixmap, as it appears in the whole tree.
+///
+/// Unlike [`render_node`], transforms, opacity, clip paths, masks, filters and blend modes
+/// of all ancestor groups are applied as well, but only to this node. Siblings of the node
+/// and of its ancestors are not rendered.
+///
+/// `transform` will be used as a root transform, the same way as in [`render_node`].
+/// The expected pixmap size can be retrieved from `usvg::Node::abs_layer_bounding_box()`.
+/// Supersampling and the checkerboard of `opt` are not supported and are ignored.
+///
+/// The output is limited to the node's own layer bounding box. Ancestor filters
+/// that extend the node, like a blur or a drop shadow, are therefore cropped
+/// at its edges. Use [`render_node`] on the filtered ancestor to get its whole
+/// filter region.
+///
+/// Returns `None` when there is no node with such ID or when it has a zero size.
+///
+/// The produced content is in the sRGB color space.
+pub fn render_node_with_context(
+    tree: &usvg::Tree,
+    id: &str,
+    mut transform: tiny_skia::Transform,
+    opt: &RenderOptions,
+    pixmap: &mut tiny_skia::PixmapMut,
+) -> Option<()> {
+    if id.is_empty() {
+        return None;
+    }
+
+    let mut ancestors = vec![tree.root()];
+    let node = find_node_with_ancestors(tree.root(), id, &mut ancestors)?;
+    let bbox = node.abs_layer_bounding_box()?;
+
+    let target_size = tiny_skia::IntSize::from_wh(pixmap.width(), pixmap.height()).unwrap();
+    transform = transform.pre_translate(-bbox.x(), -bbox.y());
+
+    let ctx = render_context(target_size, opt);
+    render::render_node_with_ancestors(&ancestors, node, &ctx, transform, pixmap);
+
+    Some(())
+}
+
+// Matches `usvg::Tree::node_by_id`, i.e. the first node in the depth-first order wins.
+//
+// `ancestors` will contain all groups between `parent` and the found node.
+fn find_node_with_ancestors<'a>(
+    parent: &'a usvg::Group,
+    id: &str,
+    ancestors: &mut Vec<&'a usvg::Group>,
+) -> Option<&'a usvg::Node> {
+    for node in parent.children() {
+        if node.id() == id {
+            return Some(node);
+        }
+
+        if let usvg::Node::Group(ref group) = node {
+            ancestors.push(group);
+            if let Some(node) = find_node_with_ancestors(group, id, ancestors) {
+                return Some(node);
+            }
+            ancestors.pop();
+        }
+    }
+
+    None
+}
+
+/// Renders multiple nodes by ID, each onto a new pixmap.
+///
+/// Each pixmap has the size of the node's [`usvg::Node::abs_layer_bounding_box`]
+/// scaled by `transform`, and the node is rendered the same way as by [`render_node_with_options`].
+/// Useful for extracting icons from a sprite sheet.
+///
+/// Produces the same pixmaps as calling [`render_node_with_options`] for each node,
+/// but all nodes are looked up during a single tree traversal, instead of calling
+/// [`usvg::Tree::node_by_id`] for each ID. Rendering itself is not batched:
+/// each node allocates its own pixmap and intermediate layers.
+///
+/// Returns pixmaps in the `ids` order. Contains `None` for IDs that are not present
+/// in the tree and for nodes with a zero size.
+///
+/// The produced content is in the sRGB color space.
+pub fn render_nodes(
+    tree: &usvg::Tree,
+    ids: &[&str],
+    transform: tiny_skia::Transform,
+    opt: &RenderOptions,
+) -> Vec<Option<tiny_skia::Pixmap>> {
+    let ids_set: std::collections::HashSet<&str> = ids.iter().copied().collect();
+    let mut nodes = std::collections::HashMap::new();
+    collect_nodes_by_id(tree.root(), &ids_set, &mut nodes);
+
+    let layers: Vec<_> = ids
+        .iter()
+        .map(|id| {
+            let node = *nodes.get(id)?;
+            let bbox = node.abs_layer_bounding_box()?;
+            let size = tiny_skia::NonZeroRect::from_xywh(0.0, 0.0, bbox.width(), bbox.height())?
+                .transform(transform)?
+                .size()
+                .to_int_size();
+            Some((node, bbox, size))
+        })
+        .collect();
+
+    // Layers of all nodes are limited relative to the largest pixmap.
+    let max_size = layers
+        .iter()
+        .flatten()
+        .fold((0, 0), |(w, h), (_, _, size)| {
+            (w.max(size.width()), h.max(size.height()))
+        });
+    let Some(max_size) = tiny_skia::IntSize::from_wh(max_size.0, max_size.1) else {
+        return vec![None; ids.len()];
+    };
+    let ctx = render_context(max_size, opt);
+
+    layers
+        .into_iter()
+        .zip(ids)
+        .map(|(layer, id)| {
+            let (node, bbox, size) = layer?;
+            let mut pixmap = tiny_skia::Pixmap::new(size.width(), size.height())
+                .log_none(|| log::warn!("Failed to allocate a pixmap for '{}'.", id))?;
+            let transform = transform.pre_translate(-bbox.x(), -bbox.y());
+            render::render_node(node, &ctx, transform, &mut pixmap.as_mut());
+            Some(pixmap)
+        })
+        .collect()
+}
+
+// Matches `usvg::Tree::node_by_id`, i.e. the first node in the depth-first order wins.
+fn collect_nodes_by_id<'a>(
+    parent: &'a usvg::Group,
+    ids: &std::collections::HashSet<&str>,
+    nodes: &mut std::collections::HashMap<&'a str, &'a usvg::Node>,
+) {
+    for node in parent.children() {
+        if !node.id().is_empty() && ids.contains(node.id()) {
+            nodes.entry(node.id()).or_insert(node);
+        }
+
+        if let usvg::Node::Group(ref group) = node {
+            collect_nodes_by_id(group, ids, nodes);
+        }
+    }
+}
+
 pub(crate) trait OptionLog {
     fn log_none<F: FnOnce()>(self, f: F) -> Self;
 }
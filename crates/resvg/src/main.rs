@@ -6,7 +6,7 @@
 use std::path;
 use std::sync::Arc;
 
-use usvg::fontdb;
+use usvg::{fontdb, FitSize};
 
 fn main() {
     if let Err(e) = process() {
@@ -104,13 +104,13 @@ fn process() -> Result<(), String> {
 
     match args.out_png.unwrap() {
         OutputTo::Stdout => {
-            use std::io::Write;
-            let buf = img.encode_png().map_err(|e| e.to_string())?;
-            std::io::stdout().write_all(&buf).unwrap();
+            let mut stdout = std::io::BufWriter::new(std::io::stdout().lock());
+            write_png(&img, &args.png, &mut stdout)?;
         }
         OutputTo::File(ref file) => {
             timed(args.perf, "Saving", || {
-                img.save_png(file).map_err(|e| e.to_string())
+                let file = std::fs::File::create(file).map_err(|e| e.to_string())?;
+                write_png(&img, &args.png, &mut std::io::BufWriter::new(file))
             })?;
         }
     };
@@ -139,10 +139,21 @@ OPTIONS:
   -w, --width LENGTH            Sets the width in pixels
   -h, --height LENGTH           Sets the height in pixels
   -z, --zoom FACTOR             Zooms the image by a factor
-      --dpi DPI                 Sets the resolution
+      --dpi DPI                 Sets the resolution used to convert
+                                absolute units, like pt and mm
                                 [default: 96] [possible values: 10..4000 (inclusive)]
   --background COLOR            Sets the background color
                                 Examples: red, #fff, #fff000
+  --transparent-padding         Applies --background only to the area covered
+                                by the root element's viewBox, keeping bars
+                                added by preserveAspectRatio transparent
+  --bit-depth DEPTH             Sets the output PNG bit depth per channel.
+                                16 bits preserve semi-transparent colors better
+                                [default: 8] [possible values: 8, 16]
+  --color-profile PROFILE       Tags the output PNG with a color profile.
+                                Colors are not converted
+                                [default: none] [possible values: none, srgb,
+                                path to an ICC file]
   --stylesheet PATH             Inject a stylesheet that should be used when resolving
                                 CSS attributes.
 
@@ -184,6 +195,13 @@ OPTIONS:
                                 [default: Impact]
   --monospace-family FAMILY     Sets the 'monospace' font family
                                 [default: Courier New]
+  --fallback-family FAMILY      Adds a font family that will be tried first
+                                when a font doesn't have a character.
+                                Families are tried in the specified order.
+                                This option can be set multiple times
+  --strict-fonts                Disables font substitution. Text spans without
+                                a matching font or with unsupported characters
+                                will be omitted. Cannot be used with --fallback-family
   --use-font-file PATH          Load a specified font file into the fonts database.
                                 Will be used during text to path conversion.
                                 This option can be set multiple times
@@ -221,6 +239,9 @@ struct CliArgs {
     zoom: Option<f32>,
     dpi: u32,
     background: Option<svgtypes::Color>,
+    transparent_padding: bool,
+    bit_depth: u8,
+    color_profile: Option<String>,
 
     languages: Vec<String>,
     shape_rendering: usvg::ShapeRendering,
@@ -235,6 +256,8 @@ struct CliArgs {
     cursive_family: Option<String>,
     fantasy_family: Option<String>,
     monospace_family: Option<String>,
+    fallback_families: Vec<String>,
+    strict_fonts: bool,
     font_files: Vec<path::PathBuf>,
     font_dirs: Vec<path::PathBuf>,
     skip_system_fonts: bool,
@@ -273,6 +296,11 @@ fn collect_args() -> Result<CliArgs, pico_args::Error> {
         zoom: input.opt_value_from_fn(["-z", "--zoom"], parse_zoom)?,
         dpi: input.opt_value_from_fn("--dpi", parse_dpi)?.unwrap_or(96),
         background: input.opt_value_from_str("--background")?,
+        transparent_padding: input.contains("--transparent-padding"),
+        bit_depth: input
+            .opt_value_from_fn("--bit-depth", parse_bit_depth)?
+            .unwrap_or(8),
+        color_profile: input.opt_value_from_str("--color-profile")?,
 
         languages: input
             .opt_value_from_fn("--languages", parse_languages)?
@@ -299,6 +327,8 @@ fn collect_args() -> Result<CliArgs, pico_args::Error> {
         cursive_family: input.opt_value_from_str("--cursive-family")?,
         fantasy_family: input.opt_value_from_str("--fantasy-family")?,
         monospace_family: input.opt_value_from_str("--monospace-family")?,
+        fallback_families: input.values_from_str("--fallback-family")?,
+        strict_fonts: input.contains("--strict-fonts"),
         font_files: input.values_from_str("--use-font-file")?,
         font_dirs: input.values_from_str("--use-fonts-dir")?,
         skip_system_fonts: input.contains("--skip-system-fonts"),
@@ -329,6 +359,23 @@ fn parse_dpi(s: &str) -> Result<u32, String> {
     }
 }
 
+fn write_png(
+    img: &tiny_skia::Pixmap,
+    opt: &resvg::PngOptions,
+    w: &mut impl std::io::Write,
+) -> Result<(), String> {
+    resvg::write_png(img.as_ref(), opt, &mut *w).map_err(|e| e.to_string())?;
+    w.flush().map_err(|e| e.to_string())
+}
+
+fn parse_bit_depth(s: &str) -> Result<u8, String> {
+    match s {
+        "8" => Ok(8),
+        "16" => Ok(16),
+        _ => Err("bit depth must be 8 or 16".to_string()),
+    }
+}
+
 fn parse_length(s: &str) -> Result<u32, String> {
     let n: u32 = s.parse().map_err(|_| "invalid length")?;
 
@@ -399,26 +446,26 @@ enum FitTo {
 }
 
 impl FitTo {
-    fn fit_to_size(&self, size: tiny_skia::IntSize) -> Option<tiny_skia::IntSize> {
+    /// Returns the scaled size and the scale factor.
+    fn fit(&self, size: tiny_skia::IntSize) -> Option<(tiny_skia::IntSize, f32)> {
         match *self {
-            FitTo::Original => Some(size),
-            FitTo::Width(w) => size.scale_to_width(w),
-            FitTo::Height(h) => size.scale_to_height(h),
-            FitTo::Size(w, h) => tiny_skia::IntSize::from_wh(w, h).map(|s| size.scale_to(s)),
-            FitTo::Zoom(z) => size.scale_by(z),
+            FitTo::Original => Some((size, 1.0)),
+            FitTo::Width(w) => size.fit_to_width(w),
+            FitTo::Height(h) => size.fit_to_height(h),
+            FitTo::Size(w, h) => tiny_skia::IntSize::from_wh(w, h).map(|s| size.fit_inside(s)),
+            FitTo::Zoom(z) => size.scale_by(z).map(|s| (s, z)),
         }
     }
 
+    fn fit_to_size(&self, size: tiny_skia::IntSize) -> Option<tiny_skia::IntSize> {
+        self.fit(size).map(|(size, _)| size)
+    }
+
     fn fit_to_transform(&self, size: tiny_skia::IntSize) -> tiny_skia::Transform {
-        let size1 = size.to_size();
-        let size2 = match self.fit_to_size(size) {
-            Some(v) => v.to_size(),
-            None => return tiny_skia::Transform::default(),
-        };
-        tiny_skia::Transform::from_scale(
-            size2.width() / size1.width(),
-            size2.height() / size1.height(),
-        )
+        match self.fit(size) {
+            Some((_, scale)) => tiny_skia::Transform::from_scale(scale, scale),
+            None => tiny_skia::Transform::default(),
+        }
     }
 }
 
@@ -466,6 +513,8 @@ struct Args {
     usvg: usvg::Options<'static>,
     fit_to: FitTo,
     background: Option<svgtypes::Color>,
+    transparent_padding: bool,
+    png: resvg::PngOptions,
     raw_args: CliArgs, // TODO: find a better way
 }
 
@@ -508,6 +557,10 @@ fn parse_args() -> Result<Args, String> {
         return Err("<out-png> must be set".to_string());
     }
 
+    if args.strict_fonts && !args.fallback_families.is_empty() {
+        return Err("--fallback-family cannot be used with --strict-fonts".to_string());
+    }
+
     if in_svg == InputFrom::Stdin && args.resources_dir.is_none() {
         eprintln!("Warning: Make sure to set --resources-dir when reading SVG from stdin.");
     }
@@ -520,6 +573,10 @@ fn parse_args() -> Result<Args, String> {
         eprintln!("Warning: --export-area-drawing has no effect when --export-id is set.");
     }
 
+    if args.transparent_padding && args.export_id.is_some() {
+        eprintln!("Warning: --transparent-padding has no effect when --export-id is set.");
+    }
+
     let export_id = args.export_id.as_ref().map(|v| v.to_string());
 
     let mut fit_to = FitTo::Original;
@@ -561,6 +618,25 @@ fn parse_args() -> Result<Args, String> {
         None => None,
     };
 
+    let color_profile = match args.color_profile.as_deref() {
+        None | Some("none") => resvg::PngColorProfile::None,
+        Some("srgb") => resvg::PngColorProfile::SRGB,
+        Some(path) => resvg::PngColorProfile::ICC(
+            std::fs::read(path).map_err(|_| "failed to read color profile".to_string())?,
+        ),
+    };
+
+    let png = resvg::PngOptions {
+        high_bit_depth: args.bit_depth == 16,
+        color_profile,
+    };
+
+    let mut font_resolver = usvg::FontResolver::default();
+    if !args.fallback_families.is_empty() {
+        font_resolver.select_fallback =
+            usvg::FontResolver::families_fallback_selector(args.fallback_families.clone());
+    }
+
     let usvg = usvg::Options {
         resources_dir,
         dpi: args.dpi as f32,
@@ -569,15 +645,27 @@ fn parse_args() -> Result<Args, String> {
             .clone()
             .unwrap_or_else(|| "Times New Roman".to_string()),
         font_size: args.font_size as f32,
+        apply_kerning: true,
         languages: args.languages.clone(),
         shape_rendering: args.shape_rendering,
         text_rendering: args.text_rendering,
         image_rendering: args.image_rendering,
         default_size,
+        fit_viewbox_to_content: false,
         image_href_resolver: usvg::ImageHrefResolver::default(),
-        font_resolver: usvg::FontResolver::default(),
+        font_resolver,
         fontdb: Arc::new(fontdb::Database::new()),
+        emoji_rendering: usvg::EmojiRendering::Color,
+        strict_fonts: args.strict_fonts,
+        glyph_cache: None,
         style_sheet,
+        media_features: usvg::Options::default().media_features,
+        keep_metadata: false,
+        keep_computed_styles: false,
+        keep_use_references: false,
+        keep_arcs: false,
+        filter_policy: usvg::FilterPolicy::default(),
+        diagnostics: None,
     };
 
     Ok(Args {
@@ -592,6 +680,8 @@ fn parse_args() -> Result<Args, String> {
         usvg,
         fit_to,
         background: args.background,
+        transparent_padding: args.transparent_padding,
+        png,
         raw_args: args,
     })
 }
@@ -619,51 +709,32 @@ fn load_fonts(args: &CliArgs, fontdb: &mut fontdb::Database) {
 }
 
 fn query_all(tree: &usvg::Tree) -> Result<(), String> {
-    let count = query_all_impl(tree.root());
-
-    if count == 0 {
+    let bboxes = tree.all_bounding_boxes(usvg::QueryCoordinateSpace::ViewportPixels);
+    if bboxes.is_empty() {
         return Err("the file has no valid ID's".to_string());
     }
 
-    Ok(())
-}
-
-fn query_all_impl(parent: &usvg::Group) -> usize {
-    let mut count = 0;
-    for node in parent.children() {
-        if node.id().is_empty() {
-            if let usvg::Node::Group(ref group) = node {
-                count += query_all_impl(group);
-            }
-            continue;
-        }
-
-        count += 1;
-
-        fn round_len(v: f32) -> f32 {
-            (v * 1000.0).round() / 1000.0
-        }
+    fn round_len(v: f32) -> f32 {
+        (v * 1000.0).round() / 1000.0
+    }
 
-        let bbox = node
-            .abs_layer_bounding_box()
+    for node_bboxes in bboxes {
+        let bbox = node_bboxes
+            .layer_bounding_box()
             .map(|r| r.to_rect())
-            .unwrap_or(node.abs_bounding_box());
+            .unwrap_or(node_bboxes.bounding_box());
 
         println!(
             "{},{},{},{},{}",
-            node.id(),
+            node_bboxes.id(),
             round_len(bbox.x()),
             round_len(bbox.y()),
             round_len(bbox.width()),
             round_len(bbox.height())
         );
-
-        if let usvg::Node::Group(ref group) = node {
-            count += query_all_impl(group);
-        }
     }
 
-    count
+    Ok(())
 }
 
 fn render_svg(args: &Args, tree: &usvg::Tree) -> Result<tiny_skia::Pixmap, String> {
@@ -725,21 +796,40 @@ fn render_svg(args: &Args, tree: &usvg::Tree) -> Result<tiny_skia::Pixmap, Strin
             pixmap
         }
     } else {
+        // The canvas includes content outside the viewport
+        // when the root element has a visible overflow.
+        let area = if tree.is_overflow_visible() {
+            tree.render_area().to_int_rect()
+        } else {
+            tree.size().to_int_size().to_int_rect(0, 0)
+        };
+
         let size = args
             .fit_to
-            .fit_to_size(tree.size().to_int_size())
+            .fit_to_size(area.size())
             .ok_or_else(|| "target size is zero".to_string())?;
 
         // Unwrap is safe, because `size` is already valid.
         let mut pixmap = tiny_skia::Pixmap::new(size.width(), size.height()).unwrap();
 
+        let ts = args
+            .fit_to
+            .fit_to_transform(area.size())
+            .pre_translate(-area.x() as f32, -area.y() as f32);
+
         if let Some(background) = args.background {
-            pixmap.fill(svg_to_skia_color(background));
+            if args.transparent_padding {
+                let mut paint = tiny_skia::Paint::default();
+                paint.set_color(svg_to_skia_color(background));
+                pixmap.fill_rect(tree.view_box_area().to_rect(), &paint, ts, None);
+            } else {
+                pixmap.fill(svg_to_skia_color(background));
+            }
         }
 
-        let ts = args.fit_to.fit_to_transform(tree.size().to_int_size());
-
-        resvg::render(tree, ts, &mut pixmap.as_mut());
+        if !resvg::render(tree, ts, &mut pixmap.as_mut()) {
+            log::warn!("SVG has nothing to render.");
+        }
 
         if args.export_area_drawing {
             trim_pixmap(tree, ts, &pixmap).unwrap_or(pixmap)
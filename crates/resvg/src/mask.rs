@@ -3,8 +3,13 @@
 
 use crate::render::Context;
 
+#[cfg_attr(
+    not(feature = "debug-hooks"),
+    allow(unused_variables, clippy::only_used_in_recursion)
+)]
 pub fn apply(
     mask: &usvg::Mask,
+    node_id: &str,
     ctx: &Context,
     transform: tiny_skia::Transform,
     pixmap: &mut tiny_skia::Pixmap,
@@ -33,14 +38,20 @@ pub fn apply(
     }
 
     if let Some(mask) = mask.mask() {
-        self::apply(mask, ctx, transform, pixmap);
+        self::apply(mask, node_id, ctx, transform, pixmap);
     }
 
+    // Alpha is not affected by premultiplication, so `Alpha` masks can be sampled as is,
+    // while `Luminance` ones are demultiplied by tiny-skia.
     let mask_type = match mask.kind() {
         usvg::MaskType::Luminance => tiny_skia::MaskType::Luminance,
         usvg::MaskType::Alpha => tiny_skia::MaskType::Alpha,
     };
 
-    let mask = tiny_skia::Mask::from_pixmap(mask_pixmap.as_ref(), mask_type);
-    pixmap.apply_mask(&mask);
+    let alpha = tiny_skia::Mask::from_pixmap(mask_pixmap.as_ref(), mask_type);
+
+    #[cfg(feature = "debug-hooks")]
+    crate::debug::report(crate::debug::LayerKind::Mask, mask.id(), node_id, &alpha);
+
+    pixmap.apply_mask(&alpha);
 }
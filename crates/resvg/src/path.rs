@@ -37,6 +37,13 @@ pub fn fill_path(
         return None;
     }
 
+    if ctx.supersampling > 1 && !path.rendering_mode().use_shape_antialiasing() {
+        let bbox = path.data().bounds();
+        return render_at_output_resolution(
+            path, bbox, blend_mode, ctx, transform, pixmap, fill_path,
+        );
+    }
+
     let rule = match fill.rule() {
         usvg::FillRule::NonZero => tiny_skia::FillRule::Winding,
         usvg::FillRule::EvenOdd => tiny_skia::FillRule::EvenOdd,
@@ -67,10 +74,15 @@ pub fn fill_path(
             )
         }
     }
-    paint.anti_alias = path.rendering_mode().use_shape_antialiasing();
+    paint.anti_alias = use_anti_aliasing(path, ctx);
     paint.blend_mode = blend_mode;
 
-    pixmap.fill_path(path.data(), &paint, rule, transform, None);
+    let snapped = snap_to_pixel_grid(path, ctx, None, transform);
+    let data = snapped.as_ref().unwrap_or(path.data());
+    let flattened = flatten_curves(data, ctx, transform);
+    let data = flattened.as_ref().unwrap_or(data);
+
+    pixmap.fill_path(data, &paint, rule, transform, None);
     Some(())
 }
 
@@ -82,6 +94,20 @@ fn stroke_path(
     pixmap: &mut tiny_skia::PixmapMut,
 ) -> Option<()> {
     let stroke = path.stroke()?;
+
+    if ctx.supersampling > 1 && !path.rendering_mode().use_shape_antialiasing() {
+        let bbox = path.stroke_bounding_box();
+        return render_at_output_resolution(
+            path,
+            bbox,
+            blend_mode,
+            ctx,
+            transform,
+            pixmap,
+            stroke_path,
+        );
+    }
+
     let pattern_pixmap;
     let mut paint = tiny_skia::Paint::default();
     match stroke.paint() {
@@ -107,14 +133,380 @@ fn stroke_path(
             )
         }
     }
-    paint.anti_alias = path.rendering_mode().use_shape_antialiasing();
+    paint.anti_alias = use_anti_aliasing(path, ctx);
     paint.blend_mode = blend_mode;
 
-    pixmap.stroke_path(path.data(), &paint, &stroke.to_tiny_skia(), transform, None);
+    // Stroke width in pixels along each axis.
+    let (width_x, width_y) = if path.vector_effect() == usvg::VectorEffect::NonScalingStroke {
+        (stroke.width().get(), stroke.width().get())
+    } else {
+        (
+            stroke.width().get() * transform.sx.abs(),
+            stroke.width().get() * transform.sy.abs(),
+        )
+    };
+    let snapped = snap_to_pixel_grid(path, ctx, Some((width_x, width_y)), transform);
+    let data = snapped.as_ref().unwrap_or(path.data());
+    let flattened = flatten_curves(data, ctx, transform);
+    let data = flattened.as_ref().unwrap_or(data);
+
+    let transformed;
+    let (data, transform) = if path.vector_effect() == usvg::VectorEffect::NonScalingStroke {
+        // The stroke must be applied in the canvas coordinates.
+        transformed = data.clone().transform(transform)?;
+        paint.shader.transform(transform);
+        (&transformed, tiny_skia::Transform::default())
+    } else {
+        (data, transform)
+    };
+
+    let mut ts_stroke = stroke.to_tiny_skia();
+    let dashed = dash_with_zero_length_subpaths(data, stroke, &ts_stroke, transform);
+    let data = match dashed {
+        Some(ref dashed) => {
+            ts_stroke.dash = None;
+            dashed
+        }
+        None => data,
+    };
+
+    pixmap.stroke_path(data, &paint, &ts_stroke, transform, None);
 
     Some(())
 }
 
+/// Dashes a path that has zero-length subpaths.
+///
+/// tiny-skia skips zero-length subpaths during dashing, but they still must be
+/// rendered as caps when the dash pattern starts with a dash.
+///
+/// Returns `None` when the path doesn't have to be dashed manually.
+fn dash_with_zero_length_subpaths(
+    path: &tiny_skia::Path,
+    stroke: &usvg::Stroke,
+    ts_stroke: &tiny_skia::Stroke,
+    transform: tiny_skia::Transform,
+) -> Option<tiny_skia::Path> {
+    if stroke.linecap() == usvg::LineCap::Butt {
+        return None;
+    }
+
+    let dash = ts_stroke.dash.as_ref()?;
+    if !dash_starts_with_dash(stroke.dasharray()?, stroke.dashoffset()) {
+        return None;
+    }
+
+    let points = zero_length_subpaths(path);
+    if points.is_empty() {
+        return None;
+    }
+
+    let res_scale = tiny_skia::PathStroker::compute_resolution_scale(&transform);
+    let mut builder = tiny_skia::PathBuilder::new();
+    if let Some(dashed) = path.dash(dash, res_scale) {
+        builder.push_path(&dashed);
+    }
+
+    for p in points {
+        builder.move_to(p.x, p.y);
+        builder.line_to(p.x, p.y);
+    }
+
+    builder.finish()
+}
+
+/// Checks that the dash pattern is "on" at the start of a subpath.
+fn dash_starts_with_dash(list: &[f32], offset: f32) -> bool {
+    let len: f32 = list.iter().sum();
+    if len <= 0.0 {
+        return false;
+    }
+
+    let mut offset = offset % len;
+    if offset < 0.0 {
+        offset += len;
+    }
+
+    for (i, d) in list.iter().enumerate() {
+        // A zero-length dash at the current position is still "on".
+        if offset > *d || (offset == *d && *d != 0.0) {
+            offset -= d;
+        } else {
+            return i % 2 == 0;
+        }
+    }
+
+    false
+}
+
+/// Returns start points of all zero-length subpaths.
+///
+/// A single `MoveTo` is not a subpath and is ignored.
+fn zero_length_subpaths(path: &tiny_skia::Path) -> Vec<tiny_skia::Point> {
+    let mut points = Vec::new();
+    // A subpath start and whether it is still zero-length.
+    let mut subpath: Option<(tiny_skia::Point, bool)> = None;
+    let mut has_segments = false;
+    for segment in path.segments() {
+        let segment_points = match segment {
+            tiny_skia::PathSegment::MoveTo(p) => {
+                if let (Some((start, true)), true) = (subpath, has_segments) {
+                    points.push(start);
+                }
+
+                subpath = Some((p, true));
+                has_segments = false;
+                continue;
+            }
+            tiny_skia::PathSegment::LineTo(p) => [p, p, p],
+            tiny_skia::PathSegment::QuadTo(p1, p) => [p1, p, p],
+            tiny_skia::PathSegment::CubicTo(p1, p2, p) => [p1, p2, p],
+            tiny_skia::PathSegment::Close => {
+                has_segments = true;
+                continue;
+            }
+        };
+
+        has_segments = true;
+        if let Some((start, ref mut is_zero_length)) = subpath {
+            *is_zero_length &= segment_points.iter().all(|p| *p == start);
+        }
+    }
+
+    if let (Some((start, true)), true) = (subpath, has_segments) {
+        points.push(start);
+    }
+
+    points
+}
+
+/// Renders a path without anti-aliasing at the output resolution during supersampling.
+///
+/// Otherwise, aliased edges would be smoothed by downsampling and pixel grid snapping
+/// would use the supersampled grid. Therefore the path is rendered onto a layer
+/// at the output resolution, which is then scaled up without filtering.
+fn render_at_output_resolution(
+    path: &usvg::Path,
+    bbox: tiny_skia::Rect,
+    blend_mode: tiny_skia::BlendMode,
+    ctx: &Context,
+    transform: tiny_skia::Transform,
+    pixmap: &mut tiny_skia::PixmapMut,
+    render: fn(
+        &usvg::Path,
+        tiny_skia::BlendMode,
+        &Context,
+        tiny_skia::Transform,
+        &mut tiny_skia::PixmapMut,
+    ) -> Option<()>,
+) -> Option<()> {
+    let factor = ctx.supersampling as f32;
+    let transform = transform.post_scale(1.0 / factor, 1.0 / factor);
+
+    // Non-scaling strokes are not included in the bounding box, so expand it a bit more.
+    let bbox = bbox.transform(transform)?;
+    let outset = if path.vector_effect() == usvg::VectorEffect::NonScalingStroke {
+        path.stroke().map(|s| s.width().get()).unwrap_or(0.0) + 2.0
+    } else {
+        2.0
+    };
+    let size = tiny_skia::IntRect::from_xywh(
+        0,
+        0,
+        (pixmap.width() + ctx.supersampling - 1) / ctx.supersampling,
+        (pixmap.height() + ctx.supersampling - 1) / ctx.supersampling,
+    )?;
+    let ibbox = tiny_skia::IntRect::from_ltrb(
+        (bbox.left() - outset).floor() as i32,
+        (bbox.top() - outset).floor() as i32,
+        (bbox.right() + outset).ceil() as i32,
+        (bbox.bottom() + outset).ceil() as i32,
+    )?
+    .intersect(&size)?;
+
+    let mut layer = tiny_skia::Pixmap::new(ibbox.width(), ibbox.height())?;
+    let layer_ctx = Context {
+        max_bbox: size,
+        max_filter_bbox: size,
+        curve_tolerance: ctx.curve_tolerance.map(|t| t / factor),
+        linear_compositing: ctx.linear_compositing,
+        anti_alias: ctx.anti_alias,
+        supersampling: 1,
+    };
+    let layer_ts = transform.post_translate(-ibbox.x() as f32, -ibbox.y() as f32);
+    render(
+        path,
+        tiny_skia::BlendMode::SourceOver,
+        &layer_ctx,
+        layer_ts,
+        &mut layer.as_mut(),
+    );
+
+    let paint = tiny_skia::PixmapPaint {
+        quality: tiny_skia::FilterQuality::Nearest,
+        // `Clear` would ignore the layer content.
+        blend_mode: match blend_mode {
+            tiny_skia::BlendMode::Clear => tiny_skia::BlendMode::DestinationOut,
+            mode => mode,
+        },
+        ..tiny_skia::PixmapPaint::default()
+    };
+    pixmap.draw_pixmap(
+        0,
+        0,
+        layer.as_ref(),
+        &paint,
+        tiny_skia::Transform::from_row(
+            factor,
+            0.0,
+            0.0,
+            factor,
+            ibbox.x() as f32 * factor,
+            ibbox.y() as f32 * factor,
+        ),
+        None,
+    );
+
+    Some(())
+}
+
+/// Checks that a path should be anti-aliased.
+///
+/// Anti-aliasing can be disabled by `shape-rendering` or [`crate::AntialiasingQuality`].
+fn use_anti_aliasing(path: &usvg::Path, ctx: &Context) -> bool {
+    ctx.anti_alias && path.rendering_mode().use_shape_antialiasing()
+}
+
+/// Aligns path edges with the pixel grid when anti-aliasing is disabled.
+///
+/// This way, lines of the same width will always have the same thickness in pixels.
+/// Strokes with an odd width in pixels must be centered on pixel centers to cover whole pixels,
+/// therefore `stroke_width` along each axis is required for strokes.
+///
+/// Only paths consisting of horizontal and vertical lines are affected,
+/// as long as the transform has no rotation and skew.
+///
+/// The grid is the output one, therefore `transform` must map to output pixels.
+/// During supersampling, such paths are rendered by `render_at_output_resolution`.
+fn snap_to_pixel_grid(
+    path: &usvg::Path,
+    ctx: &Context,
+    stroke_width: Option<(f32, f32)>,
+    transform: tiny_skia::Transform,
+) -> Option<tiny_skia::Path> {
+    if use_anti_aliasing(path, ctx) || ctx.supersampling != 1 || transform.has_skew() {
+        return None;
+    }
+
+    if !is_rectilinear(path.data()) {
+        return None;
+    }
+
+    fn offset(width: f32) -> f32 {
+        if width.round() as i32 % 2 == 1 {
+            0.5
+        } else {
+            0.0
+        }
+    }
+
+    let (offset_x, offset_y) = match stroke_width {
+        Some((width_x, width_y)) => (offset(width_x), offset(width_y)),
+        None => (0.0, 0.0),
+    };
+
+    let ts_inv = transform.invert()?;
+    let snap = |p: tiny_skia::Point| {
+        let mut p = p;
+        transform.map_point(&mut p);
+        p.x = (p.x - offset_x).round() + offset_x;
+        p.y = (p.y - offset_y).round() + offset_y;
+        ts_inv.map_point(&mut p);
+        p
+    };
+
+    let mut builder = tiny_skia::PathBuilder::with_capacity(path.data().len(), path.data().len());
+    for segment in path.data().segments() {
+        match segment {
+            tiny_skia::PathSegment::MoveTo(p) => {
+                let p = snap(p);
+                builder.move_to(p.x, p.y);
+            }
+            tiny_skia::PathSegment::LineTo(p) => {
+                let p = snap(p);
+                builder.line_to(p.x, p.y);
+            }
+            tiny_skia::PathSegment::Close => builder.close(),
+            // Checked by `is_rectilinear`.
+            tiny_skia::PathSegment::QuadTo(..) | tiny_skia::PathSegment::CubicTo(..) => {}
+        }
+    }
+
+    builder.finish()
+}
+
+/// Approximates curves with lines according to [`crate::RenderOptions::curve_tolerance`].
+///
+/// The tolerance is set in pixels, therefore it has to be converted into path coordinates.
+fn flatten_curves(
+    data: &tiny_skia::Path,
+    ctx: &Context,
+    transform: tiny_skia::Transform,
+) -> Option<tiny_skia::Path> {
+    use usvg::FlattenPath;
+
+    let tolerance = ctx.curve_tolerance?;
+
+    let has_curves = data.verbs().iter().any(|v| {
+        matches!(
+            v,
+            usvg::tiny_skia_path::PathVerb::Quad | usvg::tiny_skia_path::PathVerb::Cubic
+        )
+    });
+    if !has_curves {
+        return None;
+    }
+
+    // Use the largest scale, so the tolerance would not be exceeded along any axis.
+    let (sx, sy) = transform.get_scale();
+    data.flatten((tolerance / sx.max(sy)) as f64)
+}
+
+/// Checks that path consists only of horizontal and vertical lines.
+fn is_rectilinear(path: &tiny_skia::Path) -> bool {
+    let mut start = tiny_skia::Point::zero();
+    let mut prev = tiny_skia::Point::zero();
+    let is_straight = |p1: tiny_skia::Point, p2: tiny_skia::Point| p1.x == p2.x || p1.y == p2.y;
+
+    for segment in path.segments() {
+        match segment {
+            tiny_skia::PathSegment::MoveTo(p) => {
+                start = p;
+                prev = p;
+            }
+            tiny_skia::PathSegment::LineTo(p) => {
+                if !is_straight(prev, p) {
+                    return false;
+                }
+
+                prev = p;
+            }
+            tiny_skia::PathSegment::Close => {
+                if !is_straight(prev, start) {
+                    return false;
+                }
+
+                prev = start;
+            }
+            tiny_skia::PathSegment::QuadTo(..) | tiny_skia::PathSegment::CubicTo(..) => {
+                return false
+            }
+        }
+    }
+
+    true
+}
+
 fn convert_linear_gradient(
     gradient: &usvg::LinearGradient,
     opacity: usvg::Opacity,
@@ -160,21 +552,43 @@ fn convert_base_gradient(
         usvg::SpreadMethod::Repeat => tiny_skia::SpreadMode::Repeat,
     };
 
+    let make_stop = |offset: f32, color: usvg::Color, stop_opacity: usvg::Opacity| {
+        let alpha = stop_opacity * opacity;
+        let color = tiny_skia::Color::from_rgba8(color.red, color.green, color.blue, alpha.to_u8());
+        tiny_skia::GradientStop::new(offset, color)
+    };
+
+    // tiny-skia interpolates only in sRGB, therefore other color spaces
+    // are approximated by additional stops.
+    let is_srgb = gradient.color_interpolation() == usvg::GradientInterpolation::SRGB;
+
     let mut points = Vec::with_capacity(gradient.stops().len());
-    for stop in gradient.stops() {
-        let alpha = stop.opacity() * opacity;
-        let color = tiny_skia::Color::from_rgba8(
-            stop.color().red,
-            stop.color().green,
-            stop.color().blue,
-            alpha.to_u8(),
-        );
-        points.push(tiny_skia::GradientStop::new(stop.offset().get(), color))
+    for (i, stop) in gradient.stops().iter().enumerate() {
+        points.push(make_stop(stop.offset().get(), stop.color(), stop.opacity()));
+
+        let Some(next) = gradient.stops().get(i + 1) else {
+            continue;
+        };
+
+        let (o1, o2) = (stop.offset().get(), next.offset().get());
+        if is_srgb || o1 >= o2 {
+            continue;
+        }
+
+        for n in 1..INTERPOLATION_STEPS {
+            let offset = o1 + (o2 - o1) * n as f32 / INTERPOLATION_STEPS as f32;
+            let (color, stop_opacity) = gradient.sample(offset);
+            points.push(make_stop(offset, color, stop_opacity));
+        }
     }
 
     Some((mode, points))
 }
 
+/// The number of segments each stops pair is split into
+/// when interpolating in a non-sRGB color space.
+const INTERPOLATION_STEPS: u32 = 16;
+
 fn render_pattern_pixmap(
     pattern: &usvg::Pattern,
     ctx: &Context,
@@ -195,10 +609,7 @@ fn render_pattern_pixmap(
     let transform = tiny_skia::Transform::from_scale(sx, sy);
     crate::render::render_nodes(pattern.root(), ctx, transform, &mut pixmap.as_mut());
 
-    let mut ts = tiny_skia::Transform::default();
-    ts = ts.pre_concat(pattern.transform());
-    ts = ts.pre_translate(rect.x(), rect.y());
-    ts = ts.pre_scale(1.0 / sx, 1.0 / sy);
+    let ts = pattern.tile_transform(0, 0).pre_scale(1.0 / sx, 1.0 / sy);
 
     Some((pixmap, ts))
 }
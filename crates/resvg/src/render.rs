@@ -5,6 +5,15 @@ use crate::OptionLog;
 
 pub struct Context {
     pub max_bbox: tiny_skia::IntRect,
+    // Filter regions cannot be limited to the tile when rendering tiles,
+    // since their size affects the result.
+    pub max_filter_bbox: tiny_skia::IntRect,
+    pub curve_tolerance: Option<f32>,
+    pub linear_compositing: bool,
+    pub anti_alias: bool,
+    // The number of pixels per output pixel along each axis.
+    // Greater than 1 only when supersampling.
+    pub supersampling: u32,
 }
 
 pub fn render_nodes(
@@ -18,6 +27,38 @@ pub fn render_nodes(
     }
 }
 
+/// Renders only nodes that intersect the `area`, which is in canvas coordinates.
+///
+/// Non-isolated groups are culled recursively, while all other nodes are rendered as a whole.
+pub fn render_nodes_in_area(
+    parent: &usvg::Group,
+    area: tiny_skia::Rect,
+    ctx: &Context,
+    transform: tiny_skia::Transform,
+    pixmap: &mut tiny_skia::PixmapMut,
+) {
+    for node in parent.children() {
+        let bbox = match node {
+            usvg::Node::Group(ref group) => group.abs_layer_bounding_box().to_rect(),
+            usvg::Node::Path(ref path) => path.abs_stroke_bounding_box(),
+            usvg::Node::Image(ref image) => image.abs_bounding_box(),
+            usvg::Node::Text(ref text) => text.abs_stroke_bounding_box(),
+        };
+
+        if bbox.intersect(&area).is_none() {
+            continue;
+        }
+
+        match node {
+            usvg::Node::Group(ref group) if !group.should_isolate() => {
+                let transform = transform.pre_concat(group.transform());
+                render_nodes_in_area(group, area, ctx, transform, pixmap);
+            }
+            _ => render_node(node, ctx, transform, pixmap),
+        }
+    }
+}
+
 pub fn render_node(
     node: &usvg::Node,
     ctx: &Context,
@@ -46,16 +87,50 @@ pub fn render_node(
     }
 }
 
+/// Renders `node` as a descendant of `ancestors`, ignoring their other children.
+///
+/// `ancestors` are ordered from the outermost one.
+pub fn render_node_with_ancestors(
+    ancestors: &[&usvg::Group],
+    node: &usvg::Node,
+    ctx: &Context,
+    transform: tiny_skia::Transform,
+    pixmap: &mut tiny_skia::PixmapMut,
+) {
+    match ancestors.split_first() {
+        Some((group, ancestors)) => {
+            render_group_with(group, ctx, transform, pixmap, &mut |transform, pixmap| {
+                render_node_with_ancestors(ancestors, node, ctx, transform, pixmap)
+            });
+        }
+        None => render_node(node, ctx, transform, pixmap),
+    }
+}
+
 fn render_group(
     group: &usvg::Group,
     ctx: &Context,
     transform: tiny_skia::Transform,
     pixmap: &mut tiny_skia::PixmapMut,
+) -> Option<()> {
+    render_group_with(group, ctx, transform, pixmap, &mut |transform, pixmap| {
+        render_nodes(group, ctx, transform, pixmap)
+    })
+}
+
+/// Renders group's content using `render_content` and applies group's
+/// opacity, clip path, mask, filters and blend mode to it.
+fn render_group_with(
+    group: &usvg::Group,
+    ctx: &Context,
+    transform: tiny_skia::Transform,
+    pixmap: &mut tiny_skia::PixmapMut,
+    render_content: &mut dyn FnMut(tiny_skia::Transform, &mut tiny_skia::PixmapMut),
 ) -> Option<()> {
     let transform = transform.pre_concat(group.transform());
 
     if !group.should_isolate() {
-        render_nodes(group, ctx, transform, pixmap);
+        render_content(transform, pixmap);
         return Some(());
     }
 
@@ -77,7 +152,7 @@ fn render_group(
         // Make sure our filter region is not bigger than 4x the canvas size.
         // This is required mainly to prevent huge filter regions that would tank the performance.
         // It should not affect the final result in any way.
-        crate::geom::fit_to_rect(bbox, ctx.max_bbox)?
+        crate::geom::fit_to_rect(bbox, ctx.max_filter_bbox)?
     };
 
     // Make sure our layer is not bigger than 4x the canvas size.
@@ -103,20 +178,31 @@ fn render_group(
     let mut sub_pixmap = tiny_skia::Pixmap::new(ibbox.width(), ibbox.height())
         .log_none(|| log::warn!("Failed to allocate a group layer for: {:?}.", ibbox))?;
 
-    render_nodes(group, ctx, transform, &mut sub_pixmap.as_mut());
+    render_content(transform, &mut sub_pixmap.as_mut());
 
     if !group.filters().is_empty() {
         for filter in group.filters() {
-            crate::filter::apply(filter, transform, &mut sub_pixmap);
+            crate::filter::apply(filter, ctx, transform, &mut sub_pixmap);
         }
     }
 
     if let Some(clip_path) = group.clip_path() {
-        crate::clip::apply(clip_path, transform, &mut sub_pixmap);
+        crate::clip::apply(clip_path, group.id(), ctx, transform, &mut sub_pixmap);
     }
 
     if let Some(mask) = group.mask() {
-        crate::mask::apply(mask, ctx, transform, &mut sub_pixmap);
+        crate::mask::apply(mask, group.id(), ctx, transform, &mut sub_pixmap);
+    }
+
+    if ctx.linear_compositing && group.blend_mode() == usvg::BlendMode::Normal {
+        draw_pixmap_linear(
+            ibbox.x(),
+            ibbox.y(),
+            sub_pixmap.as_ref(),
+            group.opacity().get(),
+            pixmap,
+        );
+        return Some(());
     }
 
     let paint = tiny_skia::PixmapPaint {
@@ -125,18 +211,74 @@ fn render_group(
         quality: tiny_skia::FilterQuality::Nearest,
     };
 
-    pixmap.draw_pixmap(
-        ibbox.x(),
-        ibbox.y(),
-        sub_pixmap.as_ref(),
-        &paint,
-        tiny_skia::Transform::identity(),
-        None,
-    );
+    // `draw_pixmap` fills an extra row and column of pixels with the layer's edge
+    // when the layer starts above or to the left of the canvas. Not a problem for layers
+    // with transparent edges, but filter layers are cut at the filter region.
+    // A translation is handled correctly.
+    let (x, y, layer_ts) = if ibbox.x() < 0 || ibbox.y() < 0 {
+        let ts = tiny_skia::Transform::from_translate(ibbox.x() as f32, ibbox.y() as f32);
+        (0, 0, ts)
+    } else {
+        (ibbox.x(), ibbox.y(), tiny_skia::Transform::identity())
+    };
+
+    pixmap.draw_pixmap(x, y, sub_pixmap.as_ref(), &paint, layer_ts, None);
 
     Some(())
 }
 
+/// Draws a layer using the source-over compositing in linear RGB.
+///
+/// Colors are processed as floats to avoid the precision loss of 8-bit linear values.
+fn draw_pixmap_linear(
+    x: i32,
+    y: i32,
+    layer: tiny_skia::PixmapRef,
+    opacity: f32,
+    pixmap: &mut tiny_skia::PixmapMut,
+) {
+    let (width, height) = (pixmap.width() as i32, pixmap.height() as i32);
+    let dst = pixmap.pixels_mut();
+    for (i, src) in layer.pixels().iter().enumerate() {
+        let dx = x + (i as u32 % layer.width()) as i32;
+        let dy = y + (i as u32 / layer.width()) as i32;
+        if src.alpha() == 0 || dx < 0 || dy < 0 || dx >= width || dy >= height {
+            continue;
+        }
+
+        let d = &mut dst[(dy * width + dx) as usize];
+        let s = to_linear(*src);
+        let b = to_linear(*d);
+        let sa = s[3] * opacity;
+        *d = from_linear([0, 1, 2, 3].map(|c| s[c] * opacity + b[c] * (1.0 - sa)));
+    }
+}
+
+/// Converts a color to premultiplied linear RGB.
+fn to_linear(c: tiny_skia::PremultipliedColorU8) -> [f32; 4] {
+    let c = c.demultiply();
+    let a = c.alpha() as f32 / 255.0;
+    let [r, g, b] = usvg::Color::new_rgb(c.red(), c.green(), c.blue()).to_linear_rgb();
+    [r * a, g * a, b * a, a]
+}
+
+/// Converts a color from premultiplied linear RGB.
+fn from_linear(c: [f32; 4]) -> tiny_skia::PremultipliedColorU8 {
+    let a = c[3].clamp(0.0, 1.0);
+    if a == 0.0 {
+        return tiny_skia::PremultipliedColorU8::TRANSPARENT;
+    }
+
+    let color = usvg::Color::from_linear_rgb([c[0] / a, c[1] / a, c[2] / a]);
+    tiny_skia::ColorU8::from_rgba(
+        color.red,
+        color.green,
+        color.blue,
+        (a * 255.0).round() as u8,
+    )
+    .premultiply()
+}
+
 pub fn convert_blend_mode(mode: usvg::BlendMode) -> tiny_skia::BlendMode {
     match mode {
         usvg::BlendMode::Normal => tiny_skia::BlendMode::SourceOver,
@@ -1,7 +1,14 @@
 // Copyright 2023 the Resvg Authors
 // SPDX-License-Identifier: Apache-2.0 OR MIT
 
-use crate::{render_extra, render_extra_with_scale, render_node};
+use crate::{
+    render_cropped, render_extra, render_extra_alphas, render_extra_prepared,
+    render_extra_with_options, render_extra_with_scale, render_node, render_node_with_context,
+    render_nodes, render_png, render_tiled, render_transformed, render_with_curve_tolerance,
+};
+
+#[cfg(feature = "debug-hooks")]
+use crate::render_debug_layers;
 
 #[test]
 fn group_with_only_transform() {
@@ -43,6 +50,32 @@ fn stroke_linecap_square() {
     assert_eq!(render_extra("extra/stroke-linecap=square"), 0);
 }
 
+#[test]
+fn crisp_edges_snapping() {
+    assert_eq!(render_extra("extra/crisp-edges-snapping"), 0);
+}
+
+#[test]
+fn crisp_edges_supersampled() {
+    use resvg::AntialiasingQuality;
+
+    // Shapes without anti-aliasing must stay aliased and snapped to the output pixel grid.
+    for name in [
+        "extra/crisp-edges-snapping",
+        "extra/crisp-edges-supersampled",
+    ] {
+        let expected = render_extra_alphas(name, AntialiasingQuality::Default);
+        assert!(expected.iter().all(|a| *a == 0 || *a == 255));
+
+        for quality in [
+            AntialiasingQuality::Supersample2x,
+            AntialiasingQuality::Supersample4x,
+        ] {
+            assert!(render_extra_alphas(name, quality) == expected, "{}", name);
+        }
+    }
+}
+
 #[test]
 fn miter_join_with_acute_angle() {
     assert_eq!(render_extra("extra/miter-join-with-acute-angle"), 0);
@@ -80,3 +113,386 @@ fn render_node_filter_on_empty_group() {
 fn render_node_filter_with_transform_on_shape() {
     assert_eq!(render_node("extra/filter-with-transform-on-shape", "g1"), 0);
 }
+
+#[test]
+fn render_node_with_ancestors_context() {
+    assert_eq!(
+        render_node_with_context("extra/node-with-context", "rect1"),
+        0
+    );
+}
+
+#[test]
+fn render_transformed_rotate() {
+    let ts = tiny_skia::Transform::from_rotate(90.0);
+    assert_eq!(render_transformed("extra/render-transformed-rotate", ts), 0);
+}
+
+#[test]
+fn render_transformed_flip() {
+    let ts = tiny_skia::Transform::from_scale(-1.0, 1.0);
+    assert_eq!(render_transformed("extra/render-transformed-flip", ts), 0);
+}
+
+#[test]
+fn render_transformed_fractional_size() {
+    let svg = "<svg xmlns='http://www.w3.org/2000/svg' width='200' height='100'/>";
+    let tree = usvg::Tree::from_str(svg, &usvg::Options::default()).unwrap();
+    // 200.5x100.25 is rounded outwards.
+    let ts = tiny_skia::Transform::from_scale(1.0025, 1.0025);
+    let pixmap = resvg::render_transformed(&tree, ts).unwrap();
+    assert_eq!((pixmap.width(), pixmap.height()), (201, 101));
+}
+
+#[test]
+fn render_cropped_to_content() {
+    let ts = tiny_skia::Transform::from_scale(2.0, 2.0);
+    let (diff, area) = render_cropped("extra/render-cropped", ts);
+    assert_eq!(diff, 0);
+    assert_eq!(area, tiny_skia::IntRect::from_xywh(56, 76, 88, 48).unwrap());
+}
+
+#[test]
+fn constant_mask() {
+    assert_eq!(render_extra("extra/constant-mask"), 0);
+}
+
+#[test]
+fn constant_mask_flattened() {
+    let flatten = |tree: &mut usvg::Tree| {
+        tree.flatten_masks();
+        assert!(tree.masks().is_empty());
+    };
+    assert_eq!(render_extra_prepared("extra/constant-mask", flatten, 1), 0);
+}
+
+#[test]
+fn turbulence_is_bit_exact() {
+    // Turbulence relies only on IEEE 754 operations with exact rounding,
+    // therefore it must produce identical results on all platforms.
+    assert_eq!(render_extra_prepared("extra/turbulence", |_| {}, 0), 0);
+}
+
+#[test]
+fn curve_tolerance() {
+    // A small tolerance differs from the rasterizer's own curves approximation
+    // only in anti-aliasing, i.e. by less than a quarter of pixel coverage.
+    assert!(render_with_curve_tolerance("extra/curve-tolerance", 0.05) <= 64);
+    assert!(render_with_curve_tolerance("extra/curve-tolerance", 4.0) > 128);
+}
+
+#[test]
+fn render_transformed_overflow_visible() {
+    // The drop shadow outside the viewport is rendered as well.
+    let ts = tiny_skia::Transform::from_scale(2.0, 2.0);
+    assert_eq!(render_transformed("extra/overflow-visible", ts), 0);
+}
+
+#[test]
+fn encode_png16() {
+    let opt = resvg::PngOptions {
+        high_bit_depth: true,
+        ..resvg::PngOptions::default()
+    };
+    let (info, data, data8) = render_png("extra/png16", &opt);
+    assert_eq!(info.color_type, png::ColorType::Rgba);
+    assert_eq!(info.bit_depth, png::BitDepth::Sixteen);
+
+    let data16: Vec<u16> = data
+        .chunks(2)
+        .map(|c| u16::from_be_bytes([c[0], c[1]]))
+        .collect();
+    assert_eq!(data16.len(), data8.len());
+
+    let mut has_extra_precision = false;
+    for (c16, c8) in data16.chunks(4).zip(data8.chunks(4)) {
+        for i in 0..4 {
+            // Both are demultiplied from the same premultiplied values.
+            assert!((c16[i] as f32 / 257.0 - c8[i] as f32).abs() <= 1.0);
+        }
+
+        if c16[3] == u16::MAX {
+            assert!(c16.iter().zip(c8).all(|(a, b)| *a == *b as u16 * 257));
+        } else if c16[..3].iter().any(|c| c % 257 != 0) {
+            has_extra_precision = true;
+        }
+    }
+
+    assert!(has_extra_precision);
+}
+
+#[test]
+fn encode_png_color_profile() {
+    let (info, _, _) = render_png("extra/png16", &resvg::PngOptions::default());
+    assert!(info.srgb.is_none());
+    assert!(info.icc_profile.is_none());
+
+    let opt = resvg::PngOptions {
+        color_profile: resvg::PngColorProfile::SRGB,
+        ..resvg::PngOptions::default()
+    };
+    let (info, _, _) = render_png("extra/png16", &opt);
+    assert_eq!(info.srgb, Some(png::SrgbRenderingIntent::Perceptual));
+    assert_eq!(info.source_gamma.map(|g| g.into_scaled()), Some(45455));
+    assert!(info.source_chromaticities.is_some());
+
+    // Any data can be stored, since the profile is not parsed.
+    let icc = b"not a real profile, but long enough to be compressed".repeat(4);
+    let opt = resvg::PngOptions {
+        color_profile: resvg::PngColorProfile::ICC(icc.clone()),
+        ..resvg::PngOptions::default()
+    };
+    let (info, data, data8) = render_png("extra/png16", &opt);
+    assert_eq!(info.icc_profile.as_deref(), Some(icc.as_slice()));
+    // Colors are not converted.
+    assert_eq!(data, data8);
+}
+
+#[test]
+fn write_png() {
+    let mut pixmap = tiny_skia::Pixmap::new(300, 200).unwrap();
+    pixmap.fill(tiny_skia::Color::from_rgba8(46, 139, 87, 100));
+
+    let opt = resvg::PngOptions::default();
+    let mut data = Vec::new();
+    resvg::write_png(pixmap.as_ref(), &opt, &mut data).unwrap();
+    assert_eq!(data, resvg::encode_png(pixmap.as_ref(), &opt).unwrap());
+
+    // Row by row encoding produces the same image as `tiny-skia`.
+    let expected = tiny_skia::Pixmap::decode_png(&pixmap.encode_png().unwrap()).unwrap();
+    assert_eq!(tiny_skia::Pixmap::decode_png(&data).unwrap(), expected);
+
+    let opt = resvg::PngOptions {
+        high_bit_depth: true,
+        ..resvg::PngOptions::default()
+    };
+    let mut data = Vec::new();
+    resvg::write_png(pixmap.as_ref(), &opt, &mut data).unwrap();
+    assert_eq!(data, resvg::encode_png(pixmap.as_ref(), &opt).unwrap());
+
+    // Write errors are reported as is.
+    let mut data = [0; 64];
+    let res = resvg::write_png(pixmap.as_ref(), &opt, &mut data[..]);
+    assert!(matches!(res, Err(resvg::PngError::Io(_))));
+}
+
+#[test]
+fn render_tiles() {
+    // Tiles are not aligned with the canvas size.
+    let ts = tiny_skia::Transform::from_scale(2.0, 2.0);
+    let tile_size = tiny_skia::IntSize::from_wh(64, 48).unwrap();
+    let opt = resvg::RenderOptions::default();
+    assert_eq!(render_tiled("extra/tiles", ts, &opt, tile_size), 0);
+}
+
+#[test]
+fn render_tiles_with_curves() {
+    let ts = tiny_skia::Transform::from_scale(2.0, 2.0);
+    let tile_size = tiny_skia::IntSize::from_wh(64, 48).unwrap();
+    let opt = resvg::RenderOptions::default();
+    // The rasterizer splits curves crossing a tile edge, which changes their flattening
+    // and therefore anti-aliasing along the whole curve. About 270 pixels are affected.
+    assert!(render_tiled("extra/tiles-curves", ts, &opt, tile_size) <= 300);
+}
+
+#[test]
+fn render_tiles_with_curve_tolerance() {
+    let tile_size = tiny_skia::IntSize::from_wh(40, 40).unwrap();
+    let opt = resvg::RenderOptions {
+        curve_tolerance: Some(4.0),
+        ..resvg::RenderOptions::default()
+    };
+    let ts = tiny_skia::Transform::default();
+    assert_eq!(
+        render_tiled("extra/curve-tolerance", ts, &opt, tile_size),
+        0
+    );
+}
+
+#[test]
+fn linear_compositing() {
+    let opt = resvg::RenderOptions {
+        linear_compositing: true,
+        ..resvg::RenderOptions::default()
+    };
+    assert_eq!(
+        render_extra_with_options("extra/linear-compositing", &opt),
+        0
+    );
+    // Compositing in sRGB produces darker overlaps.
+    assert_ne!(render_extra("extra/linear-compositing"), 0);
+}
+
+#[test]
+fn checkerboard() {
+    let opt = resvg::RenderOptions {
+        checkerboard: Some(resvg::Checkerboard {
+            cell_size: 20,
+            ..resvg::Checkerboard::default()
+        }),
+        ..resvg::RenderOptions::default()
+    };
+    assert_eq!(render_extra_with_options("extra/checkerboard", &opt), 0);
+}
+
+#[test]
+fn isolation_without_opacity() {
+    // A `multiply` child of an isolated group must not blend with the background.
+    assert_eq!(render_extra("extra/isolation-without-opacity"), 0);
+}
+
+#[cfg(feature = "debug-hooks")]
+#[test]
+fn debug_layers() {
+    use resvg::debug::LayerKind;
+
+    let layers = render_debug_layers("extra/debug-layers");
+    let layers: Vec<_> = layers
+        .iter()
+        .map(|(kind, id, node_id)| (*kind, id.as_str(), node_id.as_str()))
+        .collect();
+    assert_eq!(
+        layers,
+        [
+            (LayerKind::ClipPath, "clip2", "g1"),
+            (LayerKind::ClipPath, "clip1", "g1"),
+            (LayerKind::Mask, "mask1", "g2"),
+        ]
+    );
+}
+
+#[cfg(feature = "debug-hooks")]
+#[test]
+fn debug_layers_hook_after_panic() {
+    let hook_data = std::rc::Rc::new(());
+    let hook_data2 = hook_data.clone();
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        resvg::debug::with_layer_hook(
+            move |_| drop(hook_data2.clone()),
+            || panic!("rendering failed"),
+        )
+    }));
+    assert!(result.is_err());
+
+    // The hook is removed during unwinding.
+    assert_eq!(std::rc::Rc::strong_count(&hook_data), 1);
+}
+
+#[test]
+fn render_sprites() {
+    let ids = ["icon1", "icon2", "missing", "icon3", "icon1"];
+    // Like in `render_node`, the stroke of a path is not included in its layer.
+    let size = |w, h| tiny_skia::IntSize::from_wh(w, h);
+
+    let opt = resvg::RenderOptions::default();
+    let sizes = render_nodes("extra/sprites", &ids, tiny_skia::Transform::default(), &opt);
+    assert_eq!(
+        sizes,
+        [size(20, 20), size(30, 10), None, size(20, 20), size(20, 20)]
+    );
+
+    let sizes = render_nodes(
+        "extra/sprites",
+        &ids,
+        tiny_skia::Transform::from_scale(2.0, 2.0),
+        &opt,
+    );
+    assert_eq!(
+        sizes,
+        [size(40, 40), size(60, 20), None, size(40, 40), size(40, 40)]
+    );
+
+    let opt = resvg::RenderOptions {
+        curve_tolerance: Some(4.0),
+        ..resvg::RenderOptions::default()
+    };
+    let sizes = render_nodes("extra/sprites", &ids, tiny_skia::Transform::default(), &opt);
+    assert_eq!(
+        sizes,
+        [size(20, 20), size(30, 10), None, size(20, 20), size(20, 20)]
+    );
+}
+
+#[test]
+fn antialiasing_quality() {
+    use resvg::AntialiasingQuality;
+
+    // The exact pixel coverage of the area between the top edge and `y = 13/40 * x`.
+    let coverage = |x: u32, y: u32| {
+        let steps = 1000;
+        (0..steps)
+            .map(|i| {
+                let px = x as f32 + (i as f32 + 0.5) / steps as f32;
+                (px * 13.0 / 40.0 - y as f32).clamp(0.0, 1.0)
+            })
+            .sum::<f32>()
+            / steps as f32
+    };
+
+    let error = |alphas: &[u8]| {
+        let mut sum = 0.0;
+        for y in 0..40 {
+            for x in 0..40 {
+                let a = alphas[(y * 40 + x) as usize] as f32 / 255.0;
+                sum += (a - coverage(x, y)).abs();
+            }
+        }
+        sum
+    };
+
+    let alphas = render_extra_alphas("extra/antialiasing", AntialiasingQuality::Disabled);
+    assert!(alphas.iter().all(|a| *a == 0 || *a == 255));
+
+    let errors: Vec<_> = [
+        AntialiasingQuality::Disabled,
+        AntialiasingQuality::Default,
+        AntialiasingQuality::Supersample2x,
+        AntialiasingQuality::Supersample4x,
+    ]
+    .into_iter()
+    .map(|q| error(&render_extra_alphas("extra/antialiasing", q)))
+    .collect();
+
+    // Each quality level is closer to the exact coverage.
+    assert!(errors.windows(2).all(|w| w[1] < w[0]), "{:?}", errors);
+}
+
+#[test]
+fn render_empty_tree() {
+    let render = |svg: &str, opt: &resvg::RenderOptions| {
+        let tree = usvg::Tree::from_str(svg, &usvg::Options::default()).unwrap();
+        let mut pixmap = tiny_skia::Pixmap::new(20, 20).unwrap();
+        let drawn = resvg::render_with_options(
+            &tree,
+            tiny_skia::Transform::default(),
+            opt,
+            &mut pixmap.as_mut(),
+        );
+        (drawn, pixmap)
+    };
+
+    let empty = "
+    <svg width='20' height='20' xmlns='http://www.w3.org/2000/svg'>
+        <rect width='10' height='10' opacity='0'/>
+    </svg>";
+    let (drawn, pixmap) = render(empty, &resvg::RenderOptions::default());
+    assert!(!drawn);
+    assert!(pixmap.pixels().iter().all(|p| p.alpha() == 0));
+
+    // The checkerboard is still drawn.
+    let opt = resvg::RenderOptions {
+        checkerboard: Some(resvg::Checkerboard::default()),
+        ..resvg::RenderOptions::default()
+    };
+    let (drawn, pixmap) = render(empty, &opt);
+    assert!(!drawn);
+    assert!(pixmap.pixels().iter().all(|p| p.alpha() == 255));
+
+    let (drawn, _) = render(
+        "<svg width='20' height='20' xmlns='http://www.w3.org/2000/svg'>
+            <rect width='10' height='10'/>
+        </svg>",
+        &resvg::RenderOptions::default(),
+    );
+    assert!(drawn);
+}
@@ -195,6 +195,292 @@ pub fn render_node(name: &str, id: &str) -> usize {
     pixels_d
 }
 
+pub fn render_transformed(name: &str, transform: tiny_skia::Transform) -> usize {
+    let tree = load_extra(name);
+    let pixmap = resvg::render_transformed(&tree, transform).unwrap();
+    compare_extra(name, pixmap)
+}
+
+pub fn render_cropped(name: &str, transform: tiny_skia::Transform) -> (usize, tiny_skia::IntRect) {
+    let tree = load_extra(name);
+    let (pixmap, area) = resvg::render_cropped(&tree, transform).unwrap();
+    (compare_extra(name, pixmap), area)
+}
+
+pub fn render_node_with_context(name: &str, id: &str) -> usize {
+    let tree = load_extra(name);
+    let node = tree.node_by_id(id).unwrap();
+    let size = node.abs_layer_bounding_box().unwrap().size().to_int_size();
+    let mut pixmap = tiny_skia::Pixmap::new(size.width(), size.height()).unwrap();
+    resvg::render_node_with_context(
+        &tree,
+        id,
+        tiny_skia::Transform::identity(),
+        &resvg::RenderOptions::default(),
+        &mut pixmap.as_mut(),
+    )
+    .unwrap();
+    compare_extra(name, pixmap)
+}
+
+/// Renders nodes via `resvg::render_nodes` and compares them
+/// with `resvg::render_node_with_options`.
+///
+/// Returns pixmap sizes.
+pub fn render_nodes(
+    name: &str,
+    ids: &[&str],
+    transform: tiny_skia::Transform,
+    opt: &resvg::RenderOptions,
+) -> Vec<Option<tiny_skia::IntSize>> {
+    let tree = load_extra(name);
+    let pixmaps = resvg::render_nodes(&tree, ids, transform, opt);
+    assert_eq!(pixmaps.len(), ids.len());
+
+    for (id, pixmap) in ids.iter().zip(&pixmaps) {
+        let Some(pixmap) = pixmap else {
+            continue;
+        };
+
+        let node = tree.node_by_id(id).unwrap();
+        let mut expected = tiny_skia::Pixmap::new(pixmap.width(), pixmap.height()).unwrap();
+        resvg::render_node_with_options(node, transform, opt, &mut expected.as_mut()).unwrap();
+        assert!(expected.data() == pixmap.data(), "'{}' differs", id);
+    }
+
+    pixmaps
+        .iter()
+        .map(|pixmap| {
+            let pixmap = pixmap.as_ref()?;
+            tiny_skia::IntSize::from_wh(pixmap.width(), pixmap.height())
+        })
+        .collect()
+}
+
+/// Compares rendering with and without `resvg::RenderOptions::curve_tolerance`.
+///
+/// Returns the maximum difference between channels.
+pub fn render_with_curve_tolerance(name: &str, tolerance: f32) -> u8 {
+    let tree = load_extra(name);
+    let size = tree.size().to_int_size();
+
+    let mut expected = tiny_skia::Pixmap::new(size.width(), size.height()).unwrap();
+    resvg::render(
+        &tree,
+        tiny_skia::Transform::default(),
+        &mut expected.as_mut(),
+    );
+
+    let opt = resvg::RenderOptions {
+        curve_tolerance: Some(tolerance),
+        ..resvg::RenderOptions::default()
+    };
+    let mut actual = tiny_skia::Pixmap::new(size.width(), size.height()).unwrap();
+    resvg::render_with_options(
+        &tree,
+        tiny_skia::Transform::default(),
+        &opt,
+        &mut actual.as_mut(),
+    );
+
+    // Compare premultiplied values, since a demultiplication amplifies rounding errors
+    // of semi-transparent pixels.
+    expected
+        .data()
+        .iter()
+        .zip(actual.data())
+        .map(|(a, b)| a.abs_diff(*b))
+        .max()
+        .unwrap_or(0)
+}
+
+/// Renders an image using the specified rendering options.
+pub fn render_extra_with_options(name: &str, opt: &resvg::RenderOptions) -> usize {
+    let tree = load_extra(name);
+    let size = tree.size().to_int_size();
+    let mut pixmap = tiny_skia::Pixmap::new(size.width(), size.height()).unwrap();
+    resvg::render_with_options(
+        &tree,
+        tiny_skia::Transform::default(),
+        opt,
+        &mut pixmap.as_mut(),
+    );
+    compare_extra(name, pixmap)
+}
+
+/// Renders an image using the specified anti-aliasing quality and returns its alpha channel.
+pub fn render_extra_alphas(name: &str, quality: resvg::AntialiasingQuality) -> Vec<u8> {
+    let tree = load_extra(name);
+    let size = tree.size().to_int_size();
+    let opt = resvg::RenderOptions {
+        antialiasing_quality: quality,
+        ..resvg::RenderOptions::default()
+    };
+    let mut pixmap = tiny_skia::Pixmap::new(size.width(), size.height()).unwrap();
+    resvg::render_with_options(
+        &tree,
+        tiny_skia::Transform::default(),
+        &opt,
+        &mut pixmap.as_mut(),
+    );
+    pixmap.pixels().iter().map(|p| p.alpha()).collect()
+}
+
+/// Renders an image after modifying the tree using `prepare`.
+///
+/// Pixels are counted as different when any channel differs by more than `tolerance`.
+/// Premultiplied values are compared, since a demultiplication amplifies rounding errors
+/// of semi-transparent pixels.
+pub fn render_extra_prepared(
+    name: &str,
+    prepare: impl FnOnce(&mut usvg::Tree),
+    tolerance: u8,
+) -> usize {
+    let mut tree = load_extra(name);
+    prepare(&mut tree);
+
+    let size = tree.size().to_int_size();
+    let mut pixmap = tiny_skia::Pixmap::new(size.width(), size.height()).unwrap();
+    resvg::render(&tree, tiny_skia::Transform::default(), &mut pixmap.as_mut());
+
+    // pixmap.save_png(&format!("tests/{}.png", name)).unwrap();
+
+    let expected = tiny_skia::Pixmap::load_png(format!("tests/{}.png", name)).unwrap();
+    assert_eq!(expected.data().len(), pixmap.data().len());
+
+    expected
+        .data()
+        .chunks(4)
+        .zip(pixmap.data().chunks(4))
+        .filter(|(a, b)| {
+            a.iter()
+                .zip(b.iter())
+                .any(|(a, b)| a.abs_diff(*b) > tolerance)
+        })
+        .count()
+}
+
+/// Renders an image, encodes it via `resvg::encode_png` and decodes it back.
+///
+/// Returns the decoded PNG info and data, and the channels of a `tiny-skia` pixmap.
+pub fn render_png(name: &str, opt: &resvg::PngOptions) -> (png::Info<'static>, Vec<u8>, Vec<u8>) {
+    let tree = load_extra(name);
+    let size = tree.size().to_int_size();
+    let mut pixmap = tiny_skia::Pixmap::new(size.width(), size.height()).unwrap();
+    resvg::render(&tree, tiny_skia::Transform::default(), &mut pixmap.as_mut());
+
+    let data = resvg::encode_png(pixmap.as_ref(), opt).unwrap();
+    let decoder = png::Decoder::new(data.as_slice());
+    let mut reader = decoder.read_info().unwrap();
+    let mut img_data = vec![0; reader.output_buffer_size()];
+    reader.next_frame(&mut img_data).unwrap();
+    let info = reader.info().clone();
+
+    let mut rgba = pixmap.take();
+    demultiply_alpha(rgba.as_mut_slice().as_rgba_mut());
+
+    (info, img_data, rgba)
+}
+
+/// Compares tiled rendering with a regular one.
+///
+/// Returns the number of different pixels.
+pub fn render_tiled(
+    name: &str,
+    transform: tiny_skia::Transform,
+    opt: &resvg::RenderOptions,
+    tile_size: tiny_skia::IntSize,
+) -> usize {
+    let tree = load_extra(name);
+    let size = tree.size().to_int_size().scale_by(transform.sx).unwrap();
+
+    let mut expected = tiny_skia::Pixmap::new(size.width(), size.height()).unwrap();
+    resvg::render_with_options(&tree, transform, opt, &mut expected.as_mut());
+
+    let mut actual = tiny_skia::Pixmap::new(size.width(), size.height()).unwrap();
+    for tile in resvg::render_tiles(&tree, transform, opt, size, tile_size) {
+        let (tile, rect) = tile.unwrap();
+        assert!(rect.width() <= tile_size.width() && rect.height() <= tile_size.height());
+        actual.draw_pixmap(
+            rect.x(),
+            rect.y(),
+            tile.as_ref(),
+            &tiny_skia::PixmapPaint::default(),
+            tiny_skia::Transform::identity(),
+            None,
+        );
+    }
+
+    expected
+        .data()
+        .as_rgba()
+        .iter()
+        .zip(actual.data().as_rgba())
+        .filter(|(a, b)| is_pix_diff(**a, **b))
+        .count()
+}
+
+#[cfg(feature = "debug-hooks")]
+pub fn render_debug_layers(name: &str) -> Vec<(resvg::debug::LayerKind, String, String)> {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    let tree = load_extra(name);
+    let size = tree.size().to_int_size();
+    let mut pixmap = tiny_skia::Pixmap::new(size.width(), size.height()).unwrap();
+
+    let layers = Rc::new(RefCell::new(Vec::new()));
+    let layers2 = layers.clone();
+    resvg::debug::with_layer_hook(
+        move |layer| {
+            let info = (layer.kind, layer.id.to_string(), layer.node_id.to_string());
+            layers2.borrow_mut().push(info);
+        },
+        || resvg::render(&tree, tiny_skia::Transform::default(), &mut pixmap.as_mut()),
+    );
+
+    // The hook is dropped after rendering.
+    Rc::try_unwrap(layers).unwrap().into_inner()
+}
+
+fn load_extra(name: &str) -> usvg::Tree {
+    let svg_path = format!("tests/{}.svg", name);
+
+    let opt = usvg::Options {
+        fontdb: GLOBAL_FONTDB.clone(),
+        ..usvg::Options::default()
+    };
+
+    let svg_data = std::fs::read(&svg_path).unwrap();
+    usvg::Tree::from_data(&svg_data, &opt).unwrap()
+}
+
+fn compare_extra(name: &str, pixmap: tiny_skia::Pixmap) -> usize {
+    let png_path = format!("tests/{}.png", name);
+
+    // pixmap.save_png(&png_path).unwrap();
+
+    let mut rgba = pixmap.take();
+    demultiply_alpha(rgba.as_mut_slice().as_rgba_mut());
+
+    let expected_data = load_png(&png_path);
+    assert_eq!(expected_data.len(), rgba.len());
+
+    let mut pixels_d = 0;
+    for (a, b) in expected_data
+        .as_slice()
+        .as_rgba()
+        .iter()
+        .zip(rgba.as_rgba())
+    {
+        if is_pix_diff(*a, *b) {
+            pixels_d += 1;
+        }
+    }
+
+    pixels_d
+}
+
 fn load_png(path: &str) -> Vec<u8> {
     let data = std::fs::read(path).unwrap();
     let mut decoder = png::Decoder::new(data.as_slice());
@@ -141,6 +141,8 @@ use crate::render;
 #[test] fn filters_feDiffuseLighting_surfaceScale_eq_0() { assert_eq!(render("tests/filters/feDiffuseLighting/surfaceScale=0"), 0); }
 #[test] fn filters_feDiffuseLighting_surfaceScale_eq_1_33() { assert_eq!(render("tests/filters/feDiffuseLighting/surfaceScale=1.33"), 0); }
 #[test] fn filters_feDiffuseLighting_surfaceScale_eq_5() { assert_eq!(render("tests/filters/feDiffuseLighting/surfaceScale=5"), 0); }
+#[test] fn filters_feDisplacementMap_channel_selectors() { assert_eq!(render("tests/filters/feDisplacementMap/channel-selectors"), 0); }
+#[test] fn filters_feDisplacementMap_ramp_map() { assert_eq!(render("tests/filters/feDisplacementMap/ramp-map"), 0); }
 #[test] fn filters_feDisplacementMap_simple_case() { assert_eq!(render("tests/filters/feDisplacementMap/simple-case"), 0); }
 #[test] fn filters_feDistantLight_default_attributes() { assert_eq!(render("tests/filters/feDistantLight/default-attributes"), 0); }
 #[test] fn filters_feDistantLight_negative_azimuth_and_elevation() { assert_eq!(render("tests/filters/feDistantLight/negative-azimuth-and-elevation"), 0); }
@@ -422,6 +424,7 @@ use crate::render;
 #[test] fn masking_clipPath_clipping_with_complex_text_2() { assert_eq!(render("tests/masking/clipPath/clipping-with-complex-text-2"), 0); }
 #[test] fn masking_clipPath_clipping_with_complex_text_and_clip_rule() { assert_eq!(render("tests/masking/clipPath/clipping-with-complex-text-and-clip-rule"), 0); }
 #[test] fn masking_clipPath_clipping_with_text() { assert_eq!(render("tests/masking/clipPath/clipping-with-text"), 0); }
+#[test] fn masking_clipPath_concentric_subpaths_with_evenodd() { assert_eq!(render("tests/masking/clipPath/concentric-subpaths-with-evenodd"), 0); }
 #[test] fn masking_clipPath_fill_has_no_effect() { assert_eq!(render("tests/masking/clipPath/fill-has-no-effect"), 0); }
 #[test] fn masking_clipPath_filter_has_no_effect() { assert_eq!(render("tests/masking/clipPath/filter-has-no-effect"), 0); }
 #[test] fn masking_clipPath_g_is_not_a_valid_child() { assert_eq!(render("tests/masking/clipPath/g-is-not-a-valid-child"), 0); }
@@ -468,6 +471,7 @@ use crate::render;
 #[test] fn masking_mask_mask_on_self_with_mixed_mask_type() { assert_eq!(render("tests/masking/mask/mask-on-self-with-mixed-mask-type"), 0); }
 #[test] fn masking_mask_mask_on_self() { assert_eq!(render("tests/masking/mask/mask-on-self"), 0); }
 #[test] fn masking_mask_mask_type_in_style() { assert_eq!(render("tests/masking/mask/mask-type-in-style"), 0); }
+#[test] fn masking_mask_mask_type_eq_alpha_with_gradient() { assert_eq!(render("tests/masking/mask/mask-type=alpha-with-gradient"), 0); }
 #[test] fn masking_mask_mask_type_eq_alpha() { assert_eq!(render("tests/masking/mask/mask-type=alpha"), 0); }
 #[test] fn masking_mask_mask_type_eq_invalid() { assert_eq!(render("tests/masking/mask/mask-type=invalid"), 0); }
 #[test] fn masking_mask_mask_type_eq_luminance() { assert_eq!(render("tests/masking/mask/mask-type=luminance"), 0); }
@@ -496,6 +500,7 @@ use crate::render;
 #[test] fn masking_mask_with_opacity_1() { assert_eq!(render("tests/masking/mask/with-opacity-1"), 0); }
 #[test] fn masking_mask_with_opacity_2() { assert_eq!(render("tests/masking/mask/with-opacity-2"), 0); }
 #[test] fn masking_mask_with_opacity_3() { assert_eq!(render("tests/masking/mask/with-opacity-3"), 0); }
+#[test] fn paint_servers_linearGradient_attributes_via_xlink_href_chain() { assert_eq!(render("tests/paint-servers/linearGradient/attributes-via-xlink-href-chain"), 0); }
 #[test] fn paint_servers_linearGradient_attributes_via_xlink_href_complex_order() { assert_eq!(render("tests/paint-servers/linearGradient/attributes-via-xlink-href-complex-order"), 0); }
 #[test] fn paint_servers_linearGradient_attributes_via_xlink_href_from_radialGradient() { assert_eq!(render("tests/paint-servers/linearGradient/attributes-via-xlink-href-from-radialGradient"), 0); }
 #[test] fn paint_servers_linearGradient_attributes_via_xlink_href_from_rect() { assert_eq!(render("tests/paint-servers/linearGradient/attributes-via-xlink-href-from-rect"), 0); }
@@ -550,6 +555,7 @@ use crate::render;
 #[test] fn paint_servers_pattern_pattern_on_child() { assert_eq!(render("tests/paint-servers/pattern/pattern-on-child"), 0); }
 #[test] fn paint_servers_pattern_patternContentUnits_with_viewBox() { assert_eq!(render("tests/paint-servers/pattern/patternContentUnits-with-viewBox"), 0); }
 #[test] fn paint_servers_pattern_patternContentUnits_eq_objectBoundingBox() { assert_eq!(render("tests/paint-servers/pattern/patternContentUnits=objectBoundingBox"), 0); }
+#[test] fn paint_servers_pattern_patternTransform_via_xlink_href() { assert_eq!(render("tests/paint-servers/pattern/patternTransform-via-xlink-href"), 0); }
 #[test] fn paint_servers_pattern_patternUnits_eq_objectBoundingBox_with_percent() { assert_eq!(render("tests/paint-servers/pattern/patternUnits=objectBoundingBox-with-percent"), 0); }
 #[test] fn paint_servers_pattern_patternUnits_eq_objectBoundingBox() { assert_eq!(render("tests/paint-servers/pattern/patternUnits=objectBoundingBox"), 0); }
 #[test] fn paint_servers_pattern_patternUnits_eq_userSpaceOnUse_with_percent() { assert_eq!(render("tests/paint-servers/pattern/patternUnits=userSpaceOnUse-with-percent"), 0); }
@@ -648,6 +654,7 @@ use crate::render;
 #[test] fn painting_color_recursive_nested_context_without_color() { assert_eq!(render("tests/painting/color/recursive-nested-context-without-color"), 0); }
 #[test] fn painting_color_recursive_nested_context() { assert_eq!(render("tests/painting/color/recursive-nested-context"), 0); }
 #[test] fn painting_color_simple_case() { assert_eq!(render("tests/painting/color/simple-case"), 0); }
+#[test] fn painting_context_in_marker_with_inherited_paint() { assert_eq!(render("tests/painting/context/in-marker-with-inherited-paint"), 0); }
 #[test] fn painting_context_in_marker() { assert_eq!(render("tests/painting/context/in-marker"), 0); }
 #[test] fn painting_context_in_nested_marker() { assert_eq!(render("tests/painting/context/in-nested-marker"), 0); }
 #[test] fn painting_context_in_nested_use_and_marker() { assert_eq!(render("tests/painting/context/in-nested-use-and-marker"), 0); }
@@ -743,10 +750,12 @@ use crate::render;
 #[test] fn painting_fill_opacity_with_pattern() { assert_eq!(render("tests/painting/fill-opacity/with-pattern"), 0); }
 #[test] fn painting_fill_rule_evenodd() { assert_eq!(render("tests/painting/fill-rule/evenodd"), 0); }
 #[test] fn painting_fill_rule_nonzero() { assert_eq!(render("tests/painting/fill-rule/nonzero"), 0); }
+#[test] fn painting_image_rendering_crisp_edges() { assert_eq!(render("tests/painting/image-rendering/crisp-edges"), 0); }
 #[test] fn painting_image_rendering_high_quality() { assert_eq!(render("tests/painting/image-rendering/high-quality"), 0); }
 #[test] fn painting_image_rendering_on_feImage() { assert_eq!(render("tests/painting/image-rendering/on-feImage"), 0); }
 #[test] fn painting_image_rendering_optimizeSpeed_on_SVG() { assert_eq!(render("tests/painting/image-rendering/optimizeSpeed-on-SVG"), 0); }
 #[test] fn painting_image_rendering_optimizeSpeed() { assert_eq!(render("tests/painting/image-rendering/optimizeSpeed"), 0); }
+#[test] fn painting_image_rendering_pixelated() { assert_eq!(render("tests/painting/image-rendering/pixelated"), 0); }
 #[test] fn painting_isolation_as_property() { assert_eq!(render("tests/painting/isolation/as-property"), 0); }
 #[test] fn painting_isolation_isolate() { assert_eq!(render("tests/painting/isolation/isolate"), 0); }
 #[test] fn painting_marker_default_clip() { assert_eq!(render("tests/painting/marker/default-clip"), 0); }
@@ -904,6 +913,7 @@ use crate::render;
 #[test] fn painting_stroke_dasharray_on_a_circle() { assert_eq!(render("tests/painting/stroke-dasharray/on-a-circle"), 0); }
 #[test] fn painting_stroke_dasharray_percent_units() { assert_eq!(render("tests/painting/stroke-dasharray/percent-units"), 0); }
 #[test] fn painting_stroke_dasharray_ws_separator() { assert_eq!(render("tests/painting/stroke-dasharray/ws-separator"), 0); }
+#[test] fn painting_stroke_dasharray_zero_length_path_with_round_caps() { assert_eq!(render("tests/painting/stroke-dasharray/zero-length-path-with-round-caps"), 0); }
 #[test] fn painting_stroke_dasharray_zero_sum() { assert_eq!(render("tests/painting/stroke-dasharray/zero-sum"), 0); }
 #[test] fn painting_stroke_dashoffset_default() { assert_eq!(render("tests/painting/stroke-dashoffset/default"), 0); }
 #[test] fn painting_stroke_dashoffset_em_units() { assert_eq!(render("tests/painting/stroke-dashoffset/em-units"), 0); }
@@ -917,6 +927,7 @@ use crate::render;
 #[test] fn painting_stroke_linecap_open_path_with_square() { assert_eq!(render("tests/painting/stroke-linecap/open-path-with-square"), 0); }
 #[test] fn painting_stroke_linecap_round() { assert_eq!(render("tests/painting/stroke-linecap/round"), 0); }
 #[test] fn painting_stroke_linecap_square() { assert_eq!(render("tests/painting/stroke-linecap/square"), 0); }
+#[test] fn painting_stroke_linecap_zero_length_closed_path_with_round() { assert_eq!(render("tests/painting/stroke-linecap/zero-length-closed-path-with-round"), 0); }
 #[test] fn painting_stroke_linecap_zero_length_path_with_butt() { assert_eq!(render("tests/painting/stroke-linecap/zero-length-path-with-butt"), 0); }
 #[test] fn painting_stroke_linecap_zero_length_path_with_round() { assert_eq!(render("tests/painting/stroke-linecap/zero-length-path-with-round"), 0); }
 #[test] fn painting_stroke_linecap_zero_length_path_with_square() { assert_eq!(render("tests/painting/stroke-linecap/zero-length-path-with-square"), 0); }
@@ -943,6 +954,9 @@ use crate::render;
 #[test] fn painting_stroke_width_negative() { assert_eq!(render("tests/painting/stroke-width/negative"), 0); }
 #[test] fn painting_stroke_width_percentage() { assert_eq!(render("tests/painting/stroke-width/percentage"), 0); }
 #[test] fn painting_stroke_width_zero() { assert_eq!(render("tests/painting/stroke-width/zero"), 0); }
+#[test] fn painting_vector_effect_non_scaling_stroke() { assert_eq!(render("tests/painting/vector-effect/non-scaling-stroke"), 0); }
+#[test] fn painting_vector_effect_none() { assert_eq!(render("tests/painting/vector-effect/none"), 0); }
+#[test] fn painting_vector_effect_with_gradient() { assert_eq!(render("tests/painting/vector-effect/with-gradient"), 0); }
 #[test] fn painting_visibility_bbox_impact_1() { assert_eq!(render("tests/painting/visibility/bbox-impact-1"), 0); }
 #[test] fn painting_visibility_bbox_impact_2() { assert_eq!(render("tests/painting/visibility/bbox-impact-2"), 0); }
 #[test] fn painting_visibility_bbox_impact_3() { assert_eq!(render("tests/painting/visibility/bbox-impact-3"), 0); }
@@ -1150,6 +1164,7 @@ use crate::render;
 #[test] fn structure_image_with_zero_width_and_height() { assert_eq!(render("tests/structure/image/with-zero-width-and-height"), 0); }
 #[test] fn structure_image_zero_height() { assert_eq!(render("tests/structure/image/zero-height"), 0); }
 #[test] fn structure_image_zero_width() { assert_eq!(render("tests/structure/image/zero-width"), 0); }
+#[test] fn structure_style_attribute_selector_operators() { assert_eq!(render("tests/structure/style/attribute-selector-operators"), 0); }
 #[test] fn structure_style_attribute_selector() { assert_eq!(render("tests/structure/style/attribute-selector"), 0); }
 #[test] fn structure_style_class_selector() { assert_eq!(render("tests/structure/style/class-selector"), 0); }
 #[test] fn structure_style_combined_selectors() { assert_eq!(render("tests/structure/style/combined-selectors"), 0); }
@@ -1158,6 +1173,8 @@ use crate::render;
 #[test] fn structure_style_important() { assert_eq!(render("tests/structure/style/important"), 0); }
 #[test] fn structure_style_invalid_type() { assert_eq!(render("tests/structure/style/invalid-type"), 0); }
 #[test] fn structure_style_non_presentational_attribute() { assert_eq!(render("tests/structure/style/non-presentational-attribute"), 0); }
+#[test] fn structure_style_nth_child() { assert_eq!(render("tests/structure/style/nth-child"), 0); }
+#[test] fn structure_style_nth_of_type() { assert_eq!(render("tests/structure/style/nth-of-type"), 0); }
 #[test] fn structure_style_resolve_order() { assert_eq!(render("tests/structure/style/resolve-order"), 0); }
 #[test] fn structure_style_rule_specificity() { assert_eq!(render("tests/structure/style/rule-specificity"), 0); }
 #[test] fn structure_style_style_after_usage() { assert_eq!(render("tests/structure/style/style-after-usage"), 0); }
@@ -1193,6 +1210,7 @@ use crate::render;
 #[test] fn structure_svg_nested_svg_with_rect_and_viewBox_3() { assert_eq!(render("tests/structure/svg/nested-svg-with-rect-and-viewBox-3"), 0); }
 #[test] fn structure_svg_nested_svg_with_rect_and_viewBox_and_percent_values() { assert_eq!(render("tests/structure/svg/nested-svg-with-rect-and-viewBox-and-percent-values"), 0); }
 #[test] fn structure_svg_nested_svg_with_rect() { assert_eq!(render("tests/structure/svg/nested-svg-with-rect"), 0); }
+#[test] fn structure_svg_nested_svg_with_relative_size_and_viewBox() { assert_eq!(render("tests/structure/svg/nested-svg-with-relative-size-and-viewBox"), 0); }
 #[test] fn structure_svg_nested_svg_with_relative_width_and_height() { assert_eq!(render("tests/structure/svg/nested-svg-with-relative-width-and-height"), 0); }
 #[test] fn structure_svg_nested_svg_with_viewBox_and_percent_values() { assert_eq!(render("tests/structure/svg/nested-svg-with-viewBox-and-percent-values"), 0); }
 #[test] fn structure_svg_nested_svg_with_viewBox() { assert_eq!(render("tests/structure/svg/nested-svg-with-viewBox"), 0); }
@@ -1250,6 +1268,8 @@ use crate::render;
 #[test] fn structure_systemLanguage_on_tspan() { assert_eq!(render("tests/structure/systemLanguage/on-tspan"), 0); }
 #[test] fn structure_systemLanguage_ru_Ru() { assert_eq!(render("tests/structure/systemLanguage/ru-Ru"), 0); }
 #[test] fn structure_systemLanguage_ru_en() { assert_eq!(render("tests/structure/systemLanguage/ru-en"), 0); }
+#[test] fn structure_transform_css_syntax_with_angle_units() { assert_eq!(render("tests/structure/transform/css-syntax-with-angle-units"), 0); }
+#[test] fn structure_transform_css_syntax() { assert_eq!(render("tests/structure/transform/css-syntax"), 0); }
 #[test] fn structure_transform_default() { assert_eq!(render("tests/structure/transform/default"), 0); }
 #[test] fn structure_transform_direct_transform() { assert_eq!(render("tests/structure/transform/direct-transform"), 0); }
 #[test] fn structure_transform_empty() { assert_eq!(render("tests/structure/transform/empty"), 0); }
@@ -1467,6 +1487,7 @@ use crate::render;
 #[test] fn text_font_weight_normal() { assert_eq!(render("tests/text/font-weight/normal"), 0); }
 #[test] fn text_glyph_orientation_horizontal_simple_case() { assert_eq!(render("tests/text/glyph-orientation-horizontal/simple-case"), 0); }
 #[test] fn text_glyph_orientation_vertical_simple_case() { assert_eq!(render("tests/text/glyph-orientation-vertical/simple-case"), 0); }
+#[test] fn text_glyph_orientation_vertical_upright_latin_in_CJK() { assert_eq!(render("tests/text/glyph-orientation-vertical/upright-latin-in-CJK"), 0); }
 #[test] fn text_kerning_0() { assert_eq!(render("tests/text/kerning/0"), 0); }
 #[test] fn text_kerning_10percent() { assert_eq!(render("tests/text/kerning/10percent"), 0); }
 #[test] fn text_lengthAdjust_spacingAndGlyphs() { assert_eq!(render("tests/text/lengthAdjust/spacingAndGlyphs"), 0); }
@@ -36,6 +36,25 @@ and can focus just on the rendering part.
 - Recursive elements will be detected and removed
 - `objectBoundingBox` will be replaced with `userSpaceOnUse`
 
+## Cargo features
+
+- `text` - enables text-to-path conversion. Enabled by default.
+  Without it, `text` elements are skipped and reported via
+  [`Options::diagnostics`], and `fontdb`, `rustybuzz` and Unicode data crates
+  are not compiled. Useful for SVGs with text already converted to paths,
+  like when the binary size is critical.
+- `system-fonts` - enables system fonts loading. Enabled by default.
+- `memmap-fonts` - enables font files memmaping for faster loading. Enabled by default.
+
+## Geometry in `no_std`
+
+`usvg` itself requires `std`, but its geometry primitives, like [`Rect`], [`NonZeroRect`],
+[`Size`], [`Transform`] and [`tiny_skia_path::Path`], are re-exported from
+the [`tiny_skia_path`] crate. Which supports `no_std` when built with
+`default-features = false, features = ["no-std-float"]`, using `libm` for float math.
+By depending on the same `tiny-skia-path` version, `no_std` code can share
+coordinate logic with `usvg` without any conversions.
+
 ## Limitations
 
 - Unsupported SVG features will be ignored
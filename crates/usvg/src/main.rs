@@ -23,7 +23,8 @@ OPTIONS:
   -V, --version                     Prints version information
   -c                                Prints the output SVG to the stdout
 
-  --dpi DPI                         Sets the resolution
+  --dpi DPI                         Sets the resolution used to convert
+                                    absolute units, like pt and mm
                                     [default: 96] [possible values: 10..4000 (inclusive)]
   --stylesheet PATH                 Inject a stylesheet that should be used when resolving
                                     CSS attributes.
@@ -67,6 +68,13 @@ OPTIONS:
                                     [default: Impact]
   --monospace-family FAMILY         Sets the 'monospace' font family
                                     [default: Courier New]
+  --fallback-family FAMILY          Adds a font family that will be tried first
+                                    when a font doesn't have a character.
+                                    Families are tried in the specified order.
+                                    This option can be set multiple times
+  --strict-fonts                    Disables font substitution. Text spans without
+                                    a matching font or with unsupported characters
+                                    will be omitted. Cannot be used with --fallback-family
   --use-font-file PATH              Load a specified font file into the fonts database.
                                     Will be used during text to path conversion.
                                     This option can be set multiple times
@@ -125,6 +133,8 @@ struct Args {
     cursive_family: Option<String>,
     fantasy_family: Option<String>,
     monospace_family: Option<String>,
+    fallback_families: Vec<String>,
+    strict_fonts: bool,
     font_files: Vec<PathBuf>,
     font_dirs: Vec<PathBuf>,
     skip_system_fonts: bool,
@@ -186,6 +196,8 @@ fn collect_args() -> Result<Args, pico_args::Error> {
         cursive_family: input.opt_value_from_str("--cursive-family")?,
         fantasy_family: input.opt_value_from_str("--fantasy-family")?,
         monospace_family: input.opt_value_from_str("--monospace-family")?,
+        fallback_families: input.values_from_str("--fallback-family")?,
+        strict_fonts: input.contains("--strict-fonts"),
         font_files: input.values_from_str("--use-font-file")?,
         font_dirs: input.values_from_str("--use-fonts-dir")?,
         skip_system_fonts: input.contains("--skip-system-fonts"),
@@ -340,6 +352,10 @@ fn process(args: Args) -> Result<(), String> {
         (svg_from, svg_to)
     };
 
+    if args.strict_fonts && !args.fallback_families.is_empty() {
+        return Err("--fallback-family cannot be used with --strict-fonts".to_string());
+    }
+
     let mut fontdb = usvg::fontdb::Database::new();
     if !args.skip_system_fonts {
         // TODO: only when needed
@@ -413,6 +429,12 @@ fn process(args: Args) -> Result<(), String> {
         None => None,
     };
 
+    let mut font_resolver = usvg::FontResolver::default();
+    if !args.fallback_families.is_empty() {
+        font_resolver.select_fallback =
+            usvg::FontResolver::families_fallback_selector(args.fallback_families);
+    }
+
     let re_opt = usvg::Options {
         resources_dir,
         dpi: args.dpi as f32,
@@ -422,16 +444,28 @@ fn process(args: Args) -> Result<(), String> {
             .unwrap_or("Times New Roman")
             .to_string(),
         font_size: args.font_size as f32,
+        apply_kerning: true,
         languages: args.languages,
         shape_rendering: args.shape_rendering,
         text_rendering: args.text_rendering,
         image_rendering: args.image_rendering,
         default_size: usvg::Size::from_wh(args.default_width as f32, args.default_height as f32)
             .unwrap(),
+        fit_viewbox_to_content: false,
         image_href_resolver: usvg::ImageHrefResolver::default(),
-        font_resolver: usvg::FontResolver::default(),
+        font_resolver,
         fontdb: Arc::new(fontdb),
+        emoji_rendering: usvg::EmojiRendering::Color,
+        strict_fonts: args.strict_fonts,
+        glyph_cache: None,
         style_sheet,
+        media_features: usvg::Options::default().media_features,
+        keep_metadata: false,
+        keep_computed_styles: false,
+        keep_use_references: false,
+        keep_arcs: false,
+        filter_policy: usvg::FilterPolicy::default(),
+        diagnostics: None,
     };
 
     let input_svg = match in_svg {
@@ -1,11 +1,10 @@
 // Copyright 2018 the Resvg Authors
 // SPDX-License-Identifier: Apache-2.0 OR MIT
 
-use std::str::FromStr;
 use std::sync::Arc;
 
 use super::converter;
-use super::svgtree::{AId, EId, SvgNode};
+use super::svgtree::{self, AId, EId, SvgNode};
 use crate::{ClipPath, Group, NonEmptyString, NonZeroRect, Transform, Units};
 
 pub(crate) fn convert(
@@ -42,7 +41,7 @@ pub(crate) fn convert(
         let object_bbox = match object_bbox {
             Some(v) => v,
             None => {
-                log::warn!("Clipping of zero-sized shapes is not allowed.");
+                node.warn("clipPath", "Clipping of zero-sized shapes is not allowed.");
                 return None;
             }
         };
@@ -92,7 +91,7 @@ pub(crate) fn convert(
 }
 
 fn resolve_clip_path_transform(node: SvgNode, state: &converter::State) -> Option<Transform> {
-    // Do not use Node::attribute::<Transform>, because it will always
+    // Do not use `resolve_transform`, because it will always
     // return a valid transform.
 
     let value: &str = match node.attribute(AId::Transform) {
@@ -100,23 +99,17 @@ fn resolve_clip_path_transform(node: SvgNode, state: &converter::State) -> Optio
         None => return Some(Transform::default()),
     };
 
-    let ts = match svgtypes::Transform::from_str(value) {
-        Ok(v) => v,
-        Err(_) => {
-            log::warn!("Failed to parse {} value: '{}'.", AId::Transform, value);
+    let ts = match svgtree::parse_transform(value, state.opt.dpi) {
+        Some(v) => v,
+        None => {
+            node.warn(
+                AId::Transform.to_str(),
+                &format!("Failed to parse {} value: '{}'.", AId::Transform, value),
+            );
             return None;
         }
     };
 
-    let ts = Transform::from_row(
-        ts.a as f32,
-        ts.b as f32,
-        ts.c as f32,
-        ts.d as f32,
-        ts.e as f32,
-        ts.f as f32,
-    );
-
     if ts.is_valid() {
         Some(node.resolve_transform(AId::Transform, state))
     } else {
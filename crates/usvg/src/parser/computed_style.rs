@@ -0,0 +1,79 @@
+// Copyright 2024 the Resvg Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::collections::{BTreeMap, HashMap};
+
+use super::svgtree::{Document, SvgNode, DEFAULT_VALUES};
+use crate::{ComputedStyle, ComputedValue, StyleSource};
+
+/// Collects computed styles of all elements with an ID and the root element.
+///
+/// Works on the SVG tree, since CSS is already resolved there.
+pub(crate) fn collect(doc: &Document) -> HashMap<String, ComputedStyle> {
+    let mut styles = HashMap::new();
+
+    let root = doc.root_element();
+    for node in root.descendants() {
+        if !node.is_element() {
+            continue;
+        }
+
+        // The root element is stored under an empty ID, like the root group.
+        let id = if node == root {
+            ""
+        } else {
+            match node.element_id() {
+                "" => continue,
+                id => id,
+            }
+        };
+
+        styles.insert(id.to_string(), compute(node));
+    }
+
+    styles
+}
+
+fn compute(node: SvgNode) -> ComputedStyle {
+    let mut values = BTreeMap::new();
+
+    for attr in node.attributes() {
+        if attr.name.is_presentation() {
+            values.insert(
+                attr.name.to_str(),
+                ComputedValue {
+                    value: attr.value.to_string(),
+                    source: attr.source,
+                },
+            );
+        }
+    }
+
+    // The closest ancestor wins.
+    for ancestor in node.ancestors().skip(1) {
+        for attr in ancestor.attributes() {
+            if attr.name.is_inheritable() {
+                values
+                    .entry(attr.name.to_str())
+                    .or_insert_with(|| ComputedValue {
+                        value: attr.value.to_string(),
+                        source: StyleSource::Inherited,
+                    });
+            }
+        }
+    }
+
+    for (aid, value) in DEFAULT_VALUES {
+        values.entry(aid.to_str()).or_insert_with(|| ComputedValue {
+            value: value.to_string(),
+            source: StyleSource::Default,
+        });
+    }
+
+    ComputedStyle {
+        values: values
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value))
+            .collect(),
+    }
+}
@@ -3,7 +3,6 @@
 
 use std::collections::{HashMap, HashSet};
 use std::hash::{Hash, Hasher};
-use std::str::FromStr;
 use std::sync::Arc;
 
 #[cfg(feature = "text")]
@@ -35,7 +34,7 @@ pub struct State<'a> {
 }
 
 #[derive(Clone)]
-pub struct Cache {
+pub struct Cache<'a> {
     /// This fontdb is initialized from [`Options::fontdb`] and then populated
     /// over the course of conversion.
     #[cfg(feature = "text")]
@@ -46,6 +45,8 @@ pub struct Cache {
     pub filters: HashMap<String, Arc<filter::Filter>>,
     pub paint: HashMap<String, Paint>,
 
+    diagnostics: Option<&'a Diagnostics>,
+
     // used for ID generation
     all_ids: HashSet<u64>,
     linear_gradient_index: usize,
@@ -57,8 +58,11 @@ pub struct Cache {
     image_index: usize,
 }
 
-impl Cache {
-    pub(crate) fn new(#[cfg(feature = "text")] fontdb: Arc<Database>) -> Self {
+impl<'a> Cache<'a> {
+    pub(crate) fn new(
+        #[cfg(feature = "text")] fontdb: Arc<Database>,
+        diagnostics: Option<&'a Diagnostics>,
+    ) -> Self {
         Self {
             #[cfg(feature = "text")]
             fontdb,
@@ -68,6 +72,8 @@ impl Cache {
             filters: HashMap::new(),
             paint: HashMap::new(),
 
+            diagnostics,
+
             all_ids: HashSet::new(),
             linear_gradient_index: 0,
             radial_gradient_index: 0,
@@ -79,6 +85,11 @@ impl Cache {
         }
     }
 
+    /// Logs a warning and reports it to the diagnostics collector, if any.
+    pub(crate) fn warn(&self, feature: &str, element_id: &str, message: &str) {
+        super::diagnostics::warn(self.diagnostics, feature, element_id, message);
+    }
+
     // TODO: macros?
     pub(crate) fn gen_linear_gradient_id(&mut self) -> NonEmptyString {
         loop {
@@ -146,6 +157,33 @@ impl Cache {
         }
     }
 
+    /// Marks IDs of all resource elements in the document as used.
+    pub(crate) fn reserve_resource_ids(&mut self, svg_doc: &svgtree::Document) {
+        for node in svg_doc.descendants() {
+            if let Some(tag) = node.tag_name() {
+                if matches!(
+                    tag,
+                    EId::ClipPath
+                        | EId::Filter
+                        | EId::LinearGradient
+                        | EId::Mask
+                        | EId::Pattern
+                        | EId::RadialGradient
+                        | EId::Image
+                ) {
+                    if !node.element_id().is_empty() {
+                        self.reserve_id(node.element_id());
+                    }
+                }
+            }
+        }
+    }
+
+    /// Marks an ID as used, so it will not be generated.
+    pub(crate) fn reserve_id(&mut self, id: &str) {
+        self.all_ids.insert(string_hash(id));
+    }
+
     pub(crate) fn gen_image_id(&mut self) -> NonEmptyString {
         loop {
             self.image_index += 1;
@@ -232,8 +270,8 @@ impl<'a, 'input: 'a> SvgNode<'a, 'input> {
         ))
     }
 
-    pub fn has_valid_transform(&self, aid: AId) -> bool {
-        // Do not use Node::attribute::<Transform>, because it will always
+    pub fn has_valid_transform(&self, aid: AId, opt: &crate::Options) -> bool {
+        // Do not use `resolve_transform`, because it will always
         // return a valid transform.
 
         let attr = match self.attribute(aid) {
@@ -241,25 +279,15 @@ impl<'a, 'input: 'a> SvgNode<'a, 'input> {
             None => return true,
         };
 
-        let ts = match svgtypes::Transform::from_str(attr) {
-            Ok(v) => v,
-            Err(_) => return true,
-        };
-
-        let ts = Transform::from_row(
-            ts.a as f32,
-            ts.b as f32,
-            ts.c as f32,
-            ts.d as f32,
-            ts.e as f32,
-            ts.f as f32,
-        );
-        ts.is_valid()
+        match svgtree::parse_transform(attr, opt.dpi) {
+            Some(ts) => ts.is_valid(),
+            None => true,
+        }
     }
 
     pub fn is_visible_element(&self, opt: &crate::Options) -> bool {
         self.attribute(AId::Display) != Some("none")
-            && self.has_valid_transform(AId::Transform)
+            && self.has_valid_transform(AId::Transform, opt)
             && super::switch::is_condition_passed(*self, opt)
     }
 }
@@ -304,6 +332,9 @@ pub(crate) fn convert_doc(svg_doc: &svgtree::Document, opt: &Options) -> Result<
 
     let mut tree = Tree {
         size,
+        view_box,
+        // `overflow` is not inherited and the root element viewport clips by default.
+        overflow_visible: matches!(svg.attribute(AId::Overflow), Some("visible" | "auto")),
         root: Group::empty(),
         linear_gradients: Vec::new(),
         radial_gradients: Vec::new(),
@@ -311,6 +342,8 @@ pub(crate) fn convert_doc(svg_doc: &svgtree::Document, opt: &Options) -> Result<
         clip_paths: Vec::new(),
         masks: Vec::new(),
         filters: Vec::new(),
+        metadata: HashMap::new(),
+        computed_styles: HashMap::new(),
         #[cfg(feature = "text")]
         fontdb: opt.fontdb.clone(),
     };
@@ -332,26 +365,10 @@ pub(crate) fn convert_doc(svg_doc: &svgtree::Document, opt: &Options) -> Result<
     let mut cache = Cache::new(
         #[cfg(feature = "text")]
         opt.fontdb.clone(),
+        opt.diagnostics,
     );
 
-    for node in svg_doc.descendants() {
-        if let Some(tag) = node.tag_name() {
-            if matches!(
-                tag,
-                EId::ClipPath
-                    | EId::Filter
-                    | EId::LinearGradient
-                    | EId::Mask
-                    | EId::Pattern
-                    | EId::RadialGradient
-                    | EId::Image
-            ) {
-                if !node.element_id().is_empty() {
-                    cache.all_ids.insert(string_hash(node.element_id()));
-                }
-            }
-        }
-    }
+    cache.reserve_resource_ids(svg_doc);
 
     let root_ts = view_box.to_transform(tree.size());
     if root_ts.is_identity() && background_color.is_none() {
@@ -385,10 +402,7 @@ pub(crate) fn convert_doc(svg_doc: &svgtree::Document, opt: &Options) -> Result<
         None,
         &mut cache,
     );
-    tree.collect_paint_servers();
-    tree.root.collect_clip_paths(&mut tree.clip_paths);
-    tree.root.collect_masks(&mut tree.masks);
-    tree.root.collect_filters(&mut tree.filters);
+    tree.remove_unused_defs();
     tree.root.calculate_bounding_boxes();
 
     // The fontdb might have been mutated and we want to apply these changes to
@@ -402,6 +416,10 @@ pub(crate) fn convert_doc(svg_doc: &svgtree::Document, opt: &Options) -> Result<
         calculate_svg_bbox(&mut tree);
     }
 
+    if opt.fit_viewbox_to_content && !svg.has_attribute(AId::ViewBox) {
+        fit_viewbox_to_content(&mut tree);
+    }
+
     Ok(tree)
 }
 
@@ -496,9 +514,51 @@ fn calculate_svg_bbox(tree: &mut Tree) {
     let bbox = tree.root.abs_bounding_box();
     if let Some(size) = Size::from_wh(bbox.right(), bbox.bottom()) {
         tree.size = size;
+        tree.view_box.rect = size.to_non_zero_rect(0.0, 0.0);
     }
 }
 
+/// Extends SVG's viewBox to include content outside of it.
+///
+/// The size is preserved, therefore the content is scaled down to fit it.
+fn fit_viewbox_to_content(tree: &mut Tree) {
+    if !tree.root.has_children() {
+        return;
+    }
+
+    // Without a `viewBox`, the root content is in the canvas coordinates.
+    let canvas = tree.view_box.rect;
+    let rect = match BBox::from(canvas)
+        .expand(tree.root.layer_bounding_box)
+        .to_non_zero_rect()
+    {
+        Some(v) => v,
+        None => return,
+    };
+
+    if rect == canvas {
+        return;
+    }
+
+    tree.view_box.rect = rect;
+    let ts = tree.view_box.to_transform(tree.size);
+
+    let mut g = Group {
+        transform: ts,
+        abs_transform: ts,
+        children: std::mem::take(&mut tree.root.children),
+        ..Group::empty()
+    };
+
+    for child in &mut g.children {
+        crate::tree::prepend_transform(child, ts);
+    }
+
+    g.calculate_bounding_boxes();
+    tree.root.children.push(Node::Group(Box::new(g)));
+    tree.root.calculate_bounding_boxes();
+}
+
 #[inline(never)]
 pub(crate) fn convert_children(
     parent_node: SvgNode,
@@ -559,8 +619,8 @@ fn convert_element_impl(
         | EId::Polyline
         | EId::Polygon
         | EId::Path => {
-            if let Some(path) = super::shapes::convert(node, state) {
-                convert_path(node, path, state, cache, parent);
+            if let Some((path, arc_segments)) = super::shapes::convert(node, state) {
+                convert_path(node, path, arc_segments, state, cache, parent);
             }
         }
         EId::Image => {
@@ -571,6 +631,11 @@ fn convert_element_impl(
             {
                 super::text::convert(node, state, cache, parent);
             }
+
+            #[cfg(not(feature = "text"))]
+            {
+                report_disabled_text(node, state);
+            }
         }
         EId::Svg => {
             if node.parent_element().is_some() {
@@ -635,8 +700,8 @@ fn convert_clip_path_elements_impl(
 ) {
     match tag_name {
         EId::Rect | EId::Circle | EId::Ellipse | EId::Polyline | EId::Polygon | EId::Path => {
-            if let Some(path) = super::shapes::convert(node, state) {
-                convert_path(node, path, state, cache, parent);
+            if let Some((path, arc_segments)) = super::shapes::convert(node, state) {
+                convert_path(node, path, arc_segments, state, cache, parent);
             }
         }
         EId::Text => {
@@ -644,13 +709,32 @@ fn convert_clip_path_elements_impl(
             {
                 super::text::convert(node, state, cache, parent);
             }
+
+            #[cfg(not(feature = "text"))]
+            {
+                report_disabled_text(node, state);
+            }
         }
         _ => {
             log::warn!("'{}' is no a valid 'clip-path' child.", tag_name);
+            state.opt.report(
+                &tag_name.to_string(),
+                node.element_id(),
+                "not a valid 'clip-path' child",
+            );
         }
     }
 }
 
+// Text elements are dropped when text support is disabled at compile time.
+#[cfg(not(feature = "text"))]
+fn report_disabled_text(node: SvgNode, state: &State) {
+    log::warn!("Text support is disabled. 'text' element is skipped.");
+    state
+        .opt
+        .report("text", node.element_id(), "text support is disabled");
+}
+
 #[derive(Clone, Copy, PartialEq, Debug)]
 enum Isolation {
     Auto,
@@ -716,6 +800,7 @@ pub(crate) fn convert_group(
         mask: None,
         filters: Vec::new(),
         is_context_element: false,
+        use_href: None,
         bounding_box: dummy,
         abs_bounding_box: dummy,
         stroke_bounding_box: dummy,
@@ -808,6 +893,7 @@ pub(crate) fn convert_group(
 fn convert_path(
     node: SvgNode,
     tiny_skia_path: Arc<tiny_skia_path::Path>,
+    arc_segments: Option<Arc<Vec<ArcPathSegment>>>,
     state: &State,
     cache: &mut Cache,
     parent: &mut Group,
@@ -825,8 +911,10 @@ fn convert_path(
     let rendering_mode: ShapeRendering = node
         .find_attribute(AId::ShapeRendering)
         .unwrap_or(state.opt.shape_rendering);
+    // Not inherited.
+    let vector_effect: VectorEffect = node.attribute(AId::VectorEffect).unwrap_or_default();
 
-    // TODO: handle `markers` before `stroke`
+    // Markers are handled below, by splitting the path when needed.
     let raw_paint_order: svgtypes::PaintOrder =
         node.find_attribute(AId::PaintOrder).unwrap_or_default();
     let paint_order = svg_paint_order_to_usvg(raw_paint_order);
@@ -915,21 +1003,26 @@ fn convert_path(
         String::new()
     };
 
-    let path = Path::new(
-        id,
+    let style = PathStyle {
         visible,
         fill,
         stroke,
         paint_order,
         rendering_mode,
-        tiny_skia_path,
-        path_transform,
-    );
+        vector_effect,
+    };
+    let path = Path::new(id, style, tiny_skia_path, path_transform);
 
-    let path = match path {
+    let mut path = match path {
         Some(v) => v,
         None => return,
     };
+    path.arc_segments = arc_segments;
+    // Markers are stored in a separate group, which has the same coordinate system.
+    path.markers_bounding_box = marker
+        .as_ref()
+        .filter(|g| g.has_children())
+        .map(|g| g.layer_bounding_box);
 
     match raw_paint_order.order {
         [PaintOrderKind::Markers, _, _] => {
@@ -993,7 +1086,12 @@ pub fn svg_paint_order_to_usvg(order: svgtypes::PaintOrder) -> PaintOrder {
 
 impl SvgNode<'_, '_> {
     pub(crate) fn resolve_transform(&self, transform_aid: AId, state: &State) -> Transform {
-        let mut transform: Transform = self.attribute(transform_aid).unwrap_or_default();
+        // An invalid transform is replaced with an identity one.
+        let mut transform = self
+            .attribute(transform_aid)
+            .and_then(|value| svgtree::parse_transform(value, state.opt.dpi))
+            .filter(Transform::is_valid)
+            .unwrap_or_default();
         let transform_origin: Option<TransformOrigin> = self.attribute(AId::TransformOrigin);
 
         if let Some(transform_origin) = transform_origin {
@@ -0,0 +1,152 @@
+// Copyright 2024 the Resvg Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::sync::Mutex;
+
+use super::svgtree::SVG_NS;
+use super::EId;
+
+/// A description of an SVG feature that was ignored during parsing.
+#[derive(Clone, Debug)]
+pub struct Diagnostic {
+    pub(crate) feature: String,
+    pub(crate) element_id: String,
+    pub(crate) line: Option<u32>,
+    pub(crate) note: String,
+}
+
+impl Diagnostic {
+    /// An ignored feature.
+    ///
+    /// Usually an element or an attribute value, like `animate` or `BackgroundImage`.
+    pub fn feature(&self) -> &str {
+        &self.feature
+    }
+
+    /// ID of the element that uses the feature.
+    ///
+    /// Can be empty.
+    pub fn element_id(&self) -> &str {
+        &self.element_id
+    }
+
+    /// A line in the original SVG.
+    ///
+    /// Not all diagnostics have it, since some of them are produced after
+    /// the original XML was already preprocessed.
+    pub fn line(&self) -> Option<u32> {
+        self.line
+    }
+
+    /// A human-readable explanation.
+    ///
+    /// Either a short note or the same message that was logged.
+    pub fn note(&self) -> &str {
+        &self.note
+    }
+}
+
+/// A diagnostics collector.
+///
+/// See [`Options::diagnostics`](crate::Options::diagnostics).
+#[derive(Default, Debug)]
+pub struct Diagnostics {
+    list: Mutex<Vec<Diagnostic>>,
+}
+
+impl Diagnostics {
+    /// Creates a new, empty collector.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns all collected diagnostics and clears the collector.
+    pub fn take(&self) -> Vec<Diagnostic> {
+        match self.list.lock() {
+            Ok(mut list) => std::mem::take(&mut *list),
+            Err(_) => Vec::new(),
+        }
+    }
+
+    pub(crate) fn push(&self, feature: &str, element_id: &str, line: Option<u32>, note: &str) {
+        if let Ok(mut list) = self.list.lock() {
+            list.push(Diagnostic {
+                feature: feature.to_string(),
+                element_id: element_id.to_string(),
+                line,
+                note: note.to_string(),
+            });
+        }
+    }
+}
+
+/// Logs a warning and reports it to the collector, if any.
+///
+/// The logged message is used as a note.
+pub(crate) fn warn(
+    diagnostics: Option<&Diagnostics>,
+    feature: &str,
+    element_id: &str,
+    message: &str,
+) {
+    log::warn!("{}", message);
+    if let Some(diagnostics) = diagnostics {
+        diagnostics.push(feature, element_id, None, message);
+    }
+}
+
+/// Collects unsupported elements and filter inputs.
+///
+/// Works on the original XML, since unsupported elements are not preserved
+/// by the SVG tree.
+pub(crate) fn collect(doc: &roxmltree::Document, diagnostics: &Diagnostics) {
+    for node in doc.root_element().descendants() {
+        if !node.is_element() || node.tag_name().namespace() != Some(SVG_NS) {
+            continue;
+        }
+
+        let id = node.attribute("id").unwrap_or_default();
+        let line = Some(doc.text_pos_at(node.range().start).row);
+        let name = node.tag_name().name();
+
+        match EId::from_str(name) {
+            Some(_) => {
+                // Only filter primitives have these attributes.
+                for aid in ["in", "in2"] {
+                    if let Some(
+                        value @ ("BackgroundImage" | "BackgroundAlpha" | "FillPaint"
+                        | "StrokePaint"),
+                    ) = node.attribute(aid)
+                    {
+                        let note = if value.starts_with("Background") {
+                            "unsupported filter input, replaced with a transparent image"
+                        } else {
+                            "unsupported filter input, replaced with SourceGraphic"
+                        };
+                        diagnostics.push(value, id, line, note);
+                    }
+                }
+            }
+            None => {
+                // Not rendered by design.
+                if matches!(name, "title" | "desc" | "metadata") {
+                    continue;
+                }
+
+                let note = if is_animation(name) {
+                    "animations are not supported"
+                } else {
+                    "unsupported element"
+                };
+                diagnostics.push(name, id, line, note);
+            }
+        }
+    }
+}
+
+fn is_animation(name: &str) -> bool {
+    matches!(
+        name,
+        "animate" | "animateColor" | "animateMotion" | "animateTransform" | "set"
+    )
+}
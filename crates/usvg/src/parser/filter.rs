@@ -61,9 +61,12 @@ pub(crate) fn convert(
             let object_bbox = match object_bbox {
                 Some(v) => v,
                 None => {
-                    log::warn!(
-                        "Filter '{}' has an invalid region. Skipped.",
-                        node.element_id()
+                    node.warn(
+                        "filter",
+                        &format!(
+                            "Filter '{}' has an invalid region. Skipped.",
+                            node.element_id()
+                        ),
                     );
                     return;
                 }
@@ -89,7 +92,10 @@ pub(crate) fn convert(
             Ok(v) => v,
             Err(e) => {
                 // Skip the whole attribute list on error.
-                log::warn!("Failed to parse a filter value cause {}. Skipping.", e);
+                node.warn(
+                    "filter",
+                    &format!("Failed to parse a filter value cause {}. Skipping.", e),
+                );
                 return Ok(Vec::new());
             }
         };
@@ -158,6 +164,9 @@ pub(crate) fn convert(
         return Err(());
     }
 
+    // Rejected filters are simply ignored, unlike invalid ones.
+    filters.retain(|filter| state.opt.filter_policy.allows(filter));
+
     Ok(filters)
 }
 
@@ -215,9 +224,12 @@ fn convert_url(
 
     let mut rect = rect
         .log_none(|| {
-            log::warn!(
-                "Filter '{}' has an invalid region. Skipped.",
-                node.element_id()
+            node.warn(
+                "filter",
+                &format!(
+                    "Filter '{}' has an invalid region. Skipped.",
+                    node.element_id()
+                ),
             )
         })
         .ok_or(())?;
@@ -226,7 +238,7 @@ fn convert_url(
         if let Some(object_bbox) = object_bbox {
             rect = rect.bbox_transform(object_bbox);
         } else {
-            log::warn!("Filters on zero-sized shapes are not allowed.");
+            node.warn("filter", "Filters on zero-sized shapes are not allowed.");
             return Err(());
         }
     }
@@ -268,10 +280,13 @@ fn convert_url(
 fn find_filter_with_primitives<'a>(node: SvgNode<'a, 'a>) -> Option<SvgNode<'a, 'a>> {
     for link in node.href_iter() {
         if link.tag_name() != Some(EId::Filter) {
-            log::warn!(
-                "Filter '{}' cannot reference '{}' via 'xlink:href'.",
-                node.element_id(),
-                link.tag_name().unwrap()
+            node.warn(
+                "xlink:href",
+                &format!(
+                    "Filter '{}' cannot reference '{}' via 'xlink:href'.",
+                    node.element_id(),
+                    link.tag_name().unwrap()
+                ),
             );
             return None;
         }
@@ -357,6 +372,11 @@ fn collect_children(
                     .unwrap_or_else(create_dummy_primitive),
                 tag_name => {
                     log::warn!("'{}' is not a valid filter primitive. Skipped.", tag_name);
+                    state.opt.report(
+                        &tag_name.to_string(),
+                        child.element_id(),
+                        "not a valid filter primitive",
+                    );
                     continue;
                 }
             };
@@ -373,6 +393,24 @@ fn collect_children(
         });
     }
 
+    // Background inputs are not supported and resolved to a transparent image.
+    // Since references must be valid, we have to add a primitive that produces it.
+    // It is inserted only now, since it must not affect the default inputs resolving.
+    for name in ["BackgroundAlpha", "BackgroundImage"] {
+        let input = Input::Reference(name.to_string());
+        if primitives.iter().any(|p| p.kind.has_input(&input)) {
+            primitives.insert(
+                0,
+                Primitive {
+                    rect: filter_region,
+                    color_interpolation: ColorInterpolation::SRGB,
+                    result: name.to_string(),
+                    kind: create_dummy_primitive(),
+                },
+            );
+        }
+    }
+
     // TODO: remove primitives which results are not used
 
     primitives
@@ -450,6 +488,14 @@ pub(crate) fn create_dummy_primitive() -> Kind {
 #[inline(never)]
 fn resolve_input(node: SvgNode, aid: AId, primitives: &[Primitive]) -> Input {
     match node.attribute(aid) {
+        Some(s @ ("BackgroundImage" | "BackgroundAlpha")) => {
+            // Reported by `diagnostics::collect` along with a line number.
+            log::warn!(
+                "{} filter input isn't supported. Transparent image will be used.",
+                s
+            );
+            Input::Reference(s.to_string())
+        }
         Some(s) => {
             let input = parse_in(s);
 
@@ -485,7 +531,8 @@ fn parse_in(s: &str) -> Input {
     match s {
         "SourceGraphic" => Input::SourceGraphic,
         "SourceAlpha" => Input::SourceAlpha,
-        "BackgroundImage" | "BackgroundAlpha" | "FillPaint" | "StrokePaint" => {
+        "FillPaint" | "StrokePaint" => {
+            // Reported by `diagnostics::collect` along with a line number.
             log::warn!("{} filter input isn't supported and not planed.", s);
             Input::SourceGraphic
         }
@@ -855,10 +902,15 @@ fn convert_image_inner(
     }
 
     let href = fe.try_attribute(AId::Href).log_none(|| {
-        log::warn!("The 'feImage' element lacks the 'xlink:href' attribute. Skipped.")
+        fe.warn(
+            "feImage",
+            "The 'feImage' element lacks the 'xlink:href' attribute. Skipped.",
+        )
     })?;
     let img_data = super::image::get_href_data(href, state)?;
-    let actual_size = img_data.actual_size()?;
+    let actual_size = img_data
+        .size()
+        .log_none(|| fe.warn("feImage", "Image has an invalid size. Skipped."))?;
 
     let aspect: AspectRatio = fe.attribute(AId::PreserveAspectRatio).unwrap_or_default();
 
@@ -926,7 +978,10 @@ fn convert_lighting_color(node: SvgNode) -> Color {
             if let Ok(c) = svgtypes::Color::from_str(value) {
                 c.split_alpha().0
             } else {
-                log::warn!("Failed to parse lighting-color value: '{}'.", value);
+                node.warn(
+                    "lighting-color",
+                    &format!("Failed to parse lighting-color value: '{}'.", value),
+                );
                 Color::white()
             }
         }
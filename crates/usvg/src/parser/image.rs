@@ -6,10 +6,10 @@ use std::sync::Arc;
 use svgtypes::{AspectRatio, Length};
 
 use super::svgtree::{AId, SvgNode};
-use super::{converter, OptionLog, Options};
+use super::{converter, FilterPolicy, OptionLog, Options};
 use crate::{
     ClipPath, Group, Image, ImageKind, ImageRendering, Node, NonZeroRect, Path, Size, Transform,
-    Tree, Visibility,
+    Tree, ViewBox, Visibility,
 };
 
 /// A shorthand for [ImageHrefResolver]'s data function.
@@ -20,7 +20,7 @@ pub type ImageHrefDataResolverFn<'a> =
 pub type ImageHrefStringResolverFn<'a> =
     Box<dyn Fn(&str, &Options) -> Option<ImageKind> + Send + Sync + 'a>;
 
-/// An `xlink:href` resolver for `<image>` elements.
+/// An `xlink:href` resolver for `<image>` and `<feImage>` elements.
 ///
 /// This type can be useful if you want to have an alternative `xlink:href` handling
 /// to the default one. For example, you can forbid access to local files (which is allowed by default)
@@ -90,7 +90,7 @@ impl ImageHrefResolver<'_> {
                 let data = match std::fs::read(&path) {
                     Ok(data) => data,
                     Err(_) => {
-                        log::warn!("Failed to load '{}'. Skipped.", href);
+                        opts.warn("image", "", &format!("Failed to load '{}'. Skipped.", href));
                         return None;
                     }
                 };
@@ -102,12 +102,20 @@ impl ImageHrefResolver<'_> {
                     Some(ImageFormat::WEBP) => Some(ImageKind::WEBP(Arc::new(data))),
                     Some(ImageFormat::SVG) => load_sub_svg(&data, opts),
                     _ => {
-                        log::warn!("'{}' is not a PNG, JPEG, GIF, WebP or SVG(Z) image.", href);
+                        opts.warn(
+                            "image",
+                            "",
+                            &format!("'{}' is not a PNG, JPEG, GIF, WebP or SVG(Z) image.", href),
+                        );
                         None
                     }
                 }
             } else {
-                log::warn!("'{}' is not a path to an image.", href);
+                opts.warn(
+                    "image",
+                    "",
+                    &format!("'{}' is not a path to an image.", href),
+                );
                 None
             }
         })
@@ -137,9 +145,13 @@ pub(crate) fn convert(
 ) -> Option<()> {
     let href = node
         .try_attribute(AId::Href)
-        .log_none(|| log::warn!("Image lacks the 'xlink:href' attribute. Skipped."))?;
+        .log_none(|| node.warn("image", "Image lacks the 'xlink:href' attribute. Skipped."))?;
 
-    let kind = get_href_data(href, state)?;
+    let kind = get_href_data(href, state).log_none(|| {
+        state
+            .opt
+            .report("image", node.element_id(), "failed to load an image")
+    })?;
 
     let visibility: Visibility = node.find_attribute(AId::Visibility).unwrap_or_default();
     let visible = visibility == Visibility::Visible;
@@ -155,7 +167,9 @@ pub(crate) fn convert(
         String::new()
     };
 
-    let actual_size = kind.actual_size()?;
+    let actual_size = kind
+        .size()
+        .log_none(|| node.warn("image", "Image has an invalid size. Skipped."))?;
 
     let x = node.convert_user_length(AId::X, state, Length::zero());
     let y = node.convert_user_length(AId::Y, state, Length::zero());
@@ -188,7 +202,7 @@ pub(crate) fn convert(
     let aspect: AspectRatio = node.attribute(AId::PreserveAspectRatio).unwrap_or_default();
 
     let rect = NonZeroRect::from_xywh(x, y, width, height);
-    let rect = rect.log_none(|| log::warn!("Image has an invalid size. Skipped."))?;
+    let rect = rect.log_none(|| node.warn("image", "Image has an invalid size. Skipped."))?;
 
     convert_inner(
         kind,
@@ -214,27 +228,16 @@ pub(crate) fn convert_inner(
     cache: &mut converter::Cache,
     parent: &mut Group,
 ) -> Option<()> {
-    let aligned_size = fit_view_box(actual_size, rect, aspect);
-    let (aligned_x, aligned_y) = crate::aligned_pos(
-        aspect.align,
-        rect.x(),
-        rect.y(),
-        rect.width() - aligned_size.width(),
-        rect.height() - aligned_size.height(),
-    );
-    let view_box = aligned_size.to_non_zero_rect(aligned_x, aligned_y);
-
-    let image_ts = Transform::from_row(
-        view_box.width() / actual_size.width(),
-        0.0,
-        0.0,
-        view_box.height() / actual_size.height(),
-        view_box.x(),
-        view_box.y(),
-    );
+    // An image is fitted into its rectangle the same way as a `viewBox`.
+    let view_box = ViewBox {
+        rect: actual_size.to_non_zero_rect(0.0, 0.0),
+        aspect,
+    };
+    let image_ts = Transform::from_translate(rect.x(), rect.y())
+        .pre_concat(view_box.to_transform(rect.size()));
 
     let abs_transform = parent.abs_transform.pre_concat(image_ts);
-    let abs_bounding_box = rect.transform(abs_transform)?;
+    let abs_bounding_box = view_box.rect.transform(abs_transform)?;
 
     let mut g = Group::empty();
     g.id = id;
@@ -261,6 +264,7 @@ pub(crate) fn convert_inner(
 
         let mut clip = ClipPath::empty(cache.gen_clip_path_id());
         clip.root.children.push(Node::Path(Box::new(path)));
+        clip.root.calculate_bounding_boxes();
 
         // Clip path should not be affected by the image viewbox transform.
         // The final structure should look like:
@@ -331,12 +335,29 @@ pub(crate) fn load_sub_svg(data: &[u8], opt: &Options) -> Option<ImageKind> {
     let sub_opt = Options {
         resources_dir: None,
         dpi: opt.dpi,
+        font_family: opt.font_family.clone(),
         font_size: opt.font_size,
+        apply_kerning: opt.apply_kerning,
         languages: opt.languages.clone(),
         shape_rendering: opt.shape_rendering,
         text_rendering: opt.text_rendering,
         image_rendering: opt.image_rendering,
         default_size: opt.default_size,
+        fit_viewbox_to_content: opt.fit_viewbox_to_content,
+        media_features: opt.media_features.clone(),
+        keep_metadata: opt.keep_metadata,
+        keep_computed_styles: opt.keep_computed_styles,
+        keep_use_references: opt.keep_use_references,
+        keep_arcs: opt.keep_arcs,
+        filter_policy: match opt.filter_policy {
+            FilterPolicy::AllowAll => FilterPolicy::AllowAll,
+            FilterPolicy::DenyAll => FilterPolicy::DenyAll,
+            // Can't clone the predicate, so we create a new one that forwards to it.
+            FilterPolicy::Custom(ref predicate) => {
+                FilterPolicy::Custom(Box::new(|primitive| predicate(primitive)))
+            }
+        },
+        diagnostics: opt.diagnostics,
         // The referenced SVG image cannot have any 'image' elements by itself.
         // Not only recursive. Any. Don't know why.
         image_href_resolver: ImageHrefResolver {
@@ -347,6 +368,12 @@ pub(crate) fn load_sub_svg(data: &[u8], opt: &Options) -> Option<ImageKind> {
         // fontdb, not the one from the cache.
         #[cfg(feature = "text")]
         fontdb: opt.fontdb.clone(),
+        #[cfg(feature = "text")]
+        emoji_rendering: opt.emoji_rendering,
+        #[cfg(feature = "text")]
+        strict_fonts: opt.strict_fonts,
+        #[cfg(feature = "text")]
+        glyph_cache: opt.glyph_cache,
         // Can't clone the resolver, so we create a new one that forwards to it.
         #[cfg(feature = "text")]
         font_resolver: crate::FontResolver {
@@ -362,23 +389,10 @@ pub(crate) fn load_sub_svg(data: &[u8], opt: &Options) -> Option<ImageKind> {
     let tree = match tree {
         Ok(tree) => tree,
         Err(_) => {
-            log::warn!("Failed to load subsvg image.");
+            opt.warn("image", "", "Failed to load subsvg image.");
             return None;
         }
     };
 
     Some(ImageKind::SVG(tree))
 }
-
-/// Fits size into a viewbox.
-fn fit_view_box(size: Size, rect: NonZeroRect, aspect: AspectRatio) -> Size {
-    let s = rect.size();
-
-    if aspect.align == svgtypes::Align::None {
-        s
-    } else if aspect.slice {
-        size.expand_to(s)
-    } else {
-        size.scale_to(s)
-    }
-}
@@ -63,7 +63,10 @@ pub(crate) fn convert(
             // TODO: move to svgtree
             // Check for recursive marker.
             if state.parent_markers.contains(&marker) {
-                log::warn!("Recursive marker detected: {}", marker.element_id());
+                marker.warn(
+                    "marker",
+                    &format!("Recursive marker detected: {}", marker.element_id()),
+                );
                 continue;
             }
 
@@ -126,6 +129,7 @@ fn resolve(
         path.fill = Some(Fill::default());
 
         clip_path.root.children.push(Node::Path(Box::new(path)));
+        clip_path.root.calculate_bounding_boxes();
 
         Some(Arc::new(clip_path))
     } else {
@@ -243,10 +247,8 @@ where
                     Segment::MoveTo(p) => p,
                     Segment::LineTo(p) => p,
                     Segment::CubicTo(_, _, p) => p,
-                    _ => {
-                        i += 1;
-                        continue;
-                    }
+                    // A closed subpath ends at its start.
+                    Segment::Close => get_subpath_start(path, i),
                 };
 
                 draw_marker(p, i);
@@ -274,6 +276,22 @@ where
 }
 
 fn calc_vertex_angle(path: &[Segment], idx: usize) -> f32 {
+    // The start and the end of a closed subpath are the same vertex,
+    // which is between the closing segment and the first one.
+    match path[idx] {
+        Segment::MoveTo(_) => {
+            if let Some(close_idx) = get_subpath_close(path, idx) {
+                return calc_closed_subpath_angle(path, idx, close_idx);
+            }
+        }
+        Segment::Close => {
+            if let Some(move_idx) = get_subpath_start_idx(path, idx) {
+                return calc_closed_subpath_angle(path, move_idx, idx);
+            }
+        }
+        _ => {}
+    }
+
     if idx == 0 {
         // First segment.
 
@@ -385,6 +403,65 @@ fn calc_vertex_angle(path: &[Segment], idx: usize) -> f32 {
     }
 }
 
+/// Calculates an angle at the start of a closed subpath.
+///
+/// The incoming direction is the direction of the closing segment
+/// and the outgoing one is the direction of the first segment.
+fn calc_closed_subpath_angle(path: &[Segment], move_idx: usize, close_idx: usize) -> f32 {
+    let Segment::MoveTo(start) = path[move_idx] else {
+        return 0.0;
+    };
+
+    // A subpath without segments.
+    if close_idx <= move_idx + 1 {
+        return 0.0;
+    }
+
+    let first_dir = match path[move_idx + 1] {
+        Segment::LineTo(p) => p,
+        Segment::CubicTo(p1, _, p) => {
+            if p1.x.approx_eq_ulps(&start.x, 4) && p1.y.approx_eq_ulps(&start.y, 4) {
+                p
+            } else {
+                p1
+            }
+        }
+        Segment::MoveTo(_) | Segment::Close => return 0.0,
+    };
+
+    // When the last vertex is the same as the subpath start, the closing segment
+    // has a zero length and the direction of the last segment is used instead.
+    let last = get_prev_vertex(path, close_idx);
+    let (in_x, in_y) = if last.x.approx_eq_ulps(&start.x, 4) && last.y.approx_eq_ulps(&start.y, 4) {
+        match path[close_idx - 1] {
+            Segment::CubicTo(p1, p2, p) => {
+                if p2.x.approx_eq_ulps(&p.x, 4) && p2.y.approx_eq_ulps(&p.y, 4) {
+                    (p1.x, p1.y)
+                } else {
+                    (p2.x, p2.y)
+                }
+            }
+            _ => {
+                let prev = get_prev_vertex(path, close_idx - 1);
+                (prev.x, prev.y)
+            }
+        }
+    } else {
+        (last.x, last.y)
+    };
+
+    calc_angle(
+        in_x,
+        in_y,
+        start.x,
+        start.y,
+        start.x,
+        start.y,
+        first_dir.x,
+        first_dir.y,
+    )
+}
+
 fn calc_line_angle(x1: f32, y1: f32, x2: f32, y2: f32) -> f32 {
     calc_angle(x1, y1, x2, y2, x1, y1, x2, y2)
 }
@@ -454,6 +531,25 @@ fn get_subpath_start(segments: &[Segment], idx: usize) -> tiny_skia_path::Point
     tiny_skia_path::Point::zero()
 }
 
+fn get_subpath_start_idx(segments: &[Segment], idx: usize) -> Option<usize> {
+    segments[..idx]
+        .iter()
+        .rposition(|seg| matches!(seg, Segment::MoveTo(_)))
+}
+
+/// Returns the index of the `Close` segment of a subpath that starts at `move_idx`.
+fn get_subpath_close(segments: &[Segment], move_idx: usize) -> Option<usize> {
+    let end = segments[move_idx + 1..]
+        .iter()
+        .position(|seg| matches!(seg, Segment::MoveTo(_)))
+        .map_or(segments.len(), |i| move_idx + 1 + i);
+
+    match segments[end - 1] {
+        Segment::Close => Some(end - 1),
+        _ => None,
+    }
+}
+
 fn get_prev_vertex(segments: &[Segment], idx: usize) -> tiny_skia_path::Point {
     match segments[idx - 1] {
         Segment::MoveTo(p) => p,
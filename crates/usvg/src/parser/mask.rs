@@ -46,8 +46,12 @@ pub(crate) fn convert(
         node.convert_length(AId::Width, units, state, Length::new(120.0, Unit::Percent)),
         node.convert_length(AId::Height, units, state, Length::new(120.0, Unit::Percent)),
     );
-    let mut rect =
-        rect.log_none(|| log::warn!("Mask '{}' has an invalid size. Skipped.", node.element_id()))?;
+    let mut rect = rect.log_none(|| {
+        node.warn(
+            "mask",
+            &format!("Mask '{}' has an invalid size. Skipped.", node.element_id()),
+        )
+    })?;
 
     let mut mask_all = false;
     if units == Units::ObjectBoundingBox {
@@ -112,7 +116,7 @@ pub(crate) fn convert(
         let object_bbox = match object_bbox {
             Some(v) => v,
             None => {
-                log::warn!("Masking of zero-sized shapes is not allowed.");
+                node.warn("mask", "Masking of zero-sized shapes is not allowed.");
                 return None;
             }
         };
@@ -0,0 +1,129 @@
+// Copyright 2024 the Resvg Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::collections::HashMap;
+
+use super::svgtree::{self, AId, SVG_NS};
+use super::Options;
+use crate::Metadata;
+
+/// Collects metadata of all elements with an ID and the root element.
+///
+/// Metadata is looked up by ID, therefore metadata set on other elements
+/// is dropped with a warning.
+///
+/// Works on the original XML, since `title` and `desc` elements, as well as
+/// `class` and `data-*` attributes, are not preserved by the SVG tree.
+/// Properties, like `pointer-events`, are taken from the SVG tree instead,
+/// since CSS and inheritance are already resolved there.
+pub(crate) fn collect(
+    doc: &roxmltree::Document,
+    svg_doc: &svgtree::Document,
+    opt: &Options,
+) -> HashMap<String, Metadata> {
+    let mut metadata = HashMap::new();
+
+    let root = doc.root_element();
+    for node in root.descendants() {
+        if !node.is_element() || node.tag_name().namespace() != Some(SVG_NS) {
+            continue;
+        }
+
+        let data = Metadata {
+            title: child_text(node, "title"),
+            desc: child_text(node, "desc"),
+            aria_label: node.attribute("aria-label").map(|s| s.trim().to_string()),
+            classes: node
+                .attribute("class")
+                .map(|s| s.split_ascii_whitespace().map(str::to_string).collect())
+                .unwrap_or_default(),
+            data_attributes: node
+                .attributes()
+                .filter(|a| a.namespace().is_none() && a.name().starts_with("data-"))
+                .map(|a| (a.name().to_string(), a.value().to_string()))
+                .collect(),
+            ..Metadata::default()
+        };
+
+        let has_data = data.title.is_some()
+            || data.desc.is_some()
+            || data.aria_label.is_some()
+            || !data.classes.is_empty()
+            || !data.data_attributes.is_empty();
+        // Properties set via CSS are not checked.
+        let has_properties = node.has_attribute("pointer-events") || node.has_attribute("cursor");
+        if !has_data && !has_properties {
+            continue;
+        }
+
+        // The root element is stored under an empty ID, like the root group.
+        let id = if node == root {
+            ""
+        } else {
+            match node.attribute("id") {
+                Some(id) if !id.is_empty() => id,
+                _ => {
+                    warn_dropped(opt, node.tag_name().name());
+                    continue;
+                }
+            }
+        };
+
+        if has_data {
+            metadata.insert(id.to_string(), data);
+        }
+    }
+
+    let root = svg_doc.root_element();
+    for node in root.descendants() {
+        if !node.is_element() {
+            continue;
+        }
+
+        let id = if node == root {
+            ""
+        } else {
+            match node.element_id() {
+                "" => continue,
+                id => id,
+            }
+        };
+
+        let pointer_events: Option<&str> = node.find_attribute(AId::PointerEvents);
+        let cursor: Option<&str> = node.find_attribute(AId::Cursor);
+        if pointer_events.is_none() && cursor.is_none() {
+            continue;
+        }
+
+        let data = metadata.entry(id.to_string()).or_default();
+        data.pointer_events = pointer_events.map(str::to_string);
+        data.cursor = cursor.map(str::to_string);
+    }
+
+    metadata
+}
+
+fn warn_dropped(opt: &Options, tag_name: &str) {
+    opt.warn(
+        "metadata",
+        "",
+        &format!(
+            "Metadata of a '{}' element without an ID is not preserved.",
+            tag_name
+        ),
+    );
+}
+
+fn child_text(node: roxmltree::Node, name: &str) -> Option<String> {
+    let child = node.children().find(|n| {
+        n.is_element() && n.tag_name().namespace() == Some(SVG_NS) && n.tag_name().name() == name
+    })?;
+
+    let text: String = child
+        .descendants()
+        .filter(|n| n.is_text())
+        .filter_map(|n| n.text())
+        .collect();
+
+    Some(text.trim().to_string())
+}
@@ -2,13 +2,17 @@
 // SPDX-License-Identifier: Apache-2.0 OR MIT
 
 mod clippath;
+mod computed_style;
 mod converter;
+mod diagnostics;
 mod filter;
 mod image;
 mod marker;
 mod mask;
+mod metadata;
 mod options;
 mod paint_server;
+mod reparse;
 mod shapes;
 mod style;
 mod svgtree;
@@ -19,8 +23,9 @@ mod use_node;
 #[cfg(feature = "text")]
 mod text;
 
+pub use diagnostics::{Diagnostic, Diagnostics};
 pub use image::{ImageHrefDataResolverFn, ImageHrefResolver, ImageHrefStringResolverFn};
-pub use options::Options;
+pub use options::{FilterPolicy, FilterPredicateFn, Options};
 pub(crate) use svgtree::{AId, EId};
 
 /// List of all errors.
@@ -94,6 +99,9 @@ impl crate::Tree {
     /// Parses `Tree` from an SVG data.
     ///
     /// Can contain an SVG string or a gzip compressed data.
+    ///
+    /// An SVG string is parsed in place, without copying.
+    /// A compressed one is decompressed into a single buffer first.
     pub fn from_data(data: &[u8], opt: &Options) -> Result<Self, Error> {
         if data.starts_with(&[0x1f, 0x8b]) {
             let data = decompress_svgz(data)?;
@@ -120,8 +128,28 @@ impl crate::Tree {
 
     /// Parses `Tree` from `roxmltree::Document`.
     pub fn from_xmltree(doc: &roxmltree::Document, opt: &Options) -> Result<Self, Error> {
-        let doc = svgtree::Document::parse_tree(doc, opt.style_sheet.as_deref())?;
-        self::converter::convert_doc(&doc, opt)
+        let svg_doc = svgtree::Document::parse_tree(
+            doc,
+            opt.style_sheet.as_deref(),
+            &opt.media_features,
+            opt.default_size,
+            opt.diagnostics,
+        )?;
+        let mut tree = self::converter::convert_doc(&svg_doc, opt)?;
+
+        if opt.keep_computed_styles {
+            tree.computed_styles = self::computed_style::collect(&svg_doc);
+        }
+
+        if opt.keep_metadata {
+            tree.metadata = self::metadata::collect(doc, &svg_doc, opt);
+        }
+
+        if let Some(diagnostics) = opt.diagnostics {
+            self::diagnostics::collect(doc, diagnostics);
+        }
+
+        Ok(tree)
     }
 }
 
@@ -130,13 +158,31 @@ pub fn decompress_svgz(data: &[u8]) -> Result<Vec<u8>, Error> {
     use std::io::Read;
 
     let mut decoder = flate2::read::GzDecoder::new(data);
-    let mut decoded = Vec::with_capacity(data.len() * 2);
+    let mut decoded = Vec::new();
+    // The hint comes from the input, so a failed allocation is not an error.
+    // The buffer will simply grow as usual.
+    let _ = decoded.try_reserve_exact(decompressed_size_hint(data));
     decoder
         .read_to_end(&mut decoded)
         .map_err(|_| Error::MalformedGZip)?;
     Ok(decoded)
 }
 
+/// Returns the decompressed size stored in the gzip trailer.
+///
+/// Allows allocating the output buffer once instead of growing it,
+/// which halves the peak memory usage on large files.
+/// The stored size is just a hint, since it is truncated to 32 bits and can be malformed.
+/// A forged trailer can still request up to 4 GiB, so it must be reserved fallibly.
+fn decompressed_size_hint(data: &[u8]) -> usize {
+    // Deflate cannot compress better than ~1032:1.
+    let max_size = data.len().saturating_mul(1032);
+    match data.len().checked_sub(4).map(|start| &data[start..]) {
+        Some(&[a, b, c, d]) => (u32::from_le_bytes([a, b, c, d]) as usize).min(max_size),
+        _ => data.len() * 2,
+    }
+}
+
 #[inline]
 pub(crate) fn f32_bound(min: f32, val: f32, max: f32) -> f32 {
     debug_assert!(min.is_finite());
@@ -1,12 +1,54 @@
 // Copyright 2018 the Resvg Authors
 // SPDX-License-Identifier: Apache-2.0 OR MIT
 
+use std::collections::HashMap;
 #[cfg(feature = "text")]
 use std::sync::Arc;
 
 #[cfg(feature = "text")]
 use crate::FontResolver;
-use crate::{ImageHrefResolver, ImageRendering, ShapeRendering, Size, TextRendering};
+use crate::{
+    filter, Diagnostics, ImageHrefResolver, ImageRendering, ShapeRendering, Size, TextRendering,
+};
+
+/// A shorthand for [FilterPolicy]'s predicate function.
+pub type FilterPredicateFn<'a> = Box<dyn Fn(&filter::Primitive) -> bool + Send + Sync + 'a>;
+
+/// Specifies which filters should be preserved.
+///
+/// Filters that are not allowed are removed from their groups,
+/// which will be rendered as if the filter wasn't set at all.
+/// Useful for skipping expensive filters when rendering thumbnails.
+#[derive(Default)]
+pub enum FilterPolicy<'a> {
+    /// Preserve all filters.
+    #[default]
+    AllowAll,
+    /// Remove all filters.
+    DenyAll,
+    /// Preserve only filters for which the predicate returns `true` for each primitive.
+    Custom(FilterPredicateFn<'a>),
+}
+
+impl FilterPolicy<'_> {
+    pub(crate) fn allows(&self, filter: &filter::Filter) -> bool {
+        match self {
+            FilterPolicy::AllowAll => true,
+            FilterPolicy::DenyAll => false,
+            FilterPolicy::Custom(ref predicate) => filter.primitives().iter().all(predicate),
+        }
+    }
+}
+
+impl std::fmt::Debug for FilterPolicy<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FilterPolicy::AllowAll => f.write_str("AllowAll"),
+            FilterPolicy::DenyAll => f.write_str("DenyAll"),
+            FilterPolicy::Custom(_) => f.write_str("Custom(..)"),
+        }
+    }
+}
 
 /// Processing options.
 #[derive(Debug)]
@@ -21,7 +63,18 @@ pub struct Options<'a> {
 
     /// Target DPI.
     ///
-    /// Impacts units conversion.
+    /// Used to convert absolute units, i.e. `in`, `cm`, `mm`, `pt` and `pc`, into user units
+    /// during parsing. For example, `15pt` is `20` at 96 DPI and `40` at 192 DPI.
+    /// Lengths in the CSS `transform` syntax, like `translate(1in)`, are converted as well.
+    /// Unitless numbers, `px` and relative units, like `em` and `%`, are not affected.
+    ///
+    /// This is not an output resolution. [`Tree::size`](crate::Tree::size) changes only when
+    /// the root `width` and `height` use absolute units, and the content scales with it
+    /// only through the root `viewBox`. To render at a different resolution,
+    /// scale the render transform instead.
+    ///
+    /// Converted lengths do not remember their original units,
+    /// therefore changing DPI requires parsing the document again.
     ///
     /// Default: 96.0
     pub dpi: f32,
@@ -40,11 +93,23 @@ pub struct Options<'a> {
     /// Default: 12
     pub font_size: f32,
 
+    /// Enables kerning.
+    ///
+    /// When set to `false`, kerning will be disabled for all text spans,
+    /// regardless of `kerning` and `font-kerning` properties.
+    /// This will change glyph advances and therefore the text layout,
+    /// but makes it independent from font kerning tables.
+    ///
+    /// Default: true
+    pub apply_kerning: bool,
+
     /// A list of languages.
     ///
     /// Will be used to resolve a `systemLanguage` conditional attribute.
     ///
-    /// Format: en, en-US.
+    /// Each entry is a BCP 47 language range, like `en`, `en-US` or `zh-Hant`.
+    /// A range matches a `systemLanguage` tag when it is equal to the tag or to its prefix
+    /// followed by `-`, ignoring case. So `en` matches `en-US`, but not vice versa.
     ///
     /// Default: `[en]`
     pub languages: Vec<String>,
@@ -76,6 +141,22 @@ pub struct Options<'a> {
     /// Default: `(100, 100)`
     pub default_size: Size,
 
+    /// Extends the viewport to include content outside of it
+    /// when the root element doesn't have a `viewBox`.
+    ///
+    /// Without a `viewBox`, content outside of `width` and `height` is clipped.
+    /// When set, an implicit `viewBox` is computed from the union of the viewport
+    /// and the root layer bounding box, i.e. including strokes and filter regions.
+    /// Documents that fit their viewport are not affected.
+    ///
+    /// Since the image size is preserved, this changes the mapping between
+    /// the SVG user space and the canvas: the content is scaled down and shifted
+    /// according to `preserveAspectRatio`.
+    /// See [`Tree::view_transform`](crate::Tree::view_transform).
+    ///
+    /// Default: false
+    pub fit_viewbox_to_content: bool,
+
     /// Specifies the way `xlink:href` in `<image>` elements should be handled.
     ///
     /// Default: see type's documentation for details
@@ -95,9 +176,102 @@ pub struct Options<'a> {
     /// be the same as this one.
     #[cfg(feature = "text")]
     pub fontdb: Arc<fontdb::Database>,
+
+    /// Specifies how color glyphs, like emoji, should be rendered.
+    ///
+    /// Default: [`EmojiRendering::Color`](crate::EmojiRendering::Color)
+    #[cfg(feature = "text")]
+    pub emoji_rendering: crate::EmojiRendering,
+
+    /// Disables font substitution.
+    ///
+    /// When set, only fonts of the families listed in `font-family` are used.
+    /// The default serif font and fallback fonts for unsupported characters are not.
+    /// Fonts are still selected by [`font_resolver`](Self::font_resolver),
+    /// but its fallback selection function is not used.
+    /// Text spans without a matching font or with unsupported characters are omitted
+    /// and reported to [`diagnostics`](Self::diagnostics).
+    ///
+    /// This means that text may be partially or completely missing.
+    /// Useful for detecting missing fonts.
+    ///
+    /// Default: false
+    #[cfg(feature = "text")]
+    pub strict_fonts: bool,
+
+    /// A glyph outlines cache shared between trees.
+    ///
+    /// Speeds up text to path conversion when many documents use the same fonts.
+    /// See [`GlyphCache`](crate::GlyphCache) for details.
+    ///
+    /// Default: `None`
+    #[cfg(feature = "text")]
+    pub glyph_cache: Option<&'a crate::GlyphCache>,
+
     /// A CSS stylesheet that should be injected into the SVG. Can be used to overwrite
     /// certain attributes.
     pub style_sheet: Option<String>,
+
+    /// Values of CSS media features used to evaluate `@media` rules.
+    ///
+    /// Keys are feature names without the `min-`/`max-` prefix, like `prefers-color-scheme`.
+    /// `width` and `height` default to the SVG size in pixels.
+    /// Media types other than `all` and `screen` never match.
+    ///
+    /// Default: `{"prefers-color-scheme": "light"}`
+    pub media_features: HashMap<String, String>,
+
+    /// Keep non-rendering element metadata, like `title`, `desc`, `aria-label`,
+    /// `class`, `data-*` attributes, `pointer-events` and `cursor`.
+    ///
+    /// Useful for building an interactive layer on top of a rendered image.
+    ///
+    /// Will be available via [`Tree::metadata`](crate::Tree::metadata).
+    /// Only elements with an ID and the root element are covered.
+    /// Metadata of other elements is dropped with a warning.
+    ///
+    /// Default: false
+    pub keep_metadata: bool,
+
+    /// Keep computed styles of elements with an ID.
+    ///
+    /// Will be available via [`Tree::computed_style`](crate::Tree::computed_style).
+    ///
+    /// Default: false
+    pub keep_computed_styles: bool,
+
+    /// Keep a group for each `use` element and the ID of the element it references.
+    ///
+    /// `use` elements are still expanded into copies of the referenced content,
+    /// but the group created for a `use` element is never removed,
+    /// so instances can be mapped back to their symbols.
+    ///
+    /// Will be available via [`Group::use_href`](crate::Group::use_href).
+    ///
+    /// Default: false
+    pub keep_use_references: bool,
+
+    /// Keep elliptical arcs of shapes.
+    ///
+    /// Arcs are always approximated with curves in [`Path::data`](crate::Path::data).
+    /// When set, paths with arcs will also have
+    /// [`Path::arc_segments`](crate::Path::arc_segments).
+    ///
+    /// Default: false
+    pub keep_arcs: bool,
+
+    /// Specifies which filters should be preserved.
+    ///
+    /// Default: [`FilterPolicy::AllowAll`]
+    pub filter_policy: FilterPolicy<'a>,
+
+    /// A collector for SVG features that were ignored during parsing.
+    ///
+    /// Unsupported elements, like animations, and some unsupported attribute values
+    /// will be reported there in addition to being logged.
+    ///
+    /// Default: `None`
+    pub diagnostics: Option<&'a Diagnostics>,
 }
 
 impl Default for Options<'_> {
@@ -108,17 +282,35 @@ impl Default for Options<'_> {
             // Default font is user-agent dependent so we can use whichever we like.
             font_family: "Times New Roman".to_owned(),
             font_size: 12.0,
+            apply_kerning: true,
             languages: vec!["en".to_string()],
             shape_rendering: ShapeRendering::default(),
             text_rendering: TextRendering::default(),
             image_rendering: ImageRendering::default(),
             default_size: Size::from_wh(100.0, 100.0).unwrap(),
+            fit_viewbox_to_content: false,
             image_href_resolver: ImageHrefResolver::default(),
             #[cfg(feature = "text")]
             font_resolver: FontResolver::default(),
             #[cfg(feature = "text")]
             fontdb: Arc::new(fontdb::Database::new()),
+            #[cfg(feature = "text")]
+            emoji_rendering: crate::EmojiRendering::default(),
+            #[cfg(feature = "text")]
+            strict_fonts: false,
+            #[cfg(feature = "text")]
+            glyph_cache: None,
             style_sheet: None,
+            media_features: HashMap::from([(
+                "prefers-color-scheme".to_string(),
+                "light".to_string(),
+            )]),
+            keep_metadata: false,
+            keep_computed_styles: false,
+            keep_use_references: false,
+            keep_arcs: false,
+            filter_policy: FilterPolicy::default(),
+            diagnostics: None,
         }
     }
 }
@@ -134,6 +326,18 @@ impl Options<'_> {
         }
     }
 
+    /// Reports an ignored feature to the diagnostics collector, if any.
+    pub(crate) fn report(&self, feature: &str, element_id: &str, note: &str) {
+        if let Some(diagnostics) = self.diagnostics {
+            diagnostics.push(feature, element_id, None, note);
+        }
+    }
+
+    /// Logs a warning and reports it to the diagnostics collector, if any.
+    pub(crate) fn warn(&self, feature: &str, element_id: &str, message: &str) {
+        super::diagnostics::warn(self.diagnostics, feature, element_id, message);
+    }
+
     /// Mutably acquires the database.
     ///
     /// This clones the database if it is currently shared.
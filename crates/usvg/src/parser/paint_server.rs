@@ -14,7 +14,11 @@ use crate::*;
 
 pub(crate) enum ServerOrColor {
     Server(Paint),
-    Color { color: Color, opacity: Opacity },
+    Color {
+        color: Color,
+        opacity: Opacity,
+        current_color: bool,
+    },
 }
 
 pub(crate) fn convert(
@@ -54,7 +58,8 @@ fn convert_linear(node: SvgNode, state: &converter::State) -> Option<ServerOrCol
     }
 
     let units = convert_units(node, AId::GradientUnits, Units::ObjectBoundingBox);
-    let transform = node.resolve_transform(AId::GradientTransform, state);
+    let transform =
+        resolve_attr(node, AId::GradientTransform).resolve_transform(AId::GradientTransform, state);
 
     let gradient = LinearGradient {
         x1: resolve_number(node, AId::X1, units, state, Length::zero()),
@@ -72,6 +77,7 @@ fn convert_linear(node: SvgNode, state: &converter::State) -> Option<ServerOrCol
             units,
             transform,
             spread_method: convert_spread_method(node),
+            color_interpolation: convert_color_interpolation(node),
             stops,
         },
     };
@@ -102,6 +108,7 @@ fn convert_radial(node: SvgNode, state: &converter::State) -> Option<ServerOrCol
         return Some(ServerOrColor::Color {
             color: stop.color,
             opacity: stop.opacity,
+            current_color: stop.current_color,
         });
     }
 
@@ -122,7 +129,8 @@ fn convert_radial(node: SvgNode, state: &converter::State) -> Option<ServerOrCol
     );
     let fx = resolve_number(node, AId::Fx, units, state, Length::new_number(cx as f64));
     let fy = resolve_number(node, AId::Fy, units, state, Length::new_number(cy as f64));
-    let transform = node.resolve_transform(AId::GradientTransform, state);
+    let transform =
+        resolve_attr(node, AId::GradientTransform).resolve_transform(AId::GradientTransform, state);
 
     let gradient = RadialGradient {
         cx,
@@ -135,6 +143,7 @@ fn convert_radial(node: SvgNode, state: &converter::State) -> Option<ServerOrCol
             units,
             transform,
             spread_method,
+            color_interpolation: convert_color_interpolation(node),
             stops,
         },
     };
@@ -166,7 +175,8 @@ fn convert_pattern(
     let units = convert_units(node, AId::PatternUnits, Units::ObjectBoundingBox);
     let content_units = convert_units(node, AId::PatternContentUnits, Units::UserSpaceOnUse);
 
-    let transform = node.resolve_transform(AId::PatternTransform, state);
+    let transform =
+        resolve_attr(node, AId::PatternTransform).resolve_transform(AId::PatternTransform, state);
 
     let rect = NonZeroRect::from_xywh(
         resolve_number(node, AId::X, units, state, Length::zero()),
@@ -175,9 +185,12 @@ fn convert_pattern(
         resolve_number(node, AId::Height, units, state, Length::zero()),
     );
     let rect = rect.log_none(|| {
-        log::warn!(
-            "Pattern '{}' has an invalid size. Skipped.",
-            node.element_id()
+        node.warn(
+            "pattern",
+            &format!(
+                "Pattern '{}' has an invalid size. Skipped.",
+                node.element_id()
+            ),
         )
     })?;
 
@@ -225,6 +238,12 @@ fn convert_spread_method(node: SvgNode) -> SpreadMethod {
     node.attribute(AId::SpreadMethod).unwrap_or_default()
 }
 
+fn convert_color_interpolation(node: SvgNode) -> GradientInterpolation {
+    // A presentation attribute, so it's inherited from ancestors and not via `xlink:href`.
+    node.find_attribute(AId::ColorInterpolation)
+        .unwrap_or_default()
+}
+
 pub(crate) fn convert_units(node: SvgNode, name: AId, def: Units) -> Units {
     let node = resolve_attr(node, name);
     node.attribute(name).unwrap_or(def)
@@ -235,10 +254,13 @@ fn find_gradient_with_stops<'a, 'input: 'a>(
 ) -> Option<SvgNode<'a, 'input>> {
     for link in node.href_iter() {
         if !link.tag_name().unwrap().is_gradient() {
-            log::warn!(
-                "Gradient '{}' cannot reference '{}' via 'xlink:href'.",
-                node.element_id(),
-                link.tag_name().unwrap()
+            node.warn(
+                "xlink:href",
+                &format!(
+                    "Gradient '{}' cannot reference '{}' via 'xlink:href'.",
+                    node.element_id(),
+                    link.tag_name().unwrap()
+                ),
             );
             return None;
         }
@@ -256,10 +278,13 @@ fn find_pattern_with_children<'a, 'input: 'a>(
 ) -> Option<SvgNode<'a, 'input>> {
     for link in node.href_iter() {
         if link.tag_name() != Some(EId::Pattern) {
-            log::warn!(
-                "Pattern '{}' cannot reference '{}' via 'xlink:href'.",
-                node.element_id(),
-                link.tag_name().unwrap()
+            node.warn(
+                "xlink:href",
+                &format!(
+                    "Pattern '{}' cannot reference '{}' via 'xlink:href'.",
+                    node.element_id(),
+                    link.tag_name().unwrap()
+                ),
             );
             return None;
         }
@@ -279,7 +304,10 @@ fn convert_stops(grad: SvgNode) -> Vec<Stop> {
         let mut prev_offset = Length::zero();
         for stop in grad.children() {
             if stop.tag_name() != Some(EId::Stop) {
-                log::warn!("Invalid gradient child: '{:?}'.", stop.tag_name().unwrap());
+                grad.warn(
+                    "stop",
+                    &format!("Invalid gradient child: '{:?}'.", stop.tag_name().unwrap()),
+                );
                 continue;
             }
 
@@ -293,6 +321,7 @@ fn convert_stops(grad: SvgNode) -> Vec<Stop> {
             prev_offset = Length::new_number(offset);
             let offset = crate::f32_bound(0.0, offset as f32, 1.0);
 
+            let current_color = stop.attribute(AId::StopColor) == Some("currentColor");
             let (color, opacity) = match stop.attribute(AId::StopColor) {
                 Some("currentColor") => stop
                     .find_attribute(AId::Color)
@@ -301,7 +330,10 @@ fn convert_stops(grad: SvgNode) -> Vec<Stop> {
                     if let Ok(c) = svgtypes::Color::from_str(value) {
                         c
                     } else {
-                        log::warn!("Failed to parse stop-color value: '{}'.", value);
+                        stop.warn(
+                            "stop-color",
+                            &format!("Failed to parse stop-color value: '{}'.", value),
+                        );
                         svgtypes::Color::black()
                     }
                 }
@@ -316,6 +348,7 @@ fn convert_stops(grad: SvgNode) -> Vec<Stop> {
                 offset: StopOffset::new_clamped(offset),
                 color,
                 opacity: opacity * stop_opacity,
+                current_color,
             });
         }
     }
@@ -541,6 +574,7 @@ fn stops_to_color(stops: &[Stop]) -> Option<ServerOrColor> {
         Some(ServerOrColor::Color {
             color: stops[0].color,
             opacity: stops[0].opacity,
+            current_color: stops[0].current_color,
         })
     }
 }
@@ -843,6 +877,7 @@ fn process_context_paint(
                     units: lg.units,
                     transform,
                     spread_method: lg.spread_method,
+                    color_interpolation: lg.color_interpolation,
                     stops: lg.stops.clone(),
                 },
             }));
@@ -860,6 +895,7 @@ fn process_context_paint(
                     units: rg.units,
                     transform,
                     spread_method: rg.spread_method,
+                    color_interpolation: rg.color_interpolation,
                     stops: rg.stops.clone(),
                 },
             }))
@@ -948,9 +984,13 @@ impl Paint {
         } else {
             "Gradient"
         };
-        let bbox = bbox
-            .to_non_zero_rect()
-            .log_none(|| log::warn!("{} on zero-sized shapes is not allowed.", name))?;
+        let bbox = bbox.to_non_zero_rect().log_none(|| {
+            cache.warn(
+                &name.to_lowercase(),
+                "",
+                &format!("{} on zero-sized shapes is not allowed.", name),
+            )
+        })?;
 
         // `Arc::get_mut()` allow us to modify some paint servers in-place.
         // This reduces the amount of cloning and preserves the original ID as well.
@@ -972,6 +1012,7 @@ impl Paint {
                             units: Units::UserSpaceOnUse,
                             transform,
                             spread_method: lg.spread_method,
+                            color_interpolation: lg.color_interpolation,
                             stops: lg.stops.clone(),
                         },
                     });
@@ -994,6 +1035,7 @@ impl Paint {
                             units: Units::UserSpaceOnUse,
                             transform,
                             spread_method: rg.spread_method,
+                            color_interpolation: rg.color_interpolation,
                             stops: rg.stops.clone(),
                         },
                     });
@@ -0,0 +1,317 @@
+// Copyright 2024 the Resvg Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use super::converter::{self, Cache, State};
+use super::svgtree::{self, AId, EId, SvgNode};
+use super::Options;
+use crate::{Group, Node, Paint, Transform, Tree};
+
+impl Tree {
+    /// Reconverts a single element after its attributes were changed.
+    ///
+    /// `doc` must be the document this tree was parsed from and `opt` must be the same
+    /// options. `attrs` are the new attribute values, which are set on top of the existing ones.
+    /// Like during parsing, values set by CSS take precedence over attributes.
+    ///
+    /// Only the element's subtree is converted again, while bounding boxes are updated
+    /// up to the root. Which is much faster than a full reparse when editing large documents.
+    ///
+    /// Returns `false` and leaves the tree intact when the change cannot be applied
+    /// incrementally, in which case the whole document must be parsed again. This happens when:
+    ///
+    /// - the element doesn't exist or wasn't rendered
+    /// - the element is not a `g`, a shape, `image` or `text` inside the main document,
+    ///   i.e. it is inside `defs`, `switch`, a nested `svg`, a clip path and so on
+    /// - the element or one of its ancestors is referenced by another element, like `use`
+    /// - the element contains resources, like gradients
+    /// - an attribute is unknown, is `id`, `class`, `style` or `xlink:href`,
+    ///   references another element via `url()` or is set to `inherit`
+    ///
+    /// The document itself is not modified and `attrs` are always applied on top of it.
+    /// So when the same element is edited multiple times, all its changed attributes
+    /// must be passed and not just the latest one.
+    pub fn reparse_element(
+        &mut self,
+        doc: &roxmltree::Document,
+        id: &str,
+        attrs: &[(&str, &str)],
+        opt: &Options,
+    ) -> bool {
+        self.reparse_element_impl(doc, id, attrs, opt).is_some()
+    }
+
+    fn reparse_element_impl(
+        &mut self,
+        doc: &roxmltree::Document,
+        id: &str,
+        attrs: &[(&str, &str)],
+        opt: &Options,
+    ) -> Option<()> {
+        let mut new_attrs = Vec::with_capacity(attrs.len());
+        for (name, value) in attrs {
+            let aid = AId::from_str(name)?;
+            if matches!(aid, AId::Id | AId::Class | AId::Style | AId::Href)
+                || value.contains("url(")
+                || value.trim() == "inherit"
+            {
+                return None;
+            }
+
+            new_attrs.push((aid, *value));
+        }
+
+        let mut svg_doc = svgtree::Document::parse_tree(
+            doc,
+            opt.style_sheet.as_deref(),
+            &opt.media_features,
+            opt.default_size,
+            opt.diagnostics,
+        )
+        .ok()?;
+
+        if !is_reparsable(svg_doc.element_by_id(id)?) {
+            return None;
+        }
+
+        let tree_path = find_node(&self.root, id)?;
+
+        // The element can be converted into multiple nodes, like a path wrapped into
+        // a group with a transform. To find them in the tree, we convert the unchanged
+        // element first and check where the node with the same ID ends up.
+        let (run_start, run_len, depth) = {
+            let mut cache = self.new_cache(&svg_doc, opt);
+            let mut old = Group::empty();
+            convert(svg_doc.element_by_id(id)?, self, opt, &mut cache, &mut old);
+
+            let old_path = find_node(&old, id)?;
+            let depth = tree_path.len().checked_sub(old_path.len())?;
+            if tree_path[depth + 1..] != old_path[1..] {
+                return None;
+            }
+
+            let run_start = tree_path[depth].checked_sub(old_path[0])?;
+
+            // Make sure that the tree wasn't modified in a different way.
+            let parent = group_at(&self.root, &tree_path[..depth])?;
+            let run = parent
+                .children
+                .get(run_start..run_start + old.children.len())?;
+            let is_same = run.iter().zip(&old.children).all(|(a, b)| {
+                a.id() == b.id() && std::mem::discriminant(a) == std::mem::discriminant(b)
+            });
+            if !is_same {
+                return None;
+            }
+
+            (run_start, old.children.len(), depth)
+        };
+
+        let parent = group_at(&self.root, &tree_path[..depth])?;
+
+        svg_doc.set_attributes(id, &new_attrs)?;
+
+        let mut cache = self.new_cache(&svg_doc, opt);
+        let mut new = Group::empty();
+        new.abs_transform = parent.abs_transform;
+        convert(svg_doc.element_by_id(id)?, self, opt, &mut cache, &mut new);
+
+        let parent = group_at_mut(&mut self.root, &tree_path[..depth])?;
+        parent
+            .children
+            .splice(run_start..run_start + run_len, new.children);
+
+        // Bounding boxes of ancestors depend only on their children,
+        // so we can update them from the bottom up.
+        for i in (0..=depth).rev() {
+            group_at_mut(&mut self.root, &tree_path[..i])?.calculate_bounding_boxes();
+        }
+
+        // The fontdb might have been mutated during text conversion.
+        #[cfg(feature = "text")]
+        {
+            self.fontdb = cache.fontdb;
+        }
+
+        self.remove_unused_defs();
+
+        if opt.keep_computed_styles {
+            self.computed_styles = super::computed_style::collect(&svg_doc);
+        }
+
+        Some(())
+    }
+
+    /// Creates a cache that reuses resources of this tree.
+    ///
+    /// Otherwise, the converted element would reference copies of existing resources.
+    fn new_cache<'a>(&self, svg_doc: &svgtree::Document, opt: &Options<'a>) -> Cache<'a> {
+        let mut cache = Cache::new(
+            #[cfg(feature = "text")]
+            self.fontdb.clone(),
+            opt.diagnostics,
+        );
+        cache.reserve_resource_ids(svg_doc);
+
+        for lg in &self.linear_gradients {
+            cache.reserve_id(lg.id());
+            let paint = Paint::LinearGradient(lg.clone());
+            cache.paint.insert(lg.id().to_string(), paint);
+        }
+
+        for rg in &self.radial_gradients {
+            cache.reserve_id(rg.id());
+            let paint = Paint::RadialGradient(rg.clone());
+            cache.paint.insert(rg.id().to_string(), paint);
+        }
+
+        for patt in &self.patterns {
+            cache.reserve_id(patt.id());
+            let paint = Paint::Pattern(patt.clone());
+            cache.paint.insert(patt.id().to_string(), paint);
+        }
+
+        for clip in &self.clip_paths {
+            cache.reserve_id(clip.id());
+            cache.clip_paths.insert(clip.id().to_string(), clip.clone());
+        }
+
+        for mask in &self.masks {
+            cache.reserve_id(mask.id());
+            cache.masks.insert(mask.id().to_string(), mask.clone());
+        }
+
+        for filter in &self.filters {
+            cache.reserve_id(filter.id());
+            cache
+                .filters
+                .insert(filter.id().to_string(), filter.clone());
+        }
+
+        reserve_node_ids(&self.root, &mut cache);
+
+        cache
+    }
+}
+
+fn convert(node: SvgNode, tree: &Tree, opt: &Options, cache: &mut Cache, parent: &mut Group) {
+    let state = State {
+        parent_clip_path: None,
+        context_element: None,
+        parent_markers: Vec::new(),
+        fe_image_link: false,
+        view_box: tree.view_box.rect,
+        use_size: (None, None),
+        opt,
+    };
+
+    converter::convert_element(node, &state, cache, parent);
+
+    super::paint_server::update_paint_servers(parent, Transform::default(), None, None, cache);
+}
+
+/// Checks that an element can be converted without its ancestors.
+fn is_reparsable(node: SvgNode) -> bool {
+    match node.tag_name() {
+        Some(EId::G | EId::Image | EId::Text) => {}
+        Some(tag) if tag.is_graphic() && tag != EId::Use => {}
+        _ => return false,
+    }
+
+    // Only groups are allowed between the element and the root `svg`.
+    let root = node.document().root_element();
+    for ancestor in node.ancestors().skip(1) {
+        if ancestor.id() == root.id() {
+            break;
+        }
+
+        if ancestor.tag_name() != Some(EId::G) {
+            return false;
+        }
+    }
+
+    // Resources are converted once per document.
+    if node.descendants().any(|n| {
+        matches!(
+            n.tag_name(),
+            Some(
+                EId::ClipPath
+                    | EId::Filter
+                    | EId::LinearGradient
+                    | EId::Marker
+                    | EId::Mask
+                    | EId::Pattern
+                    | EId::RadialGradient
+                    | EId::Symbol
+                    | EId::Svg
+            )
+        )
+    }) {
+        return false;
+    }
+
+    // Referenced elements are copied during parsing, so copies would not be updated.
+    let ancestors: Vec<_> = node
+        .ancestors()
+        .take_while(|n| n.id() != root.id())
+        .map(|n| n.id())
+        .collect();
+    !node.document().descendants().any(|n| {
+        n.attribute::<SvgNode>(AId::Href)
+            .map(|link| ancestors.contains(&link.id()))
+            .unwrap_or(false)
+    })
+}
+
+/// Returns a path of child indices to a node with the specified ID.
+///
+/// Only the main tree is traversed. Clip paths, masks, patterns and so on are skipped.
+fn find_node(parent: &Group, id: &str) -> Option<Vec<usize>> {
+    for (i, node) in parent.children.iter().enumerate() {
+        if node.id() == id {
+            return Some(vec![i]);
+        }
+
+        if let Node::Group(ref group) = node {
+            if let Some(mut path) = find_node(group, id) {
+                path.insert(0, i);
+                return Some(path);
+            }
+        }
+    }
+
+    None
+}
+
+fn group_at<'a>(mut group: &'a Group, path: &[usize]) -> Option<&'a Group> {
+    for i in path {
+        match group.children.get(*i)? {
+            Node::Group(ref g) => group = g,
+            _ => return None,
+        }
+    }
+
+    Some(group)
+}
+
+fn group_at_mut<'a>(mut group: &'a mut Group, path: &[usize]) -> Option<&'a mut Group> {
+    for i in path {
+        match group.children.get_mut(*i)? {
+            Node::Group(ref mut g) => group = g,
+            _ => return None,
+        }
+    }
+
+    Some(group)
+}
+
+fn reserve_node_ids(parent: &Group, cache: &mut Cache) {
+    for node in &parent.children {
+        if !node.id().is_empty() {
+            cache.reserve_id(node.id());
+        }
+
+        if let Node::Group(ref group) = node {
+            reserve_node_ids(group, cache);
+        }
+    }
+}
@@ -8,22 +8,41 @@ use tiny_skia_path::Path;
 
 use super::svgtree::{AId, EId, SvgNode};
 use super::{converter, units};
-use crate::{ApproxEqUlps, IsValidLength, Rect};
-
-pub(crate) fn convert(node: SvgNode, state: &converter::State) -> Option<Arc<Path>> {
-    match node.tag_name()? {
-        EId::Rect => convert_rect(node, state),
-        EId::Circle => convert_circle(node, state),
-        EId::Ellipse => convert_ellipse(node, state),
-        EId::Line => convert_line(node, state),
-        EId::Polyline => convert_polyline(node),
-        EId::Polygon => convert_polygon(node),
-        EId::Path => convert_path(node),
-        _ => None,
-    }
+use crate::{ApproxEqUlps, ArcPathSegment, IsValidLength, Rect};
+
+/// A shape path and its segments with arcs preserved, if any.
+pub(crate) type Shape = (Arc<Path>, Option<Arc<Vec<ArcPathSegment>>>);
+
+pub(crate) fn convert(node: SvgNode, state: &converter::State) -> Option<Shape> {
+    let (path, arc_segments) = match node.tag_name()? {
+        EId::Rect => convert_rect(node, state)?,
+        EId::Circle => convert_circle(node, state)?,
+        EId::Ellipse => convert_ellipse(node, state)?,
+        EId::Line => (convert_line(node, state)?, None),
+        EId::Polyline => (convert_polyline(node)?, None),
+        EId::Polygon => (convert_polygon(node)?, None),
+        EId::Path => {
+            let path = convert_path(node)?;
+            let arc_segments = if state.opt.keep_arcs {
+                convert_path_arcs(node)
+            } else {
+                None
+            };
+            (path, arc_segments)
+        }
+        _ => return None,
+    };
+
+    let arc_segments = if state.opt.keep_arcs {
+        arc_segments.map(Arc::new)
+    } else {
+        None
+    };
+
+    Some((path, arc_segments))
 }
 
-pub(crate) fn convert_path(node: SvgNode) -> Option<Arc<Path>> {
+fn convert_path(node: SvgNode) -> Option<Arc<Path>> {
     let value: &str = node.attribute(AId::D)?;
     let mut builder = tiny_skia_path::PathBuilder::new();
     for segment in svgtypes::SimplifyingPathParser::from(value) {
@@ -63,21 +82,30 @@ pub(crate) fn convert_path(node: SvgNode) -> Option<Arc<Path>> {
     builder.finish().map(Arc::new)
 }
 
-fn convert_rect(node: SvgNode, state: &converter::State) -> Option<Arc<Path>> {
+fn convert_rect(
+    node: SvgNode,
+    state: &converter::State,
+) -> Option<(Arc<Path>, Option<Vec<ArcPathSegment>>)> {
     // 'width' and 'height' attributes must be positive and non-zero.
     let width = node.convert_user_length(AId::Width, state, Length::zero());
     let height = node.convert_user_length(AId::Height, state, Length::zero());
     if !width.is_valid_length() {
-        log::warn!(
-            "Rect '{}' has an invalid 'width' value. Skipped.",
-            node.element_id()
+        node.warn(
+            "rect",
+            &format!(
+                "Rect '{}' has an invalid 'width' value. Skipped.",
+                node.element_id()
+            ),
         );
         return None;
     }
     if !height.is_valid_length() {
-        log::warn!(
-            "Rect '{}' has an invalid 'height' value. Skipped.",
-            node.element_id()
+        node.warn(
+            "rect",
+            &format!(
+                "Rect '{}' has an invalid 'height' value. Skipped.",
+                node.element_id()
+            ),
         );
         return None;
     }
@@ -98,30 +126,36 @@ fn convert_rect(node: SvgNode, state: &converter::State) -> Option<Arc<Path>> {
     }
 
     // Conversion according to https://www.w3.org/TR/SVG11/shapes.html#RectElement
-    let path = if rx.approx_eq_ulps(&0.0, 4) {
-        tiny_skia_path::PathBuilder::from_rect(Rect::from_xywh(x, y, width, height)?)
-    } else {
-        let mut builder = tiny_skia_path::PathBuilder::new();
-        builder.move_to(x + rx, y);
-
-        builder.line_to(x + width - rx, y);
-        builder.arc_to(rx, ry, 0.0, false, true, x + width, y + ry);
-
-        builder.line_to(x + width, y + height - ry);
-        builder.arc_to(rx, ry, 0.0, false, true, x + width - rx, y + height);
-
-        builder.line_to(x + rx, y + height);
-        builder.arc_to(rx, ry, 0.0, false, true, x, y + height - ry);
-
-        builder.line_to(x, y + ry);
-        builder.arc_to(rx, ry, 0.0, false, true, x + rx, y);
-
-        builder.close();
+    if rx.approx_eq_ulps(&0.0, 4) {
+        let path = tiny_skia_path::PathBuilder::from_rect(Rect::from_xywh(x, y, width, height)?);
+        return Some((Arc::new(path), None));
+    }
 
-        builder.finish()?
+    let arc = |x, y| ArcPathSegment::ArcTo {
+        rx,
+        ry,
+        x_axis_rotation: 0.0,
+        large_arc: false,
+        sweep: true,
+        to: tiny_skia_path::Point::from_xy(x, y),
     };
-
-    Some(Arc::new(path))
+    let line = |x, y| ArcPathSegment::LineTo(tiny_skia_path::Point::from_xy(x, y));
+
+    let segments = vec![
+        ArcPathSegment::MoveTo(tiny_skia_path::Point::from_xy(x + rx, y)),
+        line(x + width - rx, y),
+        arc(x + width, y + ry),
+        line(x + width, y + height - ry),
+        arc(x + width - rx, y + height),
+        line(x + rx, y + height),
+        arc(x, y + height - ry),
+        line(x, y + ry),
+        arc(x + rx, y),
+        ArcPathSegment::Close,
+    ];
+
+    let path = arc_segments_to_path(&segments)?;
+    Some((path, Some(segments)))
 }
 
 fn resolve_rx_ry(node: SvgNode, state: &converter::State) -> (f32, f32) {
@@ -197,10 +231,13 @@ fn points_to_path(node: SvgNode, eid: &str) -> Option<tiny_skia_path::PathBuilde
             }
         }
         _ => {
-            log::warn!(
-                "{} '{}' has an invalid 'points' value. Skipped.",
-                eid,
-                node.element_id()
+            node.warn(
+                &eid.to_lowercase(),
+                &format!(
+                    "{} '{}' has an invalid 'points' value. Skipped.",
+                    eid,
+                    node.element_id()
+                ),
             );
             return None;
         }
@@ -208,10 +245,13 @@ fn points_to_path(node: SvgNode, eid: &str) -> Option<tiny_skia_path::PathBuilde
 
     // 'polyline' and 'polygon' elements must contain at least 2 points.
     if builder.len() < 2 {
-        log::warn!(
-            "{} '{}' has less than 2 points. Skipped.",
-            eid,
-            node.element_id()
+        node.warn(
+            &eid.to_lowercase(),
+            &format!(
+                "{} '{}' has less than 2 points. Skipped.",
+                eid,
+                node.element_id()
+            ),
         );
         return None;
     }
@@ -219,15 +259,21 @@ fn points_to_path(node: SvgNode, eid: &str) -> Option<tiny_skia_path::PathBuilde
     Some(builder)
 }
 
-fn convert_circle(node: SvgNode, state: &converter::State) -> Option<Arc<Path>> {
+fn convert_circle(
+    node: SvgNode,
+    state: &converter::State,
+) -> Option<(Arc<Path>, Option<Vec<ArcPathSegment>>)> {
     let cx = node.convert_user_length(AId::Cx, state, Length::zero());
     let cy = node.convert_user_length(AId::Cy, state, Length::zero());
     let r = node.convert_user_length(AId::R, state, Length::zero());
 
     if !r.is_valid_length() {
-        log::warn!(
-            "Circle '{}' has an invalid 'r' value. Skipped.",
-            node.element_id()
+        node.warn(
+            "circle",
+            &format!(
+                "Circle '{}' has an invalid 'r' value. Skipped.",
+                node.element_id()
+            ),
         );
         return None;
     }
@@ -235,23 +281,32 @@ fn convert_circle(node: SvgNode, state: &converter::State) -> Option<Arc<Path>>
     ellipse_to_path(cx, cy, r, r)
 }
 
-fn convert_ellipse(node: SvgNode, state: &converter::State) -> Option<Arc<Path>> {
+fn convert_ellipse(
+    node: SvgNode,
+    state: &converter::State,
+) -> Option<(Arc<Path>, Option<Vec<ArcPathSegment>>)> {
     let cx = node.convert_user_length(AId::Cx, state, Length::zero());
     let cy = node.convert_user_length(AId::Cy, state, Length::zero());
     let (rx, ry) = resolve_rx_ry(node, state);
 
     if !rx.is_valid_length() {
-        log::warn!(
-            "Ellipse '{}' has an invalid 'rx' value. Skipped.",
-            node.element_id()
+        node.warn(
+            "ellipse",
+            &format!(
+                "Ellipse '{}' has an invalid 'rx' value. Skipped.",
+                node.element_id()
+            ),
         );
         return None;
     }
 
     if !ry.is_valid_length() {
-        log::warn!(
-            "Ellipse '{}' has an invalid 'ry' value. Skipped.",
-            node.element_id()
+        node.warn(
+            "ellipse",
+            &format!(
+                "Ellipse '{}' has an invalid 'ry' value. Skipped.",
+                node.element_id()
+            ),
         );
         return None;
     }
@@ -259,17 +314,247 @@ fn convert_ellipse(node: SvgNode, state: &converter::State) -> Option<Arc<Path>>
     ellipse_to_path(cx, cy, rx, ry)
 }
 
-fn ellipse_to_path(cx: f32, cy: f32, rx: f32, ry: f32) -> Option<Arc<Path>> {
+fn ellipse_to_path(
+    cx: f32,
+    cy: f32,
+    rx: f32,
+    ry: f32,
+) -> Option<(Arc<Path>, Option<Vec<ArcPathSegment>>)> {
+    let arc = |x, y| ArcPathSegment::ArcTo {
+        rx,
+        ry,
+        x_axis_rotation: 0.0,
+        large_arc: false,
+        sweep: true,
+        to: tiny_skia_path::Point::from_xy(x, y),
+    };
+
+    let segments = vec![
+        ArcPathSegment::MoveTo(tiny_skia_path::Point::from_xy(cx + rx, cy)),
+        arc(cx, cy + ry),
+        arc(cx - rx, cy),
+        arc(cx, cy - ry),
+        arc(cx + rx, cy),
+        ArcPathSegment::Close,
+    ];
+
+    let path = arc_segments_to_path(&segments)?;
+    Some((path, Some(segments)))
+}
+
+fn arc_segments_to_path(segments: &[ArcPathSegment]) -> Option<Arc<Path>> {
     let mut builder = tiny_skia_path::PathBuilder::new();
-    builder.move_to(cx + rx, cy);
-    builder.arc_to(rx, ry, 0.0, false, true, cx, cy + ry);
-    builder.arc_to(rx, ry, 0.0, false, true, cx - rx, cy);
-    builder.arc_to(rx, ry, 0.0, false, true, cx, cy - ry);
-    builder.arc_to(rx, ry, 0.0, false, true, cx + rx, cy);
-    builder.close();
+    for segment in segments {
+        match *segment {
+            ArcPathSegment::MoveTo(p) => builder.move_to(p.x, p.y),
+            ArcPathSegment::LineTo(p) => builder.line_to(p.x, p.y),
+            ArcPathSegment::QuadTo(p1, p) => builder.quad_to(p1.x, p1.y, p.x, p.y),
+            ArcPathSegment::CubicTo(p1, p2, p) => {
+                builder.cubic_to(p1.x, p1.y, p2.x, p2.y, p.x, p.y)
+            }
+            ArcPathSegment::ArcTo {
+                rx,
+                ry,
+                x_axis_rotation,
+                large_arc,
+                sweep,
+                to,
+            } => builder.arc_to(rx, ry, x_axis_rotation, large_arc, sweep, to.x, to.y),
+            ArcPathSegment::Close => builder.close(),
+        }
+    }
+
     builder.finish().map(Arc::new)
 }
 
+/// Converts path data into absolute segments, like `SimplifyingPathParser`,
+/// but without approximating arcs.
+///
+/// Returns `None` when there are no arcs.
+fn convert_path_arcs(node: SvgNode) -> Option<Vec<ArcPathSegment>> {
+    use svgtypes::PathSegment;
+
+    let value: &str = node.attribute(AId::D)?;
+
+    let point = |x: f64, y: f64| tiny_skia_path::Point::from_xy(x as f32, y as f32);
+
+    let mut segments = Vec::new();
+    let mut has_arcs = false;
+    // Current point and the start of the current subpath.
+    let (mut px, mut py) = (0.0, 0.0);
+    let (mut mx, mut my) = (0.0, 0.0);
+    // The last control point of the previous curve. Used by smooth curves.
+    let mut prev_cubic_ctrl = None;
+    let mut prev_quad_ctrl = None;
+    let mut is_closed = false;
+    for segment in svgtypes::PathParser::from(value) {
+        let segment = match segment {
+            Ok(v) => v,
+            Err(_) => break,
+        };
+
+        // If a ClosePath segment is followed by any command other than MoveTo or ClosePath
+        // then MoveTo is implicit.
+        if is_closed
+            && !matches!(
+                segment,
+                PathSegment::MoveTo { .. } | PathSegment::ClosePath { .. }
+            )
+        {
+            segments.push(ArcPathSegment::MoveTo(point(mx, my)));
+        }
+
+        // Relative coordinates are offset by the current point.
+        let (ox, oy) = if is_absolute(&segment) {
+            (0.0, 0.0)
+        } else {
+            (px, py)
+        };
+
+        let mut cubic_ctrl = None;
+        let mut quad_ctrl = None;
+        is_closed = false;
+        match segment {
+            PathSegment::MoveTo { x, y, .. } => {
+                (px, py) = (ox + x, oy + y);
+                (mx, my) = (px, py);
+                segments.push(ArcPathSegment::MoveTo(point(px, py)));
+            }
+            PathSegment::LineTo { x, y, .. } => {
+                (px, py) = (ox + x, oy + y);
+                segments.push(ArcPathSegment::LineTo(point(px, py)));
+            }
+            PathSegment::HorizontalLineTo { x, .. } => {
+                px = ox + x;
+                segments.push(ArcPathSegment::LineTo(point(px, py)));
+            }
+            PathSegment::VerticalLineTo { y, .. } => {
+                py = oy + y;
+                segments.push(ArcPathSegment::LineTo(point(px, py)));
+            }
+            PathSegment::CurveTo {
+                x1,
+                y1,
+                x2,
+                y2,
+                x,
+                y,
+                ..
+            } => {
+                let p1 = (ox + x1, oy + y1);
+                let p2 = (ox + x2, oy + y2);
+                (px, py) = (ox + x, oy + y);
+                segments.push(ArcPathSegment::CubicTo(
+                    point(p1.0, p1.1),
+                    point(p2.0, p2.1),
+                    point(px, py),
+                ));
+                cubic_ctrl = Some(p2);
+            }
+            PathSegment::SmoothCurveTo { x2, y2, x, y, .. } => {
+                let p1 = match prev_cubic_ctrl {
+                    Some((cx, cy)) => (px * 2.0 - cx, py * 2.0 - cy),
+                    None => (px, py),
+                };
+                let p2 = (ox + x2, oy + y2);
+                (px, py) = (ox + x, oy + y);
+                segments.push(ArcPathSegment::CubicTo(
+                    point(p1.0, p1.1),
+                    point(p2.0, p2.1),
+                    point(px, py),
+                ));
+                cubic_ctrl = Some(p2);
+            }
+            PathSegment::Quadratic { x1, y1, x, y, .. } => {
+                let p1 = (ox + x1, oy + y1);
+                (px, py) = (ox + x, oy + y);
+                segments.push(ArcPathSegment::QuadTo(point(p1.0, p1.1), point(px, py)));
+                quad_ctrl = Some(p1);
+            }
+            PathSegment::SmoothQuadratic { x, y, .. } => {
+                let p1 = match prev_quad_ctrl {
+                    Some((cx, cy)) => (px * 2.0 - cx, py * 2.0 - cy),
+                    None => (px, py),
+                };
+                (px, py) = (ox + x, oy + y);
+                segments.push(ArcPathSegment::QuadTo(point(p1.0, p1.1), point(px, py)));
+                quad_ctrl = Some(p1);
+            }
+            PathSegment::EllipticalArc {
+                rx,
+                ry,
+                x_axis_rotation,
+                large_arc,
+                sweep,
+                x,
+                y,
+                ..
+            } => {
+                let svg_arc = kurbo::SvgArc {
+                    from: kurbo::Point::new(px, py),
+                    to: kurbo::Point::new(ox + x, oy + y),
+                    radii: kurbo::Vec2::new(rx, ry),
+                    x_rotation: x_axis_rotation.to_radians(),
+                    large_arc,
+                    sweep,
+                };
+
+                (px, py) = (ox + x, oy + y);
+
+                // Degenerate arcs are lines, just like in `Path::data`.
+                if kurbo::Arc::from_svg_arc(&svg_arc).is_some() {
+                    has_arcs = true;
+                    segments.push(ArcPathSegment::ArcTo {
+                        rx: rx as f32,
+                        ry: ry as f32,
+                        x_axis_rotation: x_axis_rotation as f32,
+                        large_arc,
+                        sweep,
+                        to: point(px, py),
+                    });
+                } else {
+                    segments.push(ArcPathSegment::LineTo(point(px, py)));
+                }
+            }
+            PathSegment::ClosePath { .. } => {
+                // Do not add sequential ClosePath segments.
+                if segments.last() != Some(&ArcPathSegment::Close) {
+                    segments.push(ArcPathSegment::Close);
+                }
+
+                (px, py) = (mx, my);
+                is_closed = true;
+            }
+        }
+
+        prev_cubic_ctrl = cubic_ctrl;
+        prev_quad_ctrl = quad_ctrl;
+    }
+
+    if has_arcs {
+        Some(segments)
+    } else {
+        None
+    }
+}
+
+fn is_absolute(segment: &svgtypes::PathSegment) -> bool {
+    use svgtypes::PathSegment;
+
+    match *segment {
+        PathSegment::MoveTo { abs, .. }
+        | PathSegment::LineTo { abs, .. }
+        | PathSegment::HorizontalLineTo { abs, .. }
+        | PathSegment::VerticalLineTo { abs, .. }
+        | PathSegment::CurveTo { abs, .. }
+        | PathSegment::SmoothCurveTo { abs, .. }
+        | PathSegment::Quadratic { abs, .. }
+        | PathSegment::SmoothQuadratic { abs, .. }
+        | PathSegment::EllipticalArc { abs, .. }
+        | PathSegment::ClosePath { abs } => abs,
+    }
+}
+
 trait PathBuilderExt {
     fn arc_to(
         &mut self,
@@ -56,13 +56,23 @@ pub(crate) fn resolve_fill(
             opacity: Opacity::ONE,
             rule: node.find_attribute(AId::ClipRule).unwrap_or_default(),
             context_element: None,
+            current_color: false,
         });
     }
 
     let mut sub_opacity = Opacity::ONE;
+    let mut current_color = false;
     let (paint, context_element) =
         if let Some(n) = node.ancestors().find(|n| n.has_attribute(AId::Fill)) {
-            convert_paint(n, AId::Fill, has_bbox, state, &mut sub_opacity, cache)?
+            convert_paint(
+                n,
+                AId::Fill,
+                has_bbox,
+                state,
+                &mut sub_opacity,
+                &mut current_color,
+                cache,
+            )?
         } else {
             (Paint::Color(Color::black()), None)
         };
@@ -76,6 +86,7 @@ pub(crate) fn resolve_fill(
         opacity: sub_opacity * fill_opacity,
         rule: node.find_attribute(AId::FillRule).unwrap_or_default(),
         context_element,
+        current_color,
     })
 }
 
@@ -91,9 +102,18 @@ pub(crate) fn resolve_stroke(
     }
 
     let mut sub_opacity = Opacity::ONE;
+    let mut current_color = false;
     let (paint, context_element) =
         if let Some(n) = node.ancestors().find(|n| n.has_attribute(AId::Stroke)) {
-            convert_paint(n, AId::Stroke, has_bbox, state, &mut sub_opacity, cache)?
+            convert_paint(
+                n,
+                AId::Stroke,
+                has_bbox,
+                state,
+                &mut sub_opacity,
+                &mut current_color,
+                cache,
+            )?
         } else {
             return None;
         };
@@ -119,6 +139,7 @@ pub(crate) fn resolve_stroke(
         linecap: node.find_attribute(AId::StrokeLinecap).unwrap_or_default(),
         linejoin: node.find_attribute(AId::StrokeLinejoin).unwrap_or_default(),
         context_element,
+        current_color,
     };
 
     Some(stroke)
@@ -130,6 +151,7 @@ fn convert_paint(
     has_bbox: bool,
     state: &converter::State,
     opacity: &mut Opacity,
+    current_color: &mut bool,
     cache: &mut converter::Cache,
 ) -> Option<(Paint, Option<ContextElement>)> {
     let value: &str = node.attribute(aid)?;
@@ -137,15 +159,21 @@ fn convert_paint(
         Ok(v) => v,
         Err(_) => {
             if aid == AId::Fill {
-                log::warn!(
-                    "Failed to parse fill value: '{}'. Fallback to black.",
-                    value
+                node.warn(
+                    "fill",
+                    &format!(
+                        "Failed to parse fill value: '{}'. Fallback to black.",
+                        value
+                    ),
                 );
                 svgtypes::Paint::Color(svgtypes::Color::black())
             } else if aid == AId::Stroke {
-                log::warn!(
-                    "Failed to parse stroke value: '{}'. Fallback to no stroke.",
-                    value
+                node.warn(
+                    "stroke",
+                    &format!(
+                        "Failed to parse stroke value: '{}'. Fallback to no stroke.",
+                        value
+                    ),
                 );
                 return None;
             } else {
@@ -157,22 +185,25 @@ fn convert_paint(
     match paint {
         svgtypes::Paint::None => None,
         svgtypes::Paint::Inherit => None, // already resolved by svgtree
-        svgtypes::Paint::ContextFill => state
-            .context_element
-            .clone()
-            .and_then(|(f, _)| f)
-            .map(|f| (f.paint, f.context_element)),
-        svgtypes::Paint::ContextStroke => state
-            .context_element
-            .clone()
-            .and_then(|(_, s)| s)
-            .map(|s| (s.paint, s.context_element)),
+        svgtypes::Paint::ContextFill => {
+            state.context_element.clone().and_then(|(f, _)| f).map(|f| {
+                *current_color = f.current_color;
+                (f.paint, f.context_element)
+            })
+        }
+        svgtypes::Paint::ContextStroke => {
+            state.context_element.clone().and_then(|(_, s)| s).map(|s| {
+                *current_color = s.current_color;
+                (s.paint, s.context_element)
+            })
+        }
         svgtypes::Paint::CurrentColor => {
             let svg_color: svgtypes::Color = node
                 .find_attribute(AId::Color)
                 .unwrap_or_else(svgtypes::Color::black);
             let (color, alpha) = svg_color.split_alpha();
             *opacity = alpha;
+            *current_color = true;
             Some((Paint::Color(color), None))
         }
         svgtypes::Paint::Color(svg_color) => {
@@ -192,23 +223,36 @@ fn convert_paint(
                             // See SVG spec 7.11 for details.
 
                             if !has_bbox && paint.units() == Units::ObjectBoundingBox {
-                                from_fallback(node, fallback, opacity).map(|p| (p, None))
+                                from_fallback(node, fallback, opacity, current_color)
+                                    .map(|p| (p, None))
                             } else {
                                 Some((paint, None))
                             }
                         }
-                        Some(paint_server::ServerOrColor::Color { color, opacity: so }) => {
+                        Some(paint_server::ServerOrColor::Color {
+                            color,
+                            opacity: so,
+                            current_color: cc,
+                        }) => {
                             *opacity = so;
+                            *current_color = cc;
                             Some((Paint::Color(color), None))
                         }
-                        None => from_fallback(node, fallback, opacity).map(|p| (p, None)),
+                        None => {
+                            from_fallback(node, fallback, opacity, current_color).map(|p| (p, None))
+                        }
                     }
                 } else {
                     log::warn!("'{}' cannot be used to {} a shape.", tag_name, aid);
+                    state.opt.report(
+                        &tag_name.to_string(),
+                        node.element_id(),
+                        &format!("cannot be used to {} a shape", aid),
+                    );
                     None
                 }
             } else {
-                from_fallback(node, fallback, opacity).map(|p| (p, None))
+                from_fallback(node, fallback, opacity, current_color).map(|p| (p, None))
             }
         }
     }
@@ -218,6 +262,7 @@ fn from_fallback(
     node: SvgNode,
     fallback: Option<svgtypes::PaintFallback>,
     opacity: &mut Opacity,
+    current_color: &mut bool,
 ) -> Option<Paint> {
     match fallback? {
         svgtypes::PaintFallback::None => None,
@@ -227,6 +272,7 @@ fn from_fallback(
                 .unwrap_or_else(svgtypes::Color::black);
             let (color, alpha) = svg_color.split_alpha();
             *opacity = alpha;
+            *current_color = true;
             Some(Paint::Color(color))
         }
         svgtypes::PaintFallback::Color(svg_color) => {
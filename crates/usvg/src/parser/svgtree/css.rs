@@ -0,0 +1,497 @@
+// Copyright 2024 the Resvg Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Selectors that are not supported by `simplecss`.
+//!
+//! Structural pseudo-classes, like `:nth-child(2n+1)`, and substring attribute selectors,
+//! like `[href^="#"]`, are rewritten into an attribute selector with a reserved name
+//! before the style sheet is parsed and are matched by [`extension_matches`] afterwards.
+//!
+//! `@media` rules are resolved at the same stage, since `simplecss` skips all at-rules.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use crate::{ApproxEqUlps, Size};
+
+/// A reserved attribute name used by rewritten selectors.
+///
+/// Cannot collide with real attributes, since XML names cannot start with `-`.
+pub(crate) const EXTENSION_ATTRIBUTE: &str = "-usvg-ext";
+
+const STRUCTURAL_PSEUDO_CLASSES: &[&str] = &[
+    "last-child",
+    "only-child",
+    "first-of-type",
+    "last-of-type",
+    "only-of-type",
+];
+
+const NTH_PSEUDO_CLASSES: &[&str] = &[
+    "nth-child",
+    "nth-last-child",
+    "nth-of-type",
+    "nth-last-of-type",
+];
+
+/// Values used to evaluate `@media` rules.
+pub(crate) struct Media<'a> {
+    features: &'a HashMap<String, String>,
+    width: f32,
+    height: f32,
+}
+
+impl<'a> Media<'a> {
+    /// Creates a new media using the root element size.
+    ///
+    /// Relative or missing `width` and `height` fall back to `viewBox`
+    /// and then to `default_size`.
+    pub(crate) fn new(
+        features: &'a HashMap<String, String>,
+        root: roxmltree::Node,
+        default_size: Size,
+    ) -> Self {
+        let view_box = root
+            .attribute("viewBox")
+            .and_then(|v| svgtypes::ViewBox::from_str(v).ok());
+
+        let length = |name: &str| {
+            let length = svgtypes::Length::from_str(root.attribute(name)?).ok()?;
+            match length.unit {
+                svgtypes::LengthUnit::None | svgtypes::LengthUnit::Px => Some(length.number as f32),
+                _ => None,
+            }
+        };
+
+        let width = length("width")
+            .or_else(|| view_box.map(|vb| vb.w as f32))
+            .unwrap_or(default_size.width());
+        let height = length("height")
+            .or_else(|| view_box.map(|vb| vb.h as f32))
+            .unwrap_or(default_size.height());
+
+        Media {
+            features,
+            width,
+            height,
+        }
+    }
+
+    /// Checks that a comma-separated media query list matches.
+    fn matches(&self, query_list: &str) -> bool {
+        query_list
+            .to_ascii_lowercase()
+            .split(',')
+            .any(|query| self.matches_query(query.trim()))
+    }
+
+    fn matches_query(&self, query: &str) -> bool {
+        let (negated, query) = match query.strip_prefix("not ") {
+            Some(query) => (true, query),
+            None => (false, query.strip_prefix("only ").unwrap_or(query)),
+        };
+
+        let mut matches = true;
+        let mut rest = query;
+        loop {
+            rest = rest.trim_start();
+            if rest.is_empty() {
+                break;
+            }
+
+            if let Some(feature) = rest.strip_prefix('(') {
+                let end = match feature.find(')') {
+                    Some(v) => v,
+                    None => return false,
+                };
+
+                matches &= self.matches_feature(&feature[..end]);
+                rest = &feature[end + 1..];
+            } else {
+                let end = rest
+                    .find(|c: char| c.is_whitespace() || c == '(')
+                    .unwrap_or(rest.len());
+                match &rest[..end] {
+                    "and" | "all" | "screen" => {}
+                    // Other media types, like `print`, and unsupported keywords, like `or`.
+                    _ => matches = false,
+                }
+
+                rest = &rest[end..];
+            }
+        }
+
+        matches != negated
+    }
+
+    fn matches_feature(&self, feature: &str) -> bool {
+        let (name, expected) = match feature.split_once(':') {
+            Some((name, value)) => (name.trim(), Some(value.trim())),
+            None => (feature.trim(), None),
+        };
+
+        if let Some(name) = name.strip_prefix("min-") {
+            return match (self.number(name), expected.and_then(parse_number)) {
+                (Some(value), Some(expected)) => value >= expected,
+                _ => false,
+            };
+        }
+
+        if let Some(name) = name.strip_prefix("max-") {
+            return match (self.number(name), expected.and_then(parse_number)) {
+                (Some(value), Some(expected)) => value <= expected,
+                _ => false,
+            };
+        }
+
+        let value = match self.value(name) {
+            Some(v) => v,
+            None => return false,
+        };
+
+        match expected {
+            Some(expected) => match (parse_number(&value), parse_number(expected)) {
+                (Some(value), Some(expected)) => value.approx_eq_ulps(&expected, 4),
+                _ => value.eq_ignore_ascii_case(expected),
+            },
+            // A boolean context.
+            None => !matches!(value.as_str(), "" | "0" | "none"),
+        }
+    }
+
+    fn value(&self, name: &str) -> Option<String> {
+        if let Some(value) = self.features.get(name) {
+            return Some(value.clone());
+        }
+
+        match name {
+            "width" => Some(self.width.to_string()),
+            "height" => Some(self.height.to_string()),
+            "orientation" if self.height >= self.width => Some("portrait".to_string()),
+            "orientation" => Some("landscape".to_string()),
+            _ => None,
+        }
+    }
+
+    fn number(&self, name: &str) -> Option<f32> {
+        parse_number(&self.value(name)?)
+    }
+}
+
+fn parse_number(text: &str) -> Option<f32> {
+    let text = text.trim();
+    text.strip_suffix("px")
+        .unwrap_or(text)
+        .trim_end()
+        .parse()
+        .ok()
+}
+
+/// Rewrites unsupported selectors in a style sheet
+/// and resolves `@media` rules.
+///
+/// Returns the original text when there is nothing to rewrite.
+pub(crate) fn rewrite<'a>(text: &'a str, media: &Media) -> Cow<'a, str> {
+    // Whether a block was opened by a matched `@media` rule.
+    let mut blocks: Vec<bool> = Vec::new();
+
+    let mut out = String::with_capacity(text.len());
+    let mut prelude_start = 0;
+    let mut pos = 0;
+    while pos < text.len() {
+        let rest = &text[pos..];
+        if rest.starts_with("/*") {
+            pos += rest.find("*/").map(|i| i + 2).unwrap_or(rest.len());
+            continue;
+        }
+
+        let c = rest.chars().next().unwrap();
+        match c {
+            '{' => {
+                let prelude = &text[prelude_start..pos];
+                if let Some(query) = prelude.trim_start().strip_prefix("@media") {
+                    if media.matches(query) {
+                        // Keep only the block content.
+                        blocks.push(true);
+                    } else {
+                        pos += block_len(rest);
+                        prelude_start = pos;
+                        continue;
+                    }
+                } else {
+                    rewrite_prelude(prelude, &mut out);
+                    out.push('{');
+                    blocks.push(false);
+                }
+
+                prelude_start = pos + 1;
+            }
+            '}' => {
+                if blocks.pop() == Some(true) {
+                    out.push_str(&text[prelude_start..pos]);
+                } else {
+                    out.push_str(&text[prelude_start..=pos]);
+                }
+
+                prelude_start = pos + 1;
+            }
+            // Declarations and at-rule statements are preserved as is.
+            ';' => {
+                out.push_str(&text[prelude_start..=pos]);
+                prelude_start = pos + 1;
+            }
+            _ => {}
+        }
+
+        pos += c.len_utf8();
+    }
+
+    out.push_str(&text[prelude_start..]);
+
+    if out == text {
+        Cow::Borrowed(text)
+    } else {
+        Cow::Owned(out)
+    }
+}
+
+/// Returns the length of a block, including nested ones.
+///
+/// `text` must start with `{`.
+fn block_len(text: &str) -> usize {
+    let bytes = text.as_bytes();
+    let mut depth = 0;
+    let mut pos = 0;
+    while pos < bytes.len() {
+        if bytes[pos..].starts_with(b"/*") {
+            pos += text[pos..]
+                .find("*/")
+                .map(|i| i + 2)
+                .unwrap_or(bytes.len() - pos);
+            continue;
+        }
+
+        match bytes[pos] {
+            b'{' => depth += 1,
+            b'}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return pos + 1;
+                }
+            }
+            _ => {}
+        }
+
+        pos += 1;
+    }
+
+    bytes.len()
+}
+
+/// Rewrites a single selectors list, i.e. a text before `{`.
+fn rewrite_prelude(prelude: &str, out: &mut String) {
+    // At-rules, like `@media`, are not selectors.
+    if prelude.trim_start().starts_with('@') {
+        out.push_str(prelude);
+        return;
+    }
+
+    let mut pos = 0;
+    while pos < prelude.len() {
+        let rest = &prelude[pos..];
+        if rest.starts_with("/*") {
+            let len = rest.find("*/").map(|i| i + 2).unwrap_or(rest.len());
+            out.push_str(&rest[..len]);
+            pos += len;
+            continue;
+        }
+
+        let rewritten = if rest.starts_with("::") {
+            // Pseudo-elements are left to `simplecss`.
+            out.push_str("::");
+            pos += 2;
+            continue;
+        } else if let Some(pseudo_class) = rest.strip_prefix(':') {
+            rewrite_pseudo_class(pseudo_class).map(|(ext, len)| (ext, len + 1))
+        } else if rest.starts_with('[') {
+            rewrite_attribute(rest)
+        } else {
+            None
+        };
+
+        match rewritten {
+            Some((ext, len)) => {
+                out.push('[');
+                out.push_str(EXTENSION_ATTRIBUTE);
+                out.push_str("=\"");
+                out.push_str(&ext);
+                out.push_str("\"]");
+                pos += len;
+            }
+            None => {
+                let c = rest.chars().next().unwrap();
+                out.push(c);
+                pos += c.len_utf8();
+            }
+        }
+    }
+}
+
+/// Returns an extension value and the number of consumed bytes.
+fn rewrite_pseudo_class(text: &str) -> Option<(String, usize)> {
+    let name_len = text
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '-'))
+        .unwrap_or(text.len());
+    let name = &text[..name_len];
+
+    if STRUCTURAL_PSEUDO_CLASSES.contains(&name) {
+        return Some((name.to_string(), name_len));
+    }
+
+    if NTH_PSEUDO_CLASSES.contains(&name) && text[name_len..].starts_with('(') {
+        let args_len = text[name_len..].find(')')?;
+        let args: String = text[name_len + 1..name_len + args_len]
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .collect::<String>()
+            .to_ascii_lowercase();
+
+        // Things like `:nth-child(2n of .item)` are not supported.
+        parse_nth(&args)?;
+
+        return Some((format!("{} {}", name, args), name_len + args_len + 1));
+    }
+
+    None
+}
+
+/// Rewrites `[name^=value]`, `[name$=value]` and `[name*=value]`.
+///
+/// Returns an extension value and the number of consumed bytes.
+fn rewrite_attribute(text: &str) -> Option<(String, usize)> {
+    let inner_len = text.find(']')?;
+    let inner = &text[1..inner_len];
+
+    let op_pos = inner.find('=')?;
+    let (kind, name) = match inner[..op_pos].trim_end() {
+        s if s.ends_with('^') => ("prefix", &s[..s.len() - 1]),
+        s if s.ends_with('$') => ("suffix", &s[..s.len() - 1]),
+        s if s.ends_with('*') => ("substring", &s[..s.len() - 1]),
+        _ => return None,
+    };
+
+    let name = name.trim();
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return None;
+    }
+
+    let value = inner[op_pos + 1..].trim();
+    let value = match value.chars().next() {
+        Some(quote @ ('"' | '\'')) => {
+            // Quoted values cannot contain `]` in this implementation.
+            value.strip_prefix(quote)?.strip_suffix(quote)?
+        }
+        _ => value,
+    };
+
+    // The value is stored inside a double-quoted string.
+    if value.contains('"') {
+        return None;
+    }
+
+    Some((format!("{} {} {}", kind, name, value), inner_len + 1))
+}
+
+/// Parses an `An+B` expression.
+fn parse_nth(text: &str) -> Option<(i32, i32)> {
+    match text {
+        "odd" => return Some((2, 1)),
+        "even" => return Some((2, 0)),
+        _ => {}
+    }
+
+    match text.split_once('n') {
+        Some((a, b)) => {
+            let a = match a {
+                "" | "+" => 1,
+                "-" => -1,
+                _ => a.parse().ok()?,
+            };
+
+            let b = match b {
+                "" => 0,
+                _ if b.starts_with('+') || b.starts_with('-') => b.parse().ok()?,
+                _ => return None,
+            };
+
+            Some((a, b))
+        }
+        None => Some((0, text.parse().ok()?)),
+    }
+}
+
+/// Checks that a 1-based `index` matches an `An+B` expression.
+fn nth_matches(a: i32, b: i32, index: i32) -> bool {
+    if a == 0 {
+        index == b
+    } else {
+        let diff = index - b;
+        diff % a == 0 && diff / a >= 0
+    }
+}
+
+/// Checks that a node matches a rewritten selector.
+pub(crate) fn extension_matches(node: roxmltree::Node, value: &str) -> bool {
+    let (name, args) = value.split_once(' ').unwrap_or((value, ""));
+
+    let prev = || node.prev_siblings().skip(1).filter(|n| n.is_element());
+    let next = || node.next_siblings().skip(1).filter(|n| n.is_element());
+    let same_type = |n: &roxmltree::Node| n.tag_name() == node.tag_name();
+
+    match name {
+        "last-child" => next().next().is_none(),
+        "only-child" => prev().next().is_none() && next().next().is_none(),
+        "first-of-type" => !prev().any(|n| same_type(&n)),
+        "last-of-type" => !next().any(|n| same_type(&n)),
+        "only-of-type" => !prev().any(|n| same_type(&n)) && !next().any(|n| same_type(&n)),
+        "nth-child" | "nth-last-child" | "nth-of-type" | "nth-last-of-type" => {
+            let (a, b) = match parse_nth(args) {
+                Some(v) => v,
+                None => return false,
+            };
+
+            let count = match name {
+                "nth-child" => prev().count(),
+                "nth-last-child" => next().count(),
+                "nth-of-type" => prev().filter(same_type).count(),
+                _ => next().filter(same_type).count(),
+            };
+
+            nth_matches(a, b, count as i32 + 1)
+        }
+        "prefix" | "suffix" | "substring" => {
+            let (attr, pattern) = args.split_once(' ').unwrap_or((args, ""));
+            // An empty pattern never matches.
+            if pattern.is_empty() {
+                return false;
+            }
+
+            let value = match node.attribute(attr) {
+                Some(v) => v,
+                None => return false,
+            };
+
+            match name {
+                "prefix" => value.starts_with(pattern),
+                "suffix" => value.ends_with(pattern),
+                _ => value.contains(pattern),
+            }
+        }
+        _ => false,
+    }
+}
@@ -6,16 +6,20 @@ use std::num::NonZeroU32;
 use std::str::FromStr;
 
 #[rustfmt::skip] mod names;
+mod css;
 mod parse;
 mod text;
 
+use svgtypes::LengthUnit;
 use tiny_skia_path::Transform;
 
+use super::Diagnostics;
 use crate::{
-    BlendMode, ImageRendering, Opacity, ShapeRendering, SpreadMethod, TextRendering, Units,
-    Visibility,
+    BlendMode, GradientInterpolation, ImageRendering, Opacity, ShapeRendering, SpreadMethod,
+    StyleSource, TextRendering, Units, VectorEffect, Visibility,
 };
 pub use names::{AId, EId};
+pub(crate) use parse::{DEFAULT_VALUES, SVG_NS};
 
 /// An SVG tree container.
 ///
@@ -25,9 +29,15 @@ pub struct Document<'input> {
     nodes: Vec<NodeData>,
     attrs: Vec<Attribute<'input>>,
     links: HashMap<String, NodeId>,
+    diagnostics: Option<&'input Diagnostics>,
 }
 
 impl<'input> Document<'input> {
+    /// Logs a warning and reports it to the diagnostics collector, if any.
+    pub(crate) fn warn(&self, feature: &str, element_id: &str, message: &str) {
+        super::diagnostics::warn(self.diagnostics, feature, element_id, message);
+    }
+
     /// Returns the root node.
     #[inline]
     pub fn root<'a>(&'a self) -> SvgNode<'a, 'input> {
@@ -57,12 +67,58 @@ impl<'input> Document<'input> {
     ///
     /// Unlike the [`Descendants`] iterator, this is just a HashMap lookup.
     /// Meaning it's way faster.
+    ///
+    /// When multiple elements share an ID, the first one in the document order is returned.
+    /// The same rule is used to resolve all references.
     #[inline]
     pub fn element_by_id<'a>(&'a self, id: &str) -> Option<SvgNode<'a, 'input>> {
         let node_id = self.links.get(id)?;
         Some(self.get(*node_id))
     }
 
+    /// Sets attributes of an element with the specified ID.
+    ///
+    /// Like during parsing, values set by CSS take precedence over attributes.
+    pub(crate) fn set_attributes(&mut self, id: &str, attrs: &[(AId, &str)]) -> Option<()> {
+        let node_id = *self.links.get(id)?;
+        let range = match self.nodes[node_id.get_usize()].kind {
+            NodeKind::Element { ref attributes, .. } => attributes.to_urange(),
+            _ => return None,
+        };
+
+        // Element attributes must be stored continuously,
+        // therefore we have to copy the existing ones to the end first.
+        let start = self.attrs.len();
+        self.attrs.extend_from_within(range);
+
+        for (aid, value) in attrs {
+            let new_attr = Attribute {
+                name: *aid,
+                value: roxmltree::StringStorage::new_owned(*value),
+                important: false,
+                source: StyleSource::Attribute,
+            };
+
+            match self.attrs[start..].iter_mut().find(|a| a.name == *aid) {
+                Some(attr) => {
+                    if !matches!(attr.source, StyleSource::StyleSheet | StyleSource::Inline) {
+                        *attr = new_attr;
+                    }
+                }
+                None => self.attrs.push(new_attr),
+            }
+        }
+
+        if let NodeKind::Element {
+            ref mut attributes, ..
+        } = self.nodes[node_id.get_usize()].kind
+        {
+            *attributes = ShortRange::new(start as u32, self.attrs.len() as u32);
+        }
+
+        Some(())
+    }
+
     #[inline]
     fn get<'a>(&'a self, id: NodeId) -> SvgNode<'a, 'input> {
         SvgNode {
@@ -206,14 +262,16 @@ pub struct Attribute<'input> {
     pub value: roxmltree::StringStorage<'input>,
     /// Attribute's importance
     pub important: bool,
+    /// Where the attribute value came from.
+    pub source: StyleSource,
 }
 
 impl std::fmt::Debug for Attribute<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
         write!(
             f,
-            "Attribute {{ name: {:?}, value: {}, important: {} }}",
-            self.name, self.value, self.important
+            "Attribute {{ name: {:?}, value: {}, important: {}, source: {:?} }}",
+            self.name, self.value, self.important, self.source
         )
     }
 }
@@ -237,7 +295,7 @@ impl PartialEq for SvgNode<'_, '_> {
 
 impl<'a, 'input: 'a> SvgNode<'a, 'input> {
     #[inline]
-    fn id(&self) -> NodeId {
+    pub(crate) fn id(&self) -> NodeId {
         self.id
     }
 
@@ -275,6 +333,11 @@ impl<'a, 'input: 'a> SvgNode<'a, 'input> {
         self.attribute(AId::Id).unwrap_or("")
     }
 
+    /// Logs a warning about this element and reports it to the diagnostics collector, if any.
+    pub(crate) fn warn(&self, feature: &str, message: &str) {
+        self.doc.warn(feature, self.element_id(), message);
+    }
+
     /// Returns an attribute value.
     pub fn attribute<T: FromValue<'a, 'input>>(&self, aid: AId) -> Option<T> {
         let value = self
@@ -286,7 +349,10 @@ impl<'a, 'input: 'a> SvgNode<'a, 'input> {
             Some(v) => Some(v),
             None => {
                 // TODO: show position in XML
-                log::warn!("Failed to parse {} value: '{}'.", aid, value);
+                self.warn(
+                    aid.to_str(),
+                    &format!("Failed to parse {} value: '{}'.", aid, value),
+                );
                 None
             }
         }
@@ -618,9 +684,13 @@ impl<'a, 'input: 'a> Iterator for HrefIter<'a, 'input> {
 
         if let Some(link) = self.doc.get(self.curr).node_attribute(AId::Href) {
             if link.id() == self.curr || link.id() == self.origin {
-                log::warn!(
-                    "Element '#{}' cannot reference itself via 'xlink:href'.",
-                    self.doc.get(self.origin).element_id()
+                let origin = self.doc.get(self.origin);
+                origin.warn(
+                    "xlink:href",
+                    &format!(
+                        "Element '#{}' cannot reference itself via 'xlink:href'.",
+                        origin.element_id()
+                    ),
                 );
                 self.is_finished = true;
                 return None;
@@ -670,7 +740,7 @@ impl EId {
 }
 
 impl AId {
-    fn is_presentation(&self) -> bool {
+    pub(crate) fn is_presentation(&self) -> bool {
         matches!(
             self,
             AId::AlignmentBaseline
@@ -682,6 +752,7 @@ impl AId {
                 | AId::ColorInterpolation
                 | AId::ColorInterpolationFilters
                 | AId::ColorRendering
+                | AId::Cursor
                 | AId::Direction
                 | AId::Display
                 | AId::DominantBaseline
@@ -714,6 +785,9 @@ impl AId {
                 | AId::Opacity
                 | AId::Overflow
                 | AId::PaintOrder
+                | AId::PointerEvents
+                | AId::ShapeInside
+                | AId::ShapePadding
                 | AId::ShapeRendering
                 | AId::StopColor
                 | AId::StopOpacity
@@ -741,7 +815,7 @@ impl AId {
     }
 
     /// Checks if the current attribute is inheritable.
-    fn is_inheritable(&self) -> bool {
+    pub(crate) fn is_inheritable(&self) -> bool {
         if self.is_presentation() {
             !is_non_inheritable(*self)
         } else {
@@ -758,6 +832,7 @@ impl AId {
                 | AId::ClipRule
                 | AId::Color
                 | AId::ColorInterpolationFilters
+                | AId::Cursor
                 | AId::Direction
                 | AId::Display
                 | AId::DominantBaseline
@@ -770,6 +845,7 @@ impl AId {
                 | AId::FontFamily
                 | AId::FontKerning
                 | AId::FontSize
+                | AId::FontSizeAdjust
                 | AId::FontStretch
                 | AId::FontStyle
                 | AId::FontVariant
@@ -783,6 +859,7 @@ impl AId {
                 | AId::Mask
                 | AId::Opacity
                 | AId::Overflow
+                | AId::PointerEvents
                 | AId::ShapeRendering
                 | AId::StopColor
                 | AId::StopOpacity
@@ -819,6 +896,8 @@ fn is_non_inheritable(id: AId) -> bool {
             | AId::Opacity
             | AId::Overflow
             | AId::LightingColor
+            | AId::ShapeInside
+            | AId::ShapePadding
             | AId::StopColor
             | AId::StopOpacity
             | AId::TextDecoration
@@ -868,28 +947,104 @@ impl<'a, 'input: 'a> FromValue<'a, 'input> for Opacity {
     }
 }
 
-impl<'a, 'input: 'a> FromValue<'a, 'input> for Transform {
-    fn parse(_: SvgNode, _: AId, value: &str) -> Option<Self> {
-        let ts = match svgtypes::Transform::from_str(value) {
-            Ok(v) => v,
-            Err(_) => return None,
-        };
-
-        let ts = Transform::from_row(
+/// Parses a transform list.
+///
+/// Accepts both the SVG syntax, like `translate(10 5) rotate(45)`,
+/// and the CSS one, like `translateX(10px) rotate(0.25turn)`.
+/// Absolute units of the CSS syntax are converted using `dpi`.
+///
+/// Doesn't replace an invalid transform.
+pub(crate) fn parse_transform(value: &str, dpi: f32) -> Option<Transform> {
+    if let Ok(ts) = svgtypes::Transform::from_str(value) {
+        return Some(Transform::from_row(
             ts.a as f32,
             ts.b as f32,
             ts.c as f32,
             ts.d as f32,
             ts.e as f32,
             ts.f as f32,
-        );
+        ));
+    }
 
-        if ts.is_valid() {
-            Some(ts)
-        } else {
-            Some(Transform::default())
+    parse_css_transform(value, dpi)
+}
+
+fn parse_css_transform(value: &str, dpi: f32) -> Option<Transform> {
+    let mut s = value.trim();
+    if s == "none" {
+        return Some(Transform::default());
+    }
+
+    let mut ts = Transform::default();
+    while !s.is_empty() {
+        let open = s.find('(')?;
+        let close = s.find(')')?;
+        if close < open {
+            return None;
         }
+
+        let name = s[..open].trim().to_ascii_lowercase();
+        let args: Vec<&str> = s[open + 1..close].split(',').map(str::trim).collect();
+        ts = ts.pre_concat(parse_css_transform_function(&name, &args, dpi)?);
+        s = s[close + 1..].trim_start();
     }
+
+    Some(ts)
+}
+
+fn parse_css_transform_function(name: &str, args: &[&str], dpi: f32) -> Option<Transform> {
+    let number =
+        |idx: usize| -> Option<f32> { Some(svgtypes::Number::from_str(args[idx]).ok()?.0 as f32) };
+
+    // Relative units are not supported, since there is no font size
+    // or reference box at this point.
+    // Absolute units are converted like in other attributes.
+    let length = |idx: usize| -> Option<f32> {
+        let length = svgtypes::Length::from_str(args[idx]).ok()?;
+        let n = length.number as f32;
+        match length.unit {
+            LengthUnit::None | LengthUnit::Px => Some(n),
+            LengthUnit::In => Some(n * dpi),
+            LengthUnit::Cm => Some(n * dpi / 2.54),
+            LengthUnit::Mm => Some(n * dpi / 25.4),
+            LengthUnit::Pt => Some(n * dpi / 72.0),
+            LengthUnit::Pc => Some(n * dpi / 6.0),
+            LengthUnit::Em | LengthUnit::Ex | LengthUnit::Percent => None,
+        }
+    };
+
+    // Unitless angles are treated as degrees, like in the SVG syntax.
+    let angle = |idx: usize| -> Option<f32> {
+        Some(svgtypes::Angle::from_str(args[idx]).ok()?.to_degrees() as f32)
+    };
+    let skew = |idx: usize| -> Option<f32> { Some(angle(idx)?.to_radians().tan()) };
+
+    let ts = match (name, args.len()) {
+        ("matrix", 6) => Transform::from_row(
+            number(0)?,
+            number(1)?,
+            number(2)?,
+            number(3)?,
+            number(4)?,
+            number(5)?,
+        ),
+        ("translate", 1) => Transform::from_translate(length(0)?, 0.0),
+        ("translate", 2) => Transform::from_translate(length(0)?, length(1)?),
+        ("translatex", 1) => Transform::from_translate(length(0)?, 0.0),
+        ("translatey", 1) => Transform::from_translate(0.0, length(0)?),
+        ("scale", 1) => Transform::from_scale(number(0)?, number(0)?),
+        ("scale", 2) => Transform::from_scale(number(0)?, number(1)?),
+        ("scalex", 1) => Transform::from_scale(number(0)?, 1.0),
+        ("scaley", 1) => Transform::from_scale(1.0, number(0)?),
+        ("rotate", 1) => Transform::from_rotate(angle(0)?),
+        ("skew", 1) => Transform::from_row(1.0, 0.0, skew(0)?, 1.0, 0.0, 0.0),
+        ("skew", 2) => Transform::from_row(1.0, skew(1)?, skew(0)?, 1.0, 0.0, 0.0),
+        ("skewx", 1) => Transform::from_row(1.0, 0.0, skew(0)?, 1.0, 0.0, 0.0),
+        ("skewy", 1) => Transform::from_row(1.0, skew(0)?, 0.0, 1.0, 0.0, 0.0),
+        _ => return None,
+    };
+
+    Some(ts)
 }
 
 impl<'a, 'input: 'a> FromValue<'a, 'input> for svgtypes::TransformOrigin {
@@ -994,6 +1149,19 @@ impl<'a, 'input: 'a> FromValue<'a, 'input> for SpreadMethod {
     }
 }
 
+impl<'a, 'input: 'a> FromValue<'a, 'input> for GradientInterpolation {
+    fn parse(_: SvgNode, _: AId, value: &str) -> Option<Self> {
+        match value {
+            "auto" | "sRGB" | "linearRGB" => Some(GradientInterpolation::SRGB),
+            "lab" => Some(GradientInterpolation::Lab),
+            "lch" => Some(GradientInterpolation::Lch),
+            "oklab" => Some(GradientInterpolation::OkLab),
+            "oklch" => Some(GradientInterpolation::OkLch),
+            _ => None,
+        }
+    }
+}
+
 impl<'a, 'input: 'a> FromValue<'a, 'input> for ShapeRendering {
     fn parse(_: SvgNode, _: AId, value: &str) -> Option<Self> {
         match value {
@@ -1005,6 +1173,16 @@ impl<'a, 'input: 'a> FromValue<'a, 'input> for ShapeRendering {
     }
 }
 
+impl<'a, 'input: 'a> FromValue<'a, 'input> for VectorEffect {
+    fn parse(_: SvgNode, _: AId, value: &str) -> Option<Self> {
+        match value {
+            "none" => Some(VectorEffect::None),
+            "non-scaling-stroke" => Some(VectorEffect::NonScalingStroke),
+            _ => None,
+        }
+    }
+}
+
 impl<'a, 'input: 'a> FromValue<'a, 'input> for TextRendering {
     fn parse(_: SvgNode, _: AId, value: &str) -> Option<Self> {
         match value {
@@ -180,6 +180,7 @@ pub enum AId {
     ColorInterpolationFilters,
     ColorProfile,
     ColorRendering,
+    Cursor,
     Cx,
     Cy,
     D,
@@ -284,6 +285,7 @@ pub enum AId {
     PatternContentUnits,
     PatternTransform,
     PatternUnits,
+    PointerEvents,
     Points,
     PointsAtX,
     PointsAtY,
@@ -375,261 +377,264 @@ pub enum AId {
 }
 
 static ATTRIBUTES: Map<AId> = Map {
-    key: 3347381344252206323,
+    key: 3213172566270843353,
     disps: &[
-        (0, 111),
-        (0, 2),
-        (0, 45),
-        (0, 5),
+        (3, 194),
+        (0, 88),
+        (0, 0),
+        (0, 30),
+        (0, 58),
+        (0, 0),
         (0, 1),
-        (2, 56),
-        (0, 5),
-        (2, 99),
-        (13, 198),
-        (0, 61),
-        (0, 52),
-        (1, 29),
-        (0, 21),
-        (0, 70),
-        (0, 164),
-        (2, 60),
-        (3, 52),
+        (0, 0),
+        (0, 0),
+        (0, 132),
+        (0, 44),
+        (1, 23),
+        (0, 50),
         (0, 1),
-        (0, 86),
-        (0, 10),
+        (0, 162),
+        (0, 44),
+        (10, 127),
+        (2, 111),
+        (0, 66),
+        (2, 71),
+        (0, 75),
         (0, 0),
-        (0, 4),
-        (2, 175),
-        (6, 59),
-        (1, 14),
-        (0, 13),
-        (3, 175),
-        (1, 10),
-        (2, 76),
-        (0, 53),
-        (0, 24),
-        (123, 202),
-        (0, 14),
-        (0, 30),
+        (0, 61),
+        (0, 1),
+        (7, 45),
+        (2, 173),
+        (0, 75),
+        (0, 56),
+        (19, 113),
+        (0, 141),
+        (0, 149),
+        (3, 143),
         (0, 62),
-        (0, 98),
-        (11, 193),
-        (8, 79),
-        (0, 17),
-        (22, 5),
-        (36, 106),
-        (1, 1),
+        (2, 176),
+        (21, 96),
+        (0, 128),
+        (0, 0),
+        (0, 7),
+        (2, 47),
+        (0, 5),
+        (1, 0),
+        (0, 0),
+        (14, 105),
     ],
     entries: &[
-        ("mask-border-source", AId::MaskBorderSource),
-        ("stop-opacity", AId::StopOpacity),
-        ("stroke-linejoin", AId::StrokeLinejoin),
-        ("dominant-baseline", AId::DominantBaseline),
-        ("spreadMethod", AId::SpreadMethod),
-        ("order", AId::Order),
-        ("stroke", AId::Stroke),
-        ("stitchTiles", AId::StitchTiles),
-        ("height", AId::Height),
-        ("font-size", AId::FontSize),
-        ("background-color", AId::BackgroundColor),
-        ("tableValues", AId::TableValues),
-        ("x1", AId::X1),
-        ("y", AId::Y),
-        ("width", AId::Width),
-        ("text-indent", AId::TextIndent),
-        ("fill-opacity", AId::FillOpacity),
-        ("word-spacing", AId::WordSpacing),
-        ("cy", AId::Cy),
-        ("scale", AId::Scale),
-        ("x2", AId::X2),
-        ("lengthAdjust", AId::LengthAdjust),
-        ("glyph-orientation-horizontal", AId::GlyphOrientationHorizontal),
-        ("opacity", AId::Opacity),
-        ("mask-border", AId::MaskBorder),
-        ("font-stretch", AId::FontStretch),
-        ("stroke-dashoffset", AId::StrokeDashoffset),
-        ("fill", AId::Fill),
-        ("space", AId::Space),
-        ("baseline-shift", AId::BaselineShift),
-        ("text-align-last", AId::TextAlignLast),
+        ("alignment-baseline", AId::AlignmentBaseline),
+        ("fx", AId::Fx),
+        ("font-style", AId::FontStyle),
         ("font-variant-east-asian", AId::FontVariantEastAsian),
-        ("mask-border-mode", AId::MaskBorderMode),
-        ("font-variant-caps", AId::FontVariantCaps),
+        ("r", AId::R),
+        ("mask-size", AId::MaskSize),
+        ("in", AId::In),
+        ("slope", AId::Slope),
+        ("font-variant-numeric", AId::FontVariantNumeric),
+        ("clip-rule", AId::ClipRule),
+        ("background-color", AId::BackgroundColor),
         ("gradientUnits", AId::GradientUnits),
-        ("exponent", AId::Exponent),
-        ("text-decoration-color", AId::TextDecorationColor),
-        ("refX", AId::RefX),
-        ("enable-background", AId::EnableBackground),
-        ("mask-border-width", AId::MaskBorderWidth),
+        ("markerWidth", AId::MarkerWidth),
+        ("font-stretch", AId::FontStretch),
+        ("intercept", AId::Intercept),
+        ("mode", AId::Mode),
+        ("glyph-orientation-horizontal", AId::GlyphOrientationHorizontal),
+        ("xChannelSelector", AId::XChannelSelector),
         ("numOctaves", AId::NumOctaves),
-        ("kerning", AId::Kerning),
-        ("mix-blend-mode", AId::MixBlendMode),
-        ("mask-clip", AId::MaskClip),
-        ("mask-mode", AId::MaskMode),
-        ("type", AId::Type),
-        ("class", AId::Class),
+        ("x1", AId::X1),
+        ("fill-rule", AId::FillRule),
+        ("pathLength", AId::PathLength),
         ("font", AId::Font),
+        ("seed", AId::Seed),
+        ("transform-origin", AId::TransformOrigin),
+        ("path", AId::Path),
         ("mask-border-repeat", AId::MaskBorderRepeat),
+        ("transform", AId::Transform),
+        ("shape-image-threshold", AId::ShapeImageThreshold),
+        ("refX", AId::RefX),
+        ("stitchTiles", AId::StitchTiles),
+        ("result", AId::Result),
+        ("rx", AId::Rx),
+        ("cursor", AId::Cursor),
+        ("text-align-last", AId::TextAlignLast),
+        ("exponent", AId::Exponent),
+        ("clip-path", AId::ClipPath),
         ("stroke-miterlimit", AId::StrokeMiterlimit),
+        ("dy", AId::Dy),
+        ("text-decoration-color", AId::TextDecorationColor),
+        ("scale", AId::Scale),
+        ("diffuseConstant", AId::DiffuseConstant),
         ("text-decoration-stroke", AId::TextDecorationStroke),
-        ("z", AId::Z),
-        ("dx", AId::Dx),
-        ("clip-path", AId::ClipPath),
-        ("markerHeight", AId::MarkerHeight),
-        ("text-underline-position", AId::TextUnderlinePosition),
-        ("stdDeviation", AId::StdDeviation),
-        ("id", AId::Id),
-        ("paint-order", AId::PaintOrder),
-        ("elevation", AId::Elevation),
-        ("specularConstant", AId::SpecularConstant),
-        ("result", AId::Result),
-        ("font-size-adjust", AId::FontSizeAdjust),
-        ("mask-origin", AId::MaskOrigin),
-        ("direction", AId::Direction),
-        ("font-variant-numeric", AId::FontVariantNumeric),
-        ("startOffset", AId::StartOffset),
-        ("maskUnits", AId::MaskUnits),
         ("font-variant", AId::FontVariant),
-        ("text-orientation", AId::TextOrientation),
-        ("amplitude", AId::Amplitude),
-        ("rx", AId::Rx),
-        ("mask-type", AId::MaskType),
-        ("filter", AId::Filter),
-        ("in", AId::In),
-        ("display", AId::Display),
-        ("seed", AId::Seed),
-        ("unicode-range", AId::UnicodeRange),
-        ("color-profile", AId::ColorProfile),
-        ("x", AId::X),
-        ("href", AId::Href),
-        ("font-feature-settings", AId::FontFeatureSettings),
-        ("fill-rule", AId::FillRule),
-        ("fr", AId::Fr),
-        ("font-variant-ligatures", AId::FontVariantLigatures),
-        ("text-decoration-style", AId::TextDecorationStyle),
-        ("radius", AId::Radius),
-        ("xChannelSelector", AId::XChannelSelector),
         ("orient", AId::Orient),
+        ("side", AId::Side),
+        ("markerUnits", AId::MarkerUnits),
+        ("startOffset", AId::StartOffset),
+        ("mask-image", AId::MaskImage),
+        ("unicode-range", AId::UnicodeRange),
+        ("mask-composite", AId::MaskComposite),
+        ("baseline-shift", AId::BaselineShift),
+        ("k3", AId::K3),
         ("isolation", AId::Isolation),
-        ("gradientTransform", AId::GradientTransform),
-        ("transform-box", AId::TransformBox),
-        ("pointsAtY", AId::PointsAtY),
-        ("text-decoration-line", AId::TextDecorationLine),
+        ("mask-border-mode", AId::MaskBorderMode),
         ("requiredFeatures", AId::RequiredFeatures),
+        ("color-rendering", AId::ColorRendering),
+        ("text-anchor", AId::TextAnchor),
+        ("y", AId::Y),
+        ("href", AId::Href),
         ("patternContentUnits", AId::PatternContentUnits),
+        ("cy", AId::Cy),
+        ("filterUnits", AId::FilterUnits),
+        ("amplitude", AId::Amplitude),
+        ("mask-border-outset", AId::MaskBorderOutset),
+        ("dx", AId::Dx),
+        ("kerning", AId::Kerning),
+        ("in2", AId::In2),
+        ("targetX", AId::TargetX),
+        ("color", AId::Color),
+        ("operator", AId::Operator),
+        ("style", AId::Style),
+        ("patternUnits", AId::PatternUnits),
         ("shape-padding", AId::ShapePadding),
-        ("text-overflow", AId::TextOverflow),
-        ("clipPathUnits", AId::ClipPathUnits),
+        ("clip", AId::Clip),
+        ("textLength", AId::TextLength),
         ("azimuth", AId::Azimuth),
-        ("line-height", AId::LineHeight),
-        ("viewBox", AId::ViewBox),
-        ("preserveAspectRatio", AId::PreserveAspectRatio),
-        ("path", AId::Path),
-        ("k4", AId::K4),
-        ("systemLanguage", AId::SystemLanguage),
+        ("vector-effect", AId::VectorEffect),
+        ("mix-blend-mode", AId::MixBlendMode),
         ("stroke-width", AId::StrokeWidth),
-        ("specularExponent", AId::SpecularExponent),
-        ("writing-mode", AId::WritingMode),
-        ("transform-origin", AId::TransformOrigin),
-        ("stroke-linecap", AId::StrokeLinecap),
-        ("points", AId::Points),
-        ("style", AId::Style),
-        ("pointsAtZ", AId::PointsAtZ),
-        ("targetX", AId::TargetX),
-        ("font-synthesis", AId::FontSynthesis),
-        ("maskContentUnits", AId::MaskContentUnits),
-        ("text-align", AId::TextAlign),
-        ("cx", AId::Cx),
-        ("alignment-baseline", AId::AlignmentBaseline),
-        ("font-kerning", AId::FontKerning),
+        ("paint-order", AId::PaintOrder),
+        ("mask-mode", AId::MaskMode),
+        ("display", AId::Display),
+        ("stroke-linejoin", AId::StrokeLinejoin),
         ("requiredExtensions", AId::RequiredExtensions),
-        ("clip-rule", AId::ClipRule),
-        ("mask-border-outset", AId::MaskBorderOutset),
-        ("primitiveUnits", AId::PrimitiveUnits),
-        ("textLength", AId::TextLength),
-        ("text-decoration-fill", AId::TextDecorationFill),
-        ("fy", AId::Fy),
-        ("mask-size", AId::MaskSize),
-        ("k3", AId::K3),
-        ("marker-start", AId::MarkerStart),
-        ("mode", AId::Mode),
-        ("k1", AId::K1),
-        ("refY", AId::RefY),
-        ("y1", AId::Y1),
-        ("shape-rendering", AId::ShapeRendering),
-        ("operator", AId::Operator),
-        ("mask-image", AId::MaskImage),
-        ("marker-end", AId::MarkerEnd),
-        ("rotate", AId::Rotate),
-        ("limitingConeAngle", AId::LimitingConeAngle),
-        ("surfaceScale", AId::SurfaceScale),
-        ("intercept", AId::Intercept),
-        ("font-variant-position", AId::FontVariantPosition),
-        ("clip", AId::Clip),
-        ("fx", AId::Fx),
-        ("visibility", AId::Visibility),
-        ("shape-margin", AId::ShapeMargin),
-        ("font-style", AId::FontStyle),
-        ("y2", AId::Y2),
-        ("dy", AId::Dy),
-        ("yChannelSelector", AId::YChannelSelector),
+        ("values", AId::Values),
+        ("mask-type", AId::MaskType),
+        ("image-rendering", AId::ImageRendering),
+        ("k2", AId::K2),
+        ("space", AId::Space),
+        ("stroke-linecap", AId::StrokeLinecap),
         ("ry", AId::Ry),
-        ("color-rendering", AId::ColorRendering),
-        ("white-space", AId::WhiteSpace),
-        ("patternUnits", AId::PatternUnits),
+        ("offset", AId::Offset),
+        ("kernelMatrix", AId::KernelMatrix),
+        ("text-align", AId::TextAlign),
+        ("lengthAdjust", AId::LengthAdjust),
         ("shape-subtract", AId::ShapeSubtract),
-        ("markerWidth", AId::MarkerWidth),
-        ("d", AId::D),
-        ("shape-inside", AId::ShapeInside),
-        ("preserveAlpha", AId::PreserveAlpha),
-        ("shape-image-threshold", AId::ShapeImageThreshold),
-        ("image-rendering", AId::ImageRendering),
+        ("dominant-baseline", AId::DominantBaseline),
+        ("overflow", AId::Overflow),
+        ("yChannelSelector", AId::YChannelSelector),
+        ("text-decoration-fill", AId::TextDecorationFill),
         ("marker-mid", AId::MarkerMid),
-        ("filterUnits", AId::FilterUnits),
-        ("bias", AId::Bias),
-        ("mask-border-slice", AId::MaskBorderSlice),
-        ("pointsAtX", AId::PointsAtX),
-        ("kernelMatrix", AId::KernelMatrix),
-        ("color-interpolation", AId::ColorInterpolation),
+        ("color-profile", AId::ColorProfile),
+        ("type", AId::Type),
+        ("fill-opacity", AId::FillOpacity),
+        ("word-spacing", AId::WordSpacing),
+        ("fill", AId::Fill),
+        ("pointsAtZ", AId::PointsAtZ),
         ("glyph-orientation-vertical", AId::GlyphOrientationVertical),
-        ("color", AId::Color),
-        ("patternTransform", AId::PatternTransform),
+        ("preserveAspectRatio", AId::PreserveAspectRatio),
+        ("visibility", AId::Visibility),
         ("kernelUnitLength", AId::KernelUnitLength),
-        ("markerUnits", AId::MarkerUnits),
+        ("pointsAtX", AId::PointsAtX),
+        ("k1", AId::K1),
+        ("mask", AId::Mask),
+        ("opacity", AId::Opacity),
+        ("targetY", AId::TargetY),
+        ("text-rendering", AId::TextRendering),
+        ("maskContentUnits", AId::MaskContentUnits),
+        ("pointsAtY", AId::PointsAtY),
+        ("d", AId::D),
+        ("mask-border-source", AId::MaskBorderSource),
+        ("id", AId::Id),
+        ("tableValues", AId::TableValues),
+        ("order", AId::Order),
+        ("viewBox", AId::ViewBox),
+        ("stroke-opacity", AId::StrokeOpacity),
+        ("inline-size", AId::InlineSize),
+        ("shape-margin", AId::ShapeMargin),
+        ("flood-opacity", AId::FloodOpacity),
         ("font-weight", AId::FontWeight),
-        ("overflow", AId::Overflow),
+        ("specularExponent", AId::SpecularExponent),
         ("stop-color", AId::StopColor),
-        ("r", AId::R),
-        ("k2", AId::K2),
-        ("text-anchor", AId::TextAnchor),
-        ("inline-size", AId::InlineSize),
-        ("unicode-bidi", AId::UnicodeBidi),
-        ("font-family", AId::FontFamily),
-        ("color-interpolation-filters", AId::ColorInterpolationFilters),
-        ("slope", AId::Slope),
-        ("baseFrequency", AId::BaseFrequency),
-        ("transform", AId::Transform),
-        ("text-rendering", AId::TextRendering),
-        ("divisor", AId::Divisor),
+        ("stop-opacity", AId::StopOpacity),
+        ("cx", AId::Cx),
+        ("class", AId::Class),
+        ("clipPathUnits", AId::ClipPathUnits),
+        ("stroke-dashoffset", AId::StrokeDashoffset),
+        ("font-feature-settings", AId::FontFeatureSettings),
+        ("mask-origin", AId::MaskOrigin),
+        ("systemLanguage", AId::SystemLanguage),
+        ("pointer-events", AId::PointerEvents),
+        ("font-variant-caps", AId::FontVariantCaps),
+        ("text-decoration", AId::TextDecoration),
+        ("shape-inside", AId::ShapeInside),
+        ("surfaceScale", AId::SurfaceScale),
+        ("elevation", AId::Elevation),
+        ("mask-position", AId::MaskPosition),
+        ("mask-border-width", AId::MaskBorderWidth),
+        ("mask-clip", AId::MaskClip),
+        ("text-orientation", AId::TextOrientation),
+        ("radius", AId::Radius),
+        ("bias", AId::Bias),
+        ("markerHeight", AId::MarkerHeight),
         ("edgeMode", AId::EdgeMode),
+        ("line-height", AId::LineHeight),
+        ("shape-rendering", AId::ShapeRendering),
+        ("font-variant-ligatures", AId::FontVariantLigatures),
+        ("lighting-color", AId::LightingColor),
+        ("font-variant-position", AId::FontVariantPosition),
+        ("spreadMethod", AId::SpreadMethod),
+        ("font-family", AId::FontFamily),
+        ("fy", AId::Fy),
+        ("rotate", AId::Rotate),
+        ("y2", AId::Y2),
+        ("stroke", AId::Stroke),
         ("letter-spacing", AId::LetterSpacing),
+        ("fr", AId::Fr),
+        ("font-size-adjust", AId::FontSizeAdjust),
+        ("gradientTransform", AId::GradientTransform),
+        ("writing-mode", AId::WritingMode),
+        ("stdDeviation", AId::StdDeviation),
         ("flood-color", AId::FloodColor),
-        ("in2", AId::In2),
-        ("side", AId::Side),
-        ("mask-composite", AId::MaskComposite),
-        ("offset", AId::Offset),
-        ("values", AId::Values),
-        ("vector-effect", AId::VectorEffect),
-        ("mask", AId::Mask),
-        ("pathLength", AId::PathLength),
-        ("lighting-color", AId::LightingColor),
-        ("mask-position", AId::MaskPosition),
+        ("points", AId::Points),
+        ("refY", AId::RefY),
+        ("text-underline-position", AId::TextUnderlinePosition),
+        ("specularConstant", AId::SpecularConstant),
+        ("baseFrequency", AId::BaseFrequency),
         ("stroke-dasharray", AId::StrokeDasharray),
-        ("text-decoration", AId::TextDecoration),
-        ("stroke-opacity", AId::StrokeOpacity),
-        ("targetY", AId::TargetY),
-        ("flood-opacity", AId::FloodOpacity),
-        ("diffuseConstant", AId::DiffuseConstant),
+        ("preserveAlpha", AId::PreserveAlpha),
+        ("enable-background", AId::EnableBackground),
+        ("color-interpolation", AId::ColorInterpolation),
+        ("text-decoration-style", AId::TextDecorationStyle),
+        ("text-decoration-line", AId::TextDecorationLine),
+        ("filter", AId::Filter),
+        ("divisor", AId::Divisor),
+        ("text-indent", AId::TextIndent),
+        ("patternTransform", AId::PatternTransform),
+        ("white-space", AId::WhiteSpace),
+        ("direction", AId::Direction),
+        ("limitingConeAngle", AId::LimitingConeAngle),
+        ("x2", AId::X2),
+        ("k4", AId::K4),
+        ("marker-start", AId::MarkerStart),
+        ("y1", AId::Y1),
+        ("maskUnits", AId::MaskUnits),
+        ("unicode-bidi", AId::UnicodeBidi),
+        ("z", AId::Z),
+        ("text-overflow", AId::TextOverflow),
+        ("height", AId::Height),
+        ("mask-border-slice", AId::MaskBorderSlice),
+        ("primitiveUnits", AId::PrimitiveUnits),
+        ("mask-border", AId::MaskBorder),
+        ("x", AId::X),
+        ("width", AId::Width),
+        ("font-kerning", AId::FontKerning),
+        ("color-interpolation-filters", AId::ColorInterpolationFilters),
+        ("font-size", AId::FontSize),
+        ("marker-end", AId::MarkerEnd),
+        ("transform-box", AId::TransformBox),
+        ("font-synthesis", AId::FontSynthesis),
     ],
 };
 
@@ -1,6 +1,7 @@
 // Copyright 2021 the Resvg Authors
 // SPDX-License-Identifier: Apache-2.0 OR MIT
 
+use std::borrow::Cow;
 use std::collections::HashMap;
 
 use roxmltree::Error;
@@ -8,8 +9,10 @@ use simplecss::Declaration;
 use svgtypes::FontShorthand;
 
 use super::{AId, Attribute, Document, EId, NodeData, NodeId, NodeKind, ShortRange};
+use crate::parser::Diagnostics;
+use crate::{Size, StyleSource};
 
-const SVG_NS: &str = "http://www.w3.org/2000/svg";
+pub(crate) const SVG_NS: &str = "http://www.w3.org/2000/svg";
 const XLINK_NS: &str = "http://www.w3.org/1999/xlink";
 const XML_NAMESPACE_NS: &str = "http://www.w3.org/XML/1998/namespace";
 
@@ -18,8 +21,12 @@ impl<'input> Document<'input> {
     pub fn parse_tree(
         xml: &roxmltree::Document<'input>,
         injected_stylesheet: Option<&'input str>,
+        media_features: &HashMap<String, String>,
+        default_size: Size,
+        diagnostics: Option<&'input Diagnostics>,
     ) -> Result<Document<'input>, Error> {
-        parse(xml, injected_stylesheet)
+        let media = super::css::Media::new(media_features, xml.root_element(), default_size);
+        parse(xml, injected_stylesheet, &media, diagnostics)
     }
 
     pub(crate) fn append(&mut self, parent_id: NodeId, kind: NodeKind) -> NodeId {
@@ -53,11 +60,13 @@ impl<'input> Document<'input> {
         name: AId,
         value: roxmltree::StringStorage<'input>,
         important: bool,
+        source: StyleSource,
     ) {
         self.attrs.push(Attribute {
             name,
             value,
             important,
+            source,
         });
     }
 }
@@ -65,11 +74,14 @@ impl<'input> Document<'input> {
 fn parse<'input>(
     xml: &roxmltree::Document<'input>,
     injected_stylesheet: Option<&'input str>,
+    media: &super::css::Media,
+    diagnostics: Option<&'input Diagnostics>,
 ) -> Result<Document<'input>, Error> {
     let mut doc = Document {
         nodes: Vec::new(),
         attrs: Vec::new(),
         links: HashMap::new(),
+        diagnostics,
     };
 
     // build a map of id -> node for resolve_href
@@ -90,7 +102,8 @@ fn parse<'input>(
         kind: NodeKind::Root,
     });
 
-    let style_sheet = resolve_css(xml, injected_stylesheet);
+    let css_texts = collect_css(xml, injected_stylesheet, media);
+    let style_sheet = resolve_css(&css_texts);
 
     parse_xml_node_children(
         xml.root(),
@@ -114,9 +127,22 @@ fn parse<'input>(
     }
 
     // Collect all elements with `id` attribute.
+    // Like `id_map`, duplicated IDs are resolved to the first element in the document order.
     let mut links = HashMap::new();
     for node in doc.descendants() {
         if let Some(id) = node.attribute::<&str>(AId::Id) {
+            if links.contains_key(id) {
+                doc.warn(
+                    "id",
+                    id,
+                    &format!(
+                        "Element ID '{}' is not unique. Only the first element will be referenced.",
+                        id
+                    ),
+                );
+                continue;
+            }
+
             links.insert(id.to_string(), node.id);
         }
     }
@@ -228,6 +254,7 @@ pub(crate) fn parse_svg_element<'input>(
     doc: &mut Document<'input>,
 ) -> Result<NodeId, Error> {
     let attrs_start_idx = doc.attrs.len();
+    let diagnostics = doc.diagnostics;
 
     // Copy presentational attributes first.
     for attr in xml_node.attributes() {
@@ -265,11 +292,12 @@ pub(crate) fn parse_svg_element<'input>(
             aid,
             attr.value_storage().clone(),
             false,
+            StyleSource::Attribute,
             doc,
         );
     }
 
-    let mut insert_attribute = |aid, value: &str, important: bool| {
+    let mut insert_attribute = |aid, value: &str, important: bool, source: StyleSource| {
         // Check that attribute already exists.
         let idx = doc.attrs[attrs_start_idx..]
             .iter_mut()
@@ -282,6 +310,7 @@ pub(crate) fn parse_svg_element<'input>(
             aid,
             roxmltree::StringStorage::new_owned(value),
             important,
+            source,
             doc,
         );
 
@@ -318,57 +347,62 @@ pub(crate) fn parse_svg_element<'input>(
         }
     };
 
-    let mut write_declaration = |declaration: &Declaration| {
+    let mut write_declaration = |declaration: &Declaration, source: StyleSource| {
         // TODO: perform XML attribute normalization
         let imp = declaration.important;
         let val = declaration.value;
 
         if declaration.name == "marker" {
-            insert_attribute(AId::MarkerStart, val, imp);
-            insert_attribute(AId::MarkerMid, val, imp);
-            insert_attribute(AId::MarkerEnd, val, imp);
+            insert_attribute(AId::MarkerStart, val, imp, source);
+            insert_attribute(AId::MarkerMid, val, imp, source);
+            insert_attribute(AId::MarkerEnd, val, imp, source);
         } else if declaration.name == "font" {
             if let Ok(shorthand) = FontShorthand::from_str(val) {
                 // First we need to reset all values to their default.
-                insert_attribute(AId::FontStyle, "normal", imp);
-                insert_attribute(AId::FontVariant, "normal", imp);
-                insert_attribute(AId::FontWeight, "normal", imp);
-                insert_attribute(AId::FontStretch, "normal", imp);
-                insert_attribute(AId::LineHeight, "normal", imp);
-                insert_attribute(AId::FontSizeAdjust, "none", imp);
-                insert_attribute(AId::FontKerning, "auto", imp);
-                insert_attribute(AId::FontVariantCaps, "normal", imp);
-                insert_attribute(AId::FontVariantLigatures, "normal", imp);
-                insert_attribute(AId::FontVariantNumeric, "normal", imp);
-                insert_attribute(AId::FontVariantEastAsian, "normal", imp);
-                insert_attribute(AId::FontVariantPosition, "normal", imp);
+                insert_attribute(AId::FontStyle, "normal", imp, source);
+                insert_attribute(AId::FontVariant, "normal", imp, source);
+                insert_attribute(AId::FontWeight, "normal", imp, source);
+                insert_attribute(AId::FontStretch, "normal", imp, source);
+                insert_attribute(AId::LineHeight, "normal", imp, source);
+                insert_attribute(AId::FontSizeAdjust, "none", imp, source);
+                insert_attribute(AId::FontKerning, "auto", imp, source);
+                insert_attribute(AId::FontVariantCaps, "normal", imp, source);
+                insert_attribute(AId::FontVariantLigatures, "normal", imp, source);
+                insert_attribute(AId::FontVariantNumeric, "normal", imp, source);
+                insert_attribute(AId::FontVariantEastAsian, "normal", imp, source);
+                insert_attribute(AId::FontVariantPosition, "normal", imp, source);
 
                 // Then, we set the properties that have been declared.
                 shorthand
                     .font_stretch
-                    .map(|s| insert_attribute(AId::FontStretch, s, imp));
+                    .map(|s| insert_attribute(AId::FontStretch, s, imp, source));
                 shorthand
                     .font_weight
-                    .map(|s| insert_attribute(AId::FontWeight, s, imp));
+                    .map(|s| insert_attribute(AId::FontWeight, s, imp, source));
                 shorthand
                     .font_variant
-                    .map(|s| insert_attribute(AId::FontVariant, s, imp));
+                    .map(|s| insert_attribute(AId::FontVariant, s, imp, source));
                 shorthand
                     .font_style
-                    .map(|s| insert_attribute(AId::FontStyle, s, imp));
-                insert_attribute(AId::FontSize, shorthand.font_size, imp);
-                insert_attribute(AId::FontFamily, shorthand.font_family, imp);
+                    .map(|s| insert_attribute(AId::FontStyle, s, imp, source));
+                insert_attribute(AId::FontSize, shorthand.font_size, imp, source);
+                insert_attribute(AId::FontFamily, shorthand.font_family, imp, source);
             } else {
-                log::warn!(
-                    "Failed to parse {} value: '{}'",
-                    AId::Font,
-                    declaration.value
+                crate::parser::diagnostics::warn(
+                    diagnostics,
+                    AId::Font.to_str(),
+                    xml_node.attribute("id").unwrap_or_default(),
+                    &format!(
+                        "Failed to parse {} value: '{}'",
+                        AId::Font,
+                        declaration.value
+                    ),
                 );
             }
         } else if let Some(aid) = AId::from_str(declaration.name) {
             // Parse only the presentation attributes.
             if aid.is_presentation() {
-                insert_attribute(aid, val, imp);
+                insert_attribute(aid, val, imp, source);
             }
         }
     };
@@ -377,7 +411,7 @@ pub(crate) fn parse_svg_element<'input>(
     for rule in &style_sheet.rules {
         if rule.selector.matches(&XmlNode(xml_node)) {
             for declaration in &rule.declarations {
-                write_declaration(declaration);
+                write_declaration(declaration, StyleSource::StyleSheet);
             }
         }
     }
@@ -385,7 +419,7 @@ pub(crate) fn parse_svg_element<'input>(
     // Split a `style` attribute.
     if let Some(value) = xml_node.attribute("style") {
         for declaration in simplecss::DeclarationTokenizer::from(value) {
-            write_declaration(&declaration);
+            write_declaration(&declaration, StyleSource::Inline);
         }
     }
 
@@ -410,6 +444,7 @@ fn append_attribute<'input>(
     aid: AId,
     value: roxmltree::StringStorage<'input>,
     important: bool,
+    source: StyleSource,
     doc: &mut Document<'input>,
 ) -> bool {
     match aid {
@@ -430,7 +465,7 @@ fn append_attribute<'input>(
         return resolve_inherit(parent_id, aid, doc);
     }
 
-    doc.append_attribute(aid, value, important);
+    doc.append_attribute(aid, value, important, source);
     true
 }
 
@@ -454,6 +489,7 @@ fn resolve_inherit(parent_id: NodeId, aid: AId, doc: &mut Document) -> bool {
                     name: aid,
                     value: attr.value,
                     important: attr.important,
+                    source: StyleSource::Inherited,
                 });
 
                 return true;
@@ -472,6 +508,7 @@ fn resolve_inherit(parent_id: NodeId, aid: AId, doc: &mut Document) -> bool {
                 name: aid,
                 value: attr.value,
                 important: attr.important,
+                source: StyleSource::Inherited,
             });
 
             return true;
@@ -479,57 +516,76 @@ fn resolve_inherit(parent_id: NodeId, aid: AId, doc: &mut Document) -> bool {
     }
 
     // Fallback to a default value if possible.
-    let value = match aid {
-        AId::ImageRendering | AId::ShapeRendering | AId::TextRendering => "auto",
-
-        AId::ClipPath
-        | AId::Filter
-        | AId::MarkerEnd
-        | AId::MarkerMid
-        | AId::MarkerStart
-        | AId::Mask
-        | AId::Stroke
-        | AId::StrokeDasharray
-        | AId::TextDecoration => "none",
-
-        AId::FontStretch
-        | AId::FontStyle
-        | AId::FontVariant
-        | AId::FontWeight
-        | AId::LetterSpacing
-        | AId::WordSpacing => "normal",
-
-        AId::Fill | AId::FloodColor | AId::StopColor => "black",
-
-        AId::FillOpacity
-        | AId::FloodOpacity
-        | AId::Opacity
-        | AId::StopOpacity
-        | AId::StrokeOpacity => "1",
-
-        AId::ClipRule | AId::FillRule => "nonzero",
-
-        AId::BaselineShift => "baseline",
-        AId::ColorInterpolationFilters => "linearRGB",
-        AId::Direction => "ltr",
-        AId::Display => "inline",
-        AId::FontSize => "medium",
-        AId::Overflow => "visible",
-        AId::StrokeDashoffset => "0",
-        AId::StrokeLinecap => "butt",
-        AId::StrokeLinejoin => "miter",
-        AId::StrokeMiterlimit => "4",
-        AId::StrokeWidth => "1",
-        AId::TextAnchor => "start",
-        AId::Visibility => "visible",
-        AId::WritingMode => "lr-tb",
-        _ => return false,
+    let value = match default_value(aid) {
+        Some(v) => v,
+        None => return false,
     };
 
-    doc.append_attribute(aid, roxmltree::StringStorage::Borrowed(value), false);
+    doc.append_attribute(
+        aid,
+        roxmltree::StringStorage::Borrowed(value),
+        false,
+        StyleSource::Default,
+    );
     true
 }
 
+/// Returns an initial value of a presentation attribute.
+///
+/// Not all attributes are supported.
+pub(crate) fn default_value(aid: AId) -> Option<&'static str> {
+    DEFAULT_VALUES
+        .iter()
+        .find(|(name, _)| *name == aid)
+        .map(|(_, value)| *value)
+}
+
+/// Initial values of presentation attributes.
+pub(crate) static DEFAULT_VALUES: &[(AId, &str)] = &[
+    (AId::BaselineShift, "baseline"),
+    (AId::ClipPath, "none"),
+    (AId::ClipRule, "nonzero"),
+    (AId::ColorInterpolationFilters, "linearRGB"),
+    (AId::Direction, "ltr"),
+    (AId::Display, "inline"),
+    (AId::Fill, "black"),
+    (AId::FillOpacity, "1"),
+    (AId::FillRule, "nonzero"),
+    (AId::Filter, "none"),
+    (AId::FloodColor, "black"),
+    (AId::FloodOpacity, "1"),
+    (AId::FontSize, "medium"),
+    (AId::FontStretch, "normal"),
+    (AId::FontStyle, "normal"),
+    (AId::FontVariant, "normal"),
+    (AId::FontWeight, "normal"),
+    (AId::ImageRendering, "auto"),
+    (AId::LetterSpacing, "normal"),
+    (AId::MarkerEnd, "none"),
+    (AId::MarkerMid, "none"),
+    (AId::MarkerStart, "none"),
+    (AId::Mask, "none"),
+    (AId::Opacity, "1"),
+    (AId::Overflow, "visible"),
+    (AId::ShapeRendering, "auto"),
+    (AId::StopColor, "black"),
+    (AId::StopOpacity, "1"),
+    (AId::Stroke, "none"),
+    (AId::StrokeDasharray, "none"),
+    (AId::StrokeDashoffset, "0"),
+    (AId::StrokeLinecap, "butt"),
+    (AId::StrokeLinejoin, "miter"),
+    (AId::StrokeMiterlimit, "4"),
+    (AId::StrokeOpacity, "1"),
+    (AId::StrokeWidth, "1"),
+    (AId::TextAnchor, "start"),
+    (AId::TextDecoration, "none"),
+    (AId::TextRendering, "auto"),
+    (AId::Visibility, "visible"),
+    (AId::WordSpacing, "normal"),
+    (AId::WritingMode, "lr-tb"),
+];
+
 fn resolve_href<'a, 'input: 'a>(
     node: roxmltree::Node<'a, 'input>,
     id_map: &HashMap<&str, roxmltree::Node<'a, 'input>>,
@@ -558,9 +614,11 @@ fn parse_svg_use_element<'input>(
     };
 
     if link == node || link == origin {
-        log::warn!(
-            "Recursive 'use' detected. '{}' will be skipped.",
-            node.attribute((SVG_NS, "id")).unwrap_or_default()
+        let id = node.attribute("id").unwrap_or_default();
+        doc.warn(
+            "use",
+            id,
+            &format!("Recursive 'use' detected. '{}' will be skipped.", id),
         );
         return Ok(());
     }
@@ -602,9 +660,11 @@ fn parse_svg_use_element<'input>(
     }
 
     if is_recursive {
-        log::warn!(
-            "Recursive 'use' detected. '{}' will be skipped.",
-            node.attribute((SVG_NS, "id")).unwrap_or_default()
+        let id = node.attribute("id").unwrap_or_default();
+        doc.warn(
+            "use",
+            id,
+            &format!("Recursive 'use' detected. '{}' will be skipped.", id),
         );
         return Ok(());
     }
@@ -621,16 +681,17 @@ fn parse_svg_use_element<'input>(
     )
 }
 
-fn resolve_css<'a>(
-    xml: &'a roxmltree::Document<'a>,
+fn collect_css<'a>(
+    xml: &'a roxmltree::Document,
     style_sheet: Option<&'a str>,
-) -> simplecss::StyleSheet<'a> {
-    let mut sheet = simplecss::StyleSheet::new();
+    media: &super::css::Media,
+) -> Vec<Cow<'a, str>> {
+    let mut texts = Vec::new();
 
     // Injected style sheets do not override internal ones (we mimic the logic of rsvg-convert),
     // so we need to parse it first.
     if let Some(style_sheet) = style_sheet {
-        sheet.parse_more(style_sheet);
+        texts.push(super::css::rewrite(style_sheet, media));
     }
 
     for node in xml.descendants().filter(|n| n.has_tag_name("style")) {
@@ -645,6 +706,15 @@ fn resolve_css<'a>(
             None => continue,
         };
 
+        texts.push(super::css::rewrite(text, media));
+    }
+
+    texts
+}
+
+fn resolve_css<'a>(texts: &'a [Cow<'a, str>]) -> simplecss::StyleSheet<'a> {
+    let mut sheet = simplecss::StyleSheet::new();
+    for text in texts {
         sheet.parse_more(text);
     }
 
@@ -667,6 +737,15 @@ impl simplecss::Element for XmlNode<'_, '_> {
     }
 
     fn attribute_matches(&self, local_name: &str, operator: simplecss::AttributeOperator) -> bool {
+        if local_name == super::css::EXTENSION_ATTRIBUTE {
+            return match operator {
+                simplecss::AttributeOperator::Matches(value) => {
+                    super::css::extension_matches(self.0, value)
+                }
+                _ => false,
+            };
+        }
+
         match self.0.attribute(local_name) {
             Some(value) => operator.matches(value),
             None => false,
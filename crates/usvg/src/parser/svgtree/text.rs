@@ -17,18 +17,14 @@ pub(crate) fn parse_svg_text_element<'input>(
 ) -> Result<(), Error> {
     debug_assert_eq!(parent.tag_name().name(), "text");
 
-    let space = if doc.get(parent_id).has_attribute(AId::Space) {
-        get_xmlspace(doc, parent_id, XmlSpace::Default)
+    let space = if let Some(node) = doc
+        .get(parent_id)
+        .ancestors()
+        .find(|n| n.has_attribute(AId::WhiteSpace) || n.has_attribute(AId::Space))
+    {
+        get_xmlspace(doc, node.id, XmlSpace::Default)
     } else {
-        if let Some(node) = doc
-            .get(parent_id)
-            .ancestors()
-            .find(|n| n.has_attribute(AId::Space))
-        {
-            get_xmlspace(doc, node.id, XmlSpace::Default)
-        } else {
-            XmlSpace::Default
-        }
+        XmlSpace::Default
     };
 
     parse_svg_text_element_impl(parent, parent_id, style_sheet, space, doc)?;
@@ -128,14 +124,55 @@ fn resolve_tref_text(xml: &roxmltree::Document, href: &str) -> Option<String> {
     }
 }
 
+/// Whitespace processing mode.
+///
+/// Resolved from both `xml:space` and CSS `white-space`.
 #[derive(Clone, Copy, PartialEq, Debug)]
 enum XmlSpace {
+    /// Spaces are collapsed, newlines are converted into spaces.
+    ///
+    /// `xml:space="default"`, `white-space: normal` and `white-space: nowrap`.
     Default,
+    /// Spaces are preserved, newlines are converted into spaces.
+    ///
+    /// `xml:space="preserve"`.
     Preserve,
+    /// Spaces and newlines are preserved.
+    ///
+    /// `white-space: pre`, `white-space: pre-wrap` and `white-space: break-spaces`.
+    Pre,
+    /// Spaces are collapsed, newlines are preserved.
+    ///
+    /// `white-space: pre-line`.
+    PreLine,
+}
+
+impl XmlSpace {
+    #[inline]
+    fn collapses_spaces(self) -> bool {
+        matches!(self, XmlSpace::Default | XmlSpace::PreLine)
+    }
+
+    #[inline]
+    fn preserves_newlines(self) -> bool {
+        matches!(self, XmlSpace::Pre | XmlSpace::PreLine)
+    }
 }
 
+/// Resolves node's whitespace processing mode.
+///
+/// `white-space` takes precedence over `xml:space` when both are set.
 fn get_xmlspace(doc: &Document, node_id: NodeId, default: XmlSpace) -> XmlSpace {
-    match doc.get(node_id).attribute(AId::Space) {
+    let node = doc.get(node_id);
+    match node.attribute(AId::WhiteSpace) {
+        Some("normal") | Some("nowrap") => return XmlSpace::Default,
+        Some("pre") | Some("pre-wrap") | Some("break-spaces") => return XmlSpace::Pre,
+        Some("pre-line") => return XmlSpace::PreLine,
+        // `inherit` and unknown values are ignored.
+        _ => {}
+    }
+
+    match node.attribute(AId::Space) {
         Some("preserve") => XmlSpace::Preserve,
         Some(_) => XmlSpace::Default,
         _ => default,
@@ -162,7 +199,7 @@ impl StrTrim for String {
 /// Prepares text nodes according to the spec: https://www.w3.org/TR/SVG11/text.html#WhiteSpace
 ///
 /// This function handles:
-/// - 'xml:space' processing
+/// - 'xml:space' and 'white-space' processing
 /// - tabs and newlines removing/replacing
 /// - spaces trimming
 fn trim_text_nodes(text_elem_id: NodeId, xmlspace: XmlSpace, doc: &mut Document) {
@@ -177,7 +214,7 @@ fn trim_text_nodes(text_elem_id: NodeId, xmlspace: XmlSpace, doc: &mut Document)
 
         let node_id = nodes[0].0;
 
-        if xmlspace == XmlSpace::Default {
+        if xmlspace.collapses_spaces() {
             if let NodeKind::Text(ref mut text) = doc.nodes[node_id.get_usize()].kind {
                 match text.len() {
                     0 => {} // An empty string. Do nothing.
@@ -203,7 +240,7 @@ fn trim_text_nodes(text_elem_id: NodeId, xmlspace: XmlSpace, doc: &mut Document)
                 }
             }
         } else {
-            // Do nothing when xml:space=preserve.
+            // Do nothing when spaces are preserved.
         }
     } else if nodes.len() > 1 {
         // Process element with many text node children.
@@ -261,7 +298,7 @@ fn trim_text_nodes(text_elem_id: NodeId, xmlspace: XmlSpace, doc: &mut Document)
             // See text-tspan-02-b.svg for details.
             if depth1 < depth2 {
                 if c3 == Some(b' ') {
-                    if xmlspace2 == XmlSpace::Default {
+                    if xmlspace2.collapses_spaces() {
                         if let NodeKind::Text(ref mut text) = doc.nodes[node2_id.get_usize()].kind {
                             text.remove_first_space();
                         }
@@ -269,12 +306,12 @@ fn trim_text_nodes(text_elem_id: NodeId, xmlspace: XmlSpace, doc: &mut Document)
                 }
             } else {
                 if c2 == Some(b' ') && c2 == c3 {
-                    if xmlspace1 == XmlSpace::Default && xmlspace2 == XmlSpace::Default {
+                    if xmlspace1.collapses_spaces() && xmlspace2.collapses_spaces() {
                         if let NodeKind::Text(ref mut text) = doc.nodes[node1_id.get_usize()].kind {
                             text.remove_last_space();
                         }
                     } else {
-                        if xmlspace1 == XmlSpace::Preserve && xmlspace2 == XmlSpace::Default {
+                        if !xmlspace1.collapses_spaces() && xmlspace2.collapses_spaces() {
                             if let NodeKind::Text(ref mut text) =
                                 doc.nodes[node2_id.get_usize()].kind
                             {
@@ -290,7 +327,7 @@ fn trim_text_nodes(text_elem_id: NodeId, xmlspace: XmlSpace, doc: &mut Document)
 
             if is_first
                 && c1 == Some(b' ')
-                && xmlspace1 == XmlSpace::Default
+                && xmlspace1.collapses_spaces()
                 && !doc.get(node1_id).text().is_empty()
             {
                 // Remove a leading space from a first text node.
@@ -300,7 +337,7 @@ fn trim_text_nodes(text_elem_id: NodeId, xmlspace: XmlSpace, doc: &mut Document)
             } else if is_last
                 && c4 == Some(b' ')
                 && !doc.get(node2_id).text().is_empty()
-                && xmlspace2 == XmlSpace::Default
+                && xmlspace2.collapses_spaces()
             {
                 // Remove a trailing space from a last text node.
                 // Also check that 'text2' is not empty already.
@@ -345,16 +382,39 @@ fn trim_text(text: &str, space: XmlSpace) -> String {
     let mut s = String::with_capacity(text.len());
 
     let mut prev = '0';
-    for c in text.chars() {
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
         // \r, \n and \t should be converted into spaces.
+        // Unless newlines must be preserved, in which case
+        // `\r\n` and `\r` are normalized into `\n`.
         let c = match c {
+            '\r' if space.preserves_newlines() => {
+                if chars.peek() == Some(&'\n') {
+                    chars.next();
+                }
+
+                '\n'
+            }
+            '\n' if space.preserves_newlines() => '\n',
             '\r' | '\n' | '\t' => ' ',
             _ => c,
         };
 
-        // Skip continuous spaces.
-        if space == XmlSpace::Default && c == ' ' && c == prev {
-            continue;
+        if space.collapses_spaces() && c == ' ' {
+            // Skip continuous spaces.
+            if c == prev {
+                continue;
+            }
+
+            // Skip spaces after a preserved newline.
+            if prev == '\n' {
+                continue;
+            }
+        }
+
+        // Remove spaces before a preserved newline.
+        if space.collapses_spaces() && c == '\n' && prev == ' ' {
+            s.pop();
         }
 
         prev = c;
@@ -92,34 +92,44 @@ fn is_valid_sys_lang(node: SvgNode, opt: &Options) -> bool {
     // 'The attribute value is a comma-separated list of language names
     // as defined in BCP 47.'
     //
-    // But we support only simple cases like `en` or `en-US`.
-    // No one really uses this, especially with complex BCP 47 values.
+    // 'Evaluates to `true` if one of the languages indicated by user preferences exactly
+    // equals one of the languages given in the value of this parameter, or if one of
+    // the languages indicated by user preferences exactly equals a prefix of one of
+    // the languages given in the value of this parameter such that the first tag character
+    // following the prefix is `-`.'
+    //
+    // Which is the basic filtering from RFC 4647.
     if let Some(langs) = node.attribute::<&str>(AId::SystemLanguage) {
-        let mut has_match = false;
-        for lang in langs.split(',') {
-            let lang = lang.trim();
+        langs
+            .split(',')
+            .map(|lang| lang.trim())
+            .filter(|lang| !lang.is_empty())
+            .any(|lang| {
+                opt.languages
+                    .iter()
+                    .any(|range| is_lang_range_match(range.trim(), lang))
+            })
+    } else {
+        true
+    }
+}
 
-            // 'Evaluates to `true` if one of the languages indicated by user preferences exactly
-            // equals one of the languages given in the value of this parameter.'
-            if opt.languages.iter().any(|v| v == lang) {
-                has_match = true;
-                break;
-            }
+/// Checks that a language tag matches a language range using RFC 4647 basic filtering.
+///
+/// The comparison is case-insensitive. The `*` range matches any tag.
+fn is_lang_range_match(range: &str, tag: &str) -> bool {
+    if range == "*" {
+        return true;
+    }
 
-            // 'If one of the languages indicated by user preferences exactly equals a prefix
-            // of one of the languages given in the value of this parameter such that
-            // the first tag character following the prefix is `-`.'
-            if let Some(idx) = lang.bytes().position(|c| c == b'-') {
-                let lang_prefix = &lang[..idx];
-                if opt.languages.iter().any(|v| v == lang_prefix) {
-                    has_match = true;
-                    break;
-                }
-            }
-        }
+    if range.is_empty() || range.len() > tag.len() {
+        return false;
+    }
 
-        has_match
-    } else {
-        true
+    match tag.get(..range.len()) {
+        Some(prefix) if prefix.eq_ignore_ascii_case(range) => {
+            tag.len() == range.len() || tag.as_bytes()[range.len()] == b'-'
+        }
+        _ => false,
     }
 }
@@ -71,6 +71,24 @@ impl<'a, 'input: 'a> FromValue<'a, 'input> for LengthAdjust {
     }
 }
 
+impl<'a, 'input: 'a> FromValue<'a, 'input> for GlyphOrientationVertical {
+    fn parse(_: SvgNode, _: AId, value: &str) -> Option<Self> {
+        if value == "auto" {
+            return Some(GlyphOrientationVertical::Auto);
+        }
+
+        // Only `0` and `90` are supported. `180` and `270` are ignored.
+        let angle = value.parse::<svgtypes::Angle>().ok()?.to_degrees();
+        if angle.approx_zero_ulps(4) {
+            Some(GlyphOrientationVertical::Upright)
+        } else if angle.approx_eq_ulps(&90.0, 4) {
+            Some(GlyphOrientationVertical::Rotated)
+        } else {
+            None
+        }
+    }
+}
+
 impl<'a, 'input: 'a> FromValue<'a, 'input> for FontStyle {
     fn parse(_: SvgNode, _: AId, value: &str) -> Option<Self> {
         match value {
@@ -107,7 +125,9 @@ pub(crate) fn convert(
     let rotate_list = resolve_rotate_list(text_node);
     let writing_mode = convert_writing_mode(text_node);
 
-    let chunks = collect_text_chunks(text_node, &pos_list, state, cache);
+    let (chunks, line_breaks) =
+        collect_text_chunks(text_node, &pos_list, state, cache, writing_mode);
+    let shape_inside = resolve_shape_inside(text_node, state);
 
     let rendering_mode: TextRendering = text_node
         .find_attribute(AId::TextRendering)
@@ -125,9 +145,9 @@ pub(crate) fn convert(
     let mut text = Text {
         id,
         rendering_mode,
-        dx: pos_list.iter().map(|v| v.dx.unwrap_or(0.0)).collect(),
-        dy: pos_list.iter().map(|v| v.dy.unwrap_or(0.0)).collect(),
-        rotate: rotate_list,
+        dx: skip_line_breaks(pos_list.iter().map(|v| v.dx.unwrap_or(0.0)), &line_breaks),
+        dy: skip_line_breaks(pos_list.iter().map(|v| v.dy.unwrap_or(0.0)), &line_breaks),
+        rotate: skip_line_breaks(rotate_list.into_iter(), &line_breaks),
         writing_mode,
         chunks,
         abs_transform: parent.abs_transform,
@@ -140,38 +160,71 @@ pub(crate) fn convert(
         layouted: vec![],
     };
 
-    if text::convert(&mut text, &state.opt.font_resolver, &mut cache.fontdb).is_none() {
+    if let Some(ref shape) = shape_inside {
+        text::wrap_in_shape(&mut text, shape, state.opt, &mut cache.fontdb);
+    }
+
+    if text::convert(&mut text, state.opt, &mut cache.fontdb).is_none() {
         return;
     }
 
     parent.children.push(Node::Text(Box::new(text)));
 }
 
+/// A line height used for forced line breaks.
+///
+/// We do not support `line-height`, so we are using the CSS `normal` approximation.
+const LINE_HEIGHT: f32 = 1.2;
+
 struct IterState {
     chars_count: usize,
     chunk_bytes_count: usize,
     split_chunk: bool,
     text_flow: TextFlow,
     chunks: Vec<TextChunk>,
+    writing_mode: WritingMode,
+    /// The position of the current line start.
+    line_start: (f32, f32),
+    /// An offset to the next line, if a line break is pending.
+    line_offset: f32,
+    /// Indices of characters that were consumed as line breaks.
+    line_breaks: Vec<usize>,
 }
 
+/// Collects text chunks and indices of characters that were consumed as line breaks.
+///
+/// Newlines can be present in the text only when preserved by `white-space`.
+/// In which case each one of them starts a new chunk on the next line.
 fn collect_text_chunks(
     text_node: SvgNode,
     pos_list: &[CharacterPosition],
     state: &converter::State,
     cache: &mut converter::Cache,
-) -> Vec<TextChunk> {
+    writing_mode: WritingMode,
+) -> (Vec<TextChunk>, Vec<usize>) {
     let mut iter_state = IterState {
         chars_count: 0,
         chunk_bytes_count: 0,
         split_chunk: false,
         text_flow: TextFlow::Linear,
         chunks: Vec::new(),
+        writing_mode,
+        line_start: (0.0, 0.0),
+        line_offset: 0.0,
+        line_breaks: Vec::new(),
     };
 
     collect_text_chunks_impl(text_node, pos_list, state, cache, &mut iter_state);
 
-    iter_state.chunks
+    (iter_state.chunks, iter_state.line_breaks)
+}
+
+/// Removes values of characters that were consumed as line breaks.
+fn skip_line_breaks(list: impl Iterator<Item = f32>, line_breaks: &[usize]) -> Vec<f32> {
+    list.enumerate()
+        .filter(|(i, _)| !line_breaks.contains(i))
+        .map(|(_, v)| v)
+        .collect()
 }
 
 fn collect_text_chunks_impl(
@@ -257,7 +310,9 @@ fn collect_text_chunks_impl(
 
         let mut apply_kerning = true;
         #[allow(clippy::if_same_then_else)]
-        if parent.resolve_length(AId::Kerning, state, -1.0) == 0.0 {
+        if !state.opt.apply_kerning {
+            apply_kerning = false;
+        } else if parent.resolve_length(AId::Kerning, state, -1.0) == 0.0 {
             apply_kerning = false;
         } else if parent.find_attribute::<&str>(AId::FontKerning) == Some("none") {
             apply_kerning = false;
@@ -282,6 +337,9 @@ fn collect_text_chunks_impl(
             paint_order,
             font,
             font_size,
+            font_size_adjust: parent
+                .find_attribute::<f32>(AId::FontSizeAdjust)
+                .filter(|n| *n > 0.0),
             small_caps: parent.find_attribute::<&str>(AId::FontVariant) == Some("small-caps"),
             apply_kerning,
             decoration: resolve_decoration(parent, state, cache),
@@ -295,10 +353,25 @@ fn collect_text_chunks_impl(
             word_spacing: parent.resolve_length(AId::WordSpacing, state, 0.0),
             text_length,
             length_adjust: parent.find_attribute(AId::LengthAdjust).unwrap_or_default(),
+            glyph_orientation_vertical: parent
+                .find_attribute(AId::GlyphOrientationVertical)
+                .unwrap_or_default(),
         };
 
         let mut is_new_span = true;
-        for c in child.text().chars() {
+        for mut c in child.text().chars() {
+            if c == '\n' {
+                if let TextFlow::Linear = iter_state.text_flow {
+                    iter_state.line_breaks.push(iter_state.chars_count);
+                    iter_state.line_offset += font_size.get() * LINE_HEIGHT;
+                    iter_state.chars_count += 1;
+                    continue;
+                }
+
+                // Line breaks are not supported on a path.
+                c = ' ';
+            }
+
             let char_len = c.len_utf8();
 
             // Create a new chunk if:
@@ -306,9 +379,11 @@ fn collect_text_chunks_impl(
             // - text character has an absolute coordinate assigned to it (via x/y attribute)
             // - `c` is the first char of the `textPath`
             // - `c` is the first char after `textPath`
+            // - `c` is the first char after a line break
             let is_new_chunk = pos_list[iter_state.chars_count].x.is_some()
                 || pos_list[iter_state.chars_count].y.is_some()
                 || iter_state.split_chunk
+                || iter_state.line_offset != 0.0
                 || iter_state.chunks.is_empty();
 
             iter_state.split_chunk = false;
@@ -320,9 +395,33 @@ fn collect_text_chunks_impl(
                 span2.start = 0;
                 span2.end = char_len;
 
+                let mut x = pos_list[iter_state.chars_count].x;
+                let mut y = pos_list[iter_state.chars_count].y;
+                if iter_state.line_offset != 0.0 {
+                    // Explicit coordinates still take precedence.
+                    let (line_x, line_y) = iter_state.line_start;
+                    match iter_state.writing_mode {
+                        WritingMode::LeftToRight => {
+                            x = x.or(Some(line_x));
+                            y = y.or(Some(line_y + iter_state.line_offset));
+                        }
+                        WritingMode::TopToBottom => {
+                            x = x.or(Some(line_x - iter_state.line_offset));
+                            y = y.or(Some(line_y));
+                        }
+                    }
+
+                    iter_state.line_offset = 0.0;
+                }
+
+                if let TextFlow::Linear = iter_state.text_flow {
+                    let (line_x, line_y) = iter_state.line_start;
+                    iter_state.line_start = (x.unwrap_or(line_x), y.unwrap_or(line_y));
+                }
+
                 iter_state.chunks.push(TextChunk {
-                    x: pos_list[iter_state.chars_count].x,
-                    y: pos_list[iter_state.chars_count].y,
+                    x,
+                    y,
                     anchor,
                     spans: vec![span2],
                     text_flow: iter_state.text_flow.clone(),
@@ -356,9 +455,23 @@ fn collect_text_chunks_impl(
     }
 }
 
+/// Resolves a `shape-inside` region in the text coordinates.
+///
+/// Only references to shapes are supported. CSS basic shapes are not.
+fn resolve_shape_inside(text_node: SvgNode, state: &converter::State) -> Option<text::ShapeInside> {
+    let linked_node = text_node.attribute::<SvgNode>(AId::ShapeInside)?;
+    let (path, _) = super::shapes::convert(linked_node, state)?;
+
+    let transform = linked_node.resolve_transform(AId::Transform, state);
+    let path = path.as_ref().clone().transform(transform)?;
+
+    let padding = text_node.convert_user_length(AId::ShapePadding, state, Length::zero());
+    text::ShapeInside::new(&path, padding)
+}
+
 fn resolve_text_flow(node: SvgNode, state: &converter::State) -> Option<TextFlow> {
     let linked_node = node.attribute::<SvgNode>(AId::Href)?;
-    let path = super::shapes::convert(linked_node, state)?;
+    let (path, _) = super::shapes::convert(linked_node, state)?;
 
     // The reference path's transform needs to be applied
     let transform = linked_node.resolve_transform(AId::Transform, state);
@@ -403,11 +516,14 @@ fn convert_font(node: SvgNode, state: &converter::State) -> Font {
     let mut families = parse_font_families(font_families)
         .ok()
         .log_none(|| {
-            log::warn!(
-                "Failed to parse {} value: '{}'. Falling back to {}.",
-                AId::FontFamily,
-                font_families,
-                state.opt.font_family
+            node.warn(
+                AId::FontFamily.to_str(),
+                &format!(
+                    "Failed to parse {} value: '{}'. Falling back to {}.",
+                    AId::FontFamily,
+                    font_families,
+                    state.opt.font_family
+                ),
             )
         })
         .unwrap_or_default();
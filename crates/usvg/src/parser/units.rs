@@ -5,7 +5,7 @@ use svgtypes::{Length, LengthUnit as Unit};
 
 use super::converter;
 use super::svgtree::{AId, SvgNode};
-use crate::Units;
+use crate::{StyleSource, Units};
 
 #[inline(never)]
 pub(crate) fn convert_length(
@@ -97,6 +97,16 @@ pub(crate) fn resolve_font_size(node: SvgNode, state: &converter::State) -> f32
     let mut font_size = state.opt.font_size;
     for n in nodes.iter().rev().skip(1) {
         // skip Root
+        let is_inherited = n
+            .attributes()
+            .iter()
+            .any(|a| a.name == AId::FontSize && a.source == StyleSource::Inherited);
+        if is_inherited {
+            // An `inherit` value is already a copy of an ancestor's one,
+            // so resolving it again would apply relative units twice.
+            continue;
+        }
+
         if let Some(length) = n.try_attribute::<Length>(AId::FontSize) {
             let dpi = state.opt.dpi;
             let n = length.number as f32;
@@ -116,14 +126,14 @@ pub(crate) fn resolve_font_size(node: SvgNode, state: &converter::State) -> f32
                 }
             }
         } else if let Some(name) = n.attribute(AId::FontSize) {
-            font_size = convert_named_font_size(name, font_size);
+            font_size = convert_named_font_size(*n, name, font_size);
         }
     }
 
     font_size
 }
 
-fn convert_named_font_size(name: &str, parent_font_size: f32) -> f32 {
+fn convert_named_font_size(node: SvgNode, name: &str, parent_font_size: f32) -> f32 {
     let factor = match name {
         "xx-small" => -3,
         "x-small" => -2,
@@ -135,7 +145,10 @@ fn convert_named_font_size(name: &str, parent_font_size: f32) -> f32 {
         "smaller" => -1,
         "larger" => 1,
         _ => {
-            log::warn!("Invalid 'font-size' value: '{}'.", name);
+            node.warn(
+                "font-size",
+                &format!("Invalid 'font-size' value: '{}'.", name),
+            );
             0
         }
     };
@@ -52,6 +52,13 @@ pub(crate) fn convert(
 
     let linked_to_symbol = child.tag_name() == Some(EId::Symbol);
 
+    let use_href = if state.opt.keep_use_references {
+        node.attribute::<SvgNode>(AId::Href)
+            .map(|link| link.element_id().to_string())
+    } else {
+        None
+    };
+
     if linked_to_symbol {
         // If a `use` element has a width/height attribute and references a symbol
         // then relative units (like percentages) should be resolved relative
@@ -90,12 +97,13 @@ pub(crate) fn convert(
             // Make group for `use`.
             if let Some(mut g2) =
                 converter::convert_group(node, &use_state, true, cache, &mut g, &|cache, g2| {
-                    convert_children(child, new_ts, &use_state, cache, false, g2);
+                    convert_children(child, new_ts, &use_state, cache, false, None, g2);
                 })
             {
                 // We must reset transform, because it was already set
                 // to the group with clip-path.
                 g.is_context_element = true;
+                g.use_href = use_href;
                 g2.id = String::new(); // Prevent ID duplication.
                 g2.transform = Transform::default();
                 g.children.push(Node::Group(Box::new(g2)));
@@ -115,12 +123,14 @@ pub(crate) fn convert(
 
     if linked_to_symbol {
         // Make group for `use`.
+        let force = use_href.is_some();
         if let Some(mut g) =
-            converter::convert_group(node, &use_state, false, cache, parent, &|cache, g| {
-                convert_children(child, orig_ts, &use_state, cache, false, g);
+            converter::convert_group(node, &use_state, force, cache, parent, &|cache, g| {
+                convert_children(child, orig_ts, &use_state, cache, false, None, g);
             })
         {
             g.is_context_element = true;
+            g.use_href = use_href;
             g.transform = Transform::default();
             parent.children.push(Node::Group(Box::new(g)));
         }
@@ -153,9 +163,9 @@ pub(crate) fn convert(
                 use_state.use_size.1 = Some(node.convert_user_length(AId::Height, &use_state, def));
             }
 
-            convert_children(node, orig_ts, &use_state, cache, true, parent);
+            convert_children(node, orig_ts, &use_state, cache, true, use_href, parent);
         } else {
-            convert_children(node, orig_ts, &use_state, cache, true, parent);
+            convert_children(node, orig_ts, &use_state, cache, true, use_href, parent);
         }
     }
 }
@@ -201,12 +211,12 @@ pub(crate) fn convert_svg(
     if let Some(clip_rect) = get_clip_rect(node, node, state) {
         let mut g = clip_element(node, clip_rect, orig_ts, state, cache);
         g.abs_transform = parent.abs_transform;
-        convert_children(node, new_ts, &new_state, cache, false, &mut g);
+        convert_children(node, new_ts, &new_state, cache, false, None, &mut g);
         g.calculate_bounding_boxes();
         parent.children.push(Node::Group(Box::new(g)));
     } else {
         orig_ts = orig_ts.pre_concat(new_ts);
-        convert_children(node, orig_ts, &new_state, cache, false, parent);
+        convert_children(node, orig_ts, &new_state, cache, false, None, parent);
     }
 }
 
@@ -267,13 +277,14 @@ fn convert_children(
     state: &converter::State,
     cache: &mut converter::Cache,
     is_context_element: bool,
+    use_href: Option<String>,
     parent: &mut Group,
 ) {
     // Temporarily adjust absolute transform so `convert_group` would account for `transform`.
     let old_abs_transform = parent.abs_transform;
     parent.abs_transform = parent.abs_transform.pre_concat(transform);
 
-    let required = !transform.is_identity();
+    let required = !transform.is_identity() || use_href.is_some();
     if let Some(mut g) =
         converter::convert_group(node, state, required, cache, parent, &|cache, g| {
             if state.parent_clip_path.is_some() {
@@ -284,6 +295,7 @@ fn convert_children(
         })
     {
         g.is_context_element = is_context_element;
+        g.use_href = use_href;
         g.transform = transform;
         parent.children.push(Node::Group(Box::new(g)));
     }
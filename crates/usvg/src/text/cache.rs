@@ -0,0 +1,85 @@
+// Copyright 2024 the Resvg Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use fontdb::{Database, ID};
+use rustybuzz::ttf_parser::GlyphId;
+
+use super::flatten::DatabaseExt;
+
+/// A glyph outlines cache that can be shared between trees.
+///
+/// Text to path conversion outlines each glyph from the font data.
+/// When many documents use the same small set of fonts, this work is repeated
+/// for every document. A cache passed via [`Options::glyph_cache`](crate::Options::glyph_cache)
+/// stores glyph outlines, keyed by a font ID and a glyph ID, and reuses them across trees.
+///
+/// IDs are only valid within a single font database, so a cache must be used only
+/// with the same [`Options::fontdb`](crate::Options::fontdb).
+/// Fonts loaded by a custom [`FontResolver`](crate::FontResolver) are not cached.
+///
+/// The cache is thread-safe and can be shared between threads.
+/// It is never evicted, so its size is limited by the number of unique glyphs in use.
+#[derive(Debug, Default)]
+pub struct GlyphCache {
+    outlines: Mutex<HashMap<(ID, GlyphId), Option<tiny_skia_path::Path>>>,
+}
+
+impl GlyphCache {
+    /// Creates a new, empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of cached glyphs.
+    pub fn len(&self) -> usize {
+        match self.outlines.lock() {
+            Ok(outlines) => outlines.len(),
+            Err(_) => 0,
+        }
+    }
+
+    /// Checks if the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes all cached glyphs.
+    pub fn clear(&self) {
+        if let Ok(mut outlines) = self.outlines.lock() {
+            outlines.clear();
+        }
+    }
+
+    /// Returns a glyph outline, loading it from `fontdb` when needed.
+    ///
+    /// `base_fontdb` is the user-provided database. Only its fonts are cached,
+    /// since fonts added by a resolver to a copy of it can reuse IDs between trees.
+    pub(crate) fn outline(
+        &self,
+        base_fontdb: &Database,
+        fontdb: &Database,
+        id: ID,
+        glyph_id: GlyphId,
+    ) -> Option<tiny_skia_path::Path> {
+        if base_fontdb.face(id).is_none() {
+            return fontdb.outline(id, glyph_id);
+        }
+
+        if let Ok(outlines) = self.outlines.lock() {
+            if let Some(outline) = outlines.get(&(id, glyph_id)) {
+                return outline.clone();
+            }
+        }
+
+        // Do not hold the lock while outlining.
+        let outline = fontdb.outline(id, glyph_id);
+        if let Ok(mut outlines) = self.outlines.lock() {
+            outlines.insert((id, glyph_id), outline.clone());
+        }
+
+        outline
+    }
+}
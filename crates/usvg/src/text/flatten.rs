@@ -30,27 +30,64 @@ fn push_outline_paths(
     let builder = mem::replace(builder, tiny_skia_path::PathBuilder::new());
 
     if let Some(path) = builder.finish().and_then(|p| {
-        Path::new(
-            String::new(),
-            span.visible,
-            span.fill.clone(),
-            span.stroke.clone(),
-            span.paint_order,
+        let style = PathStyle {
+            visible: span.visible,
+            fill: span.fill.clone(),
+            stroke: span.stroke.clone(),
+            paint_order: span.paint_order,
             rendering_mode,
-            Arc::new(p),
-            Transform::default(),
-        )
+            vector_effect: VectorEffect::default(),
+        };
+        Path::new(String::new(), style, Arc::new(p), Transform::default())
     }) {
         new_children.push(Node::Path(Box::new(path)));
     }
 }
 
-pub(crate) fn flatten(text: &mut Text, fontdb: &fontdb::Database) -> Option<(Group, NonZeroRect)> {
+/// Pushes all paths of a color glyph into the span builder.
+///
+/// Layers are merged into a single outline, so that overlapping ones
+/// are not painted twice with a semi-transparent span style.
+fn push_monochrome_paths(
+    node: &Node,
+    transform: Transform,
+    builder: &mut tiny_skia_path::PathBuilder,
+) {
+    match node {
+        Node::Group(ref group) => {
+            let transform = transform.pre_concat(group.transform);
+            for child in &group.children {
+                push_monochrome_paths(child, transform, builder);
+            }
+        }
+        Node::Path(ref path) => {
+            if let Some(data) = path.data.as_ref().clone().transform(transform) {
+                builder.push_path(&data);
+            }
+        }
+        Node::Image(_) | Node::Text(_) => {}
+    }
+}
+
+pub(crate) fn flatten(
+    text: &mut Text,
+    opt: &Options,
+    fontdb: &fontdb::Database,
+) -> Option<(Group, NonZeroRect)> {
+    let emoji_rendering = opt.emoji_rendering;
+    let outline = |id: ID, glyph_id: GlyphId| match opt.glyph_cache {
+        Some(cache) => cache.outline(&opt.fontdb, fontdb, id, glyph_id),
+        None => fontdb.outline(id, glyph_id),
+    };
+
     let mut new_children = vec![];
 
     let rendering_mode = resolve_rendering_mode(text);
 
+    let mut children_ranges = Vec::with_capacity(text.layouted.len());
     for span in &text.layouted {
+        let children_start = new_children.len();
+
         if let Some(path) = span.overline.as_ref() {
             let mut path = path.clone();
             path.rendering_mode = rendering_mode;
@@ -72,6 +109,32 @@ pub(crate) fn flatten(text: &mut Text, fontdb: &fontdb::Database) -> Option<(Gro
         let mut span_builder = tiny_skia_path::PathBuilder::new();
 
         for glyph in &span.positioned_glyphs {
+            // A color glyph that should be rendered using its outline.
+            if emoji_rendering == EmojiRendering::Monochrome
+                && fontdb.is_color(glyph.font, glyph.id)
+            {
+                if let Some(outline) = outline(glyph.font, glyph.id)
+                    .and_then(|p| p.transform(glyph.outline_transform()))
+                {
+                    span_builder.push_path(&outline);
+                    continue;
+                }
+
+                let vector = fontdb
+                    .colr(glyph.font, glyph.id)
+                    .map(|tree| (Node::Group(Box::new(tree.root)), glyph.colr_transform()))
+                    .or_else(|| {
+                        fontdb
+                            .svg(glyph.font, glyph.id)
+                            .map(|node| (node, glyph.svg_transform()))
+                    });
+
+                if let Some((node, transform)) = vector {
+                    push_monochrome_paths(&node, transform, &mut span_builder);
+                    continue;
+                }
+            }
+
             // A (best-effort conversion of a) COLR glyph.
             if let Some(tree) = fontdb.colr(glyph.font, glyph.id) {
                 let mut group = Group {
@@ -128,9 +191,8 @@ pub(crate) fn flatten(text: &mut Text, fontdb: &fontdb::Database) -> Option<(Gro
                 group.calculate_bounding_boxes();
 
                 new_children.push(Node::Group(Box::new(group)));
-            } else if let Some(outline) = fontdb
-                .outline(glyph.font, glyph.id)
-                .and_then(|p| p.transform(glyph.outline_transform()))
+            } else if let Some(outline) =
+                outline(glyph.font, glyph.id).and_then(|p| p.transform(glyph.outline_transform()))
             {
                 span_builder.push_path(&outline);
             }
@@ -143,6 +205,12 @@ pub(crate) fn flatten(text: &mut Text, fontdb: &fontdb::Database) -> Option<(Gro
             path.rendering_mode = rendering_mode;
             new_children.push(Node::Path(Box::new(path)));
         }
+
+        children_ranges.push(children_start..new_children.len());
+    }
+
+    for (span, range) in text.layouted.iter_mut().zip(children_ranges) {
+        span.flattened_children = range;
     }
 
     let mut group = Group {
@@ -190,6 +258,7 @@ pub(crate) trait DatabaseExt {
     fn raster(&self, id: ID, glyph_id: GlyphId) -> Option<BitmapImage>;
     fn svg(&self, id: ID, glyph_id: GlyphId) -> Option<Node>;
     fn colr(&self, id: ID, glyph_id: GlyphId) -> Option<Tree>;
+    fn is_color(&self, id: ID, glyph_id: GlyphId) -> bool;
 }
 
 pub(crate) struct BitmapImage {
@@ -320,4 +389,17 @@ impl DatabaseExt for Database {
             Tree::from_data(svg.end_document().as_bytes(), &Options::default()).ok()
         })?
     }
+
+    fn is_color(&self, id: ID, glyph_id: GlyphId) -> bool {
+        self.with_face_data(id, |data, face_index| -> Option<bool> {
+            let font = ttf_parser::Face::parse(data, face_index).ok()?;
+            Some(
+                font.is_color_glyph(glyph_id)
+                    || font.glyph_svg_image(glyph_id).is_some()
+                    || font.glyph_raster_image(glyph_id, u16::MAX).is_some(),
+            )
+        })
+        .flatten()
+        .unwrap_or(false)
+    }
 }
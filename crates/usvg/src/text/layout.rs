@@ -3,6 +3,7 @@
 
 use std::collections::HashMap;
 use std::num::NonZeroU16;
+use std::ops::Range;
 use std::sync::Arc;
 
 use fontdb::{Database, ID};
@@ -13,11 +14,12 @@ use strict_num::NonZeroPositiveF32;
 use tiny_skia_path::{NonZeroRect, Transform};
 use unicode_script::UnicodeScript;
 
-use crate::tree::{BBox, IsValidLength};
+use super::has_requested_family;
+use crate::tree::{BBox, IsValidLength, PathStyle};
 use crate::{
     AlignmentBaseline, ApproxZeroUlps, BaselineShift, DominantBaseline, Fill, FillRule, Font,
-    FontResolver, LengthAdjust, PaintOrder, Path, ShapeRendering, Stroke, Text, TextAnchor,
-    TextChunk, TextDecorationStyle, TextFlow, TextPath, TextSpan, WritingMode,
+    FontResolver, GlyphOrientationVertical, LengthAdjust, Options, PaintOrder, Path, Stroke, Text,
+    TextAnchor, TextChunk, TextDecorationStyle, TextFlow, TextPath, TextSpan, WritingMode,
 };
 
 /// A glyph that has already been positioned correctly.
@@ -160,6 +162,38 @@ pub struct Span {
     /// A line-through text decoration of the span.
     /// Needs to be rendered after all glyphs.
     pub line_through: Option<Path>,
+    /// An index of the source chunk in [`Text::chunks`].
+    pub chunk_index: usize,
+    /// An index of the source span in [`TextChunk::spans`](crate::TextChunk::spans).
+    ///
+    /// The span's byte range in the chunk text is available via
+    /// [`TextSpan::start`](crate::TextSpan::start) and [`TextSpan::end`](crate::TextSpan::end).
+    pub span_index: usize,
+    /// A range of [`Text::flattened`] children generated from this span,
+    /// including text decorations.
+    ///
+    /// Can be empty.
+    pub flattened_children: Range<usize>,
+}
+
+impl Span {
+    /// Returns fonts used by the span's glyphs, in the order of the first use.
+    ///
+    /// Usually, the first font is the one resolved for the span,
+    /// while the others are fallbacks for characters it doesn't support.
+    ///
+    /// The font file path or data, the face index and the PostScript name
+    /// can be retrieved via [`Database::face`] of the [tree's font database](crate::Tree::fontdb).
+    pub fn fonts(&self) -> Vec<ID> {
+        let mut fonts = Vec::new();
+        for glyph in &self.positioned_glyphs {
+            if !fonts.contains(&glyph.font) {
+                fonts.push(glyph.font);
+            }
+        }
+
+        fonts
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -189,29 +223,25 @@ impl GlyphCluster {
 
 pub(crate) fn layout_text(
     text_node: &Text,
-    resolver: &FontResolver,
+    opt: &Options,
     fontdb: &mut Arc<fontdb::Database>,
 ) -> Option<(Vec<Span>, NonZeroRect)> {
-    let mut fonts_cache: FontsCache = HashMap::new();
-
-    for chunk in &text_node.chunks {
-        for span in &chunk.spans {
-            if !fonts_cache.contains_key(&span.font) {
-                if let Some(font) =
-                    (resolver.select_font)(&span.font, fontdb).and_then(|id| fontdb.load_font(id))
-                {
-                    fonts_cache.insert(span.font.clone(), Arc::new(font));
-                }
-            }
-        }
-    }
+    let fonts_cache = load_fonts(text_node, opt, fontdb, true);
+
+    let strict_resolver;
+    let resolver = if opt.strict_fonts {
+        strict_resolver = FontResolver::strict(&opt.font_resolver);
+        &strict_resolver
+    } else {
+        &opt.font_resolver
+    };
 
     let mut spans = vec![];
     let mut char_offset = 0;
     let mut last_x = 0.0;
     let mut last_y = 0.0;
     let mut bbox = BBox::default();
-    for chunk in &text_node.chunks {
+    for (chunk_index, chunk) in text_node.chunks.iter().enumerate() {
         let (x, y) = match chunk.text_flow {
             TextFlow::Linear => (chunk.x.unwrap_or(last_x), chunk.y.unwrap_or(last_y)),
             TextFlow::Path(_) => (0.0, 0.0),
@@ -223,7 +253,7 @@ pub(crate) fn layout_text(
             continue;
         }
 
-        apply_writing_mode(text_node.writing_mode, &mut clusters);
+        apply_writing_mode(text_node.writing_mode, chunk, fontdb, &mut clusters);
         apply_letter_spacing(chunk, &mut clusters);
         apply_word_spacing(chunk, &mut clusters);
 
@@ -244,12 +274,31 @@ pub(crate) fn layout_text(
             }
         }
 
-        for span in &chunk.spans {
+        for (span_index, span) in chunk.spans.iter().enumerate() {
             let font = match fonts_cache.get(&span.font) {
                 Some(v) => v,
                 None => continue,
             };
 
+            if opt.strict_fonts {
+                let missing = clusters
+                    .iter()
+                    .filter(|c| span_contains(span, c.byte_idx))
+                    .find(|c| c.glyphs.iter().any(|g| g.id.0 == 0));
+                if let Some(cluster) = missing {
+                    log::warn!(
+                        "No font with a U+{:04X} character. Text span is omitted.",
+                        cluster.codepoint as u32
+                    );
+                    opt.report(
+                        &format!("U+{:04X}", cluster.codepoint as u32),
+                        &text_node.id,
+                        "no font with this character, text span is omitted in the strict fonts mode",
+                    );
+                    continue;
+                }
+            }
+
             let decoration_spans = collect_decoration_spans(span, &clusters);
 
             let mut span_ts = text_ts;
@@ -348,6 +397,10 @@ pub(crate) fn layout_text(
                     underline,
                     overline,
                     line_through,
+                    chunk_index,
+                    span_index,
+                    // Will be set during flattening.
+                    flattened_children: 0..0,
                 });
             }
         }
@@ -369,6 +422,60 @@ pub(crate) fn layout_text(
     Some((spans, bbox))
 }
 
+/// Loads fonts of all text spans.
+///
+/// Spans with fonts that cannot be loaded are not present in the cache.
+/// When `report` is set, such spans are reported in the strict fonts mode.
+pub(crate) fn load_fonts(
+    text_node: &Text,
+    opt: &Options,
+    fontdb: &mut Arc<fontdb::Database>,
+    report: bool,
+) -> FontsCache {
+    let resolver = &opt.font_resolver;
+    let mut fonts_cache: FontsCache = HashMap::new();
+    let mut missing_fonts = Vec::new();
+
+    for chunk in &text_node.chunks {
+        for span in &chunk.spans {
+            if fonts_cache.contains_key(&span.font) || missing_fonts.contains(&&span.font) {
+                continue;
+            }
+
+            // In the strict mode, fonts of other families, like the default serif one,
+            // are not allowed.
+            let font = (resolver.select_font)(&span.font, fontdb)
+                .filter(|id| !opt.strict_fonts || has_requested_family(&span.font, *id, fontdb))
+                .and_then(|id| fontdb.load_font(id));
+
+            match font {
+                Some(font) => {
+                    fonts_cache.insert(span.font.clone(), Arc::new(font));
+                }
+                None => {
+                    if opt.strict_fonts && report {
+                        let families = span.font.families.iter().map(|f| f.to_string());
+                        let families = families.collect::<Vec<_>>().join(", ");
+                        log::warn!(
+                            "No match for '{}' font-family. Text span is omitted.",
+                            families
+                        );
+                        opt.report(
+                            &families,
+                            &text_node.id,
+                            "no matching font, text span is omitted in the strict fonts mode",
+                        );
+                    }
+
+                    missing_fonts.push(&span.font);
+                }
+            }
+        }
+    }
+
+    fonts_cache
+}
+
 fn convert_span(
     span: &TextSpan,
     clusters: &[GlyphCluster],
@@ -476,13 +583,15 @@ pub(crate) fn convert_decoration(
     let mut path_data = builder.finish()?;
     path_data = path_data.transform(transform)?;
 
+    let style = PathStyle {
+        visible: span.visible,
+        fill: decoration.fill.take(),
+        stroke: decoration.stroke.take(),
+        ..PathStyle::default()
+    };
     Path::new(
         String::new(),
-        span.visible,
-        decoration.fill.take(),
-        decoration.stroke.take(),
-        PaintOrder::default(),
-        ShapeRendering::default(),
+        style,
         Arc::new(path_data),
         Transform::default(),
     )
@@ -944,6 +1053,7 @@ fn process_chunk(
                 &glyphs[range],
                 &chunk.text,
                 span.font_size.get(),
+                span.font_size_adjust,
             ));
         }
     }
@@ -951,6 +1061,27 @@ fn process_chunk(
     clusters
 }
 
+/// Returns byte offsets and advances of chunk clusters in the logical order.
+///
+/// Letter and word spacing are taken into account.
+pub(crate) fn measure_chunk(
+    chunk: &TextChunk,
+    fonts_cache: &FontsCache,
+    resolver: &FontResolver,
+    fontdb: &mut Arc<fontdb::Database>,
+) -> Vec<(usize, f32)> {
+    let mut clusters = process_chunk(chunk, fonts_cache, resolver, fontdb);
+    apply_letter_spacing(chunk, &mut clusters);
+    apply_word_spacing(chunk, &mut clusters);
+
+    let mut advances: Vec<_> = clusters
+        .iter()
+        .map(|c| (c.byte_idx.value(), c.advance))
+        .collect();
+    advances.sort_by_key(|(idx, _)| *idx);
+    advances
+}
+
 fn apply_length_adjust(chunk: &TextChunk, clusters: &mut [GlyphCluster]) {
     let is_horizontal = matches!(chunk.text_flow, TextFlow::Linear);
 
@@ -1012,16 +1143,45 @@ fn apply_length_adjust(chunk: &TextChunk, clusters: &mut [GlyphCluster]) {
 }
 
 /// Rotates clusters according to
-/// [Unicode Vertical_Orientation Property](https://www.unicode.org/reports/tr50/tr50-19.html).
-fn apply_writing_mode(writing_mode: WritingMode, clusters: &mut [GlyphCluster]) {
+/// [Unicode Vertical_Orientation Property](https://www.unicode.org/reports/tr50/tr50-19.html)
+/// or `glyph-orientation-vertical`, when set.
+fn apply_writing_mode(
+    writing_mode: WritingMode,
+    chunk: &TextChunk,
+    fontdb: &fontdb::Database,
+    clusters: &mut [GlyphCluster],
+) {
     if writing_mode != WritingMode::TopToBottom {
         return;
     }
 
     for cluster in clusters {
-        let orientation = unicode_vo::char_orientation(cluster.codepoint);
-        if orientation == unicode_vo::Orientation::Upright {
-            let mut ts = Transform::default();
+        let orientation = chunk_span_at(chunk, cluster.byte_idx)
+            .map(|span| span.glyph_orientation_vertical)
+            .unwrap_or_default();
+        let is_upright = match orientation {
+            GlyphOrientationVertical::Auto => {
+                unicode_vo::char_orientation(cluster.codepoint) == unicode_vo::Orientation::Upright
+            }
+            GlyphOrientationVertical::Upright => true,
+            GlyphOrientationVertical::Rotated => false,
+        };
+
+        if is_upright {
+            // The Unicode property marks as upright only characters that fit a square,
+            // like CJK ones. Characters forced to be upright, like Latin ones,
+            // are usually narrower, so we use the vertical advance instead.
+            let forced_advance = if orientation == GlyphOrientationVertical::Upright {
+                Some(vertical_advance(cluster, fontdb)).filter(|advance| *advance > cluster.width)
+            } else {
+                None
+            };
+
+            // Center the glyph along the new advance.
+            let mut ts = Transform::from_translate(
+                forced_advance.map_or(0.0, |advance| (advance - cluster.width) / 2.0),
+                0.0,
+            );
             // Position glyph in the center of vertical axis.
             ts = ts.pre_translate(0.0, (cluster.ascent + cluster.descent) / 2.0);
             // Rotate by 90 degrees in the center.
@@ -1036,6 +1196,11 @@ fn apply_writing_mode(writing_mode: WritingMode, clusters: &mut [GlyphCluster])
             // Move "baseline" to the middle and make height equal to width.
             cluster.ascent = cluster.width / 2.0;
             cluster.descent = -cluster.width / 2.0;
+
+            if let Some(advance) = forced_advance {
+                cluster.width = advance;
+                cluster.advance = advance;
+            }
         } else {
             // Could not find a spec that explains this,
             // but this is how other applications are shifting the "rotated" characters
@@ -1047,6 +1212,21 @@ fn apply_writing_mode(writing_mode: WritingMode, clusters: &mut [GlyphCluster])
     }
 }
 
+/// Returns the vertical advance of the cluster's first glyph.
+///
+/// Falls back to 1em when the font has no vertical metrics.
+fn vertical_advance(cluster: &GlyphCluster, fontdb: &fontdb::Database) -> f32 {
+    let Some(glyph) = cluster.glyphs.first() else {
+        return 0.0;
+    };
+
+    let scale = glyph.font_size / glyph.units_per_em as f32;
+    match fontdb.glyph_ver_advance(glyph.font, glyph.id) {
+        Some(advance) => advance as f32 * scale,
+        None => glyph.font_size,
+    }
+}
+
 /// Applies the `letter-spacing` property to a text chunk clusters.
 ///
 /// [In the CSS spec](https://www.w3.org/TR/css-text-3/#letter-spacing-property).
@@ -1114,7 +1294,12 @@ fn apply_word_spacing(chunk: &TextChunk, clusters: &mut [GlyphCluster]) {
     }
 }
 
-fn form_glyph_clusters(glyphs: &[Glyph], text: &str, font_size: f32) -> GlyphCluster {
+fn form_glyph_clusters(
+    glyphs: &[Glyph],
+    text: &str,
+    font_size: f32,
+    font_size_adjust: Option<f32>,
+) -> GlyphCluster {
     debug_assert!(!glyphs.is_empty());
 
     let mut width = 0.0;
@@ -1123,6 +1308,7 @@ fn form_glyph_clusters(glyphs: &[Glyph], text: &str, font_size: f32) -> GlyphClu
     let mut positioned_glyphs = vec![];
 
     for glyph in glyphs {
+        let font_size = glyph.font.adjusted_font_size(font_size, font_size_adjust);
         let sx = glyph.font.scale(font_size);
 
         // Apply offset.
@@ -1156,6 +1342,7 @@ fn form_glyph_clusters(glyphs: &[Glyph], text: &str, font_size: f32) -> GlyphClu
 
     let byte_idx = glyphs[0].byte_idx;
     let font = glyphs[0].font.clone();
+    let font_size = font.adjusted_font_size(font_size, font_size_adjust);
     GlyphCluster {
         byte_idx,
         codepoint: byte_idx.char_from(text),
@@ -1174,6 +1361,7 @@ fn form_glyph_clusters(glyphs: &[Glyph], text: &str, font_size: f32) -> GlyphClu
 pub(crate) trait DatabaseExt {
     fn load_font(&self, id: ID) -> Option<ResolvedFont>;
     fn has_char(&self, id: ID, c: char) -> bool;
+    fn glyph_ver_advance(&self, id: ID, glyph_id: GlyphId) -> Option<u16>;
 }
 
 impl DatabaseExt for Database {
@@ -1186,6 +1374,7 @@ impl DatabaseExt for Database {
 
             let ascent = font.ascender();
             let descent = font.descender();
+            let line_gap = font.line_gap();
 
             let x_height = font
                 .x_height()
@@ -1225,8 +1414,8 @@ impl DatabaseExt for Database {
             };
 
             // 0.2 and 0.4 are generic offsets used by some applications (Inkscape/librsvg).
-            let mut subscript_offset = (units_per_em.get() as f32 / 0.2).round() as i16;
-            let mut superscript_offset = (units_per_em.get() as f32 / 0.4).round() as i16;
+            let mut subscript_offset = (units_per_em.get() as f32 * 0.2).round() as i16;
+            let mut superscript_offset = (units_per_em.get() as f32 * 0.4).round() as i16;
             if let Some(metrics) = font.subscript_metrics() {
                 subscript_offset = metrics.y_offset;
             }
@@ -1240,6 +1429,7 @@ impl DatabaseExt for Database {
                 units_per_em,
                 ascent,
                 descent,
+                line_gap,
                 x_height,
                 underline_position,
                 underline_thickness,
@@ -1260,6 +1450,14 @@ impl DatabaseExt for Database {
 
         res == Some(Some(true))
     }
+
+    #[inline(never)]
+    fn glyph_ver_advance(&self, id: ID, glyph_id: GlyphId) -> Option<u16> {
+        self.with_face_data(id, |font_data, face_index| -> Option<u16> {
+            let font = ttf_parser::Face::parse(font_data, face_index).ok()?;
+            font.glyph_ver_advance(glyph_id)
+        })?
+    }
 }
 
 /// Text shaping with font fallback.
@@ -1312,20 +1510,9 @@ pub(crate) fn shape_text(
                 break 'outer;
             }
 
-            // We assume, that shaping with an any font will produce the same amount of glyphs.
-            // This is incorrect, but good enough for now.
-            if glyphs.len() != fallback_glyphs.len() {
-                break 'outer;
-            }
-
-            // TODO: Replace clusters and not glyphs. This should be more accurate.
-
-            // Copy new glyphs.
-            for i in 0..glyphs.len() {
-                if glyphs[i].is_missing() && !fallback_glyphs[i].is_missing() {
-                    glyphs[i] = fallback_glyphs[i].clone();
-                }
-            }
+            // Replace whole clusters and not individual glyphs,
+            // so combining marks are positioned using the same font as their base character.
+            glyphs = replace_missing_clusters(&glyphs, &fallback_glyphs);
 
             // Remember this font.
             used_fonts.push(fallback_font.id);
@@ -1350,6 +1537,42 @@ pub(crate) fn shape_text(
     glyphs
 }
 
+/// Replaces clusters with missing glyphs by the same clusters from `fallback_glyphs`.
+///
+/// A cluster is replaced only when the fallback one covers the same text
+/// and has no missing glyphs. The number of glyphs in a cluster can change.
+fn replace_missing_clusters(glyphs: &[Glyph], fallback_glyphs: &[Glyph]) -> Vec<Glyph> {
+    let cluster_text_len =
+        |cluster: &[Glyph]| -> usize { cluster.iter().map(|g| g.text.len()).sum() };
+
+    // Glyphs are in the visual order, so clusters are matched by their byte index.
+    let mut fallback_clusters = HashMap::new();
+    for (range, byte_idx) in GlyphClusters::new(fallback_glyphs) {
+        fallback_clusters.entry(byte_idx).or_insert(range);
+    }
+
+    let mut new_glyphs = Vec::with_capacity(glyphs.len());
+    for (range, byte_idx) in GlyphClusters::new(glyphs) {
+        let cluster = &glyphs[range];
+        if !cluster.iter().any(|g| g.is_missing()) {
+            new_glyphs.extend_from_slice(cluster);
+            continue;
+        }
+
+        let fallback_cluster = fallback_clusters
+            .get(&byte_idx)
+            .map(|range| &fallback_glyphs[range.clone()])
+            .filter(|fallback_cluster| {
+                cluster_text_len(fallback_cluster) == cluster_text_len(cluster)
+                    && !fallback_cluster.iter().any(|g| g.is_missing())
+            });
+
+        new_glyphs.extend_from_slice(fallback_cluster.unwrap_or(cluster));
+    }
+
+    new_glyphs
+}
+
 /// Converts a text into a list of glyph IDs.
 ///
 /// This function will do the BIDI reordering and text shaping.
@@ -1548,6 +1771,7 @@ pub(crate) struct ResolvedFont {
     // All values below are in font units.
     ascent: i16,
     descent: i16,
+    line_gap: i16,
     x_height: NonZeroU16,
 
     underline_position: i16,
@@ -1589,6 +1813,16 @@ impl ResolvedFont {
         font_size / self.units_per_em.get() as f32
     }
 
+    /// Returns a font size with which the font's x-height to font size ratio
+    /// matches `font-size-adjust`.
+    #[inline]
+    pub(crate) fn adjusted_font_size(&self, font_size: f32, font_size_adjust: Option<f32>) -> f32 {
+        match font_size_adjust {
+            Some(adjust) => font_size * adjust / self.x_height(1.0),
+            None => font_size,
+        }
+    }
+
     #[inline]
     pub(crate) fn ascent(&self, font_size: f32) -> f32 {
         self.ascent as f32 * self.scale(font_size)
@@ -1604,6 +1838,11 @@ impl ResolvedFont {
         self.ascent(font_size) - self.descent(font_size)
     }
 
+    #[inline]
+    pub(crate) fn line_gap(&self, font_size: f32) -> f32 {
+        self.line_gap as f32 * self.scale(font_size)
+    }
+
     #[inline]
     pub(crate) fn x_height(&self, font_size: f32) -> f32 {
         self.x_height.get() as f32 * self.scale(font_size)
@@ -1707,7 +1946,7 @@ pub(crate) type FontsCache = HashMap<Font, Arc<ResolvedFont>>;
 /// A read-only text index in bytes.
 ///
 /// Guarantee to be on a char boundary and in text bounds.
-#[derive(Clone, Copy, PartialEq, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub(crate) struct ByteIndex(usize);
 
 impl ByteIndex {
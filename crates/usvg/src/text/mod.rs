@@ -6,10 +6,14 @@ use std::sync::Arc;
 use fontdb::{Database, ID};
 use svgtypes::FontFamily;
 
+pub use self::cache::GlyphCache;
 use self::layout::DatabaseExt;
-use crate::{Font, FontStretch, FontStyle, Text};
+pub(crate) use self::wrap::{wrap_in_shape, ShapeInside};
+use crate::{Font, FontStretch, FontStyle, Options, Text};
 
+mod cache;
 mod flatten;
+mod wrap;
 
 mod colr;
 /// Provides access to the layout of a text node.
@@ -86,14 +90,7 @@ impl FontResolver<'_> {
         Box::new(move |font, fontdb| {
             let mut name_list = Vec::new();
             for family in &font.families {
-                name_list.push(match family {
-                    FontFamily::Serif => fontdb::Family::Serif,
-                    FontFamily::SansSerif => fontdb::Family::SansSerif,
-                    FontFamily::Cursive => fontdb::Family::Cursive,
-                    FontFamily::Fantasy => fontdb::Family::Fantasy,
-                    FontFamily::Monospace => fontdb::Family::Monospace,
-                    FontFamily::Named(s) => fontdb::Family::Name(s),
-                });
+                name_list.push(to_fontdb_family(family));
             }
 
             // Use the default font as fallback.
@@ -187,6 +184,82 @@ impl FontResolver<'_> {
             None
         })
     }
+
+    /// Creates a font fallback selection resolver with an ordered families list.
+    ///
+    /// Font families are tried in the specified order, using a face with a style closest
+    /// to the first already used font. When none of them support the character,
+    /// falls back to the [default one](FontResolver::default_fallback_selector).
+    ///
+    /// Gives a control over which font is used for which script, like
+    /// `["Noto Sans CJK JP", "Noto Color Emoji", "Noto Sans Arabic"]`.
+    /// And since only the listed families are checked first, it is also faster
+    /// than a search through the entire `fontdb`.
+    pub fn families_fallback_selector(families: Vec<String>) -> FallbackSelectionFn<'static> {
+        let default_selector = FontResolver::default_fallback_selector();
+        Box::new(move |c, exclude_fonts, fontdb| {
+            let base_face = fontdb.face(exclude_fonts[0])?;
+            let (weight, stretch, style) = (base_face.weight, base_face.stretch, base_face.style);
+
+            for family in &families {
+                let query = fontdb::Query {
+                    families: &[fontdb::Family::Name(family)],
+                    weight,
+                    stretch,
+                    style,
+                };
+
+                let Some(id) = fontdb.query(&query) else {
+                    continue;
+                };
+
+                if !exclude_fonts.contains(&id) && fontdb.has_char(id, c) {
+                    return Some(id);
+                }
+            }
+
+            default_selector(c, exclude_fonts, fontdb)
+        })
+    }
+
+    /// Creates a resolver used in the strict fonts mode.
+    ///
+    /// Fonts are still selected by `resolver`, but characters are not resolved
+    /// using fallback fonts.
+    pub(crate) fn strict<'b>(resolver: &'b FontResolver) -> FontResolver<'b> {
+        FontResolver {
+            select_font: Box::new(move |font, fontdb| (resolver.select_font)(font, fontdb)),
+            select_fallback: Box::new(|_, _, _| None),
+        }
+    }
+}
+
+fn to_fontdb_family(family: &FontFamily) -> fontdb::Family<'_> {
+    match family {
+        FontFamily::Serif => fontdb::Family::Serif,
+        FontFamily::SansSerif => fontdb::Family::SansSerif,
+        FontFamily::Cursive => fontdb::Family::Cursive,
+        FontFamily::Fantasy => fontdb::Family::Fantasy,
+        FontFamily::Monospace => fontdb::Family::Monospace,
+        FontFamily::Named(s) => fontdb::Family::Name(s),
+    }
+}
+
+/// Checks that the selected font has one of the requested families.
+///
+/// Generic families are resolved using the `fontdb` settings.
+pub(crate) fn has_requested_family(font: &Font, id: ID, fontdb: &Database) -> bool {
+    let Some(face) = fontdb.face(id) else {
+        return false;
+    };
+
+    font.families.iter().any(|family| {
+        let family = to_fontdb_family(family);
+        let name = fontdb.family_name(&family);
+        face.families
+            .iter()
+            .any(|(face_name, _)| face_name.eq_ignore_ascii_case(name))
+    })
 }
 
 impl std::fmt::Debug for FontResolver<'_> {
@@ -195,6 +268,22 @@ impl std::fmt::Debug for FontResolver<'_> {
     }
 }
 
+/// An emoji rendering method.
+///
+/// Applies to all color glyphs and not only emoji.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum EmojiRendering {
+    /// Color glyphs are rendered in full color.
+    #[default]
+    Color,
+    /// Color glyphs are rendered using the text fill and stroke, like regular ones.
+    ///
+    /// A glyph's outline is used when a font has one.
+    /// Otherwise, all layers of COLR and SVG glyphs are used.
+    /// Bitmap glyphs without an outline are still rendered in color.
+    Monochrome,
+}
+
 /// Convert a text into its paths. This is done in two steps:
 /// 1. We convert the text into glyphs and position them according to the rules specified
 ///    in the SVG specification. While doing so, we also calculate the text bbox (which
@@ -203,18 +292,93 @@ impl std::fmt::Debug for FontResolver<'_> {
 /// 2. We convert all of the positioned glyphs into outlines.
 pub(crate) fn convert(
     text: &mut Text,
-    resolver: &FontResolver,
+    opt: &Options,
     fontdb: &mut Arc<fontdb::Database>,
 ) -> Option<()> {
-    let (text_fragments, bbox) = layout::layout_text(text, resolver, fontdb)?;
+    let (text_fragments, bbox) = layout::layout_text(text, opt, fontdb)?;
     text.layouted = text_fragments;
     text.bounding_box = bbox.to_rect();
     text.abs_bounding_box = bbox.transform(text.abs_transform)?.to_rect();
 
-    let (group, stroke_bbox) = flatten::flatten(text, fontdb)?;
+    let (group, stroke_bbox) = flatten::flatten(text, opt, fontdb)?;
     text.flattened = Box::new(group);
     text.stroke_bounding_box = stroke_bbox.to_rect();
     text.abs_stroke_bounding_box = stroke_bbox.transform(text.abs_transform)?.to_rect();
 
     Some(())
 }
+
+/// Font metrics of a text line.
+///
+/// All values are in user units, i.e. already scaled by the font size.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct LineMetrics {
+    /// The distance from the baseline to the top of the line.
+    pub ascent: f32,
+    /// The distance from the baseline to the bottom of the line.
+    ///
+    /// Usually negative.
+    pub descent: f32,
+    /// An additional spacing that should be added between lines.
+    pub line_gap: f32,
+    /// The height of lowercase letters.
+    pub x_height: f32,
+}
+
+impl LineMetrics {
+    /// Returns the line height, i.e. `ascent - descent + line_gap`.
+    pub fn height(&self) -> f32 {
+        self.ascent - self.descent + self.line_gap
+    }
+}
+
+impl Text {
+    /// Returns the line metrics of the text.
+    ///
+    /// Uses the same fonts that were resolved during layout, including fallback ones.
+    /// When spans use different fonts or font sizes, the maximum ascent, line gap and x-height
+    /// and the minimum descent are returned.
+    ///
+    /// `fontdb` must be the database the text was laid out with,
+    /// i.e. [`Tree::fontdb`](crate::Tree::fontdb).
+    ///
+    /// Returns `None` when the text has no glyphs or fonts cannot be loaded.
+    pub fn line_metrics(&self, fontdb: &fontdb::Database) -> Option<LineMetrics> {
+        let mut metrics: Option<LineMetrics> = None;
+        let mut seen: Vec<(ID, f32)> = Vec::new();
+        for span in &self.layouted {
+            let font_size = span.font_size.get();
+            for glyph in &span.positioned_glyphs {
+                if seen.contains(&(glyph.font, font_size)) {
+                    continue;
+                }
+
+                seen.push((glyph.font, font_size));
+
+                let font = match fontdb.load_font(glyph.font) {
+                    Some(v) => v,
+                    None => continue,
+                };
+
+                let new = LineMetrics {
+                    ascent: font.ascent(font_size),
+                    descent: font.descent(font_size),
+                    line_gap: font.line_gap(font_size),
+                    x_height: font.x_height(font_size),
+                };
+
+                metrics = Some(match metrics {
+                    Some(m) => LineMetrics {
+                        ascent: m.ascent.max(new.ascent),
+                        descent: m.descent.min(new.descent),
+                        line_gap: m.line_gap.max(new.line_gap),
+                        x_height: m.x_height.max(new.x_height),
+                    },
+                    None => new,
+                });
+            }
+        }
+
+        metrics
+    }
+}
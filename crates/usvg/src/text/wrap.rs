@@ -0,0 +1,421 @@
+// Copyright 2024 the Resvg Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::ops::Range;
+use std::sync::Arc;
+
+use tiny_skia_path::PathSegment;
+
+use super::layout::{load_fonts, measure_chunk, FontsCache};
+use super::FontResolver;
+use crate::{Options, Text, TextAnchor, TextChunk, TextFlow, WritingMode};
+
+/// A `shape-inside` region.
+///
+/// The shape outline is flattened into line segments in the text coordinates.
+#[derive(Clone, Debug)]
+pub(crate) struct ShapeInside {
+    edges: Vec<(tiny_skia_path::Point, tiny_skia_path::Point)>,
+    top: f32,
+    bottom: f32,
+    padding: f32,
+}
+
+impl ShapeInside {
+    /// Creates a new region from a shape outline.
+    ///
+    /// `padding` is the `shape-padding` value.
+    pub(crate) fn new(path: &tiny_skia_path::Path, padding: f32) -> Option<Self> {
+        let mut bez_path = kurbo::BezPath::new();
+        for segment in path.segments() {
+            match segment {
+                PathSegment::MoveTo(p) => bez_path.move_to((p.x as f64, p.y as f64)),
+                PathSegment::LineTo(p) => bez_path.line_to((p.x as f64, p.y as f64)),
+                PathSegment::QuadTo(p1, p) => {
+                    bez_path.quad_to((p1.x as f64, p1.y as f64), (p.x as f64, p.y as f64))
+                }
+                PathSegment::CubicTo(p1, p2, p) => bez_path.curve_to(
+                    (p1.x as f64, p1.y as f64),
+                    (p2.x as f64, p2.y as f64),
+                    (p.x as f64, p.y as f64),
+                ),
+                PathSegment::Close => bez_path.close_path(),
+            }
+        }
+
+        // All subpaths are implicitly closed, like during filling.
+        let mut edges = Vec::new();
+        let mut start = None;
+        let mut prev = None;
+        kurbo::flatten(bez_path, 0.1, |el| {
+            let p = match el {
+                kurbo::PathEl::MoveTo(p) => {
+                    if let (Some(start), Some(prev)) = (start, prev) {
+                        edges.push((prev, start));
+                    }
+
+                    let p = tiny_skia_path::Point::from_xy(p.x as f32, p.y as f32);
+                    start = Some(p);
+                    prev = Some(p);
+                    return;
+                }
+                kurbo::PathEl::LineTo(p) => tiny_skia_path::Point::from_xy(p.x as f32, p.y as f32),
+                kurbo::PathEl::ClosePath => match start {
+                    Some(p) => p,
+                    None => return,
+                },
+                _ => return,
+            };
+
+            if let Some(prev) = prev {
+                edges.push((prev, p));
+            }
+            prev = Some(p);
+        });
+        if let (Some(start), Some(prev)) = (start, prev) {
+            edges.push((prev, start));
+        }
+
+        let bounds = path.bounds();
+        if edges.is_empty() || bounds.width() <= padding * 2.0 {
+            return None;
+        }
+
+        Some(ShapeInside {
+            edges,
+            top: bounds.top() + padding,
+            bottom: bounds.bottom() - padding,
+            padding,
+        })
+    }
+
+    /// Returns the widest line extent among lines of `height`, starting at `top` and below.
+    fn max_line_width(&self, mut top: f32, height: f32) -> f32 {
+        let mut width = 0.0f32;
+        while top + height <= self.bottom {
+            if let Some((left, right)) = self.line_extent(top, top + height) {
+                width = width.max(right - left);
+            }
+
+            top += height;
+        }
+
+        width
+    }
+
+    /// Returns the horizontal extent of a line between `top` and `bottom`.
+    ///
+    /// The extent is the same along the whole line height,
+    /// so the line is limited by the narrowest part of the shape.
+    fn line_extent(&self, top: f32, bottom: f32) -> Option<(f32, f32)> {
+        let top = top - self.padding;
+        let bottom = bottom + self.padding;
+
+        let mut left = f32::MIN;
+        let mut right = f32::MAX;
+        let vertices = self.edges.iter().map(|(p, _)| p.y);
+        for y in [top, bottom]
+            .into_iter()
+            .chain(vertices.filter(|y| *y > top && *y < bottom))
+        {
+            let (l, r) = self.extent_at(y)?;
+            left = left.max(l);
+            right = right.min(r);
+        }
+
+        left += self.padding;
+        right -= self.padding;
+        if right > left {
+            Some((left, right))
+        } else {
+            None
+        }
+    }
+
+    fn extent_at(&self, y: f32) -> Option<(f32, f32)> {
+        let mut extent: Option<(f32, f32)> = None;
+        for (p0, p1) in &self.edges {
+            if y < p0.y.min(p1.y) || y > p0.y.max(p1.y) {
+                continue;
+            }
+
+            let xs = if p0.y == p1.y {
+                (p0.x.min(p1.x), p0.x.max(p1.x))
+            } else {
+                let x = p0.x + (y - p0.y) / (p1.y - p0.y) * (p1.x - p0.x);
+                (x, x)
+            };
+
+            extent = Some(match extent {
+                Some((l, r)) => (l.min(xs.0), r.max(xs.1)),
+                None => xs,
+            });
+        }
+
+        extent
+    }
+}
+
+/// A word and the whitespace after it.
+struct Word {
+    /// Clusters range.
+    clusters: Range<usize>,
+    /// Clusters range including trailing whitespace.
+    clusters_with_space: Range<usize>,
+}
+
+/// Splits text chunks into lines that fit into a `shape-inside` region.
+///
+/// Each chunk starts a new line. Lines are broken at whitespace, which is removed,
+/// and words that do not fit into the shape at all are broken between clusters.
+/// Text that doesn't fit into the shape is removed.
+///
+/// Only horizontal text is supported.
+pub(crate) fn wrap_in_shape(
+    text: &mut Text,
+    shape: &ShapeInside,
+    opt: &Options,
+    fontdb: &mut Arc<fontdb::Database>,
+) {
+    if text.writing_mode != WritingMode::LeftToRight
+        || text
+            .chunks
+            .iter()
+            .any(|c| !matches!(c.text_flow, TextFlow::Linear))
+    {
+        log::warn!("'shape-inside' is supported only for horizontal text. Ignored.");
+        return;
+    }
+
+    let fonts_cache = load_fonts(text, opt, fontdb, false);
+    let strict_resolver;
+    let resolver = if opt.strict_fonts {
+        strict_resolver = FontResolver::strict(&opt.font_resolver);
+        &strict_resolver
+    } else {
+        &opt.font_resolver
+    };
+
+    let mut lines = Vec::new();
+    // Indices of removed characters.
+    let mut removed = Vec::new();
+    let mut char_offset = 0;
+    let mut top = shape.top;
+    let mut overflow = false;
+    for chunk in &text.chunks {
+        let chars_count = chunk.text.chars().count();
+        let char_at = |byte: usize| char_offset + chunk.text[..byte].chars().count();
+
+        let clusters = measure_chunk(chunk, &fonts_cache, resolver, fontdb);
+        let line_height = chunk_line_height(chunk, &fonts_cache);
+        let (Some((ascent, height)), false) = (line_height, overflow) else {
+            removed.extend(char_offset..char_offset + chars_count);
+            char_offset += chars_count;
+            continue;
+        };
+
+        // Byte range of a clusters range.
+        // Characters without clusters belong to the previous one.
+        let bytes = |range: &Range<usize>| {
+            let start = match range.start {
+                0 => 0,
+                i => clusters.get(i).map_or(chunk.text.len(), |c| c.0),
+            };
+            let end = clusters.get(range.end).map_or(chunk.text.len(), |c| c.0);
+            start..end
+        };
+        let width = |range: Range<usize>| -> f32 { clusters[range].iter().map(|c| c.1).sum() };
+
+        let words = split_words(&chunk.text, &clusters);
+        if words.is_empty() {
+            removed.extend(char_offset..char_offset + chars_count);
+            char_offset += chars_count;
+            continue;
+        }
+
+        let mut word_idx = 0;
+        // The first cluster of a word that was partially placed on the previous line.
+        let mut word_start = None;
+        while word_idx < words.len() {
+            if top + height > shape.bottom {
+                overflow = true;
+                break;
+            }
+
+            let Some((left, right)) = shape.line_extent(top, top + height) else {
+                top += height;
+                continue;
+            };
+
+            let line_start = word_start.unwrap_or(words[word_idx].clusters.start);
+            let mut line_width = 0.0;
+            let mut line_end = line_start;
+            let mut space_end = line_start;
+            let mut has_words = false;
+            while let Some(word) = words.get(word_idx) {
+                let start = word_start.unwrap_or(word.clusters.start);
+                let word_width = width(start..word.clusters.end);
+                if line_width + word_width > right - left {
+                    break;
+                }
+
+                line_width += width(start..word.clusters_with_space.end);
+                line_end = word.clusters.end;
+                space_end = word.clusters_with_space.end;
+                word_start = None;
+                word_idx += 1;
+                has_words = true;
+            }
+
+            if !has_words {
+                let word = &words[word_idx];
+                let word_width = width(line_start..word.clusters.end);
+                if word_width <= shape.max_line_width(top + height, height) {
+                    // The word fits into a wider line below.
+                    top += height;
+                    continue;
+                }
+
+                // Break a word that doesn't fit into any line below.
+                // At least one cluster is placed to make progress.
+                line_end = line_start + 1;
+                while line_end < word.clusters.end
+                    && width(line_start..line_end + 1) <= right - left
+                {
+                    line_end += 1;
+                }
+
+                space_end = line_end;
+                word_start = Some(line_end);
+                if line_end == word.clusters.end {
+                    space_end = word.clusters_with_space.end;
+                    word_start = None;
+                    word_idx += 1;
+                }
+            }
+
+            let x = match chunk.anchor {
+                TextAnchor::Start => left,
+                TextAnchor::Middle => (left + right) / 2.0,
+                TextAnchor::End => right,
+            };
+
+            let line_bytes = bytes(&(line_start..line_end));
+            let space_bytes = bytes(&(line_end..space_end));
+            removed.extend(char_at(space_bytes.start)..char_at(space_bytes.end));
+            if !line_bytes.is_empty() {
+                lines.push(sub_chunk(chunk, line_bytes, x, top + ascent));
+            }
+            top += height;
+        }
+
+        if overflow {
+            let start = word_start.or(words.get(word_idx).map(|w| w.clusters.start));
+            let start = start.map_or(chunk.text.len(), |i| bytes(&(i..i)).start);
+            removed.extend(char_at(start)..char_offset + chars_count);
+        }
+
+        char_offset += chars_count;
+    }
+
+    if !removed.is_empty() {
+        // Characters are processed in order, so `removed` is sorted.
+        let retain = |list: &mut Vec<f32>| {
+            let mut removed = removed.iter().peekable();
+            let mut i = 0;
+            list.retain(|_| {
+                let is_removed = removed.next_if_eq(&&i).is_some();
+                i += 1;
+                !is_removed
+            });
+        };
+
+        retain(&mut text.dx);
+        retain(&mut text.dy);
+        retain(&mut text.rotate);
+    }
+
+    text.chunks = lines;
+}
+
+/// Returns the ascent and the height of a chunk line.
+///
+/// When spans use different fonts or font sizes, the largest values are used.
+fn chunk_line_height(chunk: &TextChunk, fonts_cache: &FontsCache) -> Option<(f32, f32)> {
+    let mut ascent = None::<f32>;
+    let mut descent = 0.0f32;
+    let mut line_gap = 0.0f32;
+    for span in &chunk.spans {
+        let Some(font) = fonts_cache.get(&span.font) else {
+            continue;
+        };
+
+        let font_size = span.font_size.get();
+        ascent = Some(ascent.unwrap_or(f32::MIN).max(font.ascent(font_size)));
+        descent = descent.min(font.descent(font_size));
+        line_gap = line_gap.max(font.line_gap(font_size));
+    }
+
+    let ascent = ascent?;
+    let height = ascent - descent + line_gap;
+    if height > 0.0 {
+        Some((ascent, height))
+    } else {
+        None
+    }
+}
+
+/// Splits clusters into words, each followed by whitespace.
+fn split_words(text: &str, clusters: &[(usize, f32)]) -> Vec<Word> {
+    let is_space = |i: usize| {
+        text[clusters[i].0..]
+            .chars()
+            .next()
+            .map_or(false, char::is_whitespace)
+    };
+
+    let mut words = Vec::new();
+    let mut i = 0;
+    while i < clusters.len() {
+        let start = i;
+        while i < clusters.len() && !is_space(i) {
+            i += 1;
+        }
+
+        let end = i;
+        while i < clusters.len() && is_space(i) {
+            i += 1;
+        }
+
+        words.push(Word {
+            clusters: start..end,
+            clusters_with_space: start..i,
+        });
+    }
+
+    words
+}
+
+/// Creates a chunk from a byte range of another one.
+fn sub_chunk(chunk: &TextChunk, range: Range<usize>, x: f32, y: f32) -> TextChunk {
+    let spans = chunk
+        .spans
+        .iter()
+        .filter(|span| span.start < range.end && span.end > range.start)
+        .map(|span| {
+            let mut span = span.clone();
+            span.start = span.start.max(range.start) - range.start;
+            span.end = span.end.min(range.end) - range.start;
+            span
+        })
+        .collect();
+
+    TextChunk {
+        x: Some(x),
+        y: Some(y),
+        anchor: chunk.anchor,
+        spans,
+        text_flow: TextFlow::Linear,
+        text: chunk.text[range].to_string(),
+    }
+}
@@ -0,0 +1,216 @@
+// Copyright 2024 the Resvg Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::collections::HashMap;
+
+use super::*;
+
+/// A [`Tree`] builder.
+///
+/// Allows constructing a tree programmatically, without an SVG document.
+/// Takes care of the data normally computed by the parser, like absolute transforms,
+/// bounding boxes and resources lists.
+///
+/// Nodes are added to the last open group. Groups are opened via [`TreeBuilder::push_group`]
+/// and closed via [`TreeBuilder::pop_group`]. Groups that are still open are closed
+/// by [`TreeBuilder::finish`].
+///
+/// # Example
+///
+/// ```
+/// use usvg::tiny_skia_path::PathBuilder;
+///
+/// let mut builder = usvg::TreeBuilder::new(usvg::Size::from_wh(100.0, 100.0).unwrap());
+/// builder.push_group(
+///     usvg::Transform::from_translate(10.0, 10.0),
+///     usvg::Opacity::new_clamped(0.5),
+/// );
+///
+/// let mut fill = usvg::Fill::default();
+/// fill.set_paint(usvg::Paint::Color(usvg::Color::new_rgb(0, 128, 0)));
+/// let rect = PathBuilder::from_rect(usvg::Rect::from_xywh(0.0, 0.0, 50.0, 50.0).unwrap());
+/// builder.push_path(rect, Some(fill), Some(usvg::Stroke::default()));
+///
+/// let tree = builder.finish();
+/// let bbox = tree.root().abs_bounding_box();
+/// assert_eq!((bbox.x(), bbox.width()), (10.0, 50.0));
+/// ```
+#[derive(Debug)]
+pub struct TreeBuilder {
+    size: Size,
+    // The first group is the root one.
+    groups: Vec<Group>,
+    gradients_count: usize,
+}
+
+impl TreeBuilder {
+    /// Creates a new builder for a tree of the specified size.
+    ///
+    /// The viewbox matches the size, i.e. no viewbox transform is applied.
+    pub fn new(size: Size) -> Self {
+        TreeBuilder {
+            size,
+            groups: vec![Group::empty()],
+            gradients_count: 0,
+        }
+    }
+
+    /// Opens a new group.
+    ///
+    /// All following nodes are added to this group until [`TreeBuilder::pop_group`].
+    pub fn push_group(&mut self, transform: Transform, opacity: Opacity) {
+        let mut g = Group::empty();
+        g.transform = transform;
+        g.abs_transform = self.abs_transform().pre_concat(transform);
+        g.opacity = opacity;
+        self.groups.push(g);
+    }
+
+    /// Closes the last open group.
+    ///
+    /// Groups without children are removed, just like during parsing.
+    ///
+    /// Does nothing when there are no open groups.
+    pub fn pop_group(&mut self) {
+        if self.groups.len() < 2 {
+            return;
+        }
+
+        let mut g = self.groups.pop().unwrap();
+        if !g.has_children() {
+            return;
+        }
+
+        if g.calculate_bounding_boxes().is_some() {
+            self.parent().children.push(Node::Group(Box::new(g)));
+        }
+    }
+
+    /// Adds a path to the last open group.
+    ///
+    /// Paths without a bounding box, like empty ones, are ignored.
+    pub fn push_path(
+        &mut self,
+        data: tiny_skia_path::Path,
+        fill: Option<Fill>,
+        stroke: Option<Stroke>,
+    ) {
+        let style = PathStyle {
+            fill,
+            stroke,
+            ..PathStyle::default()
+        };
+        let path = Path::new(String::new(), style, Arc::new(data), self.abs_transform());
+
+        if let Some(path) = path {
+            self.parent().children.push(Node::Path(Box::new(path)));
+        }
+    }
+
+    /// Creates a linear gradient paint.
+    ///
+    /// Coordinates are in the user space of the painted path.
+    /// Stops are sorted by offset.
+    ///
+    /// Returns `None` when there are less than two stops.
+    pub fn linear_gradient(
+        &mut self,
+        x1: f32,
+        y1: f32,
+        x2: f32,
+        y2: f32,
+        stops: Vec<Stop>,
+    ) -> Option<Paint> {
+        let base = self.base_gradient("linearGradient", stops)?;
+        Some(Paint::LinearGradient(Arc::new(LinearGradient {
+            base,
+            x1,
+            y1,
+            x2,
+            y2,
+        })))
+    }
+
+    /// Creates a radial gradient paint.
+    ///
+    /// Coordinates are in the user space of the painted path.
+    /// The focal point matches the center.
+    /// Stops are sorted by offset.
+    ///
+    /// Returns `None` when there are less than two stops.
+    pub fn radial_gradient(
+        &mut self,
+        cx: f32,
+        cy: f32,
+        r: PositiveF32,
+        stops: Vec<Stop>,
+    ) -> Option<Paint> {
+        let base = self.base_gradient("radialGradient", stops)?;
+        Some(Paint::RadialGradient(Arc::new(RadialGradient {
+            base,
+            cx,
+            cy,
+            r,
+            fx: cx,
+            fy: cy,
+        })))
+    }
+
+    /// Closes all open groups and creates a tree.
+    pub fn finish(mut self) -> Tree {
+        while self.groups.len() > 1 {
+            self.pop_group();
+        }
+
+        let mut root = self.groups.pop().unwrap();
+        root.calculate_bounding_boxes();
+
+        let mut tree = Tree {
+            size: self.size,
+            view_box: ViewBox {
+                rect: self.size.to_non_zero_rect(0.0, 0.0),
+                aspect: svgtypes::AspectRatio::default(),
+            },
+            overflow_visible: false,
+            root,
+            linear_gradients: Vec::new(),
+            radial_gradients: Vec::new(),
+            patterns: Vec::new(),
+            clip_paths: Vec::new(),
+            masks: Vec::new(),
+            filters: Vec::new(),
+            metadata: HashMap::new(),
+            computed_styles: HashMap::new(),
+            #[cfg(feature = "text")]
+            fontdb: Arc::new(fontdb::Database::new()),
+        };
+        tree.remove_unused_defs();
+        tree
+    }
+
+    fn base_gradient(&mut self, prefix: &str, mut stops: Vec<Stop>) -> Option<BaseGradient> {
+        if stops.len() < 2 {
+            return None;
+        }
+
+        stops.sort_by(|a, b| a.offset.get().total_cmp(&b.offset.get()));
+
+        self.gradients_count += 1;
+        Some(BaseGradient {
+            id: NonEmptyString::new(format!("{}{}", prefix, self.gradients_count)).unwrap(),
+            units: Units::UserSpaceOnUse,
+            transform: Transform::default(),
+            spread_method: SpreadMethod::default(),
+            color_interpolation: GradientInterpolation::default(),
+            stops,
+        })
+    }
+
+    fn abs_transform(&self) -> Transform {
+        self.groups.last().unwrap().abs_transform
+    }
+
+    fn parent(&mut self) -> &mut Group {
+        self.groups.last_mut().unwrap()
+    }
+}
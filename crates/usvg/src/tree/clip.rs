@@ -0,0 +1,354 @@
+// Copyright 2024 the Resvg Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::sync::Arc;
+
+use kurbo::{ParamCurve, PathSeg};
+use tiny_skia_path::{NonZeroRect, PathSegment};
+
+use super::*;
+
+impl Tree {
+    /// Applies simple clip paths directly to the clipped geometry.
+    ///
+    /// A clip path is considered simple when it consists of a single axis-aligned rectangle
+    /// and doesn't reference another clip path.
+    /// It can be applied only when the clipped group contains just filled paths,
+    /// optionally inside groups without rotation, skew and filters.
+    ///
+    /// In which case each path in the group will be intersected with the clip rectangle,
+    /// while the group itself will lose its clip path.
+    /// All other clip paths will be left intact.
+    ///
+    /// Useful for exporting into formats without clipping support.
+    pub fn flatten_clip_paths(&mut self) {
+        flatten_clip_paths(&mut self.root);
+        self.remove_unused_defs();
+        self.root.calculate_bounding_boxes();
+    }
+}
+
+fn flatten_clip_paths(parent: &mut Group) {
+    for node in &mut parent.children {
+        if let Node::Group(ref mut group) = node {
+            flatten_clip_paths(group);
+
+            // Filters and masks are applied before the clip path,
+            // so clipping the children geometry would change their output.
+            if !group.filters.is_empty() || group.mask.is_some() {
+                continue;
+            }
+
+            let rect = group.clip_path.as_deref().and_then(simple_clip_rect);
+            if let Some(rect) = rect {
+                if can_clip_group(group, rect) {
+                    clip_group(group, rect);
+                    group.clip_path = None;
+                    group.calculate_bounding_boxes();
+                }
+            }
+        }
+    }
+
+    parent.calculate_bounding_boxes();
+}
+
+/// Returns a clip path rectangle in the clipped element coordinates.
+fn simple_clip_rect(clip: &ClipPath) -> Option<NonZeroRect> {
+    if clip.clip_path.is_some() {
+        return None;
+    }
+
+    let mut ts = clip.transform;
+    let mut group = &clip.root;
+    loop {
+        if group.clip_path.is_some() || group.mask.is_some() || !group.filters.is_empty() {
+            return None;
+        }
+
+        ts = ts.pre_concat(group.transform);
+
+        match group.children.as_slice() {
+            [Node::Group(ref g)] => group = g,
+            [Node::Path(ref path)] => {
+                if !path.visible || path.fill.is_none() || ts.has_skew() {
+                    return None;
+                }
+
+                return path_to_rect(&path.data)?.transform(ts);
+            }
+            _ => return None,
+        }
+    }
+}
+
+/// Checks that path is an axis-aligned rectangle.
+pub(super) fn path_to_rect(path: &tiny_skia_path::Path) -> Option<NonZeroRect> {
+    let rect = path.bounds().to_non_zero_rect()?;
+
+    let mut count = 0;
+    for segment in path.segments() {
+        match segment {
+            PathSegment::MoveTo(p) | PathSegment::LineTo(p) => {
+                count += 1;
+
+                let on_x_edge = p.x == rect.left() || p.x == rect.right();
+                let on_y_edge = p.y == rect.top() || p.y == rect.bottom();
+                if !on_x_edge || !on_y_edge {
+                    return None;
+                }
+            }
+            PathSegment::Close => {}
+            _ => return None,
+        }
+    }
+
+    // Four corners with an optional closing line.
+    if count == 4 || count == 5 {
+        Some(rect)
+    } else {
+        None
+    }
+}
+
+fn can_clip_group(group: &Group, rect: NonZeroRect) -> bool {
+    for node in &group.children {
+        match node {
+            Node::Group(ref g) => {
+                // Filters can affect pixels outside the clip region, so they must be clipped
+                // after being applied.
+                if !g.filters.is_empty() || g.transform.has_skew() {
+                    return false;
+                }
+
+                let rect = match g.transform.invert().and_then(|ts| rect.transform(ts)) {
+                    Some(v) => v,
+                    None => return false,
+                };
+
+                if !can_clip_group(g, rect) {
+                    return false;
+                }
+            }
+            Node::Path(ref path) => {
+                if path.stroke.is_some() {
+                    return false;
+                }
+            }
+            Node::Image(_) | Node::Text(_) => return false,
+        }
+    }
+
+    true
+}
+
+fn clip_group(group: &mut Group, rect: NonZeroRect) {
+    group.children.retain_mut(|node| match node {
+        Node::Group(ref mut g) => {
+            if let Some(rect) = g.transform.invert().and_then(|ts| rect.transform(ts)) {
+                clip_group(g, rect);
+            }
+
+            g.calculate_bounding_boxes();
+            g.has_children()
+        }
+        Node::Path(ref mut path) => {
+            let new_path = clip_path_data(&path.data, rect).and_then(|data| {
+                Path::new(
+                    path.id.clone(),
+                    path.style(),
+                    Arc::new(data),
+                    path.abs_transform,
+                )
+            });
+
+            match new_path {
+                Some(new_path) => {
+                    **path = new_path;
+                    true
+                }
+                // Fully clipped.
+                None => false,
+            }
+        }
+        Node::Image(_) | Node::Text(_) => true,
+    });
+}
+
+/// Intersects a filled path with a rectangle.
+///
+/// Curves are split precisely and not flattened.
+/// Each subpath is clipped by each rectangle edge separately, which preserves
+/// the winding of the remaining area, so both fill rules are supported.
+fn clip_path_data(path: &tiny_skia_path::Path, rect: NonZeroRect) -> Option<tiny_skia_path::Path> {
+    let mut contours = collect_contours(path);
+
+    let left = rect.left() as f64;
+    let top = rect.top() as f64;
+    let right = rect.right() as f64;
+    let bottom = rect.bottom() as f64;
+
+    // Make sure that edge lines are longer than the path itself.
+    let bounds = path.bounds();
+    let min_x = (bounds.left() as f64).min(left) - 1.0;
+    let min_y = (bounds.top() as f64).min(top) - 1.0;
+    let max_x = (bounds.right() as f64).max(right) + 1.0;
+    let max_y = (bounds.bottom() as f64).max(bottom) + 1.0;
+
+    let edges: [(kurbo::Line, &dyn Fn(kurbo::Point) -> bool); 4] = [
+        (kurbo::Line::new((left, min_y), (left, max_y)), &|p| {
+            p.x >= left
+        }),
+        (kurbo::Line::new((right, min_y), (right, max_y)), &|p| {
+            p.x <= right
+        }),
+        (kurbo::Line::new((min_x, top), (max_x, top)), &|p| {
+            p.y >= top
+        }),
+        (kurbo::Line::new((min_x, bottom), (max_x, bottom)), &|p| {
+            p.y <= bottom
+        }),
+    ];
+
+    for (line, is_inside) in edges {
+        contours = contours
+            .iter()
+            .filter_map(|segments| clip_contour(segments, line, is_inside))
+            .collect();
+    }
+
+    let mut builder = tiny_skia_path::PathBuilder::new();
+    for segments in &contours {
+        let start = segments[0].start();
+        builder.move_to(start.x as f32, start.y as f32);
+        for segment in segments {
+            match segment {
+                PathSeg::Line(l) => builder.line_to(l.p1.x as f32, l.p1.y as f32),
+                PathSeg::Quad(q) => {
+                    builder.quad_to(q.p1.x as f32, q.p1.y as f32, q.p2.x as f32, q.p2.y as f32)
+                }
+                PathSeg::Cubic(c) => builder.cubic_to(
+                    c.p1.x as f32,
+                    c.p1.y as f32,
+                    c.p2.x as f32,
+                    c.p2.y as f32,
+                    c.p3.x as f32,
+                    c.p3.y as f32,
+                ),
+            }
+        }
+        builder.close();
+    }
+
+    builder.finish()
+}
+
+/// Splits a path into closed subpaths.
+fn collect_contours(path: &tiny_skia_path::Path) -> Vec<Vec<PathSeg>> {
+    fn to_point(p: tiny_skia_path::Point) -> kurbo::Point {
+        kurbo::Point::new(p.x as f64, p.y as f64)
+    }
+
+    fn close(start: kurbo::Point, prev: kurbo::Point, segments: &mut Vec<PathSeg>) {
+        if prev != start {
+            segments.push(PathSeg::Line(kurbo::Line::new(prev, start)));
+        }
+    }
+
+    let mut contours = Vec::new();
+    let mut segments = Vec::new();
+    let mut start = kurbo::Point::ZERO;
+    let mut prev = kurbo::Point::ZERO;
+    for segment in path.segments() {
+        match segment {
+            PathSegment::MoveTo(p) => {
+                close(start, prev, &mut segments);
+                if !segments.is_empty() {
+                    contours.push(std::mem::take(&mut segments));
+                }
+
+                start = to_point(p);
+                prev = start;
+            }
+            PathSegment::LineTo(p) => {
+                let p = to_point(p);
+                segments.push(PathSeg::Line(kurbo::Line::new(prev, p)));
+                prev = p;
+            }
+            PathSegment::QuadTo(p1, p) => {
+                let p = to_point(p);
+                segments.push(PathSeg::Quad(kurbo::QuadBez::new(prev, to_point(p1), p)));
+                prev = p;
+            }
+            PathSegment::CubicTo(p1, p2, p) => {
+                let p = to_point(p);
+                segments.push(PathSeg::Cubic(kurbo::CubicBez::new(
+                    prev,
+                    to_point(p1),
+                    to_point(p2),
+                    p,
+                )));
+                prev = p;
+            }
+            PathSegment::Close => {
+                close(start, prev, &mut segments);
+                prev = start;
+            }
+        }
+    }
+
+    close(start, prev, &mut segments);
+    if !segments.is_empty() {
+        contours.push(segments);
+    }
+
+    contours
+}
+
+const EPSILON: f64 = 1e-6;
+
+/// Clips a closed subpath by a half-plane.
+///
+/// Parts outside the half-plane are replaced by straight lines along its edge.
+fn clip_contour(
+    segments: &[PathSeg],
+    edge: kurbo::Line,
+    is_inside: &dyn Fn(kurbo::Point) -> bool,
+) -> Option<Vec<PathSeg>> {
+    let mut parts = Vec::new();
+    for segment in segments {
+        let mut ts: Vec<f64> = segment
+            .intersect_line(edge)
+            .iter()
+            .map(|i| i.segment_t)
+            // Ignore intersections at the segment ends.
+            .filter(|t| *t > EPSILON && *t < 1.0 - EPSILON)
+            .collect();
+        ts.sort_by(|a, b| a.total_cmp(b));
+        ts.insert(0, 0.0);
+        ts.push(1.0);
+
+        for range in ts.windows(2) {
+            let part = segment.subsegment(range[0]..range[1]);
+            if is_inside(part.eval(0.5)) {
+                parts.push(part);
+            }
+        }
+    }
+
+    if parts.is_empty() {
+        return None;
+    }
+
+    let mut clipped = Vec::with_capacity(parts.len());
+    for i in 0..parts.len() {
+        let end = parts[i].end();
+        let next_start = parts[(i + 1) % parts.len()].start();
+        clipped.push(parts[i]);
+        if end.distance(next_start) > EPSILON {
+            clipped.push(PathSeg::Line(kurbo::Line::new(end, next_start)));
+        }
+    }
+
+    Some(clipped)
+}
@@ -0,0 +1,172 @@
+// Copyright 2024 the Resvg Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use super::*;
+
+impl Tree {
+    /// Replaces all colors that were resolved from `currentColor`.
+    ///
+    /// Affects fills, strokes and gradient stops of all nodes, including text,
+    /// patterns, masks and `feImage` content.
+    ///
+    /// Only the color itself is replaced. Opacity resolved during parsing is preserved.
+    ///
+    /// Filter colors, like `flood-color`, and nested SVG images are not affected.
+    ///
+    /// Useful for re-theming an already parsed tree.
+    pub fn set_current_color(&mut self, color: Color) {
+        let mut visitor = CurrentColorVisitor {
+            color,
+            gradients: HashMap::new(),
+        };
+        // Nested SVG images resolve `currentColor` on their own.
+        self.visit_mut_without_images(&mut visitor);
+    }
+}
+
+/// Replaces `currentColor` in fills, strokes and gradients.
+///
+/// Patterns and masks are handled by [`Tree::visit_mut`].
+struct CurrentColorVisitor {
+    color: Color,
+    // Gradients are shared, therefore each one must be replaced only once
+    // to preserve ID uniqueness.
+    gradients: HashMap<*const (), Paint>,
+}
+
+impl NodeVisitor for CurrentColorVisitor {
+    fn enter(&mut self, node: &mut Node) {
+        match node {
+            Node::Path(ref mut path) => self.update_path(path),
+            Node::Text(ref mut text) => {
+                for chunk in &mut text.chunks {
+                    for span in &mut chunk.spans {
+                        self.update_fill(&mut span.fill);
+                        self.update_stroke(&mut span.stroke);
+
+                        let decoration = &mut span.decoration;
+                        for style in [
+                            &mut decoration.underline,
+                            &mut decoration.overline,
+                            &mut decoration.line_through,
+                        ]
+                        .into_iter()
+                        .flatten()
+                        {
+                            self.update_fill(&mut style.fill);
+                            self.update_stroke(&mut style.stroke);
+                        }
+                    }
+                }
+
+                #[cfg(feature = "text")]
+                for span in &mut text.layouted {
+                    self.update_fill(&mut span.fill);
+                    self.update_stroke(&mut span.stroke);
+
+                    for path in [
+                        &mut span.underline,
+                        &mut span.overline,
+                        &mut span.line_through,
+                    ]
+                    .into_iter()
+                    .flatten()
+                    {
+                        self.update_path(path);
+                    }
+                }
+            }
+            Node::Group(_) | Node::Image(_) => {}
+        }
+    }
+}
+
+impl CurrentColorVisitor {
+    fn update_path(&mut self, path: &mut Path) {
+        self.update_fill(&mut path.fill);
+        self.update_stroke(&mut path.stroke);
+    }
+
+    fn update_fill(&mut self, fill: &mut Option<Fill>) {
+        if let Some(ref mut fill) = fill {
+            if fill.current_color {
+                fill.paint = Paint::Color(self.color);
+            } else {
+                fill.paint = self.update_paint(&fill.paint);
+            }
+        }
+    }
+
+    fn update_stroke(&mut self, stroke: &mut Option<Stroke>) {
+        if let Some(ref mut stroke) = stroke {
+            if stroke.current_color {
+                stroke.paint = Paint::Color(self.color);
+            } else {
+                stroke.paint = self.update_paint(&stroke.paint);
+            }
+        }
+    }
+
+    fn update_paint(&mut self, paint: &Paint) -> Paint {
+        let key = match paint {
+            Paint::LinearGradient(ref lg) => Arc::as_ptr(lg) as *const (),
+            Paint::RadialGradient(ref rg) => Arc::as_ptr(rg) as *const (),
+            Paint::Color(_) | Paint::Pattern(_) => return paint.clone(),
+        };
+
+        if let Some(paint) = self.gradients.get(&key) {
+            return paint.clone();
+        }
+
+        let new_paint = match paint {
+            Paint::LinearGradient(ref lg) if has_current_color(&lg.stops) => {
+                Paint::LinearGradient(Arc::new(LinearGradient {
+                    base: update_gradient(&lg.base, self.color),
+                    x1: lg.x1,
+                    y1: lg.y1,
+                    x2: lg.x2,
+                    y2: lg.y2,
+                }))
+            }
+            Paint::RadialGradient(ref rg) if has_current_color(&rg.stops) => {
+                Paint::RadialGradient(Arc::new(RadialGradient {
+                    base: update_gradient(&rg.base, self.color),
+                    cx: rg.cx,
+                    cy: rg.cy,
+                    r: rg.r,
+                    fx: rg.fx,
+                    fy: rg.fy,
+                }))
+            }
+            _ => paint.clone(),
+        };
+
+        self.gradients.insert(key, new_paint.clone());
+        new_paint
+    }
+}
+
+fn has_current_color(stops: &[Stop]) -> bool {
+    stops.iter().any(|stop| stop.current_color)
+}
+
+fn update_gradient(base: &BaseGradient, color: Color) -> BaseGradient {
+    let mut stops = base.stops.clone();
+    for stop in &mut stops {
+        if stop.current_color {
+            stop.color = color;
+        }
+    }
+
+    BaseGradient {
+        id: NonEmptyString(base.id.0.clone()),
+        units: base.units,
+        transform: base.transform,
+        spread_method: base.spread_method,
+        color_interpolation: base.color_interpolation,
+        stops,
+    }
+}
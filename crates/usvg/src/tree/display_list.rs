@@ -0,0 +1,172 @@
+// Copyright 2024 the Resvg Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use super::*;
+
+/// A drawing command.
+///
+/// See [`Tree::to_display_list`] for details.
+#[derive(Clone, Copy, Debug)]
+pub enum DrawCommand<'a> {
+    /// Starts a new transparent layer.
+    ///
+    /// All commands up to the matching [`DrawCommand::PopLayer`] must be drawn onto this layer.
+    PushLayer {
+        /// Layer bounding box in canvas coordinates.
+        ///
+        /// Filter regions are already accounted for.
+        bounding_box: NonZeroRect,
+    },
+    /// Applies a filter to the current layer.
+    ApplyFilter {
+        /// The filter to apply.
+        filter: &'a filter::Filter,
+        /// Canvas transform of the filtered group.
+        transform: Transform,
+    },
+    /// Clips the current layer.
+    ApplyClipPath {
+        /// The clip path to apply.
+        clip_path: &'a ClipPath,
+        /// Canvas transform of the clipped group.
+        transform: Transform,
+    },
+    /// Masks the current layer.
+    ApplyMask {
+        /// The mask to apply.
+        mask: &'a Mask,
+        /// Canvas transform of the masked group.
+        transform: Transform,
+    },
+    /// Composites the current layer onto the previous one and removes it.
+    PopLayer {
+        /// Layer opacity.
+        opacity: Opacity,
+        /// Layer blend mode.
+        blend_mode: BlendMode,
+    },
+    /// Fills a path using [`Path::fill`].
+    FillPath {
+        /// The path to fill.
+        path: &'a Path,
+        /// Canvas transform of the path.
+        transform: Transform,
+    },
+    /// Strokes a path using [`Path::stroke`].
+    StrokePath {
+        /// The path to stroke.
+        path: &'a Path,
+        /// Canvas transform of the path.
+        transform: Transform,
+    },
+    /// Draws an image.
+    DrawImage {
+        /// The image to draw.
+        image: &'a Image,
+        /// Canvas transform of the image.
+        transform: Transform,
+    },
+}
+
+impl Tree {
+    /// Converts the tree into a flat list of drawing commands.
+    ///
+    /// Commands are in the paint order and follow the same compositing rules as `resvg`.
+    /// Groups that should be isolated (see [`Group::should_isolate`]) are represented by layers:
+    ///
+    /// 1. [`DrawCommand::PushLayer`]
+    /// 2. Group content.
+    /// 3. [`DrawCommand::ApplyFilter`] for each filter.
+    /// 4. [`DrawCommand::ApplyClipPath`], if any.
+    /// 5. [`DrawCommand::ApplyMask`], if any.
+    /// 6. [`DrawCommand::PopLayer`]
+    ///
+    /// Other groups are flattened.
+    /// Text is represented by its flattened paths.
+    /// Invisible paths are skipped.
+    ///
+    /// Filters, clip paths, masks, patterns and nested SVG images are not expanded
+    /// and must be handled by the caller.
+    pub fn to_display_list(&self) -> Vec<DrawCommand<'_>> {
+        let mut commands = Vec::new();
+        push_nodes(&self.root, Transform::default(), &mut commands);
+        commands
+    }
+}
+
+fn push_nodes<'a>(parent: &'a Group, transform: Transform, commands: &mut Vec<DrawCommand<'a>>) {
+    for node in &parent.children {
+        match node {
+            Node::Group(ref group) => push_group(group, transform, commands),
+            Node::Path(ref path) => push_path(path, transform, commands),
+            Node::Image(ref image) => {
+                if image.visible {
+                    commands.push(DrawCommand::DrawImage { image, transform });
+                }
+            }
+            Node::Text(ref text) => push_group(&text.flattened, transform, commands),
+        }
+    }
+}
+
+fn push_group<'a>(group: &'a Group, transform: Transform, commands: &mut Vec<DrawCommand<'a>>) {
+    let transform = transform.pre_concat(group.transform);
+
+    if !group.should_isolate() {
+        push_nodes(group, transform, commands);
+        return;
+    }
+
+    // Not renderable.
+    let bounding_box = match group.layer_bounding_box.transform(transform) {
+        Some(v) => v,
+        None => return,
+    };
+
+    commands.push(DrawCommand::PushLayer { bounding_box });
+
+    push_nodes(group, transform, commands);
+
+    for filter in &group.filters {
+        commands.push(DrawCommand::ApplyFilter { filter, transform });
+    }
+
+    if let Some(ref clip_path) = group.clip_path {
+        commands.push(DrawCommand::ApplyClipPath {
+            clip_path,
+            transform,
+        });
+    }
+
+    if let Some(ref mask) = group.mask {
+        commands.push(DrawCommand::ApplyMask { mask, transform });
+    }
+
+    commands.push(DrawCommand::PopLayer {
+        opacity: group.opacity,
+        blend_mode: group.blend_mode,
+    });
+}
+
+fn push_path<'a>(path: &'a Path, transform: Transform, commands: &mut Vec<DrawCommand<'a>>) {
+    if !path.visible {
+        return;
+    }
+
+    let fill = path
+        .fill
+        .is_some()
+        .then_some(DrawCommand::FillPath { path, transform });
+    let stroke = path
+        .stroke
+        .is_some()
+        .then_some(DrawCommand::StrokePath { path, transform });
+
+    let (first, second) = match path.paint_order {
+        PaintOrder::FillAndStroke => (fill, stroke),
+        PaintOrder::StrokeAndFill => (stroke, fill),
+    };
+
+    commands.extend(first);
+    commands.extend(second);
+}
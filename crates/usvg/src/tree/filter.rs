@@ -74,6 +74,49 @@ impl Primitive {
     pub fn kind(&self) -> &Kind {
         &self.kind
     }
+
+    /// Resolved filter subregion, which clips the primitive output.
+    ///
+    /// Unlike [`Primitive::rect`], it is clipped by the filter region.
+    /// `feOffset` is not clipped by its own subregion, instead it inherits
+    /// the subregion of a referenced result or uses the whole filter region.
+    /// This matches the `resvg` behavior.
+    ///
+    /// `primitiveUnits` are already resolved during parsing, therefore the returned rect
+    /// is in the same coordinates as [`Filter::rect`], i.e. in the filtered element
+    /// user space.
+    ///
+    /// Returns `None` when the primitive doesn't belong to the `filter` or
+    /// when the subregion is outside the filter region.
+    pub fn subregion(&self, filter: &Filter) -> Option<NonZeroRect> {
+        let index = filter
+            .primitives
+            .iter()
+            .position(|primitive| std::ptr::eq(primitive, self))?;
+        resolve_subregion(filter, index)
+    }
+}
+
+fn resolve_subregion(filter: &Filter, index: usize) -> Option<NonZeroRect> {
+    let primitive = &filter.primitives[index];
+    if let Kind::Offset(ref fe) = primitive.kind {
+        if let Input::Reference(ref name) = fe.input {
+            let prev = filter.primitives[..index]
+                .iter()
+                .rposition(|primitive| primitive.result == *name);
+            if let Some(prev) = prev {
+                return resolve_subregion(filter, prev);
+            }
+        }
+
+        return Some(filter.rect);
+    }
+
+    primitive
+        .rect
+        .to_rect()
+        .intersect(&filter.rect.to_rect())?
+        .to_non_zero_rect()
 }
 
 /// A filter kind.
@@ -293,6 +336,58 @@ pub enum TransferFunction {
     },
 }
 
+impl TransferFunction {
+    /// Checks that the function doesn't modify a component.
+    ///
+    /// Empty tables are treated as identity.
+    pub fn is_identity(&self) -> bool {
+        match self {
+            TransferFunction::Identity => true,
+            TransferFunction::Table(values) => values.is_empty(),
+            TransferFunction::Discrete(values) => values.is_empty(),
+            TransferFunction::Linear { .. } => false,
+            TransferFunction::Gamma { .. } => false,
+        }
+    }
+
+    /// Applies the function to a component.
+    ///
+    /// The component must be in a 0..1 range and unpremultiplied.
+    /// The result is clamped to the same range.
+    pub fn apply(&self, c: f32) -> f32 {
+        let c = match self {
+            TransferFunction::Table(values) if !values.is_empty() => {
+                let n = values.len() - 1;
+                let k = (c * (n as f32)).floor() as usize;
+                let k = std::cmp::min(k, n);
+                if k == n {
+                    values[k]
+                } else {
+                    let vk = values[k];
+                    let vk1 = values[k + 1];
+                    let k = k as f32;
+                    let n = n as f32;
+                    vk + (c - k / n) * n * (vk1 - vk)
+                }
+            }
+            TransferFunction::Discrete(values) if !values.is_empty() => {
+                let n = values.len();
+                let k = (c * (n as f32)).floor() as usize;
+                values[std::cmp::min(k, n - 1)]
+            }
+            TransferFunction::Linear { slope, intercept } => slope * c + intercept,
+            TransferFunction::Gamma {
+                amplitude,
+                exponent,
+                offset,
+            } => amplitude * c.powf(*exponent) + offset,
+            _ => c,
+        };
+
+        c.clamp(0.0, 1.0)
+    }
+}
+
 /// A composite filter primitive.
 ///
 /// `feComposite` element in the SVG.
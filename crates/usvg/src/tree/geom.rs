@@ -3,6 +3,9 @@
 
 use strict_num::ApproxEqUlps;
 use svgtypes::{Align, AspectRatio};
+// Geometry primitives are defined in `tiny-skia-path`, which supports `no_std`.
+// Types below are `usvg`-specific and require `std`, since they rely on
+// `f64` methods like `sqrt` and `atan2`, `svgtypes` and `kurbo`.
 pub use tiny_skia_path::{NonZeroRect, Rect, Size, Transform};
 
 /// Approximate zero equality comparisons.
@@ -43,6 +46,267 @@ impl IsValidLength for f64 {
     }
 }
 
+/// A transform decomposed into separate components.
+///
+/// Represents `translate(translate_x translate_y) rotate(rotate) skewX(skew_x) scale(scale_x scale_y)`,
+/// applied in this order, i.e. scale is applied to a point first.
+///
+/// Angles are in degrees.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct DecomposedTransform {
+    /// Translation along the X axis.
+    pub translate_x: f32,
+    /// Translation along the Y axis.
+    pub translate_y: f32,
+    /// Rotation angle.
+    pub rotate: f32,
+    /// Skew angle along the X axis.
+    pub skew_x: f32,
+    /// Scale along the X axis. Always positive.
+    pub scale_x: f32,
+    /// Scale along the Y axis.
+    ///
+    /// Negative when the transform flips the coordinate system.
+    pub scale_y: f32,
+}
+
+impl DecomposedTransform {
+    /// Composes components back into a transform matrix.
+    pub fn compose(&self) -> Transform {
+        Transform::from_translate(self.translate_x, self.translate_y)
+            .pre_rotate(self.rotate)
+            .pre_concat(Transform::from_skew(
+                (self.skew_x as f64).to_radians().tan() as f32,
+                0.0,
+            ))
+            .pre_scale(self.scale_x, self.scale_y)
+    }
+}
+
+/// Transform decomposition.
+pub trait DecomposeTransform {
+    /// Decomposes a transform into translation, rotation, skew and scale.
+    ///
+    /// Given a matrix `[a c e; b d f]`, the decomposition is:
+    ///
+    /// - translation is `(e, f)`
+    /// - `scale_x` is the length of the first column, i.e. `sqrt(a² + b²)`
+    /// - rotation is the angle of the first column, i.e. `atan2(b, a)`
+    /// - `scale_y` is the determinant divided by `scale_x`, i.e. `(ad - bc) / scale_x`
+    /// - skew is `atan((ac + bd) / (ad - bc))`
+    ///
+    /// Since the first column is always aligned with the rotation,
+    /// a flip (negative determinant) is represented by a negative `scale_y`.
+    ///
+    /// Returns `None` for non-invertible and non-finite transforms.
+    fn decompose(&self) -> Option<DecomposedTransform>;
+}
+
+impl DecomposeTransform for Transform {
+    fn decompose(&self) -> Option<DecomposedTransform> {
+        if !self.is_finite() {
+            return None;
+        }
+
+        let (a, b, c, d) = (
+            self.sx as f64,
+            self.ky as f64,
+            self.kx as f64,
+            self.sy as f64,
+        );
+
+        let det = a * d - b * c;
+        let scale_x = (a * a + b * b).sqrt();
+        if det.approx_zero_ulps(4) || scale_x.approx_zero_ulps(4) {
+            return None;
+        }
+
+        Some(DecomposedTransform {
+            translate_x: self.tx,
+            translate_y: self.ty,
+            rotate: b.atan2(a).to_degrees() as f32,
+            skew_x: ((a * c + b * d) / det).atan().to_degrees() as f32,
+            scale_x: scale_x as f32,
+            scale_y: (det / scale_x) as f32,
+        })
+    }
+}
+
+/// Path flattening.
+pub trait FlattenPath {
+    /// Approximates all curves with line segments.
+    ///
+    /// The result contains only `MoveTo`, `LineTo` and `Close` segments
+    /// and deviates from the original path by no more than `tolerance`.
+    ///
+    /// Unlike stroking, the geometry itself is preserved.
+    /// Useful for exporting into formats that support only polylines.
+    ///
+    /// Returns `None` when `tolerance` is not positive.
+    fn flatten(&self, tolerance: f64) -> Option<tiny_skia_path::Path>;
+}
+
+impl FlattenPath for tiny_skia_path::Path {
+    fn flatten(&self, tolerance: f64) -> Option<tiny_skia_path::Path> {
+        use tiny_skia_path::PathSegment;
+
+        if !tolerance.is_valid_length() {
+            return None;
+        }
+
+        fn to_point(p: tiny_skia_path::Point) -> kurbo::Point {
+            kurbo::Point::new(p.x as f64, p.y as f64)
+        }
+
+        let elements = self.segments().map(|segment| match segment {
+            PathSegment::MoveTo(p) => kurbo::PathEl::MoveTo(to_point(p)),
+            PathSegment::LineTo(p) => kurbo::PathEl::LineTo(to_point(p)),
+            PathSegment::QuadTo(p1, p) => kurbo::PathEl::QuadTo(to_point(p1), to_point(p)),
+            PathSegment::CubicTo(p1, p2, p) => {
+                kurbo::PathEl::CurveTo(to_point(p1), to_point(p2), to_point(p))
+            }
+            PathSegment::Close => kurbo::PathEl::ClosePath,
+        });
+
+        let mut builder = tiny_skia_path::PathBuilder::with_capacity(self.len(), self.len());
+        kurbo::flatten(elements, tolerance, |el| match el {
+            kurbo::PathEl::MoveTo(p) => builder.move_to(p.x as f32, p.y as f32),
+            kurbo::PathEl::LineTo(p) => builder.line_to(p.x as f32, p.y as f32),
+            kurbo::PathEl::ClosePath => builder.close(),
+            // `flatten` produces only lines.
+            _ => {}
+        });
+
+        builder.finish()
+    }
+}
+
+/// Aspect ratio preserving size scaling.
+///
+/// Unlike `scale_to*` methods of [`Size`] and [`tiny_skia_path::IntSize`],
+/// returns the scale factor along with the new size. The factor is calculated
+/// along the side that matches the target exactly, therefore with `IntSize`
+/// the other side might be rounded up.
+pub trait FitSize: Sized {
+    /// A side length type.
+    type Length;
+
+    /// Scales the size to the specified width.
+    ///
+    /// Returns `None` when `width` is not positive.
+    fn fit_to_width(&self, width: Self::Length) -> Option<(Self, f32)>;
+
+    /// Scales the size to the specified height.
+    ///
+    /// Returns `None` when `height` is not positive.
+    fn fit_to_height(&self, height: Self::Length) -> Option<(Self, f32)>;
+
+    /// Scales the size to the largest one that fits inside `to`.
+    ///
+    /// Same scaling as `meet` in `preserveAspectRatio`.
+    fn fit_inside(&self, to: Self) -> (Self, f32);
+
+    /// Scales the size to the smallest one that covers `to`.
+    ///
+    /// Same scaling as `slice` in `preserveAspectRatio`.
+    fn fit_outside(&self, to: Self) -> (Self, f32);
+}
+
+impl FitSize for Size {
+    type Length = f32;
+
+    fn fit_to_width(&self, width: f32) -> Option<(Self, f32)> {
+        let scale = width / self.width();
+        let size = Size::from_wh(width, self.height() * scale)?;
+        Some((size, scale))
+    }
+
+    fn fit_to_height(&self, height: f32) -> Option<(Self, f32)> {
+        let scale = height / self.height();
+        let size = Size::from_wh(self.width() * scale, height)?;
+        Some((size, scale))
+    }
+
+    fn fit_inside(&self, to: Self) -> (Self, f32) {
+        let size = self.scale_to(to);
+        (
+            size,
+            fit_scale(self.to_tuple(), size.to_tuple(), to.to_tuple()),
+        )
+    }
+
+    fn fit_outside(&self, to: Self) -> (Self, f32) {
+        let size = self.expand_to(to);
+        (
+            size,
+            fit_scale(self.to_tuple(), size.to_tuple(), to.to_tuple()),
+        )
+    }
+}
+
+impl FitSize for tiny_skia_path::IntSize {
+    type Length = u32;
+
+    fn fit_to_width(&self, width: u32) -> Option<(Self, f32)> {
+        let size = self.scale_to_width(width)?;
+        Some((size, width as f32 / self.width() as f32))
+    }
+
+    fn fit_to_height(&self, height: u32) -> Option<(Self, f32)> {
+        let size = self.scale_to_height(height)?;
+        Some((size, height as f32 / self.height() as f32))
+    }
+
+    fn fit_inside(&self, to: Self) -> (Self, f32) {
+        let size = self.scale_to(to);
+        (
+            size,
+            fit_scale(self.to_tuple(), size.to_tuple(), to.to_tuple()),
+        )
+    }
+
+    fn fit_outside(&self, to: Self) -> (Self, f32) {
+        // Unlike `Size`, `IntSize` doesn't have an `expand_to` method.
+        let (width, height) = (self.width() as f32, self.height() as f32);
+        let size = if to.width() as f32 / width >= to.height() as f32 / height {
+            self.scale_to_width(to.width())
+        } else {
+            self.scale_to_height(to.height())
+        };
+
+        // Scaling to a non-zero side cannot produce a zero size.
+        let size = size.unwrap();
+        (
+            size,
+            fit_scale(self.to_tuple(), size.to_tuple(), to.to_tuple()),
+        )
+    }
+}
+
+trait ToTuple {
+    fn to_tuple(&self) -> (f32, f32);
+}
+
+impl ToTuple for Size {
+    fn to_tuple(&self) -> (f32, f32) {
+        (self.width(), self.height())
+    }
+}
+
+impl ToTuple for tiny_skia_path::IntSize {
+    fn to_tuple(&self) -> (f32, f32) {
+        (self.width() as f32, self.height() as f32)
+    }
+}
+
+fn fit_scale(from: (f32, f32), size: (f32, f32), to: (f32, f32)) -> f32 {
+    if size.0 == to.0 {
+        to.0 / from.0
+    } else {
+        to.1 / from.1
+    }
+}
+
 /// View box.
 #[derive(Clone, Copy, Debug)]
 pub(crate) struct ViewBox {
@@ -0,0 +1,233 @@
+// Copyright 2024 the Resvg Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+// Matrices are kept exactly as published.
+#![allow(clippy::excessive_precision)]
+
+use super::*;
+
+type Convert = fn([f32; 3]) -> [f32; 3];
+
+impl BaseGradient {
+    /// Returns a color and an opacity at the specified offset.
+    ///
+    /// Colors are interpolated in the [`BaseGradient::color_interpolation`] color space,
+    /// while opacity is always interpolated linearly. Colors outside the sRGB gamut are clipped.
+    ///
+    /// `offset` is clamped to the 0..1 range, i.e. the spread method is not applied.
+    pub fn sample(&self, offset: f32) -> (Color, Opacity) {
+        let (first, last) = match (self.stops.first(), self.stops.last()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return (Color::black(), Opacity::ZERO),
+        };
+
+        let offset = offset.clamp(0.0, 1.0);
+        if offset <= first.offset.get() {
+            return (first.color, first.opacity);
+        }
+
+        for pair in self.stops.windows(2) {
+            let (prev, next) = (&pair[0], &pair[1]);
+            if offset > next.offset.get() {
+                continue;
+            }
+
+            let range = next.offset.get() - prev.offset.get();
+            let t = if range > 0.0 {
+                (offset - prev.offset.get()) / range
+            } else {
+                1.0
+            };
+
+            let color = interpolate(prev.color, next.color, t, self.color_interpolation);
+            let opacity = Opacity::new_clamped(
+                prev.opacity.get() + (next.opacity.get() - prev.opacity.get()) * t,
+            );
+            return (color, opacity);
+        }
+
+        (last.color, last.opacity)
+    }
+}
+
+fn interpolate(c1: Color, c2: Color, t: f32, space: GradientInterpolation) -> Color {
+    if t <= 0.0 {
+        return c1;
+    } else if t >= 1.0 {
+        return c2;
+    }
+
+    let lerp = |a: f32, b: f32| a + (b - a) * t;
+
+    let (lab_to_rgb, polar): (Convert, bool) = match space {
+        GradientInterpolation::SRGB => {
+            let c1 = [c1.red, c1.green, c1.blue];
+            let c2 = [c2.red, c2.green, c2.blue];
+            let c = [0, 1, 2].map(|i| lerp(c1[i] as f32, c2[i] as f32).round() as u8);
+            return Color::new_rgb(c[0], c[1], c[2]);
+        }
+        GradientInterpolation::Lab => (lab_to_linear_rgb, false),
+        GradientInterpolation::Lch => (lab_to_linear_rgb, true),
+        GradientInterpolation::OkLab => (oklab_to_linear_rgb, false),
+        GradientInterpolation::OkLch => (oklab_to_linear_rgb, true),
+    };
+
+    let rgb_to_lab: Convert = match space {
+        GradientInterpolation::Lab | GradientInterpolation::Lch => linear_rgb_to_lab,
+        _ => linear_rgb_to_oklab,
+    };
+
+    let lab1 = rgb_to_lab(c1.to_linear_rgb());
+    let lab2 = rgb_to_lab(c2.to_linear_rgb());
+
+    let lab = if polar {
+        let (l1, chroma1, hue1) = to_polar(lab1);
+        let (l2, chroma2, hue2) = to_polar(lab2);
+
+        // The hue of an achromatic color is powerless, so the other one is used.
+        let (hue1, hue2) = match (chroma1 > ACHROMATIC, chroma2 > ACHROMATIC) {
+            (false, true) => (hue2, hue2),
+            (true, false) => (hue1, hue1),
+            _ => (hue1, hue2),
+        };
+
+        // Use the shorter arc.
+        let mut delta = hue2 - hue1;
+        if delta > 180.0 {
+            delta -= 360.0;
+        } else if delta < -180.0 {
+            delta += 360.0;
+        }
+
+        from_polar(lerp(l1, l2), lerp(chroma1, chroma2), hue1 + delta * t)
+    } else {
+        [
+            lerp(lab1[0], lab2[0]),
+            lerp(lab1[1], lab2[1]),
+            lerp(lab1[2], lab2[2]),
+        ]
+    };
+
+    Color::from_linear_rgb(lab_to_rgb(lab))
+}
+
+/// Chroma below which a color is considered achromatic.
+///
+/// Large enough to ignore conversion errors of grays in both Lab and OKLab.
+const ACHROMATIC: f32 = 0.0002;
+
+fn to_polar(lab: [f32; 3]) -> (f32, f32, f32) {
+    let chroma = (lab[1] * lab[1] + lab[2] * lab[2]).sqrt();
+    let hue = lab[2].atan2(lab[1]).to_degrees();
+    (lab[0], chroma, hue)
+}
+
+fn from_polar(l: f32, chroma: f32, hue: f32) -> [f32; 3] {
+    let hue = hue.to_radians();
+    [l, chroma * hue.cos(), chroma * hue.sin()]
+}
+
+fn mul(m: &[[f32; 3]; 3], v: [f32; 3]) -> [f32; 3] {
+    [0, 1, 2].map(|i| m[i][0] * v[0] + m[i][1] * v[1] + m[i][2] * v[2])
+}
+
+// OKLab, as defined by Björn Ottosson.
+
+const LINEAR_RGB_TO_LMS: [[f32; 3]; 3] = [
+    [0.4122214708, 0.5363325363, 0.0514459929],
+    [0.2119034982, 0.6806995451, 0.1073969566],
+    [0.0883024619, 0.2817188376, 0.6299787005],
+];
+
+const LMS_TO_OKLAB: [[f32; 3]; 3] = [
+    [0.2104542553, 0.7936177850, -0.0040720468],
+    [1.9779984951, -2.4285922050, 0.4505937099],
+    [0.0259040371, 0.7827717662, -0.8086757660],
+];
+
+const OKLAB_TO_LMS: [[f32; 3]; 3] = [
+    [1.0, 0.3963377774, 0.2158037573],
+    [1.0, -0.1055613458, -0.0638541728],
+    [1.0, -0.0894841775, -1.2914855480],
+];
+
+const LMS_TO_LINEAR_RGB: [[f32; 3]; 3] = [
+    [4.0767416621, -3.3077115913, 0.2309699292],
+    [-1.2684380046, 2.6097574011, -0.3413193965],
+    [-0.0041960863, -0.7034186147, 1.7076147010],
+];
+
+fn linear_rgb_to_oklab(rgb: [f32; 3]) -> [f32; 3] {
+    let lms = mul(&LINEAR_RGB_TO_LMS, rgb).map(f32::cbrt);
+    mul(&LMS_TO_OKLAB, lms)
+}
+
+fn oklab_to_linear_rgb(lab: [f32; 3]) -> [f32; 3] {
+    let lms = mul(&OKLAB_TO_LMS, lab).map(|v| v * v * v);
+    mul(&LMS_TO_LINEAR_RGB, lms)
+}
+
+// CIE Lab with the D50 white point, like in CSS Color 4.
+
+const LINEAR_RGB_TO_XYZ_D50: [[f32; 3]; 3] = [
+    [0.4360747, 0.3850649, 0.1430804],
+    [0.2225045, 0.7168786, 0.0606169],
+    [0.0139322, 0.0971045, 0.7141733],
+];
+
+const XYZ_D50_TO_LINEAR_RGB: [[f32; 3]; 3] = [
+    [3.1338561, -1.6168667, -0.4906146],
+    [-0.9787684, 1.9161415, 0.0334540],
+    [0.0719453, -0.2289914, 1.4052427],
+];
+
+const D50_WHITE: [f32; 3] = [0.96422, 1.0, 0.82521];
+
+const LAB_EPSILON: f32 = 216.0 / 24389.0;
+const LAB_KAPPA: f32 = 24389.0 / 27.0;
+
+fn linear_rgb_to_lab(rgb: [f32; 3]) -> [f32; 3] {
+    let xyz = mul(&LINEAR_RGB_TO_XYZ_D50, rgb);
+    let f = [0, 1, 2].map(|i| {
+        let v = xyz[i] / D50_WHITE[i];
+        if v > LAB_EPSILON {
+            v.cbrt()
+        } else {
+            (LAB_KAPPA * v + 16.0) / 116.0
+        }
+    });
+
+    [
+        116.0 * f[1] - 16.0,
+        500.0 * (f[0] - f[1]),
+        200.0 * (f[1] - f[2]),
+    ]
+}
+
+fn lab_to_linear_rgb(lab: [f32; 3]) -> [f32; 3] {
+    let fy = (lab[0] + 16.0) / 116.0;
+    let fx = lab[1] / 500.0 + fy;
+    let fz = fy - lab[2] / 200.0;
+
+    let inv = |f: f32| {
+        let v = f * f * f;
+        if v > LAB_EPSILON {
+            v
+        } else {
+            (116.0 * f - 16.0) / LAB_KAPPA
+        }
+    };
+
+    let y = if lab[0] > LAB_KAPPA * LAB_EPSILON {
+        fy * fy * fy
+    } else {
+        lab[0] / LAB_KAPPA
+    };
+
+    let xyz = [
+        inv(fx) * D50_WHITE[0],
+        y * D50_WHITE[1],
+        inv(fz) * D50_WHITE[2],
+    ];
+    mul(&XYZ_D50_TO_LINEAR_RGB, xyz)
+}
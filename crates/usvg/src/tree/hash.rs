@@ -0,0 +1,301 @@
+// Copyright 2024 the Resvg Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+use super::*;
+
+impl Tree {
+    /// Returns a hash of the rendering-relevant tree content.
+    ///
+    /// Includes the image size, the viewbox, geometry, transforms, paints and
+    /// all other properties that affect rendering. Paint servers, clip paths, masks
+    /// and filters are hashed by value and not by reference. Text is hashed via its outlines.
+    ///
+    /// IDs, filter results names, bounding boxes, metadata and other data
+    /// that doesn't affect rendering are ignored. Therefore documents that differ only
+    /// in formatting, attributes order, IDs or resources layout would have the same hash.
+    ///
+    /// The hash is stable only within the same `usvg` version and
+    /// is not guaranteed to be unique, just like any other hash.
+    pub fn content_hash(&self) -> u64 {
+        let mut hasher = ContentHasher::default();
+        let mut h = DefaultHasher::new();
+        hasher.hash_tree(self, &mut h);
+        h.finish()
+    }
+}
+
+// Resources are shared, therefore we are caching their hashes.
+#[derive(Default)]
+struct ContentHasher {
+    cache: HashMap<usize, u64>,
+}
+
+impl ContentHasher {
+    fn hash_tree(&mut self, tree: &Tree, h: &mut DefaultHasher) {
+        hash_f32(tree.size.width(), h);
+        hash_f32(tree.size.height(), h);
+        hash_non_zero_rect(tree.view_box.rect, h);
+        hash_debug(&tree.view_box.aspect, h);
+        tree.overflow_visible.hash(h);
+        self.hash_group(&tree.root, h);
+    }
+
+    fn hash_group(&mut self, group: &Group, h: &mut DefaultHasher) {
+        hash_transform(group.transform, h);
+        hash_f32(group.opacity.get(), h);
+        std::mem::discriminant(&group.blend_mode).hash(h);
+        group.isolate.hash(h);
+
+        match group.clip_path {
+            Some(ref clip) => self.hash_clip_path(clip, h),
+            None => 0u64.hash(h),
+        }
+
+        match group.mask {
+            Some(ref mask) => self.hash_mask(mask, h),
+            None => 0u64.hash(h),
+        }
+
+        group.filters.len().hash(h);
+        for filter in &group.filters {
+            self.hash_filter(filter, h);
+        }
+
+        group.children.len().hash(h);
+        for child in &group.children {
+            self.hash_node(child, h);
+        }
+    }
+
+    fn hash_node(&mut self, node: &Node, h: &mut DefaultHasher) {
+        std::mem::discriminant(node).hash(h);
+        match node {
+            Node::Group(ref group) => self.hash_group(group, h),
+            Node::Path(ref path) => self.hash_path(path, h),
+            Node::Image(ref image) => {
+                image.visible.hash(h);
+                hash_f32(image.size.width(), h);
+                hash_f32(image.size.height(), h);
+                std::mem::discriminant(&image.rendering_mode).hash(h);
+                std::mem::discriminant(&image.kind).hash(h);
+                match image.kind {
+                    ImageKind::JPEG(ref data)
+                    | ImageKind::PNG(ref data)
+                    | ImageKind::GIF(ref data)
+                    | ImageKind::WEBP(ref data) => data.hash(h),
+                    ImageKind::SVG(ref tree) => self.hash_tree(tree, h),
+                }
+            }
+            // Text is rendered only via outlines.
+            Node::Text(ref text) => self.hash_group(&text.flattened, h),
+        }
+    }
+
+    fn hash_path(&mut self, path: &Path, h: &mut DefaultHasher) {
+        path.visible.hash(h);
+        std::mem::discriminant(&path.paint_order).hash(h);
+        std::mem::discriminant(&path.rendering_mode).hash(h);
+        std::mem::discriminant(&path.vector_effect).hash(h);
+
+        match path.fill {
+            Some(ref fill) => {
+                1u64.hash(h);
+                self.hash_paint(&fill.paint, h);
+                hash_f32(fill.opacity.get(), h);
+                std::mem::discriminant(&fill.rule).hash(h);
+            }
+            None => 0u64.hash(h),
+        }
+
+        match path.stroke {
+            Some(ref stroke) => {
+                1u64.hash(h);
+                self.hash_paint(&stroke.paint, h);
+                match stroke.dasharray {
+                    Some(ref list) => hash_f32_list(list, h),
+                    None => 0u64.hash(h),
+                }
+                hash_f32(stroke.dashoffset, h);
+                hash_f32(stroke.miterlimit.get(), h);
+                hash_f32(stroke.opacity.get(), h);
+                hash_f32(stroke.width.get(), h);
+                std::mem::discriminant(&stroke.linecap).hash(h);
+                std::mem::discriminant(&stroke.linejoin).hash(h);
+            }
+            None => 0u64.hash(h),
+        }
+
+        path.data.len().hash(h);
+        for p in path.data.points() {
+            hash_f32(p.x, h);
+            hash_f32(p.y, h);
+        }
+        path.data.verbs().len().hash(h);
+        for verb in path.data.verbs() {
+            std::mem::discriminant(verb).hash(h);
+        }
+    }
+
+    fn hash_paint(&mut self, paint: &Paint, h: &mut DefaultHasher) {
+        std::mem::discriminant(paint).hash(h);
+        match paint {
+            Paint::Color(c) => (c.red, c.green, c.blue).hash(h),
+            Paint::LinearGradient(ref lg) => {
+                let hash = self.cached(Arc::as_ptr(lg) as usize, |_, h| {
+                    hash_f32_list(&[lg.x1, lg.y1, lg.x2, lg.y2], h);
+                    hash_base_gradient(&lg.base, h);
+                });
+                hash.hash(h);
+            }
+            Paint::RadialGradient(ref rg) => {
+                let hash = self.cached(Arc::as_ptr(rg) as usize, |_, h| {
+                    hash_f32_list(&[rg.cx, rg.cy, rg.r.get(), rg.fx, rg.fy], h);
+                    hash_base_gradient(&rg.base, h);
+                });
+                hash.hash(h);
+            }
+            Paint::Pattern(ref patt) => {
+                let hash = self.cached(Arc::as_ptr(patt) as usize, |this, h| {
+                    hash_transform(patt.transform, h);
+                    hash_non_zero_rect(patt.rect, h);
+                    this.hash_group(&patt.root, h);
+                });
+                hash.hash(h);
+            }
+        }
+    }
+
+    fn hash_clip_path(&mut self, clip: &Arc<ClipPath>, h: &mut DefaultHasher) {
+        let hash = self.cached(Arc::as_ptr(clip) as usize, |this, h| {
+            hash_transform(clip.transform, h);
+            match clip.clip_path {
+                Some(ref sub_clip) => this.hash_clip_path(sub_clip, h),
+                None => 0u64.hash(h),
+            }
+            this.hash_group(&clip.root, h);
+        });
+        hash.hash(h);
+    }
+
+    fn hash_mask(&mut self, mask: &Arc<Mask>, h: &mut DefaultHasher) {
+        let hash = self.cached(Arc::as_ptr(mask) as usize, |this, h| {
+            hash_non_zero_rect(mask.rect, h);
+            std::mem::discriminant(&mask.kind).hash(h);
+            match mask.mask {
+                Some(ref sub_mask) => this.hash_mask(sub_mask, h),
+                None => 0u64.hash(h),
+            }
+            this.hash_group(&mask.root, h);
+        });
+        hash.hash(h);
+    }
+
+    fn hash_filter(&mut self, filter: &Arc<filter::Filter>, h: &mut DefaultHasher) {
+        let hash = self.cached(Arc::as_ptr(filter) as usize, |this, h| {
+            hash_non_zero_rect(filter.rect, h);
+            filter.primitives.len().hash(h);
+            for primitive in &filter.primitives {
+                hash_non_zero_rect(primitive.rect, h);
+                std::mem::discriminant(&primitive.color_interpolation).hash(h);
+
+                // Results names are arbitrary, so inputs are replaced
+                // with indices of referenced primitives.
+                let mut kind = primitive.kind.clone();
+                for input in inputs_mut(&mut kind) {
+                    if let filter::Input::Reference(ref mut name) = input {
+                        let idx = filter.primitives.iter().position(|p| p.result == *name);
+                        *name = format!("{:?}", idx);
+                    }
+                }
+
+                if let filter::Kind::Image(ref mut image) = kind {
+                    this.hash_group(&image.root, h);
+                    // Exclude IDs and bounding boxes from the debug output below.
+                    image.root = Group::empty();
+                }
+
+                // All other primitives contain only plain values,
+                // so we can simply hash their debug representation.
+                hash_debug(&kind, h);
+            }
+        });
+        hash.hash(h);
+    }
+
+    fn cached(&mut self, key: usize, f: impl FnOnce(&mut Self, &mut DefaultHasher)) -> u64 {
+        if let Some(hash) = self.cache.get(&key) {
+            return *hash;
+        }
+
+        let mut h = DefaultHasher::new();
+        f(self, &mut h);
+        let hash = h.finish();
+        self.cache.insert(key, hash);
+        hash
+    }
+}
+
+fn inputs_mut(kind: &mut filter::Kind) -> Vec<&mut filter::Input> {
+    use filter::Kind;
+
+    match kind {
+        Kind::Blend(ref mut fe) => vec![&mut fe.input1, &mut fe.input2],
+        Kind::ColorMatrix(ref mut fe) => vec![&mut fe.input],
+        Kind::ComponentTransfer(ref mut fe) => vec![&mut fe.input],
+        Kind::Composite(ref mut fe) => vec![&mut fe.input1, &mut fe.input2],
+        Kind::ConvolveMatrix(ref mut fe) => vec![&mut fe.input],
+        Kind::DiffuseLighting(ref mut fe) => vec![&mut fe.input],
+        Kind::DisplacementMap(ref mut fe) => vec![&mut fe.input1, &mut fe.input2],
+        Kind::DropShadow(ref mut fe) => vec![&mut fe.input],
+        Kind::Flood(_) => Vec::new(),
+        Kind::GaussianBlur(ref mut fe) => vec![&mut fe.input],
+        Kind::Image(_) => Vec::new(),
+        Kind::Merge(ref mut fe) => fe.inputs.iter_mut().collect(),
+        Kind::Morphology(ref mut fe) => vec![&mut fe.input],
+        Kind::Offset(ref mut fe) => vec![&mut fe.input],
+        Kind::SpecularLighting(ref mut fe) => vec![&mut fe.input],
+        Kind::Tile(ref mut fe) => vec![&mut fe.input],
+        Kind::Turbulence(_) => Vec::new(),
+    }
+}
+
+fn hash_base_gradient(base: &BaseGradient, h: &mut DefaultHasher) {
+    hash_transform(base.transform, h);
+    std::mem::discriminant(&base.spread_method).hash(h);
+    std::mem::discriminant(&base.color_interpolation).hash(h);
+    base.stops.len().hash(h);
+    for stop in &base.stops {
+        hash_f32(stop.offset.get(), h);
+        (stop.color.red, stop.color.green, stop.color.blue).hash(h);
+        hash_f32(stop.opacity.get(), h);
+    }
+}
+
+fn hash_transform(ts: Transform, h: &mut DefaultHasher) {
+    hash_f32_list(&[ts.sx, ts.ky, ts.kx, ts.sy, ts.tx, ts.ty], h);
+}
+
+fn hash_non_zero_rect(r: NonZeroRect, h: &mut DefaultHasher) {
+    hash_f32_list(&[r.x(), r.y(), r.width(), r.height()], h);
+}
+
+fn hash_f32_list(list: &[f32], h: &mut DefaultHasher) {
+    list.len().hash(h);
+    for n in list {
+        hash_f32(*n, h);
+    }
+}
+
+fn hash_f32(n: f32, h: &mut DefaultHasher) {
+    // Make sure that 0 and -0 are the same.
+    let n = if n == 0.0 { 0.0 } else { n };
+    n.to_bits().hash(h);
+}
+
+fn hash_debug(v: &impl std::fmt::Debug, h: &mut DefaultHasher) {
+    format!("{:?}", v).hash(h);
+}
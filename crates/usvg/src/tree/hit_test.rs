@@ -0,0 +1,536 @@
+// Copyright 2024 the Resvg Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use kurbo::Shape;
+use tiny_skia_path::{PathSegment, PathStroker, Transform};
+
+use crate::{FlattenPath, IsValidLength};
+
+use super::*;
+
+impl Tree {
+    /// Returns the topmost node at the specified point.
+    ///
+    /// The point is in the tree coordinates, i.e. the same as used by
+    /// [`Node::abs_bounding_box`].
+    ///
+    /// Nodes are tested in the reverse paint order.
+    /// Transforms, fill rules, stroke outlines, visibility and clip paths are taken into account,
+    /// while masks, filters and opacity are ignored.
+    ///
+    /// Groups are never returned, only paths, images and text nodes.
+    pub fn node_at(&self, x: f32, y: f32) -> Option<&Node> {
+        node_at(&self.root, Transform::default(), x, y)
+    }
+
+    /// Returns clip paths that limit the visible region of a node.
+    ///
+    /// Includes clip paths of all ancestor groups and of the node itself.
+    ///
+    /// Returns `None` when the node is not found or is not clipped.
+    pub fn effective_clip(&self, id: &str) -> Option<EffectiveClip<'_>> {
+        if id.is_empty() {
+            return None;
+        }
+
+        let mut clips = Vec::new();
+        if !collect_clips(&self.root, id, &mut clips) || clips.is_empty() {
+            return None;
+        }
+
+        Some(EffectiveClip { clips })
+    }
+}
+
+/// Clip paths applied to a node, from the outermost ancestor to the node itself.
+///
+/// The visible region of a node is the intersection of all clip paths.
+/// It can be tested with [`EffectiveClip::contains`] or computed
+/// with [`EffectiveClip::to_path`].
+#[derive(Clone, Debug)]
+pub struct EffectiveClip<'a> {
+    clips: Vec<(&'a ClipPath, Transform)>,
+}
+
+impl<'a> EffectiveClip<'a> {
+    /// Clip paths with their canvas transforms.
+    ///
+    /// A transform maps clip path's user space to the canvas
+    /// and doesn't include [`ClipPath::transform`].
+    pub fn clip_paths(&self) -> &[(&'a ClipPath, Transform)] {
+        &self.clips
+    }
+
+    /// Checks that a point in the canvas coordinates is inside all clip paths.
+    ///
+    /// Uses the same rules as [`Tree::node_at`].
+    pub fn contains(&self, x: f32, y: f32) -> bool {
+        self.clips
+            .iter()
+            .all(|(clip, ts)| clip_contains(clip, *ts, x, y))
+    }
+
+    /// Returns the intersection of clip paths bounding boxes in the canvas coordinates.
+    ///
+    /// Returns `None` when bounding boxes do not overlap,
+    /// i.e. the node is clipped entirely.
+    pub fn bounding_box(&self) -> Option<Rect> {
+        let mut bbox: Option<Rect> = None;
+        for (clip, ts) in &self.clips {
+            let clip_bbox = clip_bbox(clip, *ts)?;
+            bbox = Some(match bbox {
+                Some(bbox) => bbox.intersect(&clip_bbox)?,
+                None => clip_bbox,
+            });
+        }
+
+        bbox
+    }
+
+    /// Returns the intersection of all clip paths as a single path in the canvas coordinates.
+    ///
+    /// Nested clip paths, transforms and `clip-rule`s are taken into account,
+    /// using the same rules as [`EffectiveClip::contains`].
+    /// Curves are approximated by lines, which deviate from them by no more than `tolerance`.
+    ///
+    /// The result consists of non-overlapping trapezoids, so it can be filled
+    /// using any fill rule, but is not suitable for stroking.
+    ///
+    /// Returns `None` when the intersection is empty, i.e. the node is clipped entirely,
+    /// or when `tolerance` is not positive.
+    pub fn to_path(&self, tolerance: f32) -> Option<tiny_skia_path::Path> {
+        if !tolerance.is_valid_length() {
+            return None;
+        }
+
+        let mut builder = RegionBuilder {
+            tolerance: tolerance as f64,
+            ..RegionBuilder::default()
+        };
+        let region = Region::Intersection(
+            self.clips
+                .iter()
+                .map(|(clip, ts)| builder.clip_region(clip, *ts))
+                .collect(),
+        );
+
+        builder.into_path(&region)
+    }
+}
+
+/// A boolean combination of filled paths.
+enum Region {
+    /// An index of a path in [`RegionBuilder::rules`].
+    Fill(usize),
+    Union(Vec<Region>),
+    Intersection(Vec<Region>),
+}
+
+impl Region {
+    fn contains(&self, rules: &[FillRule], windings: &[i32]) -> bool {
+        match self {
+            Region::Fill(idx) => match rules[*idx] {
+                FillRule::NonZero => windings[*idx] != 0,
+                FillRule::EvenOdd => windings[*idx] % 2 != 0,
+            },
+            Region::Union(list) => list.iter().any(|r| r.contains(rules, windings)),
+            Region::Intersection(list) => list.iter().all(|r| r.contains(rules, windings)),
+        }
+    }
+}
+
+/// A non-horizontal polygon edge directed downwards.
+struct Edge {
+    x0: f64,
+    y0: f64,
+    x1: f64,
+    y1: f64,
+    /// `1` when the original edge was directed downwards and `-1` otherwise.
+    winding: i32,
+    /// An index of a path in [`RegionBuilder::rules`].
+    path: usize,
+}
+
+impl Edge {
+    fn x_at(&self, y: f64) -> f64 {
+        self.x0 + (self.x1 - self.x0) * (y - self.y0) / (self.y1 - self.y0)
+    }
+
+    /// Returns the `y` coordinate at which two edges cross, if any.
+    fn crossing(&self, other: &Edge) -> Option<f64> {
+        let top = self.y0.max(other.y0);
+        let bottom = self.y1.min(other.y1);
+        if top >= bottom {
+            return None;
+        }
+
+        let d_top = self.x_at(top) - other.x_at(top);
+        let d_bottom = self.x_at(bottom) - other.x_at(bottom);
+        if d_top * d_bottom >= 0.0 {
+            return None;
+        }
+
+        Some(top + (bottom - top) * d_top / (d_top - d_bottom))
+    }
+}
+
+/// Collects polygon edges of clip paths, which are then intersected
+/// using horizontal bands that no edges cross.
+#[derive(Default)]
+struct RegionBuilder {
+    tolerance: f64,
+    rules: Vec<FillRule>,
+    edges: Vec<Edge>,
+}
+
+impl RegionBuilder {
+    /// Mirrors clip paths rendering.
+    fn clip_region(&mut self, clip: &ClipPath, ts: Transform) -> Region {
+        let mut list = vec![self.children_region(&clip.root, ts.pre_concat(clip.transform))];
+        if let Some(ref sub_clip) = clip.clip_path {
+            list.push(self.clip_region(sub_clip, ts));
+        }
+
+        Region::Intersection(list)
+    }
+
+    fn children_region(&mut self, parent: &Group, ts: Transform) -> Region {
+        let mut list = Vec::new();
+        for node in &parent.children {
+            match node {
+                Node::Path(ref path) => {
+                    if let (true, Some(fill)) = (path.visible, path.fill.as_ref()) {
+                        list.push(self.fill_region(&path.data, fill.rule, ts));
+                    }
+                }
+                Node::Text(ref text) => list.push(self.children_region(&text.flattened, ts)),
+                Node::Group(ref group) => {
+                    let ts = ts.pre_concat(group.transform);
+                    let region = self.children_region(group, ts);
+                    list.push(match group.clip_path {
+                        Some(ref clip) => {
+                            Region::Intersection(vec![region, self.clip_region(clip, ts)])
+                        }
+                        None => region,
+                    });
+                }
+                Node::Image(_) => {}
+            }
+        }
+
+        Region::Union(list)
+    }
+
+    fn fill_region(
+        &mut self,
+        data: &tiny_skia_path::Path,
+        rule: FillRule,
+        ts: Transform,
+    ) -> Region {
+        let path = self.rules.len();
+        self.rules.push(rule);
+
+        let data = data
+            .clone()
+            .transform(ts)
+            .and_then(|data| data.flatten(self.tolerance));
+        if let Some(data) = data {
+            // All subpaths are implicitly closed, like during filling.
+            let mut polygon = Vec::new();
+            for segment in data.segments() {
+                match segment {
+                    PathSegment::MoveTo(p) => {
+                        self.push_polygon(&polygon, path);
+                        polygon.clear();
+                        polygon.push(p);
+                    }
+                    PathSegment::LineTo(p) => polygon.push(p),
+                    _ => {}
+                }
+            }
+            self.push_polygon(&polygon, path);
+        }
+
+        Region::Fill(path)
+    }
+
+    fn push_polygon(&mut self, polygon: &[tiny_skia_path::Point], path: usize) {
+        for (i, p0) in polygon.iter().enumerate() {
+            let p1 = polygon[(i + 1) % polygon.len()];
+            let (top, bottom, winding) = match p0.y.partial_cmp(&p1.y) {
+                Some(std::cmp::Ordering::Less) => (*p0, p1, 1),
+                Some(std::cmp::Ordering::Greater) => (p1, *p0, -1),
+                // Horizontal edges do not affect winding.
+                _ => continue,
+            };
+
+            self.edges.push(Edge {
+                x0: top.x as f64,
+                y0: top.y as f64,
+                x1: bottom.x as f64,
+                y1: bottom.y as f64,
+                winding,
+                path,
+            });
+        }
+    }
+
+    fn into_path(mut self, region: &Region) -> Option<tiny_skia_path::Path> {
+        self.edges.sort_by(|a, b| a.y0.total_cmp(&b.y0));
+
+        // Bands are split at edge ends and crossings, so edges do not cross inside a band.
+        let mut ys = Vec::with_capacity(self.edges.len() * 2);
+        for (i, edge) in self.edges.iter().enumerate() {
+            ys.push(edge.y0);
+            ys.push(edge.y1);
+
+            for other in &self.edges[i + 1..] {
+                if other.y0 >= edge.y1 {
+                    break;
+                }
+
+                ys.extend(edge.crossing(other));
+            }
+        }
+        ys.sort_by(|a, b| a.total_cmp(b));
+        ys.dedup();
+
+        let mut builder = tiny_skia_path::PathBuilder::new();
+        let mut windings = vec![0; self.rules.len()];
+        let mut active: Vec<&Edge> = Vec::new();
+        let mut next = 0;
+        for band in ys.windows(2) {
+            let (top, bottom) = (band[0], band[1]);
+            while next < self.edges.len() && self.edges[next].y0 <= top {
+                active.push(&self.edges[next]);
+                next += 1;
+            }
+            active.retain(|edge| edge.y1 > top);
+
+            let middle = (top + bottom) / 2.0;
+            active.sort_by(|a, b| a.x_at(middle).total_cmp(&b.x_at(middle)));
+
+            // Find spans inside the region from left to right.
+            windings.iter_mut().for_each(|w| *w = 0);
+            let mut start = None;
+            for edge in &active {
+                windings[edge.path] += edge.winding;
+                let inside = region.contains(&self.rules, &windings);
+                match start {
+                    None if inside => start = Some(edge),
+                    Some(left) if !inside => {
+                        builder.move_to(left.x_at(top) as f32, top as f32);
+                        builder.line_to(edge.x_at(top) as f32, top as f32);
+                        builder.line_to(edge.x_at(bottom) as f32, bottom as f32);
+                        builder.line_to(left.x_at(bottom) as f32, bottom as f32);
+                        builder.close();
+                        start = None;
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        builder.finish()
+    }
+}
+
+fn collect_clips<'a>(
+    parent: &'a Group,
+    id: &str,
+    clips: &mut Vec<(&'a ClipPath, Transform)>,
+) -> bool {
+    for node in &parent.children {
+        let Node::Group(ref group) = node else {
+            if node.id() == id {
+                return true;
+            }
+
+            continue;
+        };
+
+        let len = clips.len();
+        if let Some(ref clip) = group.clip_path {
+            clips.push((clip, group.abs_transform));
+        }
+
+        if group.id() == id || collect_clips(group, id, clips) {
+            return true;
+        }
+
+        clips.truncate(len);
+    }
+
+    false
+}
+
+fn clip_bbox(clip: &ClipPath, ts: Transform) -> Option<Rect> {
+    let bbox = clip
+        .root
+        .bounding_box
+        .transform(ts.pre_concat(clip.transform))?;
+    match clip.clip_path {
+        Some(ref sub_clip) => bbox.intersect(&clip_bbox(sub_clip, ts)?),
+        None => Some(bbox),
+    }
+}
+
+fn node_at(parent: &Group, ts: Transform, x: f32, y: f32) -> Option<&Node> {
+    for node in parent.children.iter().rev() {
+        match node {
+            Node::Group(ref group) => {
+                let ts = ts.pre_concat(group.transform);
+                if !bbox_contains(group.stroke_bounding_box, ts, x, y) {
+                    continue;
+                }
+
+                if let Some(ref clip) = group.clip_path {
+                    if !clip_contains(clip, ts, x, y) {
+                        continue;
+                    }
+                }
+
+                if let Some(node) = node_at(group, ts, x, y) {
+                    return Some(node);
+                }
+            }
+            Node::Path(ref path) => {
+                if path_contains(path, ts, x, y) {
+                    return Some(node);
+                }
+            }
+            Node::Image(ref image) => {
+                if image.visible && bbox_contains(image.bounding_box(), ts, x, y) {
+                    return Some(node);
+                }
+            }
+            Node::Text(ref text) => {
+                // Flattened text is already in the parent coordinates.
+                if node_at(&text.flattened, ts, x, y).is_some() {
+                    return Some(node);
+                }
+            }
+        }
+    }
+
+    None
+}
+
+fn clip_contains(clip: &ClipPath, ts: Transform, x: f32, y: f32) -> bool {
+    if let Some(ref sub_clip) = clip.clip_path {
+        if !clip_contains(sub_clip, ts, x, y) {
+            return false;
+        }
+    }
+
+    node_at(&clip.root, ts.pre_concat(clip.transform), x, y).is_some()
+}
+
+fn path_contains(path: &Path, ts: Transform, x: f32, y: f32) -> bool {
+    if !path.visible || !bbox_contains(path.stroke_bounding_box, ts, x, y) {
+        return false;
+    }
+
+    let (local_x, local_y) = match to_local(ts, x, y) {
+        Some(v) => v,
+        None => return false,
+    };
+
+    if let Some(ref fill) = path.fill {
+        if fill_contains(&path.data, fill.rule, local_x, local_y) {
+            return true;
+        }
+    }
+
+    if let Some(ref stroke) = path.stroke {
+        if path.vector_effect == VectorEffect::NonScalingStroke {
+            // The stroke is applied in the canvas coordinates.
+            if let Some(data) = path.data.as_ref().clone().transform(ts) {
+                return stroke_contains(&data, stroke, 1.0, x, y);
+            }
+        } else {
+            // Make sure that the outline is precise enough for the current scale.
+            let res_scale = PathStroker::compute_resolution_scale(&ts);
+            return stroke_contains(&path.data, stroke, res_scale, local_x, local_y);
+        }
+    }
+
+    false
+}
+
+fn stroke_contains(
+    path: &tiny_skia_path::Path,
+    stroke: &Stroke,
+    res_scale: f32,
+    x: f32,
+    y: f32,
+) -> bool {
+    let stroke = stroke.to_tiny_skia();
+    let outline = match stroke.dash {
+        Some(ref dash) => path
+            .dash(dash, res_scale)
+            .and_then(|p| p.stroke(&stroke, res_scale)),
+        None => path.stroke(&stroke, res_scale),
+    };
+
+    match outline {
+        Some(outline) => fill_contains(&outline, FillRule::NonZero, x, y),
+        None => false,
+    }
+}
+
+fn fill_contains(path: &tiny_skia_path::Path, rule: FillRule, x: f32, y: f32) -> bool {
+    fn close(bez_path: &mut kurbo::BezPath) {
+        if !matches!(
+            bez_path.elements().last(),
+            Some(kurbo::PathEl::ClosePath) | None
+        ) {
+            bez_path.close_path();
+        }
+    }
+
+    let mut bez_path = kurbo::BezPath::new();
+    for segment in path.segments() {
+        match segment {
+            PathSegment::MoveTo(p) => {
+                // All subpaths are implicitly closed during filling.
+                close(&mut bez_path);
+
+                bez_path.move_to((p.x as f64, p.y as f64));
+            }
+            PathSegment::LineTo(p) => bez_path.line_to((p.x as f64, p.y as f64)),
+            PathSegment::QuadTo(p1, p) => {
+                bez_path.quad_to((p1.x as f64, p1.y as f64), (p.x as f64, p.y as f64))
+            }
+            PathSegment::CubicTo(p1, p2, p) => bez_path.curve_to(
+                (p1.x as f64, p1.y as f64),
+                (p2.x as f64, p2.y as f64),
+                (p.x as f64, p.y as f64),
+            ),
+            PathSegment::Close => close(&mut bez_path),
+        }
+    }
+    close(&mut bez_path);
+
+    let winding = bez_path.winding(kurbo::Point::new(x as f64, y as f64));
+    match rule {
+        FillRule::NonZero => winding != 0,
+        FillRule::EvenOdd => winding % 2 != 0,
+    }
+}
+
+/// Checks that a point in the canvas coordinates is inside a local bounding box.
+fn bbox_contains(bbox: Rect, ts: Transform, x: f32, y: f32) -> bool {
+    match to_local(ts, x, y) {
+        Some((x, y)) => {
+            x >= bbox.left() && x <= bbox.right() && y >= bbox.top() && y <= bbox.bottom()
+        }
+        None => false,
+    }
+}
+
+fn to_local(ts: Transform, x: f32, y: f32) -> Option<(f32, f32)> {
+    let mut p = tiny_skia_path::Point::from_xy(x, y);
+    ts.invert()?.map_point(&mut p);
+    Some((p.x, p.y))
+}
@@ -0,0 +1,114 @@
+// Copyright 2024 the Resvg Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use tiny_skia_path::NonZeroRect;
+
+use super::*;
+
+impl Tree {
+    /// Replaces constant masks with group opacity.
+    ///
+    /// A mask is considered constant when it consists of a single axis-aligned rectangle
+    /// filled with a color, doesn't reference another mask and, together with the mask region,
+    /// covers the whole masked group.
+    ///
+    /// Such a mask is equivalent to a group opacity, which doesn't require
+    /// a separate mask layer during rendering.
+    /// All other masks will be left intact.
+    ///
+    /// Only groups of the main tree are processed. Masks applied inside patterns,
+    /// masks, clip paths, `feImage` children and text are left intact as well.
+    ///
+    /// The opacity approximates the 8-bit mask used by `resvg`, therefore the result
+    /// can differ by one 8-bit step, especially for luminance masks with fractional
+    /// values.
+    pub fn flatten_masks(&mut self) {
+        flatten_masks(&mut self.root);
+        self.remove_unused_defs();
+    }
+}
+
+fn flatten_masks(parent: &mut Group) {
+    for node in &mut parent.children {
+        if let Node::Group(ref mut group) = node {
+            flatten_masks(group);
+
+            let bbox = group.layer_bounding_box;
+            let opacity = group
+                .mask
+                .as_deref()
+                .and_then(|mask| constant_mask_opacity(mask, bbox));
+            if let Some(opacity) = opacity {
+                group.opacity = group.opacity * opacity;
+                group.mask = None;
+                // A masked group is rendered on its own layer, which affects blending
+                // of its children even when the mask is fully opaque.
+                group.isolate = true;
+            }
+        }
+    }
+}
+
+/// Returns a mask value, when the mask is constant inside the `bbox`.
+fn constant_mask_opacity(mask: &Mask, bbox: NonZeroRect) -> Option<Opacity> {
+    if mask.mask.is_some() || !contains(mask.rect, bbox) {
+        return None;
+    }
+
+    let mut ts = Transform::default();
+    let mut group = &mask.root;
+    loop {
+        if group.opacity != Opacity::ONE
+            || group.clip_path.is_some()
+            || group.mask.is_some()
+            || !group.filters.is_empty()
+        {
+            return None;
+        }
+
+        ts = ts.pre_concat(group.transform);
+
+        match group.children.as_slice() {
+            [Node::Group(ref g)] => group = g,
+            [Node::Path(ref path)] => {
+                if !path.visible || path.stroke.is_some() || ts.has_skew() {
+                    return None;
+                }
+
+                let fill = path.fill.as_ref()?;
+                let color = match fill.paint {
+                    Paint::Color(c) => c,
+                    _ => return None,
+                };
+
+                let rect = super::clip::path_to_rect(&path.data)?.transform(ts)?;
+                if !contains(rect, bbox) {
+                    return None;
+                }
+
+                // Masks are stored as 8-bit values, so the result is quantized similarly.
+                // Luminance is rounded up, which matches `resvg` for the tested colors,
+                // but not necessarily for all of them.
+                let value = match mask.kind {
+                    MaskType::Luminance => (luminance(color) * fill.opacity.get() * 255.0).ceil(),
+                    MaskType::Alpha => (fill.opacity.get() * 255.0).round(),
+                };
+
+                return Some(Opacity::new_clamped(value / 255.0));
+            }
+            _ => return None,
+        }
+    }
+}
+
+/// Uses the same coefficients as `resvg`.
+fn luminance(c: Color) -> f32 {
+    (c.red as f32 * 0.2126 + c.green as f32 * 0.7152 + c.blue as f32 * 0.0722) / 255.0
+}
+
+fn contains(outer: NonZeroRect, inner: NonZeroRect) -> bool {
+    outer.left() <= inner.left()
+        && outer.top() <= inner.top()
+        && outer.right() >= inner.right()
+        && outer.bottom() >= inner.bottom()
+}
@@ -1,20 +1,34 @@
 // Copyright 2019 the Resvg Authors
 // SPDX-License-Identifier: Apache-2.0 OR MIT
 
+mod builder;
+mod clip;
+mod current_color;
+mod display_list;
 pub mod filter;
 mod geom;
+mod gradient;
+mod hash;
+mod hit_test;
+mod mask;
+mod overlay;
 mod text;
+mod visit;
 
+use std::collections::HashMap;
 use std::sync::Arc;
 
 pub use strict_num::{self, ApproxEqUlps, NonZeroPositiveF32, NormalizedF32, PositiveF32};
 
 pub use tiny_skia_path;
 
+pub use self::builder::TreeBuilder;
+pub use self::display_list::DrawCommand;
 pub use self::geom::*;
+pub use self::hit_test::EffectiveClip;
+pub(crate) use self::overlay::prepend_transform;
 pub use self::text::*;
-
-use crate::OptionLog;
+pub use self::visit::{NodeVisitor, RecolorVisitor};
 
 /// An alias to `NormalizedF32`.
 pub type Opacity = NormalizedF32;
@@ -169,7 +183,7 @@ impl std::str::FromStr for TextRendering {
 pub enum ImageRendering {
     OptimizeQuality,
     OptimizeSpeed,
-    // The following can only appear as presentation attributes.
+    // The following can only be set via CSS or the `style` attribute.
     Smooth,
     HighQuality,
     CrispEdges,
@@ -245,6 +259,23 @@ impl Default for SpreadMethod {
     }
 }
 
+/// A color space used to interpolate gradient stops.
+///
+/// `color-interpolation` property in the SVG, extended with CSS Color 4 color spaces:
+/// `lab`, `lch`, `oklab` and `oklch`. Polar color spaces use the shorter hue arc.
+///
+/// `linearRGB` is not supported for gradients, just like in browsers, and resolves to `sRGB`.
+#[allow(missing_docs)]
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+pub enum GradientInterpolation {
+    #[default]
+    SRGB,
+    Lab,
+    Lch,
+    OkLab,
+    OkLch,
+}
+
 /// A generic gradient.
 #[derive(Debug)]
 pub struct BaseGradient {
@@ -252,6 +283,7 @@ pub struct BaseGradient {
     pub(crate) units: Units, // used only during parsing
     pub(crate) transform: Transform,
     pub(crate) spread_method: SpreadMethod,
+    pub(crate) color_interpolation: GradientInterpolation,
     pub(crate) stops: Vec<Stop>,
 }
 
@@ -278,6 +310,16 @@ impl BaseGradient {
         self.spread_method
     }
 
+    /// A color space used to interpolate stops.
+    ///
+    /// `color-interpolation` in SVG.
+    ///
+    /// `resvg` renders gradients in the sRGB color space, therefore other color spaces
+    /// have to be approximated with additional stops. See [`BaseGradient::sample`].
+    pub fn color_interpolation(&self) -> GradientInterpolation {
+        self.color_interpolation
+    }
+
     /// A list of `stop` elements.
     pub fn stops(&self) -> &[Stop] {
         &self.stops
@@ -385,9 +427,20 @@ pub struct Stop {
     pub(crate) offset: StopOffset,
     pub(crate) color: Color,
     pub(crate) opacity: Opacity,
+    pub(crate) current_color: bool,
 }
 
 impl Stop {
+    /// Creates a new gradient stop.
+    pub fn new(offset: StopOffset, color: Color, opacity: Opacity) -> Self {
+        Stop {
+            offset,
+            color,
+            opacity,
+            current_color: false,
+        }
+    }
+
     /// Gradient stop offset.
     ///
     /// `offset` in SVG.
@@ -408,6 +461,13 @@ impl Stop {
     pub fn opacity(&self) -> Opacity {
         self.opacity
     }
+
+    /// Checks that the color was resolved from `currentColor`.
+    ///
+    /// See [`Tree::set_current_color`].
+    pub fn is_current_color(&self) -> bool {
+        self.current_color
+    }
 }
 
 /// A pattern element.
@@ -443,14 +503,45 @@ impl Pattern {
     /// Pattern rectangle.
     ///
     /// `x`, `y`, `width` and `height` in SVG.
+    ///
+    /// Always in `userSpaceOnUse` units, since `objectBoundingBox` units
+    /// are resolved during parsing.
     pub fn rect(&self) -> NonZeroRect {
         self.rect
     }
 
     /// Pattern children.
+    ///
+    /// Coordinates are relative to the tile origin.
+    /// `patternContentUnits` and `viewBox` are already applied.
     pub fn root(&self) -> &Group {
         &self.root
     }
+
+    /// Returns a tile rectangle at the specified position in the tiles grid.
+    ///
+    /// The tile `(0, 0)` is the pattern rectangle itself.
+    ///
+    /// The rectangle is in the pattern coordinates, i.e. before applying
+    /// the [`Pattern::transform`].
+    pub fn tile_rect(&self, column: i32, row: i32) -> NonZeroRect {
+        let w = self.rect.width();
+        let h = self.rect.height();
+        NonZeroRect::from_xywh(
+            self.rect.x() + column as f32 * w,
+            self.rect.y() + row as f32 * h,
+            w,
+            h,
+        )
+        .unwrap_or(self.rect)
+    }
+
+    /// Returns a transform from the [`Pattern::root`] coordinates into the user space
+    /// of the painted element for a tile at the specified position in the tiles grid.
+    pub fn tile_transform(&self, column: i32, row: i32) -> Transform {
+        let rect = self.tile_rect(column, row);
+        self.transform.pre_translate(rect.x(), rect.y())
+    }
 }
 
 /// An alias to `NonZeroPositiveF32`.
@@ -551,6 +642,7 @@ pub struct Stroke {
     // Whether the current stroke needs to be resolved relative
     // to a context element.
     pub(crate) context_element: Option<ContextElement>,
+    pub(crate) current_color: bool,
 }
 
 impl Stroke {
@@ -594,6 +686,33 @@ impl Stroke {
         self.linejoin
     }
 
+    /// Checks that the paint was resolved from `currentColor`.
+    ///
+    /// See [`Tree::set_current_color`].
+    pub fn is_current_color(&self) -> bool {
+        self.current_color
+    }
+
+    /// Sets stroke paint.
+    ///
+    /// The new paint is never treated as `currentColor`.
+    pub fn set_paint(&mut self, paint: Paint) {
+        self.paint = paint;
+        self.current_color = false;
+    }
+
+    /// Sets stroke opacity.
+    pub fn set_opacity(&mut self, opacity: Opacity) {
+        self.opacity = opacity;
+    }
+
+    /// Sets stroke width.
+    ///
+    /// Bounding boxes of the path will be updated only by [`Tree::visit_mut`].
+    pub fn set_width(&mut self, width: StrokeWidth) {
+        self.width = width;
+    }
+
     /// Converts into a `tiny_skia_path::Stroke` type.
     pub fn to_tiny_skia(&self) -> tiny_skia_path::Stroke {
         let mut stroke = tiny_skia_path::Stroke {
@@ -623,6 +742,23 @@ impl Stroke {
     }
 }
 
+impl Default for Stroke {
+    fn default() -> Self {
+        Stroke {
+            paint: Paint::Color(Color::black()),
+            dasharray: None,
+            dashoffset: 0.0,
+            miterlimit: StrokeMiterlimit::default(),
+            opacity: Opacity::ONE,
+            width: StrokeWidth::new(1.0).unwrap(),
+            linecap: LineCap::default(),
+            linejoin: LineJoin::default(),
+            context_element: None,
+            current_color: false,
+        }
+    }
+}
+
 /// A fill rule.
 ///
 /// `fill-rule` attribute in the SVG.
@@ -662,6 +798,7 @@ pub struct Fill {
     // Whether the current fill needs to be resolved relative
     // to a context element.
     pub(crate) context_element: Option<ContextElement>,
+    pub(crate) current_color: bool,
 }
 
 impl Fill {
@@ -679,6 +816,26 @@ impl Fill {
     pub fn rule(&self) -> FillRule {
         self.rule
     }
+
+    /// Checks that the paint was resolved from `currentColor`.
+    ///
+    /// See [`Tree::set_current_color`].
+    pub fn is_current_color(&self) -> bool {
+        self.current_color
+    }
+
+    /// Sets fill paint.
+    ///
+    /// The new paint is never treated as `currentColor`.
+    pub fn set_paint(&mut self, paint: Paint) {
+        self.paint = paint;
+        self.current_color = false;
+    }
+
+    /// Sets fill opacity.
+    pub fn set_opacity(&mut self, opacity: Opacity) {
+        self.opacity = opacity;
+    }
 }
 
 impl Default for Fill {
@@ -688,6 +845,7 @@ impl Default for Fill {
             opacity: Opacity::ONE,
             rule: FillRule::default(),
             context_element: None,
+            current_color: false,
         }
     }
 }
@@ -719,11 +877,58 @@ impl Color {
     pub fn white() -> Color {
         Color::new_rgb(255, 255, 255)
     }
+
+    /// Converts the color into linear RGB.
+    ///
+    /// Channels are in the 0..=1 range.
+    pub fn to_linear_rgb(&self) -> [f32; 3] {
+        fn to_linear(c: u8) -> f32 {
+            let c = c as f32 / 255.0;
+            if c <= 0.04045 {
+                c / 12.92
+            } else {
+                ((c + 0.055) / 1.055).powf(2.4)
+            }
+        }
+
+        [
+            to_linear(self.red),
+            to_linear(self.green),
+            to_linear(self.blue),
+        ]
+    }
+
+    /// Constructs a new `Color` from linear RGB.
+    ///
+    /// Channels are clamped to the 0..=1 range.
+    pub fn from_linear_rgb(rgb: [f32; 3]) -> Color {
+        fn from_linear(c: f32) -> u8 {
+            let c = c.clamp(0.0, 1.0);
+            let c = if c <= 0.0031308 {
+                c * 12.92
+            } else {
+                1.055 * c.powf(1.0 / 2.4) - 0.055
+            };
+
+            (c * 255.0).round() as u8
+        }
+
+        Color::new_rgb(
+            from_linear(rgb[0]),
+            from_linear(rgb[1]),
+            from_linear(rgb[2]),
+        )
+    }
 }
 
 /// A paint style.
 ///
 /// `paint` value type in the SVG.
+///
+/// Paint servers are always in the `userSpaceOnUse` units.
+/// `objectBoundingBox` units are resolved during parsing using the bounding box
+/// of the element the paint is set on, including text spans.
+/// Therefore a single SVG paint server can be represented by multiple instances.
 #[allow(missing_docs)]
 #[derive(Clone, Debug)]
 pub enum Paint {
@@ -935,6 +1140,70 @@ impl Node {
         }
     }
 
+    /// Returns node's "ink" bounding box in object coordinates.
+    ///
+    /// The painted extent of the node. Unlike [`bounding_box`](Self::bounding_box),
+    /// which includes only the geometry, it includes:
+    ///
+    /// - stroke, like [`stroke_bounding_box`](Self::stroke_bounding_box)
+    /// - markers of a path, which are stored as a separate group next to it
+    /// - filter regions of groups, which can expand or clip the content,
+    ///   just like in [`Group::layer_bounding_box`]
+    ///
+    /// Clip paths and masks are not taken into account. Therefore it can be larger
+    /// than the actual painted area, but never smaller.
+    ///
+    /// Returns a zero rect for empty groups without filters.
+    pub fn ink_bounding_box(&self) -> Rect {
+        match self {
+            Node::Group(ref group) => {
+                if group.has_children() || !group.filters.is_empty() {
+                    group.layer_bounding_box.to_rect()
+                } else {
+                    group.stroke_bounding_box
+                }
+            }
+            Node::Path(ref path) => match path.markers_bounding_box {
+                Some(markers_bbox) => BBox::from(path.stroke_bounding_box)
+                    .expand(markers_bbox)
+                    .to_rect()
+                    .unwrap_or(path.stroke_bounding_box),
+                None => path.stroke_bounding_box,
+            },
+            Node::Image(ref image) => image.bounding_box(),
+            Node::Text(ref text) => text.stroke_bounding_box(),
+        }
+    }
+
+    /// Returns node's "ink" bounding box in canvas coordinates.
+    ///
+    /// See [`ink_bounding_box`](Self::ink_bounding_box) for details.
+    pub fn abs_ink_bounding_box(&self) -> Rect {
+        match self {
+            Node::Group(ref group) => {
+                if group.has_children() || !group.filters.is_empty() {
+                    group.abs_layer_bounding_box.to_rect()
+                } else {
+                    group.abs_stroke_bounding_box
+                }
+            }
+            Node::Path(ref path) => {
+                let markers_bbox = path
+                    .markers_bounding_box
+                    .and_then(|r| r.transform(path.abs_transform));
+                match markers_bbox {
+                    Some(markers_bbox) => BBox::from(path.abs_stroke_bounding_box)
+                        .expand(markers_bbox)
+                        .to_rect()
+                        .unwrap_or(path.abs_stroke_bounding_box),
+                    None => path.abs_stroke_bounding_box,
+                }
+            }
+            Node::Image(ref image) => image.abs_bounding_box(),
+            Node::Text(ref text) => text.abs_stroke_bounding_box(),
+        }
+    }
+
     /// Element's "layer" bounding box in canvas units, if any.
     ///
     /// For most nodes this is just `abs_bounding_box`,
@@ -951,6 +1220,50 @@ impl Node {
         }
     }
 
+    /// Returns node's bounding box as if `transform` was its absolute transform.
+    ///
+    /// Unlike [`abs_bounding_box`](Self::abs_bounding_box), which uses the stored
+    /// [`abs_transform`](Self::abs_transform), the bounding box of the whole subtree
+    /// is recalculated. Useful to measure a node before moving it into a different parent.
+    ///
+    /// Therefore `node.bounding_box_under(node.abs_transform())`
+    /// is the same as `node.abs_bounding_box()`.
+    ///
+    /// Returns `None` for empty groups.
+    pub fn bounding_box_under(&self, transform: Transform) -> Option<Rect> {
+        match self {
+            Node::Group(ref group) => {
+                let mut bbox = BBox::default();
+                for child in &group.children {
+                    let ts = match child {
+                        Node::Group(ref g) => transform.pre_concat(g.transform),
+                        _ => transform,
+                    };
+
+                    if let Some(r) = child.bounding_box_under(ts) {
+                        bbox = bbox.expand(r);
+                    }
+                }
+
+                bbox.to_rect()
+            }
+            Node::Path(ref path) => {
+                let mut stroke_bbox = path.bounding_box;
+                Path::calculate_abs_bboxes(
+                    &path.data,
+                    None,
+                    false,
+                    path.bounding_box,
+                    &mut stroke_bbox,
+                    transform,
+                )
+                .map(|(bbox, _)| bbox)
+            }
+            Node::Image(ref image) => image.bounding_box().transform(transform),
+            Node::Text(ref text) => text.bounding_box.transform(transform),
+        }
+    }
+
     /// Calls a closure for each subroot this `Node` has.
     ///
     /// The [`Tree::root`](Tree::root) field contain only render-able SVG elements.
@@ -1002,6 +1315,7 @@ pub struct Group {
     pub(crate) clip_path: Option<Arc<ClipPath>>,
     /// Whether the group is a context element (i.e. a use node)
     pub(crate) is_context_element: bool,
+    pub(crate) use_href: Option<String>,
     pub(crate) mask: Option<Arc<Mask>>,
     pub(crate) filters: Vec<Arc<filter::Filter>>,
     pub(crate) bounding_box: Rect,
@@ -1027,6 +1341,7 @@ impl Group {
             mask: None,
             filters: Vec::new(),
             is_context_element: false,
+            use_href: None,
             bounding_box: dummy,
             abs_bounding_box: dummy,
             stroke_bounding_box: dummy,
@@ -1046,6 +1361,17 @@ impl Group {
         &self.id
     }
 
+    /// ID of the element referenced by the `use` element this group was created from.
+    ///
+    /// When a `use` element references a `symbol` or a nested `svg`,
+    /// this is the `symbol` or `svg` ID.
+    ///
+    /// Set only when [`Options::keep_use_references`](crate::Options::keep_use_references)
+    /// is enabled. Otherwise, always `None`.
+    pub fn use_href(&self) -> Option<&str> {
+        self.use_href.as_deref()
+    }
+
     /// Element's transform.
     ///
     /// This is a relative transform. The one that is set via the `transform` attribute in SVG.
@@ -1237,6 +1563,47 @@ impl Default for PaintOrder {
     }
 }
 
+/// Representation of the [`vector-effect`] property.
+///
+/// [`vector-effect`]: https://www.w3.org/TR/SVG2/coords.html#VectorEffects
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum VectorEffect {
+    /// No effect.
+    #[default]
+    None,
+    /// The stroke must be applied in the canvas coordinates,
+    /// meaning that its width will not be affected by transforms.
+    NonScalingStroke,
+}
+
+/// A path segment with elliptical arcs preserved.
+///
+/// See [`Path::arc_segments`].
+#[allow(missing_docs)]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ArcPathSegment {
+    MoveTo(tiny_skia_path::Point),
+    LineTo(tiny_skia_path::Point),
+    QuadTo(tiny_skia_path::Point, tiny_skia_path::Point),
+    CubicTo(
+        tiny_skia_path::Point,
+        tiny_skia_path::Point,
+        tiny_skia_path::Point,
+    ),
+    /// An elliptical arc in the SVG endpoint parameterization.
+    ///
+    /// `x_axis_rotation` is in degrees.
+    ArcTo {
+        rx: f32,
+        ry: f32,
+        x_axis_rotation: f32,
+        large_arc: bool,
+        sweep: bool,
+        to: tiny_skia_path::Point,
+    },
+    Close,
+}
+
 /// A path element.
 #[derive(Clone, Debug)]
 pub struct Path {
@@ -1246,23 +1613,46 @@ pub struct Path {
     pub(crate) stroke: Option<Stroke>,
     pub(crate) paint_order: PaintOrder,
     pub(crate) rendering_mode: ShapeRendering,
+    pub(crate) vector_effect: VectorEffect,
     pub(crate) data: Arc<tiny_skia_path::Path>,
+    pub(crate) arc_segments: Option<Arc<Vec<ArcPathSegment>>>,
     pub(crate) abs_transform: Transform,
     pub(crate) bounding_box: Rect,
     pub(crate) abs_bounding_box: Rect,
     pub(crate) stroke_bounding_box: Rect,
     pub(crate) abs_stroke_bounding_box: Rect,
+    pub(crate) markers_bounding_box: Option<NonZeroRect>,
+}
+
+/// Presentation attributes of a [`Path`].
+#[derive(Clone, Debug)]
+pub(crate) struct PathStyle {
+    pub(crate) visible: bool,
+    pub(crate) fill: Option<Fill>,
+    pub(crate) stroke: Option<Stroke>,
+    pub(crate) paint_order: PaintOrder,
+    pub(crate) rendering_mode: ShapeRendering,
+    pub(crate) vector_effect: VectorEffect,
+}
+
+impl Default for PathStyle {
+    fn default() -> Self {
+        PathStyle {
+            visible: true,
+            fill: None,
+            stroke: None,
+            paint_order: PaintOrder::default(),
+            rendering_mode: ShapeRendering::default(),
+            vector_effect: VectorEffect::default(),
+        }
+    }
 }
 
 impl Path {
     pub(crate) fn new_simple(data: Arc<tiny_skia_path::Path>) -> Option<Self> {
         Self::new(
             String::new(),
-            true,
-            None,
-            None,
-            PaintOrder::default(),
-            ShapeRendering::default(),
+            PathStyle::default(),
             data,
             Transform::default(),
         )
@@ -1270,32 +1660,34 @@ impl Path {
 
     pub(crate) fn new(
         id: String,
-        visible: bool,
-        fill: Option<Fill>,
-        stroke: Option<Stroke>,
-        paint_order: PaintOrder,
-        rendering_mode: ShapeRendering,
+        style: PathStyle,
         data: Arc<tiny_skia_path::Path>,
         abs_transform: Transform,
     ) -> Option<Self> {
+        let PathStyle {
+            visible,
+            fill,
+            stroke,
+            paint_order,
+            rendering_mode,
+            vector_effect,
+        } = style;
+
         let bounding_box = data.compute_tight_bounds()?;
-        let stroke_bounding_box =
+        let mut stroke_bounding_box =
             Path::calculate_stroke_bbox(stroke.as_ref(), &data).unwrap_or(bounding_box);
 
-        let abs_bounding_box: Rect;
-        let abs_stroke_bounding_box: Rect;
-        if abs_transform.has_skew() {
-            // TODO: avoid re-alloc
-            let path2 = data.as_ref().clone();
-            let path2 = path2.transform(abs_transform)?;
-            abs_bounding_box = path2.compute_tight_bounds()?;
-            abs_stroke_bounding_box =
-                Path::calculate_stroke_bbox(stroke.as_ref(), &path2).unwrap_or(abs_bounding_box);
-        } else {
-            // A transform without a skew can be performed just on a bbox.
-            abs_bounding_box = bounding_box.transform(abs_transform)?;
-            abs_stroke_bounding_box = stroke_bounding_box.transform(abs_transform)?;
-        }
+        let non_scaling_stroke =
+            vector_effect == VectorEffect::NonScalingStroke && stroke.is_some();
+
+        let (abs_bounding_box, abs_stroke_bounding_box) = Path::calculate_abs_bboxes(
+            &data,
+            stroke.as_ref(),
+            non_scaling_stroke,
+            bounding_box,
+            &mut stroke_bounding_box,
+            abs_transform,
+        )?;
 
         Some(Path {
             id,
@@ -1304,15 +1696,86 @@ impl Path {
             stroke,
             paint_order,
             rendering_mode,
+            vector_effect,
             data,
+            arc_segments: None,
             abs_transform,
             bounding_box,
             abs_bounding_box,
             stroke_bounding_box,
             abs_stroke_bounding_box,
+            markers_bounding_box: None,
         })
     }
 
+    /// Calculates fill and stroke bounding boxes of `data` under `abs_transform`.
+    ///
+    /// For a non-scaling stroke, `stroke_bounding_box` is updated as well,
+    /// since the stroke is applied in the canvas coordinates.
+    fn calculate_abs_bboxes(
+        data: &tiny_skia_path::Path,
+        stroke: Option<&Stroke>,
+        non_scaling_stroke: bool,
+        bounding_box: Rect,
+        stroke_bounding_box: &mut Rect,
+        abs_transform: Transform,
+    ) -> Option<(Rect, Rect)> {
+        if abs_transform.has_skew() || non_scaling_stroke {
+            // TODO: avoid re-alloc
+            let path2 = data.clone();
+            let path2 = path2.transform(abs_transform)?;
+            let abs_bounding_box = path2.compute_tight_bounds()?;
+            let abs_stroke_bounding_box =
+                Path::calculate_stroke_bbox(stroke, &path2).unwrap_or(abs_bounding_box);
+
+            if non_scaling_stroke {
+                *stroke_bounding_box = abs_transform
+                    .invert()
+                    .and_then(|ts| abs_stroke_bounding_box.transform(ts))
+                    .unwrap_or(bounding_box);
+            }
+
+            Some((abs_bounding_box, abs_stroke_bounding_box))
+        } else {
+            // A transform without a skew can be performed just on a bbox.
+            Some((
+                bounding_box.transform(abs_transform)?,
+                stroke_bounding_box.transform(abs_transform)?,
+            ))
+        }
+    }
+
+    /// Recalculates bounding boxes after the stroke or the absolute transform were changed.
+    pub(crate) fn recalculate_bounding_boxes(&mut self, abs_transform: Transform) -> Option<()> {
+        let style = PathStyle {
+            visible: self.visible,
+            fill: None,
+            stroke: self.stroke.clone(),
+            paint_order: self.paint_order,
+            rendering_mode: self.rendering_mode,
+            vector_effect: self.vector_effect,
+        };
+        let path = Path::new(String::new(), style, self.data.clone(), abs_transform)?;
+
+        self.abs_transform = path.abs_transform;
+        self.bounding_box = path.bounding_box;
+        self.abs_bounding_box = path.abs_bounding_box;
+        self.stroke_bounding_box = path.stroke_bounding_box;
+        self.abs_stroke_bounding_box = path.abs_stroke_bounding_box;
+        Some(())
+    }
+
+    pub(crate) fn style(&self) -> PathStyle {
+        PathStyle {
+            visible: self.visible,
+            fill: self.fill.clone(),
+            stroke: self.stroke.clone(),
+            paint_order: self.paint_order,
+            rendering_mode: self.rendering_mode,
+            vector_effect: self.vector_effect,
+        }
+    }
+
     /// Element's ID.
     ///
     /// Taken from the SVG itself.
@@ -1332,11 +1795,21 @@ impl Path {
         self.fill.as_ref()
     }
 
+    /// Mutable fill style.
+    pub fn fill_mut(&mut self) -> Option<&mut Fill> {
+        self.fill.as_mut()
+    }
+
     /// Stroke style.
     pub fn stroke(&self) -> Option<&Stroke> {
         self.stroke.as_ref()
     }
 
+    /// Mutable stroke style.
+    pub fn stroke_mut(&mut self) -> Option<&mut Stroke> {
+        self.stroke.as_mut()
+    }
+
     /// Fill and stroke paint order.
     ///
     /// Since markers will be replaced with regular nodes automatically,
@@ -1354,6 +1827,13 @@ impl Path {
         self.rendering_mode
     }
 
+    /// Vector effect.
+    ///
+    /// `vector-effect` in SVG.
+    pub fn vector_effect(&self) -> VectorEffect {
+        self.vector_effect
+    }
+
     // TODO: find a better name
     /// Segments list.
     ///
@@ -1362,6 +1842,19 @@ impl Path {
         self.data.as_ref()
     }
 
+    /// Segments list with elliptical arcs preserved.
+    ///
+    /// Describes the same geometry as [`data`](Self::data), in the same coordinate system,
+    /// but without approximating arcs with curves.
+    /// Arcs come from `rect` with rounded corners, `circle`, `ellipse`
+    /// and the `A` command in `path`.
+    ///
+    /// Available only when [`Options::keep_arcs`](crate::Options::keep_arcs) is set
+    /// and the path has at least one arc. Otherwise, `data` should be used.
+    pub fn arc_segments(&self) -> Option<&[ArcPathSegment]> {
+        self.arc_segments.as_deref().map(|v| v.as_slice())
+    }
+
     /// Element's absolute transform.
     ///
     /// Contains all ancestors transforms including elements's transform.
@@ -1400,6 +1893,39 @@ impl Path {
         self.abs_stroke_bounding_box
     }
 
+    /// Converts the stroke into a fillable outline.
+    ///
+    /// Dashes will be applied only when `include_dash` is set.
+    /// The outline is in the same coordinates as [`Path::data`] and must be filled
+    /// using the `nonzero` fill rule.
+    ///
+    /// Returns `None` when path has no stroke or the outline is empty.
+    pub fn stroke_to_path(&self, include_dash: bool) -> Option<tiny_skia_path::Path> {
+        let mut stroke = self.stroke.as_ref()?.to_tiny_skia();
+        if !include_dash {
+            stroke.dash = None;
+        }
+
+        let stroke_path = |path: &tiny_skia_path::Path, res_scale: f32| match stroke.dash {
+            Some(ref dash) => path
+                .dash(dash, res_scale)
+                .and_then(|p| p.stroke(&stroke, res_scale)),
+            None => path.stroke(&stroke, res_scale),
+        };
+
+        if self.vector_effect == VectorEffect::NonScalingStroke {
+            // The stroke is applied in the canvas coordinates.
+            let ts = self.abs_transform.invert()?;
+            let path = self.data.as_ref().clone().transform(self.abs_transform)?;
+            stroke_path(&path, 1.0)?.transform(ts)
+        } else {
+            // Make sure that the outline is precise enough in the canvas coordinates.
+            let res_scale =
+                tiny_skia_path::PathStroker::compute_resolution_scale(&self.abs_transform);
+            stroke_path(&self.data, res_scale)
+        }
+    }
+
     fn calculate_stroke_bbox(stroke: Option<&Stroke>, path: &tiny_skia_path::Path) -> Option<Rect> {
         let mut stroke = stroke?.to_tiny_skia();
         // According to the spec, dash should not be accounted during bbox calculation.
@@ -1441,15 +1967,20 @@ pub enum ImageKind {
 }
 
 impl ImageKind {
-    pub(crate) fn actual_size(&self) -> Option<Size> {
+    /// Returns an image intrinsic size.
+    ///
+    /// For raster images, the size is read from the image header without decoding.
+    /// For SVG, returns [`Tree::size`].
+    ///
+    /// Returns `None` when the header is malformed or the size is zero.
+    pub fn size(&self) -> Option<Size> {
         match self {
             ImageKind::JPEG(ref data)
             | ImageKind::PNG(ref data)
             | ImageKind::GIF(ref data)
             | ImageKind::WEBP(ref data) => imagesize::blob_size(data)
                 .ok()
-                .and_then(|size| Size::from_wh(size.width as f32, size.height as f32))
-                .log_none(|| log::warn!("Image has an invalid size. Skipped.")),
+                .and_then(|size| Size::from_wh(size.width as f32, size.height as f32)),
             ImageKind::SVG(ref svg) => Some(svg.size),
         }
     }
@@ -1547,11 +2078,231 @@ impl Image {
     }
 }
 
+/// Non-rendering element metadata.
+///
+/// See [`Tree::metadata`].
+#[derive(Clone, Default, Debug)]
+pub struct Metadata {
+    pub(crate) title: Option<String>,
+    pub(crate) desc: Option<String>,
+    pub(crate) aria_label: Option<String>,
+    pub(crate) classes: Vec<String>,
+    pub(crate) data_attributes: Vec<(String, String)>,
+    pub(crate) pointer_events: Option<String>,
+    pub(crate) cursor: Option<String>,
+}
+
+impl Metadata {
+    /// Text of the first `title` child element.
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    /// Text of the first `desc` child element.
+    pub fn desc(&self) -> Option<&str> {
+        self.desc.as_deref()
+    }
+
+    /// The `aria-label` attribute.
+    pub fn aria_label(&self) -> Option<&str> {
+        self.aria_label.as_deref()
+    }
+
+    /// Class names from the `class` attribute.
+    pub fn classes(&self) -> &[String] {
+        &self.classes
+    }
+
+    /// `data-*` attributes in the document order.
+    ///
+    /// Names include the `data-` prefix.
+    pub fn data_attributes(&self) -> &[(String, String)] {
+        &self.data_attributes
+    }
+
+    /// The `pointer-events` property value.
+    ///
+    /// Can be set via CSS or inherited from an ancestor.
+    /// Doesn't affect rendering.
+    pub fn pointer_events(&self) -> Option<&str> {
+        self.pointer_events.as_deref()
+    }
+
+    /// The `cursor` property value.
+    ///
+    /// Can be set via CSS or inherited from an ancestor.
+    /// Doesn't affect rendering.
+    pub fn cursor(&self) -> Option<&str> {
+        self.cursor.as_deref()
+    }
+}
+
+/// A source of a computed style value.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum StyleSource {
+    /// A presentation attribute, like `fill="red"`.
+    Attribute,
+    /// A CSS rule from a `style` element or [`Options::style_sheet`](crate::Options::style_sheet).
+    StyleSheet,
+    /// An inline `style` attribute.
+    Inline,
+    /// Inherited from an ancestor, either implicitly or via the `inherit` keyword.
+    Inherited,
+    /// An initial value.
+    Default,
+}
+
+/// A computed style value.
+///
+/// See [`ComputedStyle`].
+#[derive(Clone, PartialEq, Debug)]
+pub struct ComputedValue {
+    pub(crate) value: String,
+    pub(crate) source: StyleSource,
+}
+
+impl ComputedValue {
+    /// A value as is.
+    ///
+    /// Values are not parsed, therefore relative units and keywords like `currentColor`
+    /// are preserved.
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// Where the value came from.
+    pub fn source(&self) -> StyleSource {
+        self.source
+    }
+}
+
+/// Presentation attributes of an element after the CSS cascade and inheritance.
+///
+/// See [`Tree::computed_style`].
+#[derive(Clone, Default, Debug)]
+pub struct ComputedStyle {
+    // Sorted by name.
+    pub(crate) values: Vec<(String, ComputedValue)>,
+}
+
+impl ComputedStyle {
+    /// Returns a property value by name, like `fill` or `font-size`.
+    ///
+    /// Properties without an explicit value and without a known initial value are not present.
+    pub fn get(&self, name: &str) -> Option<&ComputedValue> {
+        let idx = self
+            .values
+            .binary_search_by(|(n, _)| n.as_str().cmp(name))
+            .ok()?;
+        Some(&self.values[idx].1)
+    }
+
+    /// Returns an iterator over all properties, sorted by name.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &ComputedValue)> {
+        self.values.iter().map(|(n, v)| (n.as_str(), v))
+    }
+}
+
+/// A coordinate space of bounding boxes returned by [`Tree::all_bounding_boxes`]
+/// and [`Tree::bounding_boxes`].
+///
+/// Values in each space are part of the public contract and will not change between versions,
+/// unless mentioned in the changelog.
+///
+/// For example, for:
+///
+/// ```xml
+/// <svg width="15pt" height="15pt" viewBox="0 0 10 10" xmlns="http://www.w3.org/2000/svg">
+///     <rect id="rect1" x="2" y="2" width="5" height="5"/>
+/// </svg>
+/// ```
+///
+/// the `rect1` bounding box is `2 2 5 5` in [`UserSpace`](Self::UserSpace)
+/// and `4 4 10 10` in [`ViewportPixels`](Self::ViewportPixels),
+/// since `15pt` is `20px` and the `viewBox` is scaled by 2.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum QueryCoordinateSpace {
+    /// The root `svg` element user space, i.e. `viewBox` units.
+    ///
+    /// Includes all transforms, except the one produced by the root element's
+    /// `viewBox` and `preserveAspectRatio`. See [`Tree::view_transform`].
+    UserSpace,
+    /// Pixels of an image of [`Tree::size`].
+    ///
+    /// Same as [`Node::abs_bounding_box`] and other `abs_` methods.
+    ViewportPixels,
+}
+
+/// Bounding boxes of a node with an ID.
+///
+/// All boxes are in the same [`QueryCoordinateSpace`], requested
+/// in [`Tree::all_bounding_boxes`] or [`Tree::bounding_boxes`].
+#[derive(Clone, Debug)]
+pub struct NodeBoundingBoxes {
+    pub(crate) id: String,
+    pub(crate) bounding_box: Rect,
+    pub(crate) ink_bounding_box: Rect,
+    pub(crate) layer_bounding_box: Option<NonZeroRect>,
+}
+
+impl NodeBoundingBoxes {
+    fn new(node: &Node, ts: Option<Transform>) -> Self {
+        let mut bboxes = NodeBoundingBoxes {
+            id: node.id().to_string(),
+            bounding_box: node.abs_bounding_box(),
+            ink_bounding_box: node.abs_ink_bounding_box(),
+            layer_bounding_box: node.abs_layer_bounding_box(),
+        };
+
+        // A `viewBox` transform is just a scale and a translate,
+        // so transforming a rect cannot fail.
+        if let Some(ts) = ts {
+            let bbox = bboxes.bounding_box;
+            bboxes.bounding_box = bbox.transform(ts).unwrap_or(bbox);
+            let ink_bbox = bboxes.ink_bounding_box;
+            bboxes.ink_bounding_box = ink_bbox.transform(ts).unwrap_or(ink_bbox);
+            bboxes.layer_bounding_box = bboxes.layer_bounding_box.and_then(|r| r.transform(ts));
+        }
+
+        bboxes
+    }
+
+    /// Node's ID.
+    ///
+    /// Never empty.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Node's geometry bounding box.
+    ///
+    /// In [`QueryCoordinateSpace::ViewportPixels`], same as [`Node::abs_bounding_box`].
+    pub fn bounding_box(&self) -> Rect {
+        self.bounding_box
+    }
+
+    /// Node's "ink" bounding box, including stroke.
+    ///
+    /// In [`QueryCoordinateSpace::ViewportPixels`], same as [`Node::abs_ink_bounding_box`].
+    pub fn ink_bounding_box(&self) -> Rect {
+        self.ink_bounding_box
+    }
+
+    /// Node's "layer" bounding box, if any.
+    ///
+    /// In [`QueryCoordinateSpace::ViewportPixels`], same as [`Node::abs_layer_bounding_box`].
+    pub fn layer_bounding_box(&self) -> Option<NonZeroRect> {
+        self.layer_bounding_box
+    }
+}
+
 /// A nodes tree container.
 #[allow(missing_debug_implementations)]
 #[derive(Clone, Debug)]
 pub struct Tree {
     pub(crate) size: Size,
+    pub(crate) view_box: ViewBox,
+    pub(crate) overflow_visible: bool,
     pub(crate) root: Group,
     pub(crate) linear_gradients: Vec<Arc<LinearGradient>>,
     pub(crate) radial_gradients: Vec<Arc<RadialGradient>>,
@@ -1559,6 +2310,8 @@ pub struct Tree {
     pub(crate) clip_paths: Vec<Arc<ClipPath>>,
     pub(crate) masks: Vec<Arc<Mask>>,
     pub(crate) filters: Vec<Arc<filter::Filter>>,
+    pub(crate) metadata: HashMap<String, Metadata>,
+    pub(crate) computed_styles: HashMap<String, ComputedStyle>,
     #[cfg(feature = "text")]
     pub(crate) fontdb: Arc<fontdb::Database>,
 }
@@ -1573,6 +2326,57 @@ impl Tree {
         self.size
     }
 
+    /// Returns a transform that maps the SVG user space into a viewport of the specified size.
+    ///
+    /// Uses the root element's `viewBox` and `preserveAspectRatio`.
+    /// When `target` is equal to [`Tree::size`], this is the transform already applied
+    /// to the root element's content.
+    ///
+    /// Can be inverted to map a pixel position back into the SVG user space.
+    pub fn view_transform(&self, target: Size) -> Transform {
+        self.view_box.to_transform(target)
+    }
+
+    /// Checks that the root `svg` element has `overflow` set to `visible` or `auto`.
+    ///
+    /// In which case, content outside the viewport should be rendered as well.
+    /// See [`Tree::render_area`].
+    pub fn is_overflow_visible(&self) -> bool {
+        self.overflow_visible
+    }
+
+    /// Returns the canvas area that should be rendered.
+    ///
+    /// Usually, it's just a rect at `0 0` with [`Tree::size`]. But when the root element
+    /// has a visible overflow, it's extended by the root layer bounding box,
+    /// i.e. the area can have a negative position and be larger than the size.
+    pub fn render_area(&self) -> NonZeroRect {
+        let canvas = self.size.to_non_zero_rect(0.0, 0.0);
+        if !self.overflow_visible || !self.root.has_children() {
+            return canvas;
+        }
+
+        BBox::from(canvas)
+            .expand(self.root.layer_bounding_box)
+            .to_non_zero_rect()
+            .unwrap_or(canvas)
+    }
+
+    /// Returns the canvas area covered by the root element's `viewBox`.
+    ///
+    /// Smaller than [`Tree::size`] when `preserveAspectRatio` letterboxes the content,
+    /// i.e. when the `viewBox` and the image size have different aspect ratios.
+    /// Never exceeds the canvas, even with `slice`.
+    pub fn view_box_area(&self) -> NonZeroRect {
+        let canvas = self.size.to_non_zero_rect(0.0, 0.0);
+        self.view_box
+            .rect
+            .transform(self.view_transform(self.size))
+            .and_then(|r| r.to_rect().intersect(&canvas.to_rect()))
+            .and_then(|r| r.to_non_zero_rect())
+            .unwrap_or(canvas)
+    }
+
     /// The root element of the SVG tree.
     pub fn root(&self) -> &Group {
         &self.root
@@ -1580,6 +2384,9 @@ impl Tree {
 
     /// Returns a renderable node by ID.
     ///
+    /// When multiple nodes share an ID, the first one in the document order is returned,
+    /// just like during references resolving.
+    ///
     /// If an empty ID is provided, than this method will always return `None`.
     pub fn node_by_id(&self, id: &str) -> Option<&Node> {
         if id.is_empty() {
@@ -1589,11 +2396,101 @@ impl Tree {
         node_by_id(&self.root, id)
     }
 
+    /// Returns bounding boxes of all renderable nodes with a non-empty ID.
+    ///
+    /// Uses already calculated boxes, so it's the same as calling [`Tree::bounding_boxes`]
+    /// for each ID, but requires only a single traversal.
+    ///
+    /// Nodes are returned in the document order. Children of clip paths, masks, patterns
+    /// and text nodes are not visited.
+    pub fn all_bounding_boxes(&self, space: QueryCoordinateSpace) -> Vec<NodeBoundingBoxes> {
+        let ts = self.query_transform(space);
+        let mut list = Vec::new();
+        collect_bounding_boxes(&self.root, ts, &mut list);
+        list
+    }
+
+    /// Returns bounding boxes of a renderable node by ID.
+    ///
+    /// If an empty ID is provided, than this method will always return `None`.
+    pub fn bounding_boxes(
+        &self,
+        id: &str,
+        space: QueryCoordinateSpace,
+    ) -> Option<NodeBoundingBoxes> {
+        let node = self.node_by_id(id)?;
+        Some(NodeBoundingBoxes::new(node, self.query_transform(space)))
+    }
+
+    fn query_transform(&self, space: QueryCoordinateSpace) -> Option<Transform> {
+        match space {
+            QueryCoordinateSpace::UserSpace => self.view_transform(self.size).invert(),
+            QueryCoordinateSpace::ViewportPixels => None,
+        }
+    }
+
     /// Checks if the current tree has any text nodes.
+    ///
+    /// Always returns `false` when the `text` feature is disabled.
     pub fn has_text_nodes(&self) -> bool {
+        // Text nodes cannot be created without the `text` feature.
+        if !cfg!(feature = "text") {
+            return false;
+        }
+
         has_text_nodes(&self.root)
     }
 
+    /// Checks that the current tree has nothing to render.
+    ///
+    /// A tree is considered empty when it has no visible paths with a non-transparent
+    /// fill or stroke and a non-zero bounding box, no visible images, no text producing
+    /// such paths and no filters, which can produce an output from an empty input.
+    /// Groups with a zero opacity are ignored as well.
+    pub fn is_empty(&self) -> bool {
+        !has_renderable_nodes(&self.root)
+    }
+
+    /// Returns an iterator over paths, images and text nodes that produce pixels.
+    ///
+    /// Nodes are returned in the paint order. Hidden nodes, subtrees of groups with
+    /// a zero opacity, paths without a fill and a stroke or with fully transparent ones,
+    /// empty nested SVG images and nodes with a zero bounding box are skipped.
+    /// A group with filters and no visible children is returned itself,
+    /// since filters like `feFlood` produce pixels from an empty input.
+    /// Matches [`Tree::is_empty`], which is `true` only when nothing is returned.
+    ///
+    /// Content that was clipped away or masked out is not filtered, since it requires
+    /// geometry intersection. Clip paths, masks, patterns and nested SVG images
+    /// are not visited.
+    pub fn visible_nodes(&self) -> impl Iterator<Item = &Node> {
+        let mut nodes = Vec::new();
+        collect_visible_nodes(&self.root, &mut nodes);
+        nodes.into_iter()
+    }
+
+    /// Returns metadata of an element with the specified ID.
+    ///
+    /// The root `svg` element metadata is stored under an empty ID,
+    /// which matches [`Tree::root`]'s ID. Metadata of other elements without an ID
+    /// is not preserved.
+    ///
+    /// Available only when [`Options::keep_metadata`](crate::Options::keep_metadata) is set.
+    pub fn metadata(&self, id: &str) -> Option<&Metadata> {
+        self.metadata.get(id)
+    }
+
+    /// Returns a computed style of an element with the specified ID.
+    ///
+    /// The root `svg` element style is stored under an empty ID,
+    /// which matches [`Tree::root`]'s ID.
+    ///
+    /// Available only when
+    /// [`Options::keep_computed_styles`](crate::Options::keep_computed_styles) is set.
+    pub fn computed_style(&self, id: &str) -> Option<&ComputedStyle> {
+        self.computed_styles.get(id)
+    }
+
     /// Returns a list of all unique [`LinearGradient`]s in the tree.
     pub fn linear_gradients(&self) -> &[Arc<LinearGradient>] {
         &self.linear_gradients
@@ -1624,6 +2521,27 @@ impl Tree {
         &self.filters
     }
 
+    /// Removes resources that are no longer referenced by any node.
+    ///
+    /// Gradients, patterns, clip paths, masks and filters are collected again
+    /// by walking all references: paints, clip paths, masks, filters and `feImage` children.
+    /// Resources referenced only by removed ones are removed as well.
+    ///
+    /// Methods that modify the tree, like [`Tree::visit_mut`], call it automatically.
+    pub fn remove_unused_defs(&mut self) {
+        self.linear_gradients.clear();
+        self.radial_gradients.clear();
+        self.patterns.clear();
+        self.collect_paint_servers();
+
+        self.clip_paths.clear();
+        self.root.collect_clip_paths(&mut self.clip_paths);
+        self.masks.clear();
+        self.root.collect_masks(&mut self.masks);
+        self.filters.clear();
+        self.root.collect_filters(&mut self.filters);
+    }
+
     /// Returns the font database that applies to all text nodes in the tree.
     #[cfg(feature = "text")]
     pub fn fontdb(&self) -> &Arc<fontdb::Database> {
@@ -1676,6 +2594,22 @@ fn node_by_id<'a>(parent: &'a Group, id: &str) -> Option<&'a Node> {
     None
 }
 
+fn collect_bounding_boxes(
+    parent: &Group,
+    ts: Option<Transform>,
+    list: &mut Vec<NodeBoundingBoxes>,
+) {
+    for node in &parent.children {
+        if !node.id().is_empty() {
+            list.push(NodeBoundingBoxes::new(node, ts));
+        }
+
+        if let Node::Group(ref group) = node {
+            collect_bounding_boxes(group, ts, list);
+        }
+    }
+}
+
 fn has_text_nodes(root: &Group) -> bool {
     for node in &root.children {
         if let Node::Text(_) = node {
@@ -1684,12 +2618,14 @@ fn has_text_nodes(root: &Group) -> bool {
 
         let mut has_text = false;
 
-        if let Node::Image(ref image) = node {
-            if let ImageKind::SVG(ref tree) = image.kind {
-                if has_text_nodes(&tree.root) {
-                    has_text = true;
+        match node {
+            Node::Group(ref group) => has_text = has_text_nodes(group),
+            Node::Image(ref image) => {
+                if let ImageKind::SVG(ref tree) = image.kind {
+                    has_text = has_text_nodes(&tree.root);
                 }
             }
+            _ => {}
         }
 
         node.subroots(|subroot| has_text |= has_text_nodes(subroot));
@@ -1699,7 +2635,56 @@ fn has_text_nodes(root: &Group) -> bool {
         }
     }
 
-    true
+    false
+}
+
+fn has_renderable_nodes(parent: &Group) -> bool {
+    parent.children.iter().any(is_renderable)
+}
+
+/// Checks that a node produces pixels.
+///
+/// Shared by [`Tree::is_empty`] and [`Tree::visible_nodes`], so they always agree.
+fn is_renderable(node: &Node) -> bool {
+    match node {
+        Node::Group(ref group) => {
+            group.opacity != Opacity::ZERO
+                && (!group.filters.is_empty() || has_renderable_nodes(group))
+        }
+        Node::Path(ref path) => {
+            let has_fill = matches!(path.fill, Some(ref f) if f.opacity != Opacity::ZERO);
+            let has_stroke = matches!(path.stroke, Some(ref s) if s.opacity != Opacity::ZERO);
+
+            path.visible
+                && (has_fill || has_stroke)
+                && path.stroke_bounding_box.to_non_zero_rect().is_some()
+        }
+        Node::Image(ref image) => {
+            image.visible
+                && image.bounding_box().to_non_zero_rect().is_some()
+                && match image.kind {
+                    ImageKind::SVG(ref tree) => !tree.is_empty(),
+                    _ => true,
+                }
+        }
+        Node::Text(ref text) => has_renderable_nodes(&text.flattened),
+    }
+}
+
+fn collect_visible_nodes<'a>(parent: &'a Group, nodes: &mut Vec<&'a Node>) {
+    for node in parent.children.iter().filter(|node| is_renderable(node)) {
+        match node {
+            Node::Group(ref group) => {
+                let len = nodes.len();
+                collect_visible_nodes(group, nodes);
+                // A filter can produce pixels from an empty input, like `feFlood`.
+                if nodes.len() == len {
+                    nodes.push(node);
+                }
+            }
+            _ => nodes.push(node),
+        }
+    }
 }
 
 fn loop_over_paint_servers(parent: &Group, f: &mut dyn FnMut(&Paint)) {
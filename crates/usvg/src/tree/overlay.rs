@@ -0,0 +1,421 @@
+// Copyright 2024 the Resvg Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::sync::Arc;
+
+use tiny_skia_path::Transform;
+
+use super::*;
+
+impl Tree {
+    /// Draws another tree on top of the current one.
+    ///
+    /// All `other` root children will be placed into a new group with the provided transform,
+    /// which is applied in the current tree coordinates.
+    /// The size of the current tree is preserved.
+    ///
+    /// To avoid collisions, all non-empty IDs from `other`, including IDs of paint servers,
+    /// clip paths, masks and filters, will be prefixed with a unique `overlayN-` prefix.
+    ///
+    /// Text nodes will still reference fonts from the `other` font database.
+    /// This affects only [`Text::layouted`], since flattened text doesn't rely on fonts.
+    ///
+    /// Returns `false` and leaves the current tree unchanged when `other` has no children
+    /// or has a zero bounding box after applying `transform`.
+    pub fn overlay(&mut self, other: &Tree, transform: Transform) -> bool {
+        if !other.root.has_children() {
+            return false;
+        }
+
+        let mut index = 1;
+        let prefix = loop {
+            let prefix = format!("overlay{}-", index);
+            if !self.has_id_with_prefix(&prefix) {
+                break prefix;
+            }
+
+            index += 1;
+        };
+
+        let mut g = Group {
+            transform,
+            abs_transform: transform,
+            children: other.root.children.clone(),
+            ..Group::empty()
+        };
+
+        let mut prefixer = IdPrefixer::new(&prefix);
+        for child in &mut g.children {
+            prefixer.node(child);
+            prepend_transform(child, transform);
+        }
+
+        if g.calculate_bounding_boxes().is_none() {
+            return false;
+        }
+
+        self.root.children.push(Node::Group(Box::new(g)));
+
+        self.remove_unused_defs();
+        self.root.calculate_bounding_boxes();
+        true
+    }
+
+    fn has_id_with_prefix(&self, prefix: &str) -> bool {
+        fn has_node_id(parent: &Group, prefix: &str) -> bool {
+            for node in &parent.children {
+                if node.id().starts_with(prefix) {
+                    return true;
+                }
+
+                let mut has = false;
+                if let Node::Group(ref group) = node {
+                    has = has_node_id(group, prefix);
+                }
+
+                node.subroots(|subroot| has |= has_node_id(subroot, prefix));
+
+                if has {
+                    return true;
+                }
+            }
+
+            false
+        }
+
+        has_node_id(&self.root, prefix)
+            || self
+                .linear_gradients
+                .iter()
+                .any(|v| v.id().starts_with(prefix))
+            || self
+                .radial_gradients
+                .iter()
+                .any(|v| v.id().starts_with(prefix))
+            || self.patterns.iter().any(|v| v.id().starts_with(prefix))
+            || self.clip_paths.iter().any(|v| v.id().starts_with(prefix))
+            || self.masks.iter().any(|v| v.id().starts_with(prefix))
+            || self.filters.iter().any(|v| v.id().starts_with(prefix))
+    }
+}
+
+/// Applies an additional parent transform to absolute transforms and bounding boxes.
+///
+/// Clip paths, masks, patterns and filters have their own coordinate systems
+/// and are not affected.
+pub(crate) fn prepend_transform(node: &mut Node, ts: Transform) {
+    match node {
+        Node::Group(ref mut group) => prepend_group_transform(group, ts),
+        Node::Path(ref mut path) => {
+            path.recalculate_bounding_boxes(ts.pre_concat(path.abs_transform));
+        }
+        Node::Image(ref mut image) => {
+            let abs_transform = ts.pre_concat(image.abs_transform);
+            if let Some(bbox) = image
+                .size
+                .to_non_zero_rect(0.0, 0.0)
+                .transform(abs_transform)
+            {
+                image.abs_transform = abs_transform;
+                image.abs_bounding_box = bbox;
+            }
+        }
+        Node::Text(ref mut text) => {
+            let abs_transform = ts.pre_concat(text.abs_transform);
+            let bbox = text.bounding_box.transform(abs_transform);
+            let stroke_bbox = text.stroke_bounding_box.transform(abs_transform);
+            if let (Some(bbox), Some(stroke_bbox)) = (bbox, stroke_bbox) {
+                text.abs_transform = abs_transform;
+                text.abs_bounding_box = bbox;
+                text.abs_stroke_bounding_box = stroke_bbox;
+            }
+
+            prepend_group_transform(&mut text.flattened, ts);
+        }
+    }
+}
+
+fn prepend_group_transform(group: &mut Group, ts: Transform) {
+    group.abs_transform = ts.pre_concat(group.abs_transform);
+    for child in &mut group.children {
+        prepend_transform(child, ts);
+    }
+
+    group.calculate_bounding_boxes();
+}
+
+/// Prefixes all IDs in a subtree, including referenced resources.
+///
+/// Resources are recreated only once, so shared resources would stay shared.
+struct IdPrefixer<'a> {
+    prefix: &'a str,
+    linear_gradients: Vec<(Arc<LinearGradient>, Arc<LinearGradient>)>,
+    radial_gradients: Vec<(Arc<RadialGradient>, Arc<RadialGradient>)>,
+    patterns: Vec<(Arc<Pattern>, Arc<Pattern>)>,
+    clip_paths: Vec<(Arc<ClipPath>, Arc<ClipPath>)>,
+    masks: Vec<(Arc<Mask>, Arc<Mask>)>,
+    filters: Vec<(Arc<filter::Filter>, Arc<filter::Filter>)>,
+}
+
+fn find_resource<T>(list: &[(Arc<T>, Arc<T>)], old: &Arc<T>) -> Option<Arc<T>> {
+    list.iter()
+        .find(|(v, _)| Arc::ptr_eq(v, old))
+        .map(|(_, v)| v.clone())
+}
+
+impl<'a> IdPrefixer<'a> {
+    fn new(prefix: &'a str) -> Self {
+        IdPrefixer {
+            prefix,
+            linear_gradients: Vec::new(),
+            radial_gradients: Vec::new(),
+            patterns: Vec::new(),
+            clip_paths: Vec::new(),
+            masks: Vec::new(),
+            filters: Vec::new(),
+        }
+    }
+
+    fn id(&self, id: &mut String) {
+        if !id.is_empty() {
+            id.insert_str(0, self.prefix);
+        }
+    }
+
+    fn non_empty_id(&self, id: &NonEmptyString) -> NonEmptyString {
+        NonEmptyString(format!("{}{}", self.prefix, id.get()))
+    }
+
+    fn node(&mut self, node: &mut Node) {
+        match node {
+            Node::Group(ref mut group) => self.group(group),
+            Node::Path(ref mut path) => self.path(path),
+            Node::Image(ref mut image) => {
+                // A nested SVG is a separate document, so its IDs cannot collide.
+                self.id(&mut image.id);
+            }
+            Node::Text(ref mut text) => self.text(text),
+        }
+    }
+
+    fn group(&mut self, group: &mut Group) {
+        self.id(&mut group.id);
+
+        if let Some(ref clip_path) = group.clip_path {
+            group.clip_path = Some(self.clip_path(clip_path));
+        }
+
+        if let Some(ref mask) = group.mask {
+            group.mask = Some(self.mask(mask));
+        }
+
+        for filter in &mut group.filters {
+            *filter = self.filter(filter);
+        }
+
+        for child in &mut group.children {
+            self.node(child);
+        }
+    }
+
+    fn path(&mut self, path: &mut Path) {
+        self.id(&mut path.id);
+        self.fill_and_stroke(&mut path.fill, &mut path.stroke);
+    }
+
+    fn text(&mut self, text: &mut Text) {
+        self.id(&mut text.id);
+
+        for chunk in &mut text.chunks {
+            for span in &mut chunk.spans {
+                self.fill_and_stroke(&mut span.fill, &mut span.stroke);
+
+                let decoration = &mut span.decoration;
+                for style in [
+                    &mut decoration.underline,
+                    &mut decoration.overline,
+                    &mut decoration.line_through,
+                ]
+                .into_iter()
+                .flatten()
+                {
+                    self.fill_and_stroke(&mut style.fill, &mut style.stroke);
+                }
+            }
+
+            if let TextFlow::Path(ref text_path) = chunk.text_flow {
+                chunk.text_flow = TextFlow::Path(Arc::new(TextPath {
+                    id: self.non_empty_id(&text_path.id),
+                    start_offset: text_path.start_offset,
+                    path: text_path.path.clone(),
+                }));
+            }
+        }
+
+        #[cfg(feature = "text")]
+        for span in &mut text.layouted {
+            self.fill_and_stroke(&mut span.fill, &mut span.stroke);
+
+            for path in [
+                &mut span.underline,
+                &mut span.overline,
+                &mut span.line_through,
+            ]
+            .into_iter()
+            .flatten()
+            {
+                self.path(path);
+            }
+        }
+
+        self.group(&mut text.flattened);
+    }
+
+    fn fill_and_stroke(&mut self, fill: &mut Option<Fill>, stroke: &mut Option<Stroke>) {
+        if let Some(ref mut fill) = fill {
+            self.paint(&mut fill.paint);
+        }
+
+        if let Some(ref mut stroke) = stroke {
+            self.paint(&mut stroke.paint);
+        }
+    }
+
+    fn paint(&mut self, paint: &mut Paint) {
+        match paint {
+            Paint::Color(_) => {}
+            Paint::LinearGradient(ref mut lg) => *lg = self.linear_gradient(lg),
+            Paint::RadialGradient(ref mut rg) => *rg = self.radial_gradient(rg),
+            Paint::Pattern(ref mut patt) => *patt = self.pattern(patt),
+        }
+    }
+
+    fn base_gradient(&self, base: &BaseGradient) -> BaseGradient {
+        BaseGradient {
+            id: self.non_empty_id(&base.id),
+            units: base.units,
+            transform: base.transform,
+            spread_method: base.spread_method,
+            color_interpolation: base.color_interpolation,
+            stops: base.stops.clone(),
+        }
+    }
+
+    fn linear_gradient(&mut self, lg: &Arc<LinearGradient>) -> Arc<LinearGradient> {
+        if let Some(v) = find_resource(&self.linear_gradients, lg) {
+            return v;
+        }
+
+        let new_lg = Arc::new(LinearGradient {
+            base: self.base_gradient(&lg.base),
+            x1: lg.x1,
+            y1: lg.y1,
+            x2: lg.x2,
+            y2: lg.y2,
+        });
+
+        self.linear_gradients.push((lg.clone(), new_lg.clone()));
+        new_lg
+    }
+
+    fn radial_gradient(&mut self, rg: &Arc<RadialGradient>) -> Arc<RadialGradient> {
+        if let Some(v) = find_resource(&self.radial_gradients, rg) {
+            return v;
+        }
+
+        let new_rg = Arc::new(RadialGradient {
+            base: self.base_gradient(&rg.base),
+            cx: rg.cx,
+            cy: rg.cy,
+            r: rg.r,
+            fx: rg.fx,
+            fy: rg.fy,
+        });
+
+        self.radial_gradients.push((rg.clone(), new_rg.clone()));
+        new_rg
+    }
+
+    fn pattern(&mut self, patt: &Arc<Pattern>) -> Arc<Pattern> {
+        if let Some(v) = find_resource(&self.patterns, patt) {
+            return v;
+        }
+
+        let mut root = patt.root.clone();
+        self.group(&mut root);
+
+        let new_patt = Arc::new(Pattern {
+            id: self.non_empty_id(&patt.id),
+            units: patt.units,
+            content_units: patt.content_units,
+            transform: patt.transform,
+            rect: patt.rect,
+            view_box: patt.view_box,
+            root,
+        });
+
+        self.patterns.push((patt.clone(), new_patt.clone()));
+        new_patt
+    }
+
+    fn clip_path(&mut self, clip: &Arc<ClipPath>) -> Arc<ClipPath> {
+        if let Some(v) = find_resource(&self.clip_paths, clip) {
+            return v;
+        }
+
+        let mut root = clip.root.clone();
+        self.group(&mut root);
+
+        let new_clip = Arc::new(ClipPath {
+            id: self.non_empty_id(&clip.id),
+            transform: clip.transform,
+            clip_path: clip.clip_path.as_ref().map(|v| self.clip_path(v)),
+            root,
+        });
+
+        self.clip_paths.push((clip.clone(), new_clip.clone()));
+        new_clip
+    }
+
+    fn mask(&mut self, mask: &Arc<Mask>) -> Arc<Mask> {
+        if let Some(v) = find_resource(&self.masks, mask) {
+            return v;
+        }
+
+        let mut root = mask.root.clone();
+        self.group(&mut root);
+
+        let new_mask = Arc::new(Mask {
+            id: self.non_empty_id(&mask.id),
+            rect: mask.rect,
+            kind: mask.kind,
+            mask: mask.mask.as_ref().map(|v| self.mask(v)),
+            root,
+        });
+
+        self.masks.push((mask.clone(), new_mask.clone()));
+        new_mask
+    }
+
+    fn filter(&mut self, filter: &Arc<filter::Filter>) -> Arc<filter::Filter> {
+        if let Some(v) = find_resource(&self.filters, filter) {
+            return v;
+        }
+
+        let mut primitives = filter.primitives.clone();
+        for primitive in &mut primitives {
+            if let filter::Kind::Image(ref mut fe) = primitive.kind {
+                self.group(&mut fe.root);
+            }
+        }
+
+        let new_filter = Arc::new(filter::Filter {
+            id: self.non_empty_id(&filter.id),
+            rect: filter.rect,
+            primitives,
+        });
+
+        self.filters.push((filter.clone(), new_filter.clone()));
+        new_filter
+    }
+}
@@ -218,6 +218,20 @@ impl Default for LengthAdjust {
     }
 }
 
+/// A glyph orientation in a vertical text.
+///
+/// `glyph-orientation-vertical` attribute in the SVG.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum GlyphOrientationVertical {
+    /// Resolved using the Unicode `Vertical_Orientation` property of a character.
+    #[default]
+    Auto,
+    /// Glyphs are upright. Set by `0`.
+    Upright,
+    /// Glyphs are rotated by 90 degrees clockwise. Set by `90`.
+    Rotated,
+}
+
 /// A text span decoration style.
 ///
 /// In SVG, text decoration and text it's applied to can have different styles.
@@ -279,6 +293,7 @@ pub struct TextSpan {
     pub(crate) paint_order: PaintOrder,
     pub(crate) font: Font,
     pub(crate) font_size: NonZeroPositiveF32,
+    pub(crate) font_size_adjust: Option<f32>,
     pub(crate) small_caps: bool,
     pub(crate) apply_kerning: bool,
     pub(crate) decoration: TextDecoration,
@@ -290,6 +305,7 @@ pub struct TextSpan {
     pub(crate) word_spacing: f32,
     pub(crate) text_length: Option<f32>,
     pub(crate) length_adjust: LengthAdjust,
+    pub(crate) glyph_orientation_vertical: GlyphOrientationVertical,
 }
 
 impl TextSpan {
@@ -332,6 +348,16 @@ impl TextSpan {
         self.font_size
     }
 
+    /// A font size adjust.
+    ///
+    /// When set, the font size of each font used by the span, including fallback ones,
+    /// is scaled so the font's x-height is equal to `font_size * font_size_adjust`.
+    ///
+    /// `None` when `font-size-adjust` is `none`.
+    pub fn font_size_adjust(&self) -> Option<f32> {
+        self.font_size_adjust
+    }
+
     /// Indicates that small caps should be used.
     ///
     /// Set by `font-variant="small-caps"`
@@ -392,6 +418,13 @@ impl TextSpan {
     pub fn length_adjust(&self) -> LengthAdjust {
         self.length_adjust
     }
+
+    /// A glyph orientation in a vertical text.
+    ///
+    /// Has no effect on a horizontal text.
+    pub fn glyph_orientation_vertical(&self) -> GlyphOrientationVertical {
+        self.glyph_orientation_vertical
+    }
 }
 
 /// A text chunk anchor property.
@@ -620,6 +653,10 @@ impl Text {
 
     /// Text converted into paths, ready to render.
     ///
+    /// Text is shaped and converted only once, during parsing, and in user units.
+    /// Therefore the same tree can be rendered at any size, by changing only the render
+    /// transform, without a new layout.
+    ///
     /// Note that this is only a
     /// "best-effort" attempt: The text will be converted into group/paths/image
     /// primitives, so that they can be rendered with the existing infrastructure.
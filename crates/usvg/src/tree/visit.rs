@@ -0,0 +1,340 @@
+// Copyright 2024 the Resvg Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use super::*;
+
+/// A mutable tree visitor.
+///
+/// See [`Tree::visit_mut`].
+pub trait NodeVisitor {
+    /// Called before visiting node's children and subroots.
+    fn enter(&mut self, _node: &mut Node) {}
+
+    /// Called after visiting node's children and subroots.
+    fn leave(&mut self, _node: &mut Node) {}
+}
+
+/// A visitor that replaces one solid color with another.
+///
+/// Affects fills and strokes of all paths, including text outlines and subroots.
+/// Gradients and text spans are not affected.
+///
+/// # Example
+///
+/// ```
+/// let svg = "<svg xmlns='http://www.w3.org/2000/svg'><rect width='10' height='10' fill='red'/></svg>";
+/// let mut tree = usvg::Tree::from_str(svg, &usvg::Options::default()).unwrap();
+///
+/// let red = usvg::Color::new_rgb(255, 0, 0);
+/// let blue = usvg::Color::new_rgb(0, 0, 255);
+/// tree.visit_mut(&mut usvg::RecolorVisitor::new(red, blue));
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct RecolorVisitor {
+    from: Color,
+    to: Color,
+}
+
+impl RecolorVisitor {
+    /// Creates a new visitor.
+    pub fn new(from: Color, to: Color) -> Self {
+        RecolorVisitor { from, to }
+    }
+}
+
+impl NodeVisitor for RecolorVisitor {
+    fn enter(&mut self, node: &mut Node) {
+        if let Node::Path(ref mut path) = node {
+            if let Some(fill) = path.fill_mut() {
+                if fill.paint == Paint::Color(self.from) {
+                    fill.set_paint(Paint::Color(self.to));
+                }
+            }
+
+            if let Some(stroke) = path.stroke_mut() {
+                if stroke.paint == Paint::Color(self.from) {
+                    stroke.set_paint(Paint::Color(self.to));
+                }
+            }
+        }
+    }
+}
+
+impl Tree {
+    /// Visits all nodes, allowing to modify them.
+    ///
+    /// Nodes are visited in the rendering order. Subroots, like clip paths, masks,
+    /// patterns, `feImage` children, text outlines and nested SVG images are visited
+    /// after node's children. Shared resources are visited only once.
+    ///
+    /// Bounding boxes are updated afterwards.
+    ///
+    /// Useful for various post-processing passes, like recoloring.
+    pub fn visit_mut(&mut self, visitor: &mut dyn NodeVisitor) {
+        self.visit_mut_with(visitor, Cache::default());
+    }
+
+    /// Like [`Tree::visit_mut`], but doesn't visit nested SVG images.
+    pub(crate) fn visit_mut_without_images(&mut self, visitor: &mut dyn NodeVisitor) {
+        let cache = Cache {
+            skip_images: true,
+            ..Cache::default()
+        };
+        self.visit_mut_with(visitor, cache);
+    }
+
+    fn visit_mut_with(&mut self, visitor: &mut dyn NodeVisitor, mut cache: Cache) {
+        visit_children(&mut self.root, visitor, &mut cache);
+        self.root.calculate_bounding_boxes();
+
+        // Shared resources were replaced, so the lists must be updated as well.
+        self.remove_unused_defs();
+    }
+}
+
+// Resources are shared, therefore each one must be replaced only once
+// to preserve ID uniqueness.
+#[derive(Default)]
+struct Cache {
+    clip_paths: HashMap<*const ClipPath, Arc<ClipPath>>,
+    masks: HashMap<*const Mask, Arc<Mask>>,
+    patterns: HashMap<*const Pattern, Arc<Pattern>>,
+    filters: HashMap<*const filter::Filter, Arc<filter::Filter>>,
+    skip_images: bool,
+}
+
+fn visit_children(parent: &mut Group, visitor: &mut dyn NodeVisitor, cache: &mut Cache) {
+    for node in &mut parent.children {
+        visit_node(node, visitor, cache);
+    }
+}
+
+fn visit_node(node: &mut Node, visitor: &mut dyn NodeVisitor, cache: &mut Cache) {
+    visitor.enter(node);
+
+    match node {
+        Node::Group(ref mut group) => {
+            visit_children(group, visitor, cache);
+
+            if let Some(ref mut clip) = group.clip_path {
+                *clip = visit_clip_path(clip, visitor, cache);
+            }
+
+            if let Some(ref mut mask) = group.mask {
+                *mask = visit_mask(mask, visitor, cache);
+            }
+
+            for filter in &mut group.filters {
+                *filter = visit_filter(filter, visitor, cache);
+            }
+        }
+        Node::Path(ref mut path) => {
+            visit_paints(&mut path.fill, &mut path.stroke, visitor, cache);
+        }
+        Node::Image(ref mut image) => {
+            if let ImageKind::SVG(ref mut tree) = image.kind {
+                if !cache.skip_images {
+                    tree.visit_mut(visitor);
+                }
+            }
+        }
+        Node::Text(ref mut text) => {
+            // Spans share patterns with the flattened text.
+            for chunk in &mut text.chunks {
+                for span in &mut chunk.spans {
+                    visit_paints(&mut span.fill, &mut span.stroke, visitor, cache);
+
+                    let decoration = &mut span.decoration;
+                    for style in [
+                        &mut decoration.underline,
+                        &mut decoration.overline,
+                        &mut decoration.line_through,
+                    ]
+                    .into_iter()
+                    .flatten()
+                    {
+                        visit_paints(&mut style.fill, &mut style.stroke, visitor, cache);
+                    }
+                }
+            }
+
+            #[cfg(feature = "text")]
+            for span in &mut text.layouted {
+                visit_paints(&mut span.fill, &mut span.stroke, visitor, cache);
+
+                for path in [
+                    &mut span.underline,
+                    &mut span.overline,
+                    &mut span.line_through,
+                ]
+                .into_iter()
+                .flatten()
+                {
+                    visit_paints(&mut path.fill, &mut path.stroke, visitor, cache);
+                }
+            }
+
+            visit_children(&mut text.flattened, visitor, cache);
+        }
+    }
+
+    visitor.leave(node);
+
+    // The visitor could have changed the node or its children.
+    match node {
+        Node::Group(ref mut group) => {
+            group.calculate_bounding_boxes();
+        }
+        Node::Path(ref mut path) => {
+            path.recalculate_bounding_boxes(path.abs_transform);
+        }
+        Node::Image(_) => {}
+        Node::Text(ref mut text) => {
+            text.flattened.calculate_bounding_boxes();
+
+            let stroke_bbox = text.flattened.stroke_bounding_box();
+            if let Some(abs_stroke_bbox) = stroke_bbox.transform(text.abs_transform) {
+                text.stroke_bounding_box = stroke_bbox;
+                text.abs_stroke_bounding_box = abs_stroke_bbox;
+            }
+        }
+    }
+}
+
+fn visit_subroot(root: &mut Group, visitor: &mut dyn NodeVisitor, cache: &mut Cache) {
+    visit_children(root, visitor, cache);
+    root.calculate_bounding_boxes();
+}
+
+fn visit_paints(
+    fill: &mut Option<Fill>,
+    stroke: &mut Option<Stroke>,
+    visitor: &mut dyn NodeVisitor,
+    cache: &mut Cache,
+) {
+    if let Some(ref mut fill) = fill {
+        visit_paint(&mut fill.paint, visitor, cache);
+    }
+
+    if let Some(ref mut stroke) = stroke {
+        visit_paint(&mut stroke.paint, visitor, cache);
+    }
+}
+
+fn visit_paint(paint: &mut Paint, visitor: &mut dyn NodeVisitor, cache: &mut Cache) {
+    let Paint::Pattern(ref patt) = paint else {
+        return;
+    };
+
+    let key = Arc::as_ptr(patt);
+    if let Some(patt) = cache.patterns.get(&key) {
+        *paint = Paint::Pattern(patt.clone());
+        return;
+    }
+
+    let mut root = patt.root.clone();
+    visit_subroot(&mut root, visitor, cache);
+
+    let new_patt = Arc::new(Pattern {
+        id: NonEmptyString(patt.id.0.clone()),
+        units: patt.units,
+        content_units: patt.content_units,
+        transform: patt.transform,
+        rect: patt.rect,
+        view_box: patt.view_box,
+        root,
+    });
+
+    cache.patterns.insert(key, new_patt.clone());
+    *paint = Paint::Pattern(new_patt);
+}
+
+fn visit_clip_path(
+    clip: &Arc<ClipPath>,
+    visitor: &mut dyn NodeVisitor,
+    cache: &mut Cache,
+) -> Arc<ClipPath> {
+    let key = Arc::as_ptr(clip);
+    if let Some(clip) = cache.clip_paths.get(&key) {
+        return clip.clone();
+    }
+
+    let mut root = clip.root.clone();
+    visit_subroot(&mut root, visitor, cache);
+
+    let new_clip = Arc::new(ClipPath {
+        id: NonEmptyString(clip.id.0.clone()),
+        transform: clip.transform,
+        clip_path: clip
+            .clip_path
+            .as_ref()
+            .map(|sub_clip| visit_clip_path(sub_clip, visitor, cache)),
+        root,
+    });
+
+    cache.clip_paths.insert(key, new_clip.clone());
+    new_clip
+}
+
+fn visit_mask(mask: &Arc<Mask>, visitor: &mut dyn NodeVisitor, cache: &mut Cache) -> Arc<Mask> {
+    let key = Arc::as_ptr(mask);
+    if let Some(mask) = cache.masks.get(&key) {
+        return mask.clone();
+    }
+
+    let mut root = mask.root.clone();
+    visit_subroot(&mut root, visitor, cache);
+
+    let new_mask = Arc::new(Mask {
+        id: NonEmptyString(mask.id.0.clone()),
+        rect: mask.rect,
+        kind: mask.kind,
+        mask: mask
+            .mask
+            .as_ref()
+            .map(|sub_mask| visit_mask(sub_mask, visitor, cache)),
+        root,
+    });
+
+    cache.masks.insert(key, new_mask.clone());
+    new_mask
+}
+
+fn visit_filter(
+    filter: &Arc<filter::Filter>,
+    visitor: &mut dyn NodeVisitor,
+    cache: &mut Cache,
+) -> Arc<filter::Filter> {
+    let key = Arc::as_ptr(filter);
+    if let Some(filter) = cache.filters.get(&key) {
+        return filter.clone();
+    }
+
+    // Only `feImage` has children.
+    let has_images = filter
+        .primitives
+        .iter()
+        .any(|p| matches!(p.kind, filter::Kind::Image(_)));
+    if !has_images {
+        return filter.clone();
+    }
+
+    let mut primitives = filter.primitives.clone();
+    for primitive in &mut primitives {
+        if let filter::Kind::Image(ref mut image) = primitive.kind {
+            visit_subroot(&mut image.root, visitor, cache);
+        }
+    }
+
+    let new_filter = Arc::new(filter::Filter {
+        id: NonEmptyString(filter.id.0.clone()),
+        rect: filter.rect,
+        primitives,
+    });
+
+    cache.filters.insert(key, new_filter.clone());
+    new_filter
+}
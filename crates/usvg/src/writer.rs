@@ -12,6 +12,16 @@ use crate::*;
 
 impl Tree {
     /// Writes `usvg::Tree` back to SVG.
+    ///
+    /// Since the tree is already simplified, the output is a fully static SVG,
+    /// which can be rendered by any renderer the same way:
+    /// text is converted into paths, unless [`WriteOptions::preserve_text`] is set,
+    /// markers, `use`, `switch`, CSS and nested `svg` are resolved
+    /// and all paint servers are in `userSpaceOnUse` units.
+    /// Gradients, patterns, clip paths, masks and filters are still written into `defs`,
+    /// since SVG has no way to inline them.
+    ///
+    /// Fonts are needed only during parsing and not here.
     pub fn to_string(&self, opt: &WriteOptions) -> String {
         convert(self, opt)
     }
@@ -148,6 +158,9 @@ pub(crate) fn convert(tree: &Tree, opt: &WriteOptions) -> String {
     xml.start_svg_element(EId::Svg);
     xml.write_svg_attribute(AId::Width, &tree.size.width());
     xml.write_svg_attribute(AId::Height, &tree.size.height());
+    if tree.overflow_visible {
+        xml.write_svg_attribute(AId::Overflow, "visible");
+    }
     xml.write_attribute("xmlns", "http://www.w3.org/2000/svg");
     if has_xlink(&tree.root) {
         xml.write_attribute("xmlns:xlink", "http://www.w3.org/1999/xlink");
@@ -600,11 +613,7 @@ fn write_text_path_paths(parent: &Group, opt: &WriteOptions, xml: &mut XmlWriter
                 if let TextFlow::Path(ref text_path) = chunk.text_flow {
                     let path = Path::new(
                         text_path.id().to_string(),
-                        true,
-                        None,
-                        None,
-                        PaintOrder::default(),
-                        ShapeRendering::default(),
+                        PathStyle::default(),
                         text_path.path.clone(),
                         Transform::default(),
                     );
@@ -1169,6 +1178,14 @@ fn write_base_grad(g: &BaseGradient, opt: &WriteOptions, xml: &mut XmlWriter) {
         SpreadMethod::Repeat => xml.write_svg_attribute(AId::SpreadMethod, "repeat"),
     }
 
+    match g.color_interpolation {
+        GradientInterpolation::SRGB => {}
+        GradientInterpolation::Lab => xml.write_svg_attribute(AId::ColorInterpolation, "lab"),
+        GradientInterpolation::Lch => xml.write_svg_attribute(AId::ColorInterpolation, "lch"),
+        GradientInterpolation::OkLab => xml.write_svg_attribute(AId::ColorInterpolation, "oklab"),
+        GradientInterpolation::OkLch => xml.write_svg_attribute(AId::ColorInterpolation, "oklch"),
+    }
+
     for s in &g.stops {
         xml.start_svg_element(EId::Stop);
         xml.write_svg_attribute(AId::Offset, &s.offset.get());
@@ -1211,6 +1228,10 @@ fn write_path(
         ShapeRendering::GeometricPrecision => {}
     }
 
+    if path.vector_effect == VectorEffect::NonScalingStroke {
+        xml.write_svg_attribute(AId::VectorEffect, "non-scaling-stroke");
+    }
+
     if let Some(id) = clip_path {
         xml.write_func_iri(AId::ClipPath, id, opt);
     }
@@ -1417,6 +1438,8 @@ fn write_num(num: f32, buf: &mut Vec<u8>, precision: u8) {
     // can be slightly different. So having a lower precision makes
     // our output and tests reproducible.
     let v = (num * POW_VEC[precision as usize]).round() / POW_VEC[precision as usize];
+    // Small negative numbers can be rounded to -0.
+    let v = if v == 0.0 { 0.0 } else { v };
 
     write!(buf, "{}", v).unwrap();
 }
@@ -1490,6 +1513,10 @@ fn write_span(
 
     xml.write_svg_attribute(AId::FontSize, &span.font_size);
 
+    if let Some(font_size_adjust) = span.font_size_adjust {
+        xml.write_svg_attribute(AId::FontSizeAdjust, &font_size_adjust);
+    }
+
     xml.write_visibility(span.visible);
 
     if span.letter_spacing != 0.0 {
@@ -1508,6 +1535,16 @@ fn write_span(
         xml.write_svg_attribute(AId::LengthAdjust, "spacingAndGlyphs");
     }
 
+    match span.glyph_orientation_vertical {
+        GlyphOrientationVertical::Auto => {}
+        GlyphOrientationVertical::Upright => {
+            xml.write_svg_attribute(AId::GlyphOrientationVertical, "0")
+        }
+        GlyphOrientationVertical::Rotated => {
+            xml.write_svg_attribute(AId::GlyphOrientationVertical, "90")
+        }
+    }
+
     if span.small_caps {
         xml.write_svg_attribute(AId::FontVariant, "small-caps");
     }
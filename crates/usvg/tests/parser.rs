@@ -1,8 +1,19 @@
 // Copyright 2018 the Resvg Authors
 // SPDX-License-Identifier: Apache-2.0 OR MIT
 
+use std::sync::Arc;
+
+use once_cell::sync::Lazy;
 use usvg::Color;
 
+static GLOBAL_FONTDB: Lazy<Arc<usvg::fontdb::Database>> = Lazy::new(|| {
+    let mut fontdb = usvg::fontdb::Database::new();
+    fontdb.load_fonts_dir("../resvg/tests/fonts");
+    fontdb.set_serif_family("Noto Serif");
+    fontdb.set_sans_serif_family("Noto Sans");
+    Arc::new(fontdb)
+});
+
 #[test]
 fn clippath_with_invalid_child() {
     let svg = "
@@ -19,6 +30,69 @@ fn clippath_with_invalid_child() {
     assert!(!tree.root().has_children());
 }
 
+#[test]
+fn duplicated_ids() {
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 200 200'>
+        <linearGradient id='lg1'><stop stop-color='green'/></linearGradient>
+        <linearGradient id='lg1'><stop stop-color='red'/></linearGradient>
+        <rect id='rect1' width='10' height='10' fill='url(#lg1)'/>
+        <rect id='rect1' x='20' width='10' height='10' fill='blue'/>
+    </svg>
+    ";
+
+    let tree = usvg::Tree::from_str(&svg, &usvg::Options::default()).unwrap();
+
+    // The first element in the document order is used, both by references and by lookups.
+    let usvg::Node::Path(ref path) = tree.node_by_id("rect1").unwrap() else {
+        unreachable!()
+    };
+    assert_eq!(
+        path.fill().unwrap().paint(),
+        &usvg::Paint::Color(Color::new_rgb(0, 128, 0))
+    );
+}
+
+#[test]
+fn keep_use_references() {
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' xmlns:xlink='http://www.w3.org/1999/xlink'
+         viewBox='0 0 200 200'>
+        <symbol id='symbol1'>
+            <rect width='10' height='10'/>
+        </symbol>
+        <rect id='rect1' width='10' height='10'/>
+        <use xlink:href='#rect1'/>
+        <use xlink:href='#symbol1' x='20'/>
+    </svg>
+    ";
+
+    let refs = |tree: &usvg::Tree| -> Vec<Option<String>> {
+        tree.root()
+            .children()
+            .iter()
+            .map(|node| match node {
+                usvg::Node::Group(ref g) => g.use_href().map(|s| s.to_string()),
+                _ => None,
+            })
+            .collect()
+    };
+
+    // A `use` without a transform doesn't require a group.
+    let tree = usvg::Tree::from_str(&svg, &usvg::Options::default()).unwrap();
+    assert_eq!(refs(&tree), vec![None, None, None]);
+
+    let opt = usvg::Options {
+        keep_use_references: true,
+        ..usvg::Options::default()
+    };
+    let tree = usvg::Tree::from_str(&svg, &opt).unwrap();
+    assert_eq!(
+        refs(&tree),
+        vec![None, Some("rect1".to_string()), Some("symbol1".to_string())]
+    );
+}
+
 #[test]
 fn stylesheet_injection() {
     let svg = "<svg id='svg1' viewBox='0 0 200 200' xmlns='http://www.w3.org/2000/svg'>
@@ -216,6 +290,45 @@ fn size_detection_5() {
     assert_eq!(tree.size(), usvg::Size::from_wh(100.0, 100.0).unwrap());
 }
 
+#[test]
+fn size_detection_dpi() {
+    let svg = "
+    <svg width='15pt' height='15pt' viewBox='0 0 10 10' xmlns='http://www.w3.org/2000/svg'>
+        <rect id='rect1' width='1in' height='1'/>
+        <rect id='rect2' width='5' height='1'/>
+        <g id='g1' style='transform: translateX(1in)'/>
+    </svg>
+    ";
+
+    let parse = |dpi| {
+        let opt = usvg::Options {
+            dpi,
+            ..usvg::Options::default()
+        };
+        let tree = usvg::Tree::from_str(&svg, &opt).unwrap();
+        let width = |id| tree.node_by_id(id).unwrap().bounding_box().width();
+        let usvg::Node::Group(ref g1) = tree.node_by_id("g1").unwrap() else {
+            unreachable!()
+        };
+        (
+            tree.size(),
+            width("rect1"),
+            width("rect2"),
+            g1.transform().tx,
+        )
+    };
+
+    // Only absolute units are affected. Everything else is scaled only by the `viewBox`.
+    assert_eq!(
+        parse(96.0),
+        (usvg::Size::from_wh(20.0, 20.0).unwrap(), 96.0, 5.0, 96.0)
+    );
+    assert_eq!(
+        parse(192.0),
+        (usvg::Size::from_wh(40.0, 40.0).unwrap(), 192.0, 5.0, 192.0)
+    );
+}
+
 #[test]
 fn invalid_size_1() {
     let svg = "<svg width='0' height='0' viewBox='0 0 10 20' xmlns='http://www.w3.org/2000/svg'/>";
@@ -490,3 +603,3476 @@ fn path_transform_in_svg() {
         usvg::Transform::from_translate(100.0, 150.0)
     );
 }
+
+#[test]
+fn disable_kerning_globally() {
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 200 100'>
+        <text x='10' y='50' font-family='Noto Sans' font-size='48'>AVAV</text>
+    </svg>
+    ";
+
+    let text_width = |apply_kerning| {
+        let opt = usvg::Options {
+            fontdb: GLOBAL_FONTDB.clone(),
+            apply_kerning,
+            ..usvg::Options::default()
+        };
+
+        let tree = usvg::Tree::from_str(&svg, &opt).unwrap();
+        tree.root().children()[0].bounding_box().width()
+    };
+
+    // `AV` is a classic kerning pair, so the text must become wider.
+    assert!(text_width(false) > text_width(true));
+}
+
+#[test]
+fn baseline_shift_without_font_metrics() {
+    // Hide the `OS/2` table, which contains subscript and superscript metrics,
+    // by renaming it. The fallback offsets should be used instead.
+    let mut data = std::fs::read("../resvg/tests/fonts/NotoSans-Regular.ttf").unwrap();
+    let num_tables = u16::from_be_bytes([data[4], data[5]]) as usize;
+    for i in 0..num_tables {
+        let offset = 12 + i * 16;
+        if &data[offset..offset + 4] == b"OS/2" {
+            data[offset..offset + 4].copy_from_slice(b"XS/2");
+        }
+    }
+
+    let mut fontdb = usvg::fontdb::Database::new();
+    fontdb.load_font_data(data);
+
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 200 200'>
+        <text id='text1' x='10' y='100' font-family='Noto Sans' font-size='100'>X</text>
+        <text id='text2' x='10' y='100' font-family='Noto Sans' font-size='100'>
+            <tspan baseline-shift='sub'>X</tspan></text>
+        <text id='text3' x='10' y='100' font-family='Noto Sans' font-size='100'>
+            <tspan baseline-shift='super'>X</tspan></text>
+    </svg>
+    ";
+
+    let opt = usvg::Options {
+        fontdb: Arc::new(fontdb),
+        ..usvg::Options::default()
+    };
+    let tree = usvg::Tree::from_str(&svg, &opt).unwrap();
+
+    let y = |id| tree.node_by_id(id).unwrap().abs_bounding_box().y();
+    // 0.2em down and 0.4em up.
+    assert!((y("text2") - y("text1") - 20.0).abs() < 0.01);
+    assert!((y("text1") - y("text3") - 40.0).abs() < 0.01);
+}
+
+#[test]
+fn text_line_metrics() {
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 200 200'>
+        <text id='text1' y='50' font-family='Noto Sans' font-size='20'>
+            Text<tspan font-size='40'>Text</tspan></text>
+        <text id='text2' y='150' font-family='Noto Sans' font-size='40'>Text</text>
+    </svg>
+    ";
+
+    let opt = usvg::Options {
+        fontdb: GLOBAL_FONTDB.clone(),
+        ..usvg::Options::default()
+    };
+    let tree = usvg::Tree::from_str(&svg, &opt).unwrap();
+
+    let metrics = |id| match tree.node_by_id(id).unwrap() {
+        usvg::Node::Text(ref text) => text.line_metrics(tree.fontdb()).unwrap(),
+        _ => unreachable!(),
+    };
+
+    let mixed = metrics("text1");
+    assert!(mixed.ascent > 0.0);
+    assert!(mixed.descent < 0.0);
+    assert!(mixed.x_height > 0.0 && mixed.x_height < mixed.ascent);
+    // The largest span wins.
+    assert_eq!(mixed, metrics("text2"));
+}
+
+#[test]
+fn white_space_pre() {
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 200 200'>
+        <text id='text1' x='10' y='20' font-family='Noto Sans' font-size='10'
+              xml:space='default' style='white-space:pre'>a  b
+c</text>
+        <text id='text2' x='10' y='20' font-family='Noto Sans' font-size='10'
+              xml:space='preserve'>a  b
+c</text>
+        <text id='text3' x='10' y='20' font-family='Noto Sans' font-size='10'>a  b
+c</text>
+    </svg>
+    ";
+
+    let opt = usvg::Options {
+        fontdb: GLOBAL_FONTDB.clone(),
+        ..usvg::Options::default()
+    };
+    let tree = usvg::Tree::from_str(&svg, &opt).unwrap();
+
+    let chunks = |id| match tree.node_by_id(id).unwrap() {
+        usvg::Node::Text(ref text) => text
+            .chunks()
+            .iter()
+            .map(|c| (c.text().to_string(), c.x(), c.y()))
+            .collect::<Vec<_>>(),
+        _ => unreachable!(),
+    };
+
+    // `white-space` wins over `xml:space`. Newlines start a new line.
+    assert_eq!(
+        chunks("text1"),
+        vec![
+            ("a  b".to_string(), Some(10.0), Some(20.0)),
+            ("c".to_string(), Some(10.0), Some(32.0)),
+        ]
+    );
+    assert_eq!(
+        chunks("text2"),
+        vec![("a  b c".to_string(), Some(10.0), Some(20.0))]
+    );
+    assert_eq!(
+        chunks("text3"),
+        vec![("a b c".to_string(), Some(10.0), Some(20.0))]
+    );
+}
+
+#[test]
+fn text_in_nested_svg_image() {
+    // <svg xmlns='http://www.w3.org/2000/svg' width='100' height='100'>
+    //     <text x='10' y='50' font-size='20'>Text</text>
+    // </svg>
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' xmlns:xlink='http://www.w3.org/1999/xlink' viewBox='0 0 200 200'>
+        <g>
+            <image id='image1' width='100' height='100' xlink:href='data:image/svg+xml;base64,PHN2ZyB4bWxucz0naHR0cDovL3d3dy53My5vcmcvMjAwMC9zdmcnIHdpZHRoPScxMDAnIGhlaWdodD0nMTAwJz48dGV4dCB4PScxMCcgeT0nNTAnIGZvbnQtc2l6ZT0nMjAnPlRleHQ8L3RleHQ+PC9zdmc+'/>
+        </g>
+    </svg>
+    ";
+
+    let opt = usvg::Options {
+        font_family: "Noto Sans".to_string(),
+        fontdb: GLOBAL_FONTDB.clone(),
+        ..usvg::Options::default()
+    };
+    let tree = usvg::Tree::from_str(&svg, &opt).unwrap();
+    assert!(tree.has_text_nodes());
+
+    let sub_tree = match tree.node_by_id("image1").unwrap() {
+        usvg::Node::Group(ref group) => match group.children().first().unwrap() {
+            usvg::Node::Image(ref image) => match image.kind() {
+                usvg::ImageKind::SVG(ref tree) => tree,
+                _ => unreachable!(),
+            },
+            _ => unreachable!(),
+        },
+        _ => unreachable!(),
+    };
+
+    // The nested text must be converted using the same fonts.
+    let text = match sub_tree.root().children().first().unwrap() {
+        usvg::Node::Text(ref text) => text,
+        _ => unreachable!(),
+    };
+    assert!(text.flattened().has_children());
+
+    let font_id = text.layouted()[0].positioned_glyphs[0].font;
+    let face = sub_tree.fontdb().face(font_id).unwrap();
+    assert_eq!(face.families[0].0, "Noto Sans");
+}
+
+#[test]
+fn no_text_nodes() {
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 200 200'>
+        <g>
+            <rect width='10' height='10'/>
+        </g>
+    </svg>
+    ";
+
+    let tree = usvg::Tree::from_str(&svg, &usvg::Options::default()).unwrap();
+    assert!(!tree.has_text_nodes());
+}
+
+#[test]
+fn overlay() {
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 200 200'>
+        <linearGradient id='lg1'>
+            <stop offset='0' stop-color='white'/>
+            <stop offset='1' stop-color='black'/>
+        </linearGradient>
+        <rect id='rect1' width='10' height='10' fill='url(#lg1)'/>
+    </svg>
+    ";
+
+    let mut tree = usvg::Tree::from_str(&svg, &usvg::Options::default()).unwrap();
+    let other = usvg::Tree::from_str(&svg, &usvg::Options::default()).unwrap();
+    assert!(tree.overlay(&other, usvg::Transform::from_translate(100.0, 50.0)));
+    assert!(tree.overlay(&other, usvg::Transform::from_scale(2.0, 2.0)));
+
+    assert_eq!(tree.size(), other.size());
+    assert_eq!(
+        tree.linear_gradients()
+            .iter()
+            .map(|lg| lg.id())
+            .collect::<Vec<_>>(),
+        vec!["lg1", "overlay1-lg1", "overlay2-lg1"]
+    );
+
+    let bbox = |id| tree.node_by_id(id).unwrap().abs_bounding_box();
+    assert_eq!(
+        bbox("rect1"),
+        usvg::Rect::from_xywh(0.0, 0.0, 10.0, 10.0).unwrap()
+    );
+    assert_eq!(
+        bbox("overlay1-rect1"),
+        usvg::Rect::from_xywh(100.0, 50.0, 10.0, 10.0).unwrap()
+    );
+    assert_eq!(
+        bbox("overlay2-rect1"),
+        usvg::Rect::from_xywh(0.0, 0.0, 20.0, 20.0).unwrap()
+    );
+    assert_eq!(
+        tree.root().abs_bounding_box(),
+        usvg::Rect::from_xywh(0.0, 0.0, 110.0, 60.0).unwrap()
+    );
+
+    match tree.node_by_id("overlay1-rect1").unwrap() {
+        usvg::Node::Path(ref path) => match path.fill().unwrap().paint() {
+            usvg::Paint::LinearGradient(ref lg) => assert_eq!(lg.id(), "overlay1-lg1"),
+            _ => unreachable!(),
+        },
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn overlay_empty() {
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 200 200'>
+        <rect id='rect1' width='10' height='10'/>
+    </svg>
+    ";
+
+    let mut tree = usvg::Tree::from_str(&svg, &usvg::Options::default()).unwrap();
+    let empty = "<svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 200 200'/>";
+    let empty = usvg::Tree::from_str(empty, &usvg::Options::default()).unwrap();
+    assert!(!tree.overlay(&empty, usvg::Transform::default()));
+
+    let other = usvg::Tree::from_str(&svg, &usvg::Options::default()).unwrap();
+    assert!(!tree.overlay(&other, usvg::Transform::from_scale(0.0, 0.0)));
+
+    assert_eq!(tree.root().children().len(), 1);
+}
+
+#[test]
+fn overlay_text() {
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 200 200'>
+        <text id='text1' x='10' y='50' font-family='Noto Sans'>Text</text>
+    </svg>
+    ";
+
+    let opt = usvg::Options {
+        fontdb: GLOBAL_FONTDB.clone(),
+        ..usvg::Options::default()
+    };
+    let mut tree = usvg::Tree::from_str(&svg, &opt).unwrap();
+    let other = usvg::Tree::from_str(&svg, &opt).unwrap();
+    assert!(tree.overlay(&other, usvg::Transform::from_translate(100.0, 50.0)));
+
+    let text = |id| match tree.node_by_id(id).unwrap() {
+        usvg::Node::Text(ref text) => text.clone(),
+        _ => unreachable!(),
+    };
+
+    let ts = usvg::Transform::from_translate(100.0, 50.0);
+    let (orig, text) = (text("text1"), text("overlay1-text1"));
+    assert_eq!(
+        text.abs_bounding_box(),
+        orig.abs_bounding_box().transform(ts).unwrap()
+    );
+    // Flattened text is moved as well.
+    assert_eq!(text.flattened().abs_transform(), ts);
+    assert_eq!(
+        text.flattened().abs_bounding_box(),
+        orig.flattened().abs_bounding_box().transform(ts).unwrap()
+    );
+}
+
+#[test]
+fn flatten_clip_paths() {
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 200 200'>
+        <clipPath id='clip1'>
+            <rect width='50' height='100'/>
+        </clipPath>
+        <clipPath id='clip2'>
+            <circle cx='150' cy='150' r='20'/>
+        </clipPath>
+        <circle id='circle1' cx='50' cy='50' r='50' clip-path='url(#clip1)'/>
+        <rect id='rect1' x='100' y='100' width='100' height='100' clip-path='url(#clip2)'/>
+    </svg>
+    ";
+
+    let mut tree = usvg::Tree::from_str(&svg, &usvg::Options::default()).unwrap();
+    tree.flatten_clip_paths();
+
+    // Only the rectangular clip path can be flattened.
+    assert_eq!(
+        tree.clip_paths().iter().map(|c| c.id()).collect::<Vec<_>>(),
+        vec!["clip2"]
+    );
+
+    let path = match tree.node_by_id("circle1").unwrap() {
+        usvg::Node::Path(ref path) => path,
+        _ => unreachable!(),
+    };
+    let bbox = path.bounding_box();
+    assert!(bbox.left().abs() < 0.001 && bbox.top().abs() < 0.001);
+    assert!((bbox.width() - 50.0).abs() < 0.001 && (bbox.height() - 100.0).abs() < 0.001);
+    // The left half of the circle, i.e. two arcs and a straight cut.
+    let segments: Vec<_> = path.data().segments().collect();
+    assert!(matches!(
+        segments.as_slice(),
+        [
+            usvg::tiny_skia_path::PathSegment::MoveTo(_),
+            usvg::tiny_skia_path::PathSegment::CubicTo(..),
+            usvg::tiny_skia_path::PathSegment::CubicTo(..),
+            usvg::tiny_skia_path::PathSegment::LineTo(_),
+            usvg::tiny_skia_path::PathSegment::Close,
+        ]
+    ));
+}
+
+#[test]
+fn flatten_clip_paths_with_filter() {
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 200 200'>
+        <clipPath id='clip1'>
+            <rect width='50' height='100'/>
+        </clipPath>
+        <filter id='filter1'>
+            <feGaussianBlur stdDeviation='5'/>
+        </filter>
+        <circle id='circle1' cx='50' cy='50' r='50' clip-path='url(#clip1)' filter='url(#filter1)'/>
+    </svg>
+    ";
+
+    let mut tree = usvg::Tree::from_str(&svg, &usvg::Options::default()).unwrap();
+    tree.flatten_clip_paths();
+
+    // The blur must be clipped after being applied.
+    assert_eq!(
+        tree.clip_paths().iter().map(|c| c.id()).collect::<Vec<_>>(),
+        vec!["clip1"]
+    );
+
+    let path = match tree.node_by_id("circle1").unwrap() {
+        usvg::Node::Path(ref path) => path,
+        _ => unreachable!(),
+    };
+    assert!((path.bounding_box().width() - 100.0).abs() < 0.001);
+}
+
+#[test]
+fn node_at() {
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 200 200'>
+        <clipPath id='clip1'>
+            <rect width='80' height='200'/>
+        </clipPath>
+        <rect id='background' width='200' height='200'/>
+        <path id='frame' fill-rule='evenodd' d='M 10 10 H 90 V 90 H 10 Z M 30 30 V 70 H 70 V 30 Z'/>
+        <line id='line' x1='100' y1='150' x2='200' y2='150' stroke='black' stroke-width='10'/>
+        <g transform='translate(100 0)' clip-path='url(#clip1)'>
+            <circle id='circle' cx='50' cy='50' r='40'/>
+        </g>
+        <g transform='translate(100 0)' clip-path='url(#clip1)'>
+            <circle id='clipped-circle' cx='100' cy='50' r='40'/>
+        </g>
+    </svg>
+    ";
+
+    let tree = usvg::Tree::from_str(&svg, &usvg::Options::default()).unwrap();
+    let id_at = |x, y| tree.node_at(x, y).map(|n| n.id().to_string());
+
+    assert_eq!(id_at(20.0, 20.0).as_deref(), Some("frame"));
+    // A hole.
+    assert_eq!(id_at(50.0, 50.0).as_deref(), Some("background"));
+    assert_eq!(id_at(150.0, 154.0).as_deref(), Some("line"));
+    assert_eq!(id_at(140.0, 50.0).as_deref(), Some("circle"));
+    assert_eq!(id_at(170.0, 50.0).as_deref(), Some("clipped-circle"));
+    // Clipped.
+    assert_eq!(id_at(185.0, 50.0).as_deref(), Some("background"));
+    assert_eq!(id_at(250.0, 50.0), None);
+}
+
+#[test]
+fn transform_decomposition() {
+    use usvg::DecomposeTransform;
+
+    fn check(ts: usvg::Transform) {
+        let decomposed = ts.decompose().unwrap();
+        let ts2 = decomposed.compose();
+        for (v1, v2) in [
+            (ts.sx, ts2.sx),
+            (ts.ky, ts2.ky),
+            (ts.kx, ts2.kx),
+            (ts.sy, ts2.sy),
+            (ts.tx, ts2.tx),
+            (ts.ty, ts2.ty),
+        ] {
+            assert!((v1 - v2).abs() < 0.0001, "{:?} != {:?}", ts, ts2);
+        }
+    }
+
+    let ts = usvg::Transform::from_translate(10.0, 20.0)
+        .pre_rotate(30.0)
+        .pre_scale(2.0, 3.0);
+    let decomposed = ts.decompose().unwrap();
+    assert_eq!(decomposed.translate_x, 10.0);
+    assert_eq!(decomposed.translate_y, 20.0);
+    assert!((decomposed.rotate - 30.0).abs() < 0.0001);
+    assert!(decomposed.skew_x.abs() < 0.0001);
+    assert!((decomposed.scale_x - 2.0).abs() < 0.0001);
+    assert!((decomposed.scale_y - 3.0).abs() < 0.0001);
+    check(ts);
+
+    // A flip is represented by a negative vertical scale.
+    let decomposed = usvg::Transform::from_scale(-1.0, 1.0).decompose().unwrap();
+    assert!((decomposed.rotate.abs() - 180.0).abs() < 0.0001);
+    assert!((decomposed.scale_y + 1.0).abs() < 0.0001);
+    check(usvg::Transform::from_scale(-1.0, 1.0));
+    check(usvg::Transform::from_scale(1.0, -2.0).pre_rotate(45.0));
+
+    check(usvg::Transform::from_row(1.0, 0.5, -0.3, 2.0, 5.0, 6.0));
+    check(usvg::Transform::from_skew(0.5, 0.0).pre_rotate(-120.0));
+
+    assert!(usvg::Transform::from_scale(0.0, 1.0).decompose().is_none());
+    assert!(usvg::Transform::from_row(1.0, 2.0, 2.0, 4.0, 0.0, 0.0)
+        .decompose()
+        .is_none());
+}
+
+#[test]
+fn pattern_tiles() {
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 200 200'>
+        <pattern id='patt1' width='0.5' height='0.25' patternTransform='scale(2)'>
+            <rect width='5' height='5'/>
+        </pattern>
+        <rect x='10' y='20' width='100' height='100' fill='url(#patt1)'/>
+    </svg>
+    ";
+
+    let tree = usvg::Tree::from_str(&svg, &usvg::Options::default()).unwrap();
+    let pattern = &tree.patterns()[0];
+
+    // `objectBoundingBox` units are already resolved.
+    assert_eq!(
+        pattern.rect(),
+        usvg::NonZeroRect::from_xywh(10.0, 20.0, 50.0, 25.0).unwrap()
+    );
+    assert_eq!(pattern.tile_rect(0, 0), pattern.rect());
+    assert_eq!(
+        pattern.tile_rect(1, -2),
+        usvg::NonZeroRect::from_xywh(60.0, -30.0, 50.0, 25.0).unwrap()
+    );
+    assert_eq!(
+        pattern.tile_transform(1, 1),
+        usvg::Transform::from_row(2.0, 0.0, 0.0, 2.0, 120.0, 90.0)
+    );
+}
+
+#[test]
+fn preserve_aspect_ratio() {
+    fn first_image(parent: &usvg::Group) -> Option<&usvg::Image> {
+        for node in parent.children() {
+            match node {
+                usvg::Node::Image(ref image) => return Some(image),
+                usvg::Node::Group(ref group) => {
+                    if let Some(image) = first_image(group) {
+                        return Some(image);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        None
+    }
+
+    fn pattern_content_transform(pattern: &usvg::Pattern) -> usvg::Transform {
+        match pattern.root().children().first() {
+            Some(usvg::Node::Group(ref group)) => group.transform(),
+            _ => usvg::Transform::default(),
+        }
+    }
+
+    // A 20x10 image, a 10x10 nested SVG and a 10x10 pattern tile,
+    // each fitted into a rectangle with a different aspect ratio.
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 200 200'>
+        <pattern id='patt1' patternUnits='userSpaceOnUse' width='100' height='50'
+                 viewBox='0 0 10 10' preserveAspectRatio='xMidYMid ASPECT'>
+            <rect width='10' height='10'/>
+        </pattern>
+        <image width='100' height='100' preserveAspectRatio='xMidYMid ASPECT'
+               href=\"data:image/svg+xml;utf8,&lt;svg xmlns='http://www.w3.org/2000/svg' width='20' height='10'&gt;&lt;rect width='20' height='10'/&gt;&lt;/svg&gt;\"/>
+        <svg width='100' height='50' viewBox='0 0 10 10' preserveAspectRatio='xMidYMid ASPECT'>
+            <rect id='rect1' width='10' height='10'/>
+        </svg>
+        <rect width='100' height='50' fill='url(#patt1)'/>
+    </svg>
+    ";
+
+    let tree =
+        usvg::Tree::from_str(&svg.replace("ASPECT", "meet"), &usvg::Options::default()).unwrap();
+    let image = first_image(tree.root()).unwrap();
+    assert_eq!(
+        image.abs_transform(),
+        usvg::Transform::from_row(5.0, 0.0, 0.0, 5.0, 0.0, 25.0)
+    );
+    assert_eq!(
+        image.abs_bounding_box(),
+        usvg::Rect::from_xywh(0.0, 25.0, 100.0, 50.0).unwrap()
+    );
+    assert_eq!(
+        tree.node_by_id("rect1").unwrap().abs_transform(),
+        usvg::Transform::from_row(5.0, 0.0, 0.0, 5.0, 25.0, 0.0)
+    );
+    assert_eq!(
+        pattern_content_transform(&tree.patterns()[0]),
+        usvg::Transform::from_row(5.0, 0.0, 0.0, 5.0, 25.0, 0.0)
+    );
+
+    let tree =
+        usvg::Tree::from_str(&svg.replace("ASPECT", "slice"), &usvg::Options::default()).unwrap();
+    let image = first_image(tree.root()).unwrap();
+    assert_eq!(
+        image.abs_transform(),
+        usvg::Transform::from_row(10.0, 0.0, 0.0, 10.0, -50.0, 0.0)
+    );
+    assert_eq!(
+        image.abs_bounding_box(),
+        usvg::Rect::from_xywh(-50.0, 0.0, 200.0, 100.0).unwrap()
+    );
+    assert_eq!(
+        tree.node_by_id("rect1").unwrap().abs_transform(),
+        usvg::Transform::from_row(10.0, 0.0, 0.0, 10.0, 0.0, -25.0)
+    );
+    assert_eq!(
+        pattern_content_transform(&tree.patterns()[0]),
+        usvg::Transform::from_row(10.0, 0.0, 0.0, 10.0, 0.0, -25.0)
+    );
+}
+
+#[test]
+fn tree_is_empty() {
+    fn is_empty(svg: &str) -> bool {
+        let svg =
+            format!("<svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 100 100'>{svg}</svg>");
+        usvg::Tree::from_str(&svg, &usvg::Options::default())
+            .unwrap()
+            .is_empty()
+    }
+
+    assert!(is_empty(""));
+    assert!(is_empty(
+        "<defs><rect id='rect1' width='10' height='10'/></defs>"
+    ));
+    assert!(is_empty(
+        "<rect width='10' height='10' visibility='hidden'/>"
+    ));
+    assert!(is_empty("<path d='M 10 10 L 50 10'/>"));
+    assert!(is_empty("<g><path d='M 10 10 L 50 10' stroke='none'/></g>"));
+    assert!(is_empty("<rect width='10' height='10' opacity='0'/>"));
+    assert!(is_empty("<rect width='10' height='10' fill-opacity='0'/>"));
+    assert!(is_empty(
+        "<rect width='10' height='10' fill-opacity='0' stroke='black' stroke-opacity='0'/>"
+    ));
+    assert!(is_empty(
+        "<g opacity='0'><rect width='10' height='10'/><rect x='20' width='10' height='10'/></g>"
+    ));
+
+    assert!(!is_empty("<rect width='10' height='10'/>"));
+    assert!(!is_empty(
+        "<g><path d='M 10 10 L 50 10' stroke='black'/></g>"
+    ));
+    assert!(!is_empty(
+        "<filter id='filter1' filterUnits='userSpaceOnUse' x='0' y='0' width='50' height='50'>
+            <feFlood flood-color='green'/>
+         </filter>
+         <g filter='url(#filter1)'/>"
+    ));
+}
+
+#[test]
+fn view_transform() {
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' width='200' height='100' viewBox='0 0 10 10'>
+        <rect width='10' height='10'/>
+    </svg>
+    ";
+
+    let tree = usvg::Tree::from_str(&svg, &usvg::Options::default()).unwrap();
+    let ts = tree.view_transform(tree.size());
+    assert_eq!(
+        ts,
+        usvg::Transform::from_row(10.0, 0.0, 0.0, 10.0, 50.0, 0.0)
+    );
+    match tree.root().children().first() {
+        Some(usvg::Node::Group(ref group)) => assert_eq!(group.transform(), ts),
+        _ => unreachable!(),
+    }
+
+    assert_eq!(
+        tree.view_transform(usvg::Size::from_wh(50.0, 100.0).unwrap()),
+        usvg::Transform::from_row(5.0, 0.0, 0.0, 5.0, 0.0, 25.0)
+    );
+
+    let tree = usvg::Tree::from_str(
+        &svg.replace("viewBox", "preserveAspectRatio='xMinYMax slice' viewBox"),
+        &usvg::Options::default(),
+    )
+    .unwrap();
+    assert_eq!(
+        tree.view_transform(tree.size()),
+        usvg::Transform::from_row(20.0, 0.0, 0.0, 20.0, 0.0, -100.0)
+    );
+}
+
+#[test]
+fn view_box_area() {
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' width='200' height='100' viewBox='0 0 10 10'>
+        <rect width='10' height='10'/>
+    </svg>
+    ";
+
+    let tree = usvg::Tree::from_str(&svg, &usvg::Options::default()).unwrap();
+    assert_eq!(
+        tree.view_box_area(),
+        usvg::NonZeroRect::from_xywh(50.0, 0.0, 100.0, 100.0).unwrap()
+    );
+
+    // Never larger than the canvas.
+    let tree = usvg::Tree::from_str(
+        &svg.replace("viewBox", "preserveAspectRatio='xMinYMax slice' viewBox"),
+        &usvg::Options::default(),
+    )
+    .unwrap();
+    assert_eq!(
+        tree.view_box_area(),
+        usvg::NonZeroRect::from_xywh(0.0, 0.0, 200.0, 100.0).unwrap()
+    );
+
+    let tree = usvg::Tree::from_str(
+        &svg.replace("viewBox", "preserveAspectRatio='none' viewBox"),
+        &usvg::Options::default(),
+    )
+    .unwrap();
+    assert_eq!(
+        tree.view_box_area(),
+        usvg::NonZeroRect::from_xywh(0.0, 0.0, 200.0, 100.0).unwrap()
+    );
+}
+
+#[test]
+fn non_scaling_stroke() {
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 200 200'>
+        <g transform='scale(4 1)'>
+            <rect id='rect1' x='10' y='40' width='30' height='120' fill='none'
+                  stroke='black' stroke-width='4' vector-effect='non-scaling-stroke'/>
+        </g>
+    </svg>
+    ";
+
+    let tree = usvg::Tree::from_str(&svg, &usvg::Options::default()).unwrap();
+    let path = match tree.node_by_id("rect1") {
+        Some(usvg::Node::Path(ref path)) => path,
+        _ => unreachable!(),
+    };
+
+    assert_eq!(path.vector_effect(), usvg::VectorEffect::NonScalingStroke);
+    // The stroke width is the same in both directions.
+    assert_eq!(
+        path.abs_stroke_bounding_box(),
+        usvg::Rect::from_xywh(38.0, 38.0, 124.0, 124.0).unwrap()
+    );
+    assert_eq!(
+        path.stroke_bounding_box(),
+        usvg::Rect::from_xywh(9.5, 38.0, 31.0, 124.0).unwrap()
+    );
+}
+
+#[test]
+fn marker_auto_start_reverse() {
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 200 200'>
+        <marker id='arrow' orient='auto-start-reverse' markerUnits='userSpaceOnUse'
+                markerWidth='10' markerHeight='10' refX='0' refY='5'>
+            <path d='M 0 0 L 10 5 L 0 10 Z'/>
+        </marker>
+        <path d='M 50 100 L 150 100' stroke='black'
+              marker-start='url(#arrow)' marker-end='url(#arrow)'/>
+    </svg>
+    ";
+
+    let tree = usvg::Tree::from_str(&svg, &usvg::Options::default()).unwrap();
+
+    // Markers are placed into a separate group after the path.
+    let group = match tree.root().children().last() {
+        Some(usvg::Node::Group(ref group)) => group,
+        _ => unreachable!(),
+    };
+    let markers: Vec<_> = group
+        .children()
+        .iter()
+        .map(|node| node.abs_bounding_box())
+        .collect();
+
+    // Both arrowheads must point outward.
+    assert_eq!(
+        markers,
+        vec![
+            usvg::Rect::from_xywh(40.0, 95.0, 10.0, 10.0).unwrap(),
+            usvg::Rect::from_xywh(150.0, 95.0, 10.0, 10.0).unwrap(),
+        ]
+    );
+}
+
+#[test]
+fn marker_overflow() {
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 200 200'>
+        <marker id='marker1' markerUnits='userSpaceOnUse' markerWidth='10' markerHeight='10'
+                style='overflow:OVERFLOW'>
+            <circle r='20'/>
+        </marker>
+        <path d='M 50 100 L 150 100' stroke='black' marker-start='url(#marker1)'/>
+    </svg>
+    ";
+
+    fn marker_clip_rect(svg: &str) -> Option<usvg::Rect> {
+        let tree = usvg::Tree::from_str(svg, &usvg::Options::default()).unwrap();
+        let group = match tree.root().children().last() {
+            Some(usvg::Node::Group(ref group)) => group,
+            _ => unreachable!(),
+        };
+
+        match group.children().first() {
+            Some(usvg::Node::Group(ref marker)) => {
+                marker.clip_path().map(|clip| clip.root().bounding_box())
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    // Clipped by the marker viewport.
+    assert_eq!(
+        marker_clip_rect(&svg.replace("OVERFLOW", "hidden")),
+        usvg::Rect::from_xywh(0.0, 0.0, 10.0, 10.0)
+    );
+    assert!(marker_clip_rect(&svg.replace("OVERFLOW", "scroll")).is_some());
+
+    assert!(marker_clip_rect(&svg.replace("OVERFLOW", "visible")).is_none());
+    assert!(marker_clip_rect(&svg.replace("OVERFLOW", "auto")).is_none());
+}
+
+#[test]
+fn marker_on_closed_subpath() {
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 200 200'>
+        <marker id='marker1' orient='auto' markerUnits='userSpaceOnUse'
+                markerWidth='10' markerHeight='10'>
+            <path d='M 0 0 L 10 5 L 0 10 Z'/>
+        </marker>
+        <path d='M 10 10 L 90 10 L 50 80 Z' stroke='black' fill='none'
+              marker-start='url(#marker1)' marker-mid='url(#marker1)' marker-end='url(#marker1)'/>
+    </svg>
+    ";
+
+    let tree = usvg::Tree::from_str(&svg, &usvg::Options::default()).unwrap();
+
+    let group = match tree.root().children().last() {
+        Some(usvg::Node::Group(ref group)) => group,
+        _ => unreachable!(),
+    };
+    // Marker positions and angles.
+    let markers: Vec<_> = group
+        .children()
+        .iter()
+        .map(|node| {
+            let ts = match node {
+                usvg::Node::Group(ref marker) => marker.transform(),
+                _ => unreachable!(),
+            };
+            let angle = ts.ky.atan2(ts.sx).to_degrees().rem_euclid(360.0);
+            (ts.tx, ts.ty, angle.round())
+        })
+        .collect();
+
+    // The closing vertex has a marker-end and the start and end markers
+    // bisect the closing segment and the first one.
+    assert_eq!(
+        markers,
+        vec![
+            (10.0, 10.0, 300.0),
+            (90.0, 10.0, 60.0),
+            (50.0, 80.0, 180.0),
+            (10.0, 10.0, 300.0),
+        ]
+    );
+
+    // The closing vertex of a subpath that is not the last one has a marker-mid.
+    let svg = svg.replace("Z'", "Z M 150 150 L 190 150'");
+    let tree = usvg::Tree::from_str(&svg, &usvg::Options::default()).unwrap();
+    let group = match tree.root().children().last() {
+        Some(usvg::Node::Group(ref group)) => group,
+        _ => unreachable!(),
+    };
+    assert_eq!(group.children().len(), 6);
+}
+
+#[test]
+fn path_flattening() {
+    use usvg::tiny_skia_path::{PathBuilder, PathSegment, Point};
+    use usvg::FlattenPath;
+
+    let (cx, cy, r) = (100.0, 100.0, 50.0);
+    let circle = PathBuilder::from_circle(cx, cy, r).unwrap();
+
+    let flatten = |tolerance: f64| {
+        let path = circle.flatten(tolerance).unwrap();
+
+        let mut points = Vec::new();
+        for segment in path.segments() {
+            match segment {
+                PathSegment::MoveTo(p) | PathSegment::LineTo(p) => points.push(p),
+                PathSegment::Close => {}
+                _ => panic!("a curve after flattening"),
+            }
+        }
+
+        // Vertices are on the circle, while segment midpoints are within the tolerance.
+        // Allow a small error introduced by approximating a circle with cubic curves.
+        let max_error = tolerance as f32 + 0.05;
+        for pair in points.windows(2) {
+            let mid = Point::from_xy((pair[0].x + pair[1].x) / 2.0, (pair[0].y + pair[1].y) / 2.0);
+            for p in [pair[0], mid] {
+                let d = (p.x - cx).hypot(p.y - cy);
+                assert!(
+                    (d - r).abs() <= max_error,
+                    "{} is too far from the circle",
+                    d
+                );
+            }
+        }
+
+        points.len()
+    };
+
+    let precise = flatten(0.01);
+    let coarse = flatten(1.0);
+    assert!(coarse > 4);
+    assert!(precise > coarse);
+
+    assert!(circle.flatten(0.0).is_none());
+}
+
+#[test]
+fn stroke_to_path() {
+    use usvg::tiny_skia_path::PathSegment;
+
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 200 200'>
+        <path id='path1' d='M 10 10 L 110 10' stroke='black' stroke-width='10'
+              stroke-dasharray='20 20'/>
+        <path id='path2' d='M 10 10 L 110 10 L 110 110 Z'/>
+    </svg>
+    ";
+
+    let tree = usvg::Tree::from_str(&svg, &usvg::Options::default()).unwrap();
+    let get_path = |id| match tree.node_by_id(id) {
+        Some(usvg::Node::Path(ref path)) => path,
+        _ => unreachable!(),
+    };
+    let count_contours = |path: &usvg::tiny_skia_path::Path| {
+        path.segments()
+            .filter(|s| matches!(s, PathSegment::MoveTo(_)))
+            .count()
+    };
+
+    let path = get_path("path1");
+
+    let outline = path.stroke_to_path(false).unwrap();
+    assert_eq!(count_contours(&outline), 1);
+    assert_eq!(outline.bounds(), path.stroke_bounding_box());
+
+    let dashed_outline = path.stroke_to_path(true).unwrap();
+    assert_eq!(count_contours(&dashed_outline), 3);
+    assert_eq!(
+        dashed_outline.bounds(),
+        usvg::Rect::from_xywh(10.0, 5.0, 100.0, 10.0).unwrap()
+    );
+
+    assert!(get_path("path2").stroke_to_path(true).is_none());
+}
+
+#[test]
+fn paint_servers_in_user_space() {
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 200 200'>
+        <linearGradient id='lg1'>
+            <stop offset='0' stop-color='white'/>
+            <stop offset='1' stop-color='black'/>
+        </linearGradient>
+        <rect id='rect1' x='10' y='10' width='50' height='20' fill='url(#lg1)'/>
+        <rect id='rect2' x='100' y='100' width='80' height='40' fill='url(#lg1)'/>
+        <text id='text1' x='10' y='180' font-family='Noto Sans' font-size='20'
+              fill='url(#lg1)'>Text</text>
+    </svg>
+    ";
+
+    let opt = usvg::Options {
+        fontdb: GLOBAL_FONTDB.clone(),
+        ..usvg::Options::default()
+    };
+    let tree = usvg::Tree::from_str(&svg, &opt).unwrap();
+
+    let gradient_ts = |id| match tree.node_by_id(id) {
+        Some(usvg::Node::Path(ref path)) => match path.fill().unwrap().paint() {
+            usvg::Paint::LinearGradient(ref lg) => lg.transform(),
+            _ => unreachable!(),
+        },
+        _ => unreachable!(),
+    };
+
+    // Each element gets its own gradient with the bounding box applied.
+    assert_eq!(
+        gradient_ts("rect1"),
+        usvg::Transform::from_row(50.0, 0.0, 0.0, 20.0, 10.0, 10.0)
+    );
+    assert_eq!(
+        gradient_ts("rect2"),
+        usvg::Transform::from_row(80.0, 0.0, 0.0, 40.0, 100.0, 100.0)
+    );
+    assert_eq!(tree.linear_gradients().len(), 3);
+}
+
+#[test]
+fn display_list() {
+    use usvg::DrawCommand;
+
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 200 200'>
+        <clipPath id='clip1'>
+            <rect width='100' height='100'/>
+        </clipPath>
+        <g transform='translate(10 20)'>
+            <rect id='rect1' width='10' height='10' stroke='black' paint-order='stroke'/>
+        </g>
+        <g opacity='0.5' clip-path='url(#clip1)'>
+            <rect id='rect2' width='50' height='50' visibility='hidden'/>
+            <rect id='rect3' x='20' y='20' width='50' height='50'/>
+        </g>
+    </svg>
+    ";
+
+    let tree = usvg::Tree::from_str(&svg, &usvg::Options::default()).unwrap();
+    let commands = tree.to_display_list();
+
+    let summary: Vec<String> = commands
+        .iter()
+        .map(|command| match command {
+            DrawCommand::PushLayer { bounding_box } => format!(
+                "push {} {} {} {}",
+                bounding_box.x(),
+                bounding_box.y(),
+                bounding_box.width(),
+                bounding_box.height()
+            ),
+            DrawCommand::ApplyFilter { .. } => "filter".to_string(),
+            DrawCommand::ApplyClipPath { clip_path, .. } => format!("clip {}", clip_path.id()),
+            DrawCommand::ApplyMask { mask, .. } => format!("mask {}", mask.id()),
+            DrawCommand::PopLayer { opacity, .. } => format!("pop {}", opacity.get()),
+            DrawCommand::FillPath { path, transform } => {
+                format!("fill {} {} {}", path.id(), transform.tx, transform.ty)
+            }
+            DrawCommand::StrokePath { path, transform } => {
+                format!("stroke {} {} {}", path.id(), transform.tx, transform.ty)
+            }
+            DrawCommand::DrawImage { image, .. } => format!("image {}", image.id()),
+        })
+        .collect();
+
+    assert_eq!(
+        summary,
+        vec![
+            "stroke rect1 10 20",
+            "fill rect1 10 20",
+            "push 0 0 70 70",
+            "fill rect3 0 0",
+            "clip clip1",
+            "pop 0.5",
+        ]
+    );
+}
+
+#[test]
+fn image_kind_size() {
+    use usvg::ImageKind;
+
+    let read =
+        |name: &str| Arc::new(std::fs::read(format!("../resvg/tests/resources/{}", name)).unwrap());
+
+    let size = |kind: ImageKind| kind.size().map(|s| (s.width(), s.height()));
+
+    assert_eq!(
+        size(ImageKind::PNG(read("image-63x61.png"))),
+        Some((63.0, 61.0))
+    );
+    assert_eq!(size(ImageKind::JPEG(read("image.jpg"))), Some((64.0, 64.0)));
+    assert_eq!(size(ImageKind::GIF(read("image.gif"))), Some((64.0, 64.0)));
+    assert_eq!(
+        size(ImageKind::WEBP(read("image.webp"))),
+        Some((64.0, 64.0))
+    );
+    assert_eq!(size(ImageKind::PNG(Arc::new(vec![0; 16]))), None);
+
+    let svg = "<svg xmlns='http://www.w3.org/2000/svg' width='120' height='80'/>";
+    let tree = usvg::Tree::from_str(&svg, &usvg::Options::default()).unwrap();
+    assert_eq!(size(ImageKind::SVG(tree)), Some((120.0, 80.0)));
+}
+
+#[test]
+fn system_language() {
+    let matched = |languages: &[&str], system_language: &str| {
+        let svg = format!(
+            "
+        <svg xmlns='http://www.w3.org/2000/svg'>
+            <switch>
+                <rect id='rect1' width='10' height='10' systemLanguage='{}'/>
+                <rect id='rect2' width='10' height='10'/>
+            </switch>
+        </svg>
+        ",
+            system_language
+        );
+
+        let opt = usvg::Options {
+            languages: languages.iter().map(|s| s.to_string()).collect(),
+            ..usvg::Options::default()
+        };
+        let tree = usvg::Tree::from_str(&svg, &opt).unwrap();
+        tree.node_by_id("rect1").is_some()
+    };
+
+    assert!(matched(&["en"], "en"));
+    assert!(matched(&["en"], "en-US"));
+    assert!(matched(&["pt"], "pt-BR"));
+    assert!(matched(&["pt-br"], "pt-BR"));
+    assert!(matched(&["zh-Hant"], "zh-Hant-TW"));
+    assert!(matched(&["zh-Hant"], "zh-Hans, zh-Hant-HK"));
+    assert!(matched(&["de", "sr-Latn"], "sr-Latn-RS"));
+    assert!(matched(&["*"], "fr"));
+
+    assert!(!matched(&["en-US"], "en"));
+    assert!(!matched(&["zh-Hant"], "zh-Hans-CN"));
+    assert!(!matched(&["zh-Hant"], "zh"));
+    assert!(!matched(&["pt"], "ptx"));
+    assert!(!matched(&["en"], ""));
+}
+
+#[test]
+fn display_none_and_hidden_bbox() {
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 200 200'>
+        <g id='g1'>
+            <rect id='rect1' x='50' y='50' width='20' height='20'/>
+            <rect id='rect2' x='100' y='100' width='50' height='50' visibility='hidden'/>
+            <rect id='rect3' x='10' y='10' width='20' height='20' display='none'/>
+            <g display='none'>
+                <rect id='rect4' x='150' y='150' width='40' height='40'/>
+            </g>
+        </g>
+    </svg>
+    ";
+
+    let tree = usvg::Tree::from_str(&svg, &usvg::Options::default()).unwrap();
+
+    // `display:none` elements are removed completely.
+    assert!(tree.node_by_id("rect3").is_none());
+    assert!(tree.node_by_id("rect4").is_none());
+
+    // Hidden elements are preserved, but not painted.
+    let usvg::Node::Path(ref path) = tree.node_by_id("rect2").unwrap() else {
+        unreachable!()
+    };
+    assert!(!path.is_visible());
+    assert!(tree.to_display_list().iter().all(|command| match command {
+        usvg::DrawCommand::FillPath { path, .. } => path.id() != "rect2",
+        _ => true,
+    }));
+
+    // And still contribute to the bounding box.
+    assert_eq!(
+        tree.node_by_id("g1").unwrap().abs_bounding_box(),
+        usvg::Rect::from_xywh(50.0, 50.0, 100.0, 100.0).unwrap()
+    );
+    assert_eq!(
+        tree.root().abs_bounding_box(),
+        usvg::Rect::from_xywh(50.0, 50.0, 100.0, 100.0).unwrap()
+    );
+}
+
+#[test]
+fn visible_nodes() {
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 200 200'>
+        <rect id='rect1' width='10' height='10'/>
+        <rect id='rect2' width='10' height='10' visibility='hidden'/>
+        <g opacity='0'>
+            <rect id='rect3' width='10' height='10'/>
+        </g>
+        <rect id='rect4' width='10' height='10' fill='none'/>
+        <rect id='rect5' width='10' height='10' fill-opacity='0' stroke='black'/>
+        <rect id='rect6' width='10' height='10' fill-opacity='0'/>
+        <path id='path1' d='M 10 10 L 100 10'/>
+        <path id='path2' d='M 10 10 L 100 10' stroke='black'/>
+        <clipPath id='clip1'>
+            <rect id='rect7' width='10' height='10'/>
+        </clipPath>
+        <g clip-path='url(#clip1)'>
+            <rect id='rect8' x='100' y='100' width='10' height='10'/>
+        </g>
+    </svg>
+    ";
+
+    let tree = usvg::Tree::from_str(&svg, &usvg::Options::default()).unwrap();
+    let ids: Vec<&str> = tree.visible_nodes().map(|node| node.id()).collect();
+    // Clipped away content is preserved.
+    assert_eq!(ids, vec!["rect1", "rect5", "path2", "rect8"]);
+}
+
+#[test]
+fn visible_nodes_match_is_empty() {
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 200 200'>
+        <filter id='filter1' filterUnits='userSpaceOnUse' x='0' y='0' width='50' height='50'>
+            <feFlood flood-color='green'/>
+        </filter>
+        <g id='g1' filter='url(#filter1)'/>
+        <g id='g2' filter='url(#filter1)'>
+            <rect id='rect1' width='10' height='10'/>
+        </g>
+        <image id='image1' width='10' height='10'
+               xlink:href='data:image/svg+xml,%3Csvg xmlns=%22http://www.w3.org/2000/svg%22 width=%2210%22 height=%2210%22/%3E'
+               xmlns:xlink='http://www.w3.org/1999/xlink'/>
+    </svg>
+    ";
+
+    let tree = usvg::Tree::from_str(&svg, &usvg::Options::default()).unwrap();
+    let ids: Vec<&str> = tree.visible_nodes().map(|node| node.id()).collect();
+    // An empty filtered group is visible, while an empty nested SVG image is not.
+    assert_eq!(ids, vec!["g1", "rect1"]);
+
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 200 200'>
+        <image id='image1' width='10' height='10'
+               xlink:href='data:image/svg+xml,%3Csvg xmlns=%22http://www.w3.org/2000/svg%22 width=%2210%22 height=%2210%22/%3E'
+               xmlns:xlink='http://www.w3.org/1999/xlink'/>
+    </svg>
+    ";
+
+    let tree = usvg::Tree::from_str(&svg, &usvg::Options::default()).unwrap();
+    assert!(tree.is_empty());
+    assert_eq!(tree.visible_nodes().count(), 0);
+}
+
+#[test]
+fn metadata() {
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 200 200'>
+        <title>Logo</title>
+        <desc>
+            A company logo.
+        </desc>
+        <g id='g1' aria-label='Shapes'>
+            <rect id='rect1' width='10' height='10'>
+                <title>A <tspan>square</tspan></title>
+            </rect>
+            <rect id='rect2' width='10' height='10'/>
+        </g>
+    </svg>
+    ";
+
+    let tree = usvg::Tree::from_str(&svg, &usvg::Options::default()).unwrap();
+    assert!(tree.metadata("").is_none());
+    assert!(tree.metadata("rect1").is_none());
+
+    let opt = usvg::Options {
+        keep_metadata: true,
+        ..usvg::Options::default()
+    };
+    let tree = usvg::Tree::from_str(&svg, &opt).unwrap();
+
+    let root = tree.metadata(tree.root().id()).unwrap();
+    assert_eq!(root.title(), Some("Logo"));
+    assert_eq!(root.desc(), Some("A company logo."));
+    assert_eq!(root.aria_label(), None);
+
+    let g1 = tree.metadata("g1").unwrap();
+    assert_eq!(g1.title(), None);
+    assert_eq!(g1.aria_label(), Some("Shapes"));
+
+    assert_eq!(tree.metadata("rect1").unwrap().title(), Some("A square"));
+    assert!(tree.metadata("rect2").is_none());
+}
+
+#[test]
+fn metadata_interactive_attributes() {
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 200 200' cursor='pointer'>
+        <style>.button { pointer-events: none }</style>
+        <g id='g1' class=' button  primary ' data-index='1' data-action='open'>
+            <rect id='rect1' width='10' height='10' pointer-events='visibleFill'/>
+            <rect id='rect2' width='10' height='10'/>
+        </g>
+    </svg>
+    ";
+
+    let opt = usvg::Options {
+        keep_metadata: true,
+        ..usvg::Options::default()
+    };
+    let tree = usvg::Tree::from_str(&svg, &opt).unwrap();
+
+    let root = tree.metadata("").unwrap();
+    assert_eq!(root.cursor(), Some("pointer"));
+    assert_eq!(root.pointer_events(), None);
+
+    let g1 = tree.metadata("g1").unwrap();
+    assert_eq!(g1.classes(), ["button", "primary"]);
+    assert_eq!(
+        g1.data_attributes(),
+        [
+            ("data-index".to_string(), "1".to_string()),
+            ("data-action".to_string(), "open".to_string()),
+        ]
+    );
+    assert_eq!(g1.pointer_events(), Some("none"));
+    assert_eq!(g1.cursor(), Some("pointer"));
+
+    let rect1 = tree.metadata("rect1").unwrap();
+    assert!(rect1.classes().is_empty());
+    assert!(rect1.data_attributes().is_empty());
+    assert_eq!(rect1.pointer_events(), Some("visibleFill"));
+
+    let rect2 = tree.metadata("rect2").unwrap();
+    assert_eq!(rect2.pointer_events(), Some("none"));
+    assert_eq!(rect2.cursor(), Some("pointer"));
+}
+
+#[test]
+fn metadata_without_id() {
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 200 200'>
+        <g class='button' cursor='pointer'>
+            <rect id='rect1' width='10' height='10'/>
+            <rect width='10' height='10' pointer-events='none'/>
+        </g>
+    </svg>
+    ";
+
+    let diagnostics = usvg::Diagnostics::new();
+    let opt = usvg::Options {
+        keep_metadata: true,
+        diagnostics: Some(&diagnostics),
+        ..usvg::Options::default()
+    };
+    let tree = usvg::Tree::from_str(&svg, &opt).unwrap();
+
+    // Inherited values are still available.
+    assert_eq!(tree.metadata("rect1").unwrap().cursor(), Some("pointer"));
+
+    let notes: Vec<_> = diagnostics
+        .take()
+        .iter()
+        .map(|d| (d.feature().to_string(), d.note().to_string()))
+        .collect();
+    let entry = |tag_name: &str| {
+        (
+            "metadata".to_string(),
+            format!("Metadata of a '{tag_name}' element without an ID is not preserved."),
+        )
+    };
+    assert_eq!(notes, [entry("g"), entry("rect")]);
+}
+
+#[test]
+fn set_current_color() {
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 200 200' color='red'>
+        <linearGradient id='lg1'>
+            <stop offset='0' stop-color='currentColor'/>
+            <stop offset='1' stop-color='green'/>
+        </linearGradient>
+        <pattern id='patt1' patternUnits='userSpaceOnUse' width='20' height='20'>
+            <rect id='rect5' width='10' height='10' fill='currentColor'/>
+        </pattern>
+        <mask id='mask1'>
+            <rect id='rect6' width='10' height='10' fill='currentColor'/>
+        </mask>
+        <rect id='rect1' width='10' height='10' fill='currentColor' stroke='blue'/>
+        <rect id='rect2' width='10' height='10' fill='green' stroke='currentColor'/>
+        <rect id='rect3' width='10' height='10' fill='url(#lg1)'/>
+        <rect id='rect4' width='10' height='10' fill='url(#patt1)'/>
+        <g mask='url(#mask1)'>
+            <rect width='10' height='10'/>
+        </g>
+        <text id='text1' x='10' y='50' fill='currentColor' font-family='Noto Sans'>Text</text>
+        <image id='image1' width='10' height='10'
+               xlink:href='data:image/svg+xml,%3Csvg xmlns=%22http://www.w3.org/2000/svg%22 width=%2210%22 height=%2210%22%3E%3Crect width=%2210%22 height=%2210%22 fill=%22currentColor%22/%3E%3C/svg%3E'
+               xmlns:xlink='http://www.w3.org/1999/xlink'/>
+    </svg>
+    ";
+
+    let opt = usvg::Options {
+        fontdb: GLOBAL_FONTDB.clone(),
+        ..usvg::Options::default()
+    };
+    let mut tree = usvg::Tree::from_str(&svg, &opt).unwrap();
+
+    let white = Color::white();
+    tree.set_current_color(white);
+
+    let path = |tree: &usvg::Tree, id: &str| match tree.node_by_id(id) {
+        Some(usvg::Node::Path(ref path)) => (**path).clone(),
+        _ => unreachable!(),
+    };
+    let color = |paint: &usvg::Paint| match paint {
+        usvg::Paint::Color(c) => *c,
+        _ => unreachable!(),
+    };
+
+    let rect1 = path(&tree, "rect1");
+    assert!(rect1.fill().unwrap().is_current_color());
+    assert_eq!(color(rect1.fill().unwrap().paint()), white);
+    assert!(!rect1.stroke().unwrap().is_current_color());
+    assert_eq!(
+        color(rect1.stroke().unwrap().paint()),
+        Color::new_rgb(0, 0, 255)
+    );
+
+    let rect2 = path(&tree, "rect2");
+    assert_eq!(
+        color(rect2.fill().unwrap().paint()),
+        Color::new_rgb(0, 128, 0)
+    );
+    assert_eq!(color(rect2.stroke().unwrap().paint()), white);
+
+    let rect3 = path(&tree, "rect3");
+    let usvg::Paint::LinearGradient(ref lg) = rect3.fill().unwrap().paint() else {
+        unreachable!()
+    };
+    assert!(lg.stops()[0].is_current_color());
+    assert_eq!(lg.stops()[0].color(), white);
+    assert_eq!(lg.stops()[1].color(), Color::new_rgb(0, 128, 0));
+    assert!(Arc::ptr_eq(lg, &tree.linear_gradients()[0]));
+
+    let rect4 = path(&tree, "rect4");
+    let usvg::Paint::Pattern(ref patt) = rect4.fill().unwrap().paint() else {
+        unreachable!()
+    };
+    let usvg::Node::Path(ref rect5) = patt.root().children()[0] else {
+        unreachable!()
+    };
+    assert_eq!(color(rect5.fill().unwrap().paint()), white);
+    assert!(Arc::ptr_eq(patt, &tree.patterns()[0]));
+
+    let usvg::Node::Path(ref rect6) = tree.masks()[0].root().children()[0] else {
+        unreachable!()
+    };
+    assert_eq!(color(rect6.fill().unwrap().paint()), white);
+
+    let usvg::Node::Text(ref text) = tree.node_by_id("text1").unwrap() else {
+        unreachable!()
+    };
+    let usvg::Node::Path(ref glyph) = text.flattened().children()[0] else {
+        unreachable!()
+    };
+    assert_eq!(color(glyph.fill().unwrap().paint()), white);
+
+    // Nested SVG images resolve `currentColor` on their own.
+    let usvg::Node::Group(ref image) = tree.node_by_id("image1").unwrap() else {
+        unreachable!()
+    };
+    let usvg::Node::Image(ref image) = image.children()[0] else {
+        unreachable!()
+    };
+    let usvg::ImageKind::SVG(ref sub_tree) = image.kind() else {
+        unreachable!()
+    };
+    let usvg::Node::Path(ref sub_rect) = sub_tree.root().children()[0] else {
+        unreachable!()
+    };
+    assert_eq!(color(sub_rect.fill().unwrap().paint()), Color::black());
+}
+
+#[test]
+fn flatten_masks() {
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 200 200'>
+        <mask id='mask1'>
+            <rect width='200' height='200' fill='white' fill-opacity='0.5'/>
+        </mask>
+        <mask id='mask2'>
+            <rect width='50' height='50' fill='white'/>
+        </mask>
+        <mask id='mask3'>
+            <circle cx='100' cy='100' r='150' fill='white'/>
+        </mask>
+        <g id='g1' mask='url(#mask1)' opacity='0.5'>
+            <rect x='10' y='10' width='100' height='100'/>
+        </g>
+        <g id='g2' mask='url(#mask2)'>
+            <rect x='10' y='10' width='100' height='100'/>
+        </g>
+        <g id='g3' mask='url(#mask3)'>
+            <rect x='10' y='10' width='100' height='100'/>
+        </g>
+    </svg>
+    ";
+
+    let mut tree = usvg::Tree::from_str(&svg, &usvg::Options::default()).unwrap();
+    tree.flatten_masks();
+
+    let group = |id: &str| match tree.node_by_id(id) {
+        Some(usvg::Node::Group(ref group)) => (**group).clone(),
+        _ => unreachable!(),
+    };
+
+    let g1 = group("g1");
+    assert!(g1.mask().is_none());
+    assert_eq!(g1.opacity().get(), 0.5 * 128.0 / 255.0);
+
+    // Doesn't cover the whole group.
+    assert!(group("g2").mask().is_some());
+    // Not a rectangle.
+    assert!(group("g3").mask().is_some());
+
+    assert_eq!(tree.masks().len(), 2);
+}
+
+#[test]
+fn fe_image_href_resolver() {
+    use std::sync::Mutex;
+
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' xmlns:xlink='http://www.w3.org/1999/xlink'>
+        <filter id='filter1'>
+            <feImage xlink:href='logo.png'/>
+        </filter>
+        <rect width='100' height='100' filter='url(#filter1)'/>
+    </svg>
+    ";
+
+    let hrefs = Mutex::new(Vec::new());
+    let opt = usvg::Options {
+        image_href_resolver: usvg::ImageHrefResolver {
+            resolve_string: Box::new(|href, _| {
+                hrefs.lock().unwrap().push(href.to_string());
+                let data = std::fs::read("../resvg/tests/resources/image.png").unwrap();
+                Some(usvg::ImageKind::PNG(Arc::new(data)))
+            }),
+            ..usvg::ImageHrefResolver::default()
+        },
+        ..usvg::Options::default()
+    };
+
+    let tree = usvg::Tree::from_str(&svg, &opt).unwrap();
+    assert_eq!(*hrefs.lock().unwrap(), ["logo.png"]);
+
+    let image = match tree.filters()[0].primitives()[0].kind() {
+        usvg::filter::Kind::Image(ref image) => image,
+        _ => unreachable!(),
+    };
+
+    fn has_png(group: &usvg::Group) -> bool {
+        group.children().iter().any(|node| match node {
+            usvg::Node::Group(ref group) => has_png(group),
+            usvg::Node::Image(ref image) => matches!(image.kind(), usvg::ImageKind::PNG(_)),
+            _ => false,
+        })
+    }
+
+    assert!(has_png(image.root()));
+}
+
+#[test]
+fn filter_primitive_subregion() {
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 200 200'>
+        <filter id='filter1' primitiveUnits='objectBoundingBox'
+                x='0' y='0' width='1' height='1'>
+            <feFlood x='0.5' y='0' width='1' height='0.25' result='flood'/>
+            <feOffset in='flood' dx='10'/>
+            <feGaussianBlur stdDeviation='0.01'/>
+        </filter>
+        <rect x='20' y='40' width='100' height='80' filter='url(#filter1)'/>
+    </svg>
+    ";
+
+    let tree = usvg::Tree::from_str(&svg, &usvg::Options::default()).unwrap();
+    let filter = &tree.filters()[0];
+    let subregions: Vec<_> = filter
+        .primitives()
+        .iter()
+        .map(|primitive| {
+            let r = primitive.subregion(filter).unwrap();
+            (r.x(), r.y(), r.width(), r.height())
+        })
+        .collect();
+
+    assert_eq!(
+        subregions,
+        [
+            // Clipped by the filter region.
+            (70.0, 40.0, 50.0, 20.0),
+            // Inherited from the input.
+            (70.0, 40.0, 50.0, 20.0),
+            // Defaults to the filter region.
+            (20.0, 40.0, 100.0, 80.0),
+        ]
+    );
+
+    // Not from this filter.
+    let other = filter.primitives()[0].clone();
+    assert!(other.subregion(filter).is_none());
+}
+
+#[test]
+fn transfer_function_apply() {
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg'>
+        <filter id='filter1'>
+            <feComponentTransfer>
+                <feFuncR type='table' tableValues='0 1 0.5'/>
+                <feFuncG type='discrete' tableValues='0 0.5 1'/>
+                <feFuncB type='linear' slope='2' intercept='-0.5'/>
+                <feFuncA type='table' tableValues=''/>
+            </feComponentTransfer>
+        </filter>
+        <rect width='100' height='100' filter='url(#filter1)'/>
+    </svg>
+    ";
+
+    let tree = usvg::Tree::from_str(&svg, &usvg::Options::default()).unwrap();
+    let fe = match tree.filters()[0].primitives()[0].kind() {
+        usvg::filter::Kind::ComponentTransfer(ref fe) => fe,
+        _ => unreachable!(),
+    };
+
+    assert_eq!(fe.func_r().apply(0.25), 0.5);
+    assert_eq!(fe.func_r().apply(0.75), 0.75);
+    assert_eq!(fe.func_r().apply(1.0), 0.5);
+
+    assert_eq!(fe.func_g().apply(0.3), 0.0);
+    assert_eq!(fe.func_g().apply(0.5), 0.5);
+    assert_eq!(fe.func_g().apply(1.0), 1.0);
+
+    // Clamped.
+    assert_eq!(fe.func_b().apply(0.1), 0.0);
+    assert_eq!(fe.func_b().apply(0.5), 0.5);
+    assert_eq!(fe.func_b().apply(0.9), 1.0);
+
+    // An empty table is an identity.
+    assert!(fe.func_a().is_identity());
+    assert_eq!(fe.func_a().apply(0.3), 0.3);
+}
+
+#[test]
+fn filter_policy() {
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg'>
+        <filter id='filter1'>
+            <feGaussianBlur stdDeviation='1'/>
+        </filter>
+        <filter id='filter2'>
+            <feGaussianBlur stdDeviation='10'/>
+        </filter>
+        <rect width='10' height='10' filter='url(#filter1)'/>
+        <rect width='10' height='10' filter='url(#filter2)'/>
+        <rect width='10' height='10' filter='url(#filter1) url(#filter2)'/>
+    </svg>
+    ";
+
+    let filters_count = |policy| {
+        let opt = usvg::Options {
+            filter_policy: policy,
+            ..usvg::Options::default()
+        };
+
+        let tree = usvg::Tree::from_str(&svg, &opt).unwrap();
+        tree.root()
+            .children()
+            .iter()
+            .map(|node| match node {
+                usvg::Node::Group(ref group) => group.filters().len(),
+                // No group is created without filters.
+                _ => 0,
+            })
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(filters_count(usvg::FilterPolicy::AllowAll), [1, 1, 2]);
+    assert_eq!(filters_count(usvg::FilterPolicy::DenyAll), [0, 0, 0]);
+
+    let policy = usvg::FilterPolicy::Custom(Box::new(|primitive| match primitive.kind() {
+        usvg::filter::Kind::GaussianBlur(ref fe) => fe.std_dev_x().get() <= 2.0,
+        _ => true,
+    }));
+    assert_eq!(filters_count(policy), [1, 0, 1]);
+}
+
+#[test]
+fn diagnostics() {
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg'>
+        <title>Title</title>
+        <filter id='filter1'>
+            <feOffset in='BackgroundImage'/>
+        </filter>
+        <rect id='rect1' width='10' height='10' filter='url(#filter1)'>
+            <animate attributeName='x' to='10' dur='1s'/>
+        </rect>
+        <rect id='rect2' width='10' height='10' fill='url(#filter1)'/>
+        <foreignObject id='fo1' width='10' height='10'/>
+    </svg>
+    ";
+
+    let diagnostics = usvg::Diagnostics::new();
+    let opt = usvg::Options {
+        diagnostics: Some(&diagnostics),
+        ..usvg::Options::default()
+    };
+    usvg::Tree::from_str(&svg, &opt).unwrap();
+
+    let list: Vec<_> = diagnostics
+        .take()
+        .iter()
+        .map(|d| {
+            (
+                d.feature().to_string(),
+                d.element_id().to_string(),
+                d.line(),
+                d.note().to_string(),
+            )
+        })
+        .collect();
+
+    let entry = |feature: &str, id: &str, line, note: &str| {
+        (feature.to_string(), id.to_string(), line, note.to_string())
+    };
+
+    assert_eq!(
+        list,
+        [
+            entry("filter", "rect2", None, "cannot be used to fill a shape"),
+            entry(
+                "BackgroundImage",
+                "",
+                Some(5),
+                "unsupported filter input, replaced with a transparent image",
+            ),
+            entry("animate", "", Some(8), "animations are not supported"),
+            entry("foreignObject", "fo1", Some(11), "unsupported element"),
+        ]
+    );
+
+    assert!(diagnostics.take().is_empty());
+}
+
+#[test]
+fn diagnostics_from_warnings() {
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg'>
+        <filter id='filter1' width='0'>
+            <feFlood/>
+        </filter>
+        <rect id='rect1' width='10' height='10' filter='url(#filter1)'/>
+        <image id='img1' width='10' height='10'/>
+    </svg>
+    ";
+
+    let diagnostics = usvg::Diagnostics::new();
+    let opt = usvg::Options {
+        diagnostics: Some(&diagnostics),
+        ..usvg::Options::default()
+    };
+    usvg::Tree::from_str(&svg, &opt).unwrap();
+
+    let list: Vec<_> = diagnostics
+        .take()
+        .iter()
+        .map(|d| {
+            (
+                d.feature().to_string(),
+                d.element_id().to_string(),
+                d.line(),
+            )
+        })
+        .collect();
+
+    assert_eq!(
+        list,
+        [
+            ("filter".to_string(), "filter1".to_string(), None),
+            ("image".to_string(), "img1".to_string(), None),
+        ]
+    );
+}
+
+#[test]
+fn computed_style() {
+    use usvg::StyleSource;
+
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' font-size='20'>
+        <style>.red { fill: red; stroke-width: 4 }</style>
+        <g id='g1' stroke='green' opacity='0.5'>
+            <rect id='rect1' class='red' width='10' height='10'
+                  stroke-width='2' style='fill-opacity:0.3' opacity='inherit'/>
+        </g>
+    </svg>
+    ";
+
+    let opt = usvg::Options {
+        keep_computed_styles: true,
+        ..usvg::Options::default()
+    };
+    let tree = usvg::Tree::from_str(&svg, &opt).unwrap();
+
+    let style = tree.computed_style("rect1").unwrap();
+    let get = |name: &str| {
+        let value = style.get(name).unwrap();
+        (value.value(), value.source())
+    };
+
+    assert_eq!(get("fill"), ("red", StyleSource::StyleSheet));
+    // CSS overrides presentation attributes.
+    assert_eq!(get("stroke-width"), ("4", StyleSource::StyleSheet));
+    assert_eq!(get("fill-opacity"), ("0.3", StyleSource::Inline));
+    assert_eq!(get("stroke"), ("green", StyleSource::Inherited));
+    assert_eq!(get("font-size"), ("20", StyleSource::Inherited));
+    assert_eq!(get("opacity"), ("0.5", StyleSource::Inherited));
+    assert_eq!(get("stroke-linecap"), ("butt", StyleSource::Default));
+    assert!(style.get("width").is_none());
+
+    let style = tree.computed_style("g1").unwrap();
+    assert_eq!(
+        style.get("opacity").unwrap().source(),
+        StyleSource::Attribute
+    );
+
+    assert_eq!(
+        tree.computed_style("")
+            .unwrap()
+            .get("font-size")
+            .unwrap()
+            .value(),
+        "20"
+    );
+
+    let tree = usvg::Tree::from_str(&svg, &usvg::Options::default()).unwrap();
+    assert!(tree.computed_style("rect1").is_none());
+}
+
+#[test]
+fn media_queries() {
+    let svg = "
+    <svg width='200' height='100' xmlns='http://www.w3.org/2000/svg'>
+        <style>
+            rect { fill: red }
+            @media (prefers-color-scheme: dark) {
+                #rect1 { fill: black }
+            }
+            @media screen and (prefers-color-scheme: light) {
+                #rect1 { fill: white }
+            }
+            @media (min-width: 150px) and (orientation: landscape) {
+                #rect2 { fill: green }
+            }
+            @media print, (max-width: 150px) {
+                #rect2 { fill: blue }
+            }
+            @media not all and (monochrome) {
+                #rect3 { fill: green }
+            }
+        </style>
+        <rect id='rect1' width='10' height='10'/>
+        <rect id='rect2' width='10' height='10'/>
+        <rect id='rect3' width='10' height='10'/>
+    </svg>
+    ";
+
+    let fill = |tree: &usvg::Tree, id: &str| {
+        let usvg::Node::Path(ref path) = tree.node_by_id(id).unwrap() else {
+            unreachable!()
+        };
+
+        path.fill().unwrap().paint().clone()
+    };
+
+    let tree = usvg::Tree::from_str(&svg, &usvg::Options::default()).unwrap();
+    assert_eq!(
+        fill(&tree, "rect1"),
+        usvg::Paint::Color(Color::new_rgb(255, 255, 255))
+    );
+    assert_eq!(
+        fill(&tree, "rect2"),
+        usvg::Paint::Color(Color::new_rgb(0, 128, 0))
+    );
+    assert_eq!(
+        fill(&tree, "rect3"),
+        usvg::Paint::Color(Color::new_rgb(0, 128, 0))
+    );
+
+    let mut opt = usvg::Options::default();
+    opt.media_features
+        .insert("prefers-color-scheme".to_string(), "dark".to_string());
+    opt.media_features
+        .insert("width".to_string(), "100".to_string());
+    let tree = usvg::Tree::from_str(&svg, &opt).unwrap();
+    assert_eq!(
+        fill(&tree, "rect1"),
+        usvg::Paint::Color(Color::new_rgb(0, 0, 0))
+    );
+    assert_eq!(
+        fill(&tree, "rect2"),
+        usvg::Paint::Color(Color::new_rgb(0, 0, 255))
+    );
+}
+
+#[test]
+fn decompress_svgz() {
+    use std::io::Write;
+
+    let svg = "<svg xmlns='http://www.w3.org/2000/svg' width='10' height='10'/>";
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(svg.as_bytes()).unwrap();
+    let mut data = encoder.finish().unwrap();
+
+    assert_eq!(usvg::decompress_svgz(&data).unwrap(), svg.as_bytes());
+    let tree = usvg::Tree::from_data(&data, &usvg::Options::default()).unwrap();
+    assert_eq!(tree.size(), usvg::Size::from_wh(10.0, 10.0).unwrap());
+
+    // A malformed size in the trailer must not cause a huge allocation.
+    let len = data.len();
+    data[len - 4..].copy_from_slice(&u32::MAX.to_le_bytes());
+    assert!(usvg::decompress_svgz(&data).is_err());
+}
+
+#[test]
+fn reparse_element() {
+    let svg = "
+    <svg viewBox='0 0 200 200' xmlns='http://www.w3.org/2000/svg' xmlns:xlink='http://www.w3.org/1999/xlink'>
+        <style>#rect3 { fill: green }</style>
+        <linearGradient id='lg1' x1='0' x2='200' gradientUnits='userSpaceOnUse'>
+            <stop offset='0' stop-color='white'/>
+            <stop offset='1' stop-color='black'/>
+        </linearGradient>
+        <g id='g1' transform='translate(10 20)' stroke='black'>
+            <rect id='rect1' x='10' y='10' width='50' height='50' fill='url(#lg1)'/>
+            <rect id='rect2' x='70' y='10' width='50' height='50'/>
+        </g>
+        <rect id='rect3' x='10' y='100' width='50' height='50'/>
+        <rect id='rect4' x='70' y='100' width='50' height='50'/>
+        <use id='use1' xlink:href='#rect4' x='60'/>
+    </svg>
+    ";
+
+    let write = |tree: &usvg::Tree| tree.to_string(&usvg::WriteOptions::default());
+
+    let opt = usvg::Options::default();
+    let doc = usvg::roxmltree::Document::parse(svg).unwrap();
+    let mut tree = usvg::Tree::from_xmltree(&doc, &opt).unwrap();
+
+    // A path gets a wrapper group and its sibling is not affected.
+    assert!(tree.reparse_element(
+        &doc,
+        "rect2",
+        &[("opacity", "0.5"), ("transform", "rotate(10)"), ("x", "80")],
+        &opt
+    ));
+    let expected = svg.replace(
+        "id='rect2' x='70'",
+        "id='rect2' opacity='0.5' transform='rotate(10)' x='80'",
+    );
+    assert_eq!(
+        write(&tree),
+        write(&usvg::Tree::from_str(&expected, &opt).unwrap())
+    );
+
+    // Inherited attributes are applied to children.
+    let mut tree = usvg::Tree::from_xmltree(&doc, &opt).unwrap();
+    assert!(tree.reparse_element(
+        &doc,
+        "g1",
+        &[("stroke", "blue"), ("transform", "scale(2)")],
+        &opt
+    ));
+    let expected = svg.replace(
+        "transform='translate(10 20)' stroke='black'",
+        "transform='scale(2)' stroke='blue'",
+    );
+    assert_eq!(
+        write(&tree),
+        write(&usvg::Tree::from_str(&expected, &opt).unwrap())
+    );
+    assert_eq!(
+        tree.root().abs_bounding_box(),
+        usvg::Tree::from_str(&expected, &opt)
+            .unwrap()
+            .root()
+            .abs_bounding_box()
+    );
+
+    // Consecutive changes of the same element.
+    assert!(tree.reparse_element(
+        &doc,
+        "g1",
+        &[("stroke", "green"), ("transform", "scale(2)")],
+        &opt
+    ));
+    let expected = svg.replace(
+        "transform='translate(10 20)' stroke='black'",
+        "transform='scale(2)' stroke='green'",
+    );
+    assert_eq!(
+        write(&tree),
+        write(&usvg::Tree::from_str(&expected, &opt).unwrap())
+    );
+
+    // CSS takes precedence over attributes.
+    let mut tree = usvg::Tree::from_xmltree(&doc, &opt).unwrap();
+    let original = write(&tree);
+    assert!(tree.reparse_element(&doc, "rect3", &[("fill", "red")], &opt));
+    assert_eq!(write(&tree), original);
+
+    // Unsupported changes.
+    let mut tree = usvg::Tree::from_xmltree(&doc, &opt).unwrap();
+    assert!(!tree.reparse_element(&doc, "rect4", &[("fill", "red")], &opt));
+    assert!(!tree.reparse_element(&doc, "rect2", &[("fill", "url(#lg1)")], &opt));
+    assert!(!tree.reparse_element(&doc, "rect2", &[("style", "fill:red")], &opt));
+    assert!(!tree.reparse_element(&doc, "lg1", &[("x1", "10")], &opt));
+    assert!(!tree.reparse_element(&doc, "missing", &[("fill", "red")], &opt));
+    assert_eq!(write(&tree), original);
+}
+
+#[test]
+fn visit_mut() {
+    let svg = "
+    <svg viewBox='0 0 100 100' xmlns='http://www.w3.org/2000/svg'>
+        <clipPath id='clip1'>
+            <rect id='rect1' width='50' height='50' fill='red'/>
+        </clipPath>
+        <pattern id='patt1' width='10' height='10' patternUnits='userSpaceOnUse'>
+            <rect id='rect2' width='5' height='5' fill='red'/>
+        </pattern>
+        <g id='g1' clip-path='url(#clip1)'>
+            <rect id='rect3' x='10' y='10' width='20' height='20' fill='red' stroke='green'/>
+            <rect id='rect4' x='40' y='10' width='20' height='20' fill='url(#patt1)'/>
+            <rect id='rect5' x='70' y='10' width='20' height='20' fill='url(#patt1)'/>
+        </g>
+    </svg>
+    ";
+
+    let mut tree = usvg::Tree::from_str(&svg, &usvg::Options::default()).unwrap();
+
+    #[derive(Default)]
+    struct Recorder {
+        events: Vec<String>,
+    }
+
+    impl usvg::NodeVisitor for Recorder {
+        fn enter(&mut self, node: &mut usvg::Node) {
+            self.events.push(format!("enter {}", node.id()));
+        }
+
+        fn leave(&mut self, node: &mut usvg::Node) {
+            self.events.push(format!("leave {}", node.id()));
+
+            if let usvg::Node::Path(ref mut path) = node {
+                if let Some(stroke) = path.stroke_mut() {
+                    stroke.set_width(usvg::StrokeWidth::new(10.0).unwrap());
+                }
+            }
+        }
+    }
+
+    let mut recorder = Recorder::default();
+    tree.visit_mut(&mut recorder);
+    assert_eq!(
+        recorder.events,
+        [
+            "enter g1",
+            "enter rect3",
+            "leave rect3",
+            "enter rect4",
+            "enter rect2",
+            "leave rect2",
+            "leave rect4",
+            // The pattern is visited only once.
+            "enter rect5",
+            "leave rect5",
+            "enter rect1",
+            "leave rect1",
+            "leave g1",
+        ]
+    );
+
+    // Bounding boxes are updated.
+    let rect3 = tree.node_by_id("rect3").unwrap();
+    assert_eq!(
+        rect3.abs_stroke_bounding_box(),
+        usvg::Rect::from_xywh(5.0, 5.0, 30.0, 30.0).unwrap()
+    );
+    assert_eq!(
+        tree.root().abs_stroke_bounding_box(),
+        usvg::Rect::from_xywh(5.0, 5.0, 85.0, 30.0).unwrap()
+    );
+
+    let red = Color::new_rgb(255, 0, 0);
+    let blue = Color::new_rgb(0, 0, 255);
+    tree.visit_mut(&mut usvg::RecolorVisitor::new(red, blue));
+    // Clip path children are always black.
+    let black = Color::black();
+    tree.visit_mut(&mut usvg::RecolorVisitor::new(black, blue));
+
+    let fill = |node: &usvg::Node| match node {
+        usvg::Node::Path(ref path) => path.fill().unwrap().paint().clone(),
+        _ => unreachable!(),
+    };
+
+    assert_eq!(
+        fill(tree.node_by_id("rect3").unwrap()),
+        usvg::Paint::Color(blue)
+    );
+    assert_eq!(
+        fill(&tree.clip_paths()[0].root().children()[0]),
+        usvg::Paint::Color(blue)
+    );
+    assert_eq!(
+        fill(&tree.patterns()[0].root().children()[0]),
+        usvg::Paint::Color(blue)
+    );
+    assert_eq!(tree.patterns().len(), 1);
+}
+
+#[test]
+fn background_image_fallback() {
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg'>
+        <filter id='filter1'>
+            <feBlend in='BackgroundImage'/>
+            <feOffset in='BackgroundImage' dx='10'/>
+        </filter>
+        <rect width='10' height='10' filter='url(#filter1)'/>
+    </svg>
+    ";
+
+    let tree = usvg::Tree::from_str(&svg, &usvg::Options::default()).unwrap();
+    let primitives = tree.filters()[0].primitives();
+    assert_eq!(primitives.len(), 3);
+
+    // A transparent image is used instead of the background.
+    assert_eq!(primitives[0].result(), "BackgroundImage");
+    match primitives[0].kind() {
+        usvg::filter::Kind::Flood(ref fe) => assert_eq!(fe.opacity(), usvg::Opacity::ZERO),
+        _ => unreachable!(),
+    }
+
+    let background = usvg::filter::Input::Reference("BackgroundImage".to_string());
+    match primitives[1].kind() {
+        usvg::filter::Kind::Blend(ref fe) => {
+            assert_eq!(*fe.input1(), background);
+            // Default inputs are not affected.
+            assert_eq!(*fe.input2(), usvg::filter::Input::SourceGraphic);
+        }
+        _ => unreachable!(),
+    }
+
+    match primitives[2].kind() {
+        usvg::filter::Kind::Offset(ref fe) => assert_eq!(*fe.input(), background),
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn keep_arcs() {
+    use usvg::tiny_skia_path::{PathSegment, Point};
+    use usvg::ArcPathSegment;
+
+    let svg = "
+    <svg viewBox='0 0 200 200' xmlns='http://www.w3.org/2000/svg'>
+        <circle id='circle1' cx='50' cy='50' r='10'/>
+        <rect id='rect1' width='20' height='20' rx='5'/>
+        <rect id='rect2' width='20' height='20'/>
+        <path id='path1' d='M 10 10 Q 20 0 30 10 t 10 0 S 60 20 70 10 z l 5 5
+                            A 0 5 0 0 1 90 90 a 5 5 0 0 0 10 0'/>
+        <path id='path2' d='M 10 10 L 20 20'/>
+    </svg>
+    ";
+
+    let opt = usvg::Options {
+        keep_arcs: true,
+        ..usvg::Options::default()
+    };
+    let tree = usvg::Tree::from_str(&svg, &opt).unwrap();
+
+    let path = |id| match tree.node_by_id(id).unwrap() {
+        usvg::Node::Path(ref path) => path.clone(),
+        _ => unreachable!(),
+    };
+
+    let arc = |rx, ry, sweep, x, y| ArcPathSegment::ArcTo {
+        rx,
+        ry,
+        x_axis_rotation: 0.0,
+        large_arc: false,
+        sweep,
+        to: Point::from_xy(x, y),
+    };
+
+    assert_eq!(
+        path("circle1").arc_segments().unwrap(),
+        [
+            ArcPathSegment::MoveTo(Point::from_xy(60.0, 50.0)),
+            arc(10.0, 10.0, true, 50.0, 60.0),
+            arc(10.0, 10.0, true, 40.0, 50.0),
+            arc(10.0, 10.0, true, 50.0, 40.0),
+            arc(10.0, 10.0, true, 60.0, 50.0),
+            ArcPathSegment::Close,
+        ]
+    );
+
+    let rect1 = path("rect1");
+    let rect_segments = rect1.arc_segments().unwrap();
+    assert_eq!(rect_segments.len(), 10);
+    assert_eq!(rect_segments[2], arc(5.0, 5.0, true, 20.0, 5.0));
+    assert!(path("rect2").arc_segments().is_none());
+    assert!(path("path2").arc_segments().is_none());
+
+    let path1 = path("path1");
+    let expected = [
+        ArcPathSegment::MoveTo(Point::from_xy(10.0, 10.0)),
+        ArcPathSegment::QuadTo(Point::from_xy(20.0, 0.0), Point::from_xy(30.0, 10.0)),
+        ArcPathSegment::QuadTo(Point::from_xy(40.0, 20.0), Point::from_xy(40.0, 10.0)),
+        ArcPathSegment::CubicTo(
+            Point::from_xy(40.0, 10.0),
+            Point::from_xy(60.0, 20.0),
+            Point::from_xy(70.0, 10.0),
+        ),
+        ArcPathSegment::Close,
+        ArcPathSegment::MoveTo(Point::from_xy(10.0, 10.0)),
+        ArcPathSegment::LineTo(Point::from_xy(15.0, 15.0)),
+        // An arc with a zero radius is a line.
+        ArcPathSegment::LineTo(Point::from_xy(90.0, 90.0)),
+        arc(5.0, 5.0, false, 100.0, 90.0),
+    ];
+    assert_eq!(path1.arc_segments().unwrap(), expected);
+
+    // Segments before the arc must be the same as in the approximated path.
+    let data: Vec<_> = path1.data().segments().take(8).collect();
+    let converted: Vec<_> = expected[..8]
+        .iter()
+        .map(|seg| match *seg {
+            ArcPathSegment::MoveTo(p) => PathSegment::MoveTo(p),
+            ArcPathSegment::LineTo(p) => PathSegment::LineTo(p),
+            ArcPathSegment::QuadTo(p1, p) => PathSegment::QuadTo(p1, p),
+            ArcPathSegment::CubicTo(p1, p2, p) => PathSegment::CubicTo(p1, p2, p),
+            ArcPathSegment::Close => PathSegment::Close,
+            ArcPathSegment::ArcTo { .. } => unreachable!(),
+        })
+        .collect();
+    assert_eq!(data, converted);
+
+    // Disabled by default.
+    let tree = usvg::Tree::from_str(&svg, &usvg::Options::default()).unwrap();
+    match tree.node_by_id("circle1").unwrap() {
+        usvg::Node::Path(ref path) => assert!(path.arc_segments().is_none()),
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn ink_bounding_box() {
+    let svg = "
+    <svg viewBox='0 0 200 200' xmlns='http://www.w3.org/2000/svg'>
+        <marker id='marker1' markerWidth='10' markerHeight='10' refX='5' refY='5'
+                markerUnits='userSpaceOnUse'>
+            <rect width='10' height='10'/>
+        </marker>
+        <filter id='filter1' x='0' y='0' width='200' height='100' filterUnits='userSpaceOnUse'>
+            <feGaussianBlur stdDeviation='5'/>
+        </filter>
+        <g transform='translate(10 20)'>
+            <path id='path1' d='M 10 10 L 50 10' stroke='black' stroke-width='2'
+                  marker-end='url(#marker1)'/>
+        </g>
+        <g id='g1' filter='url(#filter1)'>
+            <rect width='20' height='20'/>
+        </g>
+        <g id='g2'>
+            <rect width='20' height='20' stroke='black' stroke-width='4'/>
+        </g>
+    </svg>
+    ";
+
+    let tree = usvg::Tree::from_str(&svg, &usvg::Options::default()).unwrap();
+
+    let path1 = tree.node_by_id("path1").unwrap();
+    assert_eq!(
+        path1.bounding_box(),
+        usvg::Rect::from_ltrb(10.0, 10.0, 50.0, 10.0).unwrap()
+    );
+    assert_eq!(
+        path1.stroke_bounding_box(),
+        usvg::Rect::from_ltrb(10.0, 9.0, 50.0, 11.0).unwrap()
+    );
+    // The end marker is centered at the last point.
+    assert_eq!(
+        path1.ink_bounding_box(),
+        usvg::Rect::from_ltrb(10.0, 5.0, 55.0, 15.0).unwrap()
+    );
+    assert_eq!(
+        path1.abs_ink_bounding_box(),
+        usvg::Rect::from_ltrb(20.0, 25.0, 65.0, 35.0).unwrap()
+    );
+
+    // The filter region is used.
+    let g1 = tree.node_by_id("g1").unwrap();
+    assert_eq!(
+        g1.ink_bounding_box(),
+        usvg::Rect::from_xywh(0.0, 0.0, 200.0, 100.0).unwrap()
+    );
+
+    let g2 = tree.node_by_id("g2").unwrap();
+    assert_eq!(g2.ink_bounding_box(), g2.stroke_bounding_box());
+    assert_eq!(
+        g2.ink_bounding_box(),
+        usvg::Rect::from_ltrb(-2.0, -2.0, 22.0, 22.0).unwrap()
+    );
+}
+
+#[test]
+fn content_hash() {
+    let svg1 = "
+    <svg viewBox='0 0 100 100' xmlns='http://www.w3.org/2000/svg'>
+        <linearGradient id='lg1'>
+            <stop offset='0' stop-color='green'/>
+            <stop offset='1' stop-color='blue'/>
+        </linearGradient>
+        <filter id='filter1'>
+            <feOffset dx='5' result='offset'/>
+            <feMerge>
+                <feMergeNode in='SourceGraphic'/>
+                <feMergeNode in='offset'/>
+            </feMerge>
+        </filter>
+        <rect id='rect1' x='10' y='10' width='30' height='30' fill='url(#lg1)'/>
+        <rect id='rect2' x='50' y='10' width='30' height='30' fill='url(#lg1)'
+              filter='url(#filter1)'/>
+    </svg>
+    ";
+
+    // Different formatting, IDs, attributes order, results names and resources layout.
+    let svg2 = "<svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 100 100'>
+        <defs>
+            <linearGradient id='a'><stop stop-color='#008000' offset='0'/><stop offset='1' stop-color='#00f'/></linearGradient>
+            <linearGradient id='b' href='#a'/>
+            <filter id='f'><feOffset result='r1' dx='5'/><feMerge><feMergeNode in='SourceGraphic'/><feMergeNode in='r1'/></feMerge></filter>
+        </defs>
+        <rect height='30' width='30' y='10' x='10' style='fill:url(#a)'/>
+        <rect height='30' width='30' y='10' x='50' fill='url(#b)' filter='url(#f)'/>
+    </svg>";
+
+    let svg3 = svg1.replace("stop-color='blue'", "stop-color='red'");
+
+    let hash = |svg: &str| {
+        usvg::Tree::from_str(svg, &usvg::Options::default())
+            .unwrap()
+            .content_hash()
+    };
+
+    assert_eq!(hash(svg1), hash(svg1));
+    assert_eq!(hash(svg1), hash(svg2));
+    assert_ne!(hash(svg1), hash(&svg3));
+}
+
+#[test]
+fn paint_order_markers() {
+    fn layers(paint_order: &str) -> Vec<&'static str> {
+        let svg = format!(
+            "
+        <svg viewBox='0 0 200 200' xmlns='http://www.w3.org/2000/svg'>
+            <marker id='marker1' markerWidth='10' markerHeight='10'>
+                <rect width='10' height='10'/>
+            </marker>
+            <path d='M 20 20 L 180 20 L 180 180 Z' fill='green' stroke='blue'
+                  marker-start='url(#marker1)' paint-order='{}'/>
+        </svg>
+        ",
+            paint_order
+        );
+
+        let tree = usvg::Tree::from_str(&svg, &usvg::Options::default()).unwrap();
+        tree.root()
+            .children()
+            .iter()
+            .map(|node| match node {
+                usvg::Node::Group(_) => "markers",
+                usvg::Node::Path(ref path) => match (path.fill(), path.stroke()) {
+                    (Some(_), Some(_)) => "path",
+                    (Some(_), None) => "fill",
+                    (None, Some(_)) => "stroke",
+                    (None, None) => unreachable!(),
+                },
+                _ => unreachable!(),
+            })
+            .collect()
+    }
+
+    assert_eq!(layers("normal"), ["path", "markers"]);
+    assert_eq!(layers("markers"), ["markers", "path"]);
+    assert_eq!(layers("markers stroke"), ["markers", "path"]);
+    assert_eq!(layers("fill markers stroke"), ["fill", "markers", "stroke"]);
+    assert_eq!(layers("stroke markers"), ["stroke", "markers", "fill"]);
+    assert_eq!(layers("stroke fill markers"), ["path", "markers"]);
+}
+
+#[test]
+fn remove_unused_defs() {
+    let svg = "
+    <svg viewBox='0 0 200 200' xmlns='http://www.w3.org/2000/svg'>
+        <linearGradient id='lg1' x2='200' gradientUnits='userSpaceOnUse'>
+            <stop offset='0' stop-color='green'/>
+            <stop offset='1' stop-color='blue'/>
+        </linearGradient>
+        <linearGradient id='lg2' x2='10' gradientUnits='userSpaceOnUse'>
+            <stop offset='0' stop-color='red'/>
+            <stop offset='1' stop-color='black'/>
+        </linearGradient>
+        <pattern id='patt1' width='20' height='20' patternUnits='userSpaceOnUse'>
+            <rect width='10' height='10' fill='url(#lg2)'/>
+        </pattern>
+        <rect width='100' height='100' fill='url(#lg1)'/>
+        <rect x='100' width='100' height='100' fill='url(#patt1)' stroke='url(#lg1)'/>
+    </svg>
+    ";
+
+    // Replaces all pattern fills with a solid color.
+    struct RemovePatterns;
+    impl usvg::NodeVisitor for RemovePatterns {
+        fn enter(&mut self, node: &mut usvg::Node) {
+            if let usvg::Node::Path(ref mut path) = node {
+                if let Some(fill) = path.fill_mut() {
+                    if matches!(fill.paint(), usvg::Paint::Pattern(_)) {
+                        fill.set_paint(usvg::Paint::Color(usvg::Color::black()));
+                    }
+                }
+            }
+        }
+    }
+
+    let mut tree = usvg::Tree::from_str(&svg, &usvg::Options::default()).unwrap();
+    assert_eq!(tree.linear_gradients().len(), 2);
+    assert_eq!(tree.patterns().len(), 1);
+
+    tree.visit_mut(&mut RemovePatterns);
+
+    // The gradient used only by the pattern is gone as well.
+    assert_eq!(tree.linear_gradients().len(), 1);
+    assert_eq!(tree.linear_gradients()[0].id(), "lg1");
+    assert!(tree.patterns().is_empty());
+
+    let output = tree.to_string(&usvg::WriteOptions::default());
+    assert!(!output.contains("lg2"));
+    assert!(!output.contains("<pattern"));
+
+    // Nothing else to remove.
+    tree.remove_unused_defs();
+    assert_eq!(tree.linear_gradients().len(), 1);
+}
+
+#[test]
+fn root_overflow() {
+    let svg = "
+    <svg width='100' height='100' viewBox='0 0 100 100' overflow='visible'
+         xmlns='http://www.w3.org/2000/svg'>
+        <rect x='-10' y='20' width='50' height='100'/>
+    </svg>
+    ";
+
+    let tree = usvg::Tree::from_str(&svg, &usvg::Options::default()).unwrap();
+    assert!(tree.is_overflow_visible());
+    assert_eq!(
+        tree.render_area(),
+        usvg::NonZeroRect::from_ltrb(-10.0, 0.0, 100.0, 120.0).unwrap()
+    );
+
+    // Preserved by the writer.
+    let output = tree.to_string(&usvg::WriteOptions::default());
+    let tree = usvg::Tree::from_str(&output, &usvg::Options::default()).unwrap();
+    assert!(tree.is_overflow_visible());
+
+    // The root element clips by default.
+    let svg = svg.replace("overflow='visible'", "");
+    let tree = usvg::Tree::from_str(&svg, &usvg::Options::default()).unwrap();
+    assert!(!tree.is_overflow_visible());
+    assert_eq!(
+        tree.render_area(),
+        usvg::NonZeroRect::from_xywh(0.0, 0.0, 100.0, 100.0).unwrap()
+    );
+}
+
+#[test]
+fn fit_viewbox_to_content() {
+    let svg = "
+    <svg width='100' height='100' xmlns='http://www.w3.org/2000/svg'>
+        <rect x='-100' width='200' height='100'/>
+    </svg>
+    ";
+
+    let opt = usvg::Options {
+        fit_viewbox_to_content: true,
+        ..usvg::Options::default()
+    };
+
+    let tree = usvg::Tree::from_str(&svg, &opt).unwrap();
+    assert_eq!(tree.size(), usvg::Size::from_wh(100.0, 100.0).unwrap());
+    // The content is scaled down and centered.
+    assert_eq!(
+        tree.view_transform(tree.size()),
+        usvg::Transform::from_row(0.5, 0.0, 0.0, 0.5, 50.0, 25.0)
+    );
+    assert_eq!(
+        tree.root().abs_layer_bounding_box(),
+        usvg::NonZeroRect::from_xywh(0.0, 25.0, 100.0, 50.0).unwrap()
+    );
+
+    // Content inside the viewport is not affected.
+    let svg = svg.replace("x='-100' width='200'", "x='10' width='50'");
+    let tree = usvg::Tree::from_str(&svg, &opt).unwrap();
+    assert!(tree.view_transform(tree.size()).is_identity());
+
+    // An explicit `viewBox` is always preserved.
+    let svg = "
+    <svg width='100' height='100' viewBox='0 0 100 100' xmlns='http://www.w3.org/2000/svg'>
+        <rect x='-100' width='200' height='100'/>
+    </svg>
+    ";
+    let tree = usvg::Tree::from_str(&svg, &opt).unwrap();
+    assert!(tree.view_transform(tree.size()).is_identity());
+}
+
+#[test]
+fn gradient_color_interpolation() {
+    let make_svg = |space: &str| {
+        format!(
+            "
+    <svg viewBox='0 0 100 100' xmlns='http://www.w3.org/2000/svg'>
+        <linearGradient id='lg1' {}>
+            <stop offset='0' stop-color='blue'/>
+            <stop offset='1' stop-color='yellow'/>
+        </linearGradient>
+        <rect width='100' height='100' fill='url(#lg1)'/>
+    </svg>
+    ",
+            space
+        )
+    };
+
+    let midpoint = |space: &str| {
+        let tree = usvg::Tree::from_str(&make_svg(space), &usvg::Options::default()).unwrap();
+        let lg = &tree.linear_gradients()[0];
+        (lg.color_interpolation(), lg.sample(0.5).0)
+    };
+
+    let (space, srgb) = midpoint("");
+    assert_eq!(space, usvg::GradientInterpolation::SRGB);
+    assert_eq!(srgb, usvg::Color::new_rgb(128, 128, 128));
+
+    // `linearRGB` is resolved to sRGB, like in browsers.
+    let (space, _) = midpoint("color-interpolation='linearRGB'");
+    assert_eq!(space, usvg::GradientInterpolation::SRGB);
+
+    // Perceptual spaces produce a lighter midpoint and, in polar spaces, keep it chromatic.
+    let (space, lab) = midpoint("color-interpolation='lab'");
+    assert_eq!(space, usvg::GradientInterpolation::Lab);
+    assert!(lab.red > srgb.red && lab.blue > srgb.blue);
+
+    let (space, oklch) = midpoint("color-interpolation='oklch'");
+    assert_eq!(space, usvg::GradientInterpolation::OkLch);
+    let max = oklch.red.max(oklch.green).max(oklch.blue);
+    let min = oklch.red.min(oklch.green).min(oklch.blue);
+    assert!(max - min > 100, "{:?}", oklch);
+
+    // Endpoints are not affected.
+    let tree = usvg::Tree::from_str(
+        &make_svg("color-interpolation='oklch'"),
+        &usvg::Options::default(),
+    )
+    .unwrap();
+    let lg = &tree.linear_gradients()[0];
+    assert_eq!(lg.sample(0.0).0, usvg::Color::new_rgb(0, 0, 255));
+    assert_eq!(lg.sample(1.0).0, usvg::Color::new_rgb(255, 255, 0));
+
+    // The color space is preserved by the writer.
+    let svg = tree.to_string(&usvg::WriteOptions::default());
+    assert!(svg.contains("color-interpolation=\"oklch\""), "{}", svg);
+}
+
+#[test]
+fn tree_builder() {
+    use usvg::tiny_skia_path::PathBuilder;
+
+    let mut builder = usvg::TreeBuilder::new(usvg::Size::from_wh(200.0, 100.0).unwrap());
+    builder.push_group(
+        usvg::Transform::from_translate(10.0, 20.0),
+        usvg::Opacity::new_clamped(0.5),
+    );
+
+    let paint = builder
+        .linear_gradient(
+            0.0,
+            0.0,
+            50.0,
+            0.0,
+            vec![
+                usvg::Stop::new(
+                    usvg::StopOffset::ONE,
+                    Color::new_rgb(0, 0, 255),
+                    usvg::Opacity::ONE,
+                ),
+                usvg::Stop::new(
+                    usvg::StopOffset::ZERO,
+                    Color::new_rgb(0, 128, 0),
+                    usvg::Opacity::ONE,
+                ),
+            ],
+        )
+        .unwrap();
+    let mut fill = usvg::Fill::default();
+    fill.set_paint(paint);
+    let rect = usvg::Rect::from_xywh(0.0, 0.0, 50.0, 40.0).unwrap();
+    builder.push_path(
+        PathBuilder::from_rect(rect),
+        Some(fill),
+        Some(usvg::Stroke::default()),
+    );
+
+    // Empty groups are removed.
+    builder.push_group(usvg::Transform::default(), usvg::Opacity::ONE);
+    builder.pop_group();
+
+    let tree = builder.finish();
+
+    let usvg::Node::Group(ref group) = tree.root().children()[0] else {
+        unreachable!()
+    };
+    assert_eq!(group.children().len(), 1);
+    assert_eq!(
+        group.abs_transform(),
+        usvg::Transform::from_translate(10.0, 20.0)
+    );
+    assert_eq!(
+        tree.root().abs_stroke_bounding_box(),
+        usvg::Rect::from_xywh(9.5, 19.5, 51.0, 41.0).unwrap()
+    );
+    assert_eq!(tree.linear_gradients().len(), 1);
+
+    let svg = "
+    <svg width='200' height='100' xmlns='http://www.w3.org/2000/svg'>
+        <linearGradient id='lg1' x2='50' gradientUnits='userSpaceOnUse'>
+            <stop offset='0' stop-color='green'/>
+            <stop offset='1' stop-color='blue'/>
+        </linearGradient>
+        <g transform='translate(10 20)' opacity='0.5'>
+            <rect width='50' height='40' fill='url(#lg1)' stroke='black'/>
+        </g>
+    </svg>
+    ";
+    let parsed = usvg::Tree::from_str(svg, &usvg::Options::default()).unwrap();
+    assert_eq!(tree.content_hash(), parsed.content_hash());
+}
+
+#[test]
+fn bounding_box_under() {
+    let svg = "
+    <svg viewBox='0 0 200 200' xmlns='http://www.w3.org/2000/svg'>
+        <g id='g1' transform='translate(50 50)'>
+            <rect id='rect1' width='20' height='10'/>
+            <g id='g2' transform='rotate(45) scale(2)'>
+                <circle id='circle1' cx='10' cy='10' r='10'/>
+            </g>
+            <g id='g3' transform='skewX(30)'>
+                <circle id='circle2' cx='10' cy='10' r='10'/>
+            </g>
+        </g>
+    </svg>
+    ";
+
+    let tree = usvg::Tree::from_str(&svg, &usvg::Options::default()).unwrap();
+
+    let assert_rect_eq = |r1: usvg::Rect, r2: usvg::Rect| {
+        assert!((r1.x() - r2.x()).abs() < 0.001, "{:?} != {:?}", r1, r2);
+        assert!((r1.y() - r2.y()).abs() < 0.001, "{:?} != {:?}", r1, r2);
+        assert!(
+            (r1.right() - r2.right()).abs() < 0.001,
+            "{:?} != {:?}",
+            r1,
+            r2
+        );
+        assert!(
+            (r1.bottom() - r2.bottom()).abs() < 0.001,
+            "{:?} != {:?}",
+            r1,
+            r2
+        );
+    };
+
+    // With the stored transform, the stored bounding box is returned.
+    for id in ["g1", "rect1", "g2", "circle1", "g3", "circle2"] {
+        let node = tree.node_by_id(id).unwrap();
+        let bbox = node.bounding_box_under(node.abs_transform()).unwrap();
+        assert_rect_eq(bbox, node.abs_bounding_box());
+    }
+
+    // Measure `g1` as if it was moved into the root.
+    let node = tree.node_by_id("g1").unwrap();
+    let bbox = node
+        .bounding_box_under(usvg::Transform::default())
+        .unwrap()
+        .transform(usvg::Transform::from_translate(50.0, 50.0))
+        .unwrap();
+    assert_rect_eq(bbox, node.abs_bounding_box());
+
+    assert_eq!(
+        tree.node_by_id("rect1")
+            .unwrap()
+            .bounding_box_under(usvg::Transform::from_scale(2.0, 3.0)),
+        usvg::Rect::from_xywh(0.0, 0.0, 40.0, 30.0)
+    );
+}
+
+#[test]
+fn all_bounding_boxes() {
+    let svg = "
+    <svg viewBox='0 0 200 200' xmlns='http://www.w3.org/2000/svg'>
+        <clipPath id='clip1'>
+            <rect id='rect1' width='10' height='10'/>
+        </clipPath>
+        <g id='g1' transform='translate(50 50)'>
+            <rect id='rect2' width='20' height='10' stroke='black' stroke-width='4'/>
+            <g>
+                <circle id='circle1' cx='10' cy='10' r='10' clip-path='url(#clip1)'/>
+            </g>
+        </g>
+        <rect width='10' height='10'/>
+    </svg>
+    ";
+
+    let tree = usvg::Tree::from_str(&svg, &usvg::Options::default()).unwrap();
+    let bboxes = tree.all_bounding_boxes(usvg::QueryCoordinateSpace::ViewportPixels);
+
+    let ids: Vec<_> = bboxes.iter().map(|b| b.id()).collect();
+    assert_eq!(ids, ["g1", "rect2", "circle1"]);
+
+    for bboxes in &bboxes {
+        let node = tree.node_by_id(bboxes.id()).unwrap();
+        assert_eq!(bboxes.bounding_box(), node.abs_bounding_box());
+        assert_eq!(bboxes.ink_bounding_box(), node.abs_ink_bounding_box());
+        assert_eq!(bboxes.layer_bounding_box(), node.abs_layer_bounding_box());
+    }
+
+    assert_eq!(
+        bboxes[1].bounding_box(),
+        usvg::Rect::from_xywh(50.0, 50.0, 20.0, 10.0).unwrap()
+    );
+    assert_eq!(
+        bboxes[1].ink_bounding_box(),
+        usvg::Rect::from_xywh(48.0, 48.0, 24.0, 14.0).unwrap()
+    );
+}
+
+#[test]
+fn bounding_boxes_coordinate_space() {
+    // Must match the `QueryCoordinateSpace` documentation.
+    let svg = "
+    <svg width='15pt' height='15pt' viewBox='0 0 10 10' xmlns='http://www.w3.org/2000/svg'>
+        <rect id='rect1' x='2' y='2' width='5' height='5' stroke='black'/>
+    </svg>
+    ";
+
+    let tree = usvg::Tree::from_str(&svg, &usvg::Options::default()).unwrap();
+
+    let bboxes = tree
+        .bounding_boxes("rect1", usvg::QueryCoordinateSpace::UserSpace)
+        .unwrap();
+    assert_eq!(
+        bboxes.bounding_box(),
+        usvg::Rect::from_xywh(2.0, 2.0, 5.0, 5.0).unwrap()
+    );
+    assert_eq!(
+        bboxes.ink_bounding_box(),
+        usvg::Rect::from_xywh(1.5, 1.5, 6.0, 6.0).unwrap()
+    );
+    assert_eq!(
+        bboxes.layer_bounding_box(),
+        usvg::NonZeroRect::from_xywh(2.0, 2.0, 5.0, 5.0)
+    );
+
+    let bboxes = tree
+        .bounding_boxes("rect1", usvg::QueryCoordinateSpace::ViewportPixels)
+        .unwrap();
+    assert_eq!(
+        bboxes.bounding_box(),
+        usvg::Rect::from_xywh(4.0, 4.0, 10.0, 10.0).unwrap()
+    );
+    assert_eq!(
+        bboxes.ink_bounding_box(),
+        usvg::Rect::from_xywh(3.0, 3.0, 12.0, 12.0).unwrap()
+    );
+    assert_eq!(
+        bboxes.layer_bounding_box(),
+        usvg::NonZeroRect::from_xywh(4.0, 4.0, 10.0, 10.0)
+    );
+
+    let all = tree.all_bounding_boxes(usvg::QueryCoordinateSpace::UserSpace);
+    assert_eq!(all.len(), 1);
+    assert_eq!(
+        all[0].bounding_box(),
+        usvg::Rect::from_xywh(2.0, 2.0, 5.0, 5.0).unwrap()
+    );
+
+    assert!(tree
+        .bounding_boxes("", usvg::QueryCoordinateSpace::UserSpace)
+        .is_none());
+}
+
+#[test]
+fn effective_clip() {
+    let svg = "
+    <svg viewBox='0 0 100 100' xmlns='http://www.w3.org/2000/svg'>
+        <clipPath id='clip1'>
+            <rect width='50' height='50'/>
+        </clipPath>
+        <clipPath id='clip2'>
+            <circle cx='40' cy='40' r='20'/>
+        </clipPath>
+        <g id='g1' transform='translate(10 0)' clip-path='url(#clip1)'>
+            <g id='g2' clip-path='url(#clip2)'>
+                <rect id='rect1' width='100' height='100'/>
+            </g>
+        </g>
+        <rect id='rect2' width='10' height='10'/>
+    </svg>
+    ";
+
+    let tree = usvg::Tree::from_str(&svg, &usvg::Options::default()).unwrap();
+
+    let clip = tree.effective_clip("rect1").unwrap();
+    let ids: Vec<_> = clip.clip_paths().iter().map(|(c, _)| c.id()).collect();
+    assert_eq!(ids, ["clip1", "clip2"]);
+    assert_eq!(
+        clip.bounding_box(),
+        usvg::Rect::from_ltrb(30.0, 20.0, 60.0, 50.0)
+    );
+    assert!(clip.contains(50.0, 40.0));
+    // Outside of `clip1`.
+    assert!(!clip.contains(65.0, 40.0));
+    // Outside of `clip2`.
+    assert!(!clip.contains(15.0, 5.0));
+
+    assert_eq!(tree.effective_clip("g1").unwrap().clip_paths().len(), 1);
+    assert!(tree.effective_clip("rect2").is_none());
+    assert!(tree.effective_clip("missing").is_none());
+}
+
+#[test]
+fn effective_clip_path() {
+    let svg = "
+    <svg viewBox='0 0 200 200' xmlns='http://www.w3.org/2000/svg'>
+        <clipPath id='clip1'>
+            <circle cx='50' cy='30' r='30'/>
+        </clipPath>
+        <clipPath id='clip2' transform='translate(20 0)' clip-path='url(#clip1)'>
+            <path d='M 0 0 H 60 V 60 H 0 Z M 15 15 H 45 V 45 H 15 Z' clip-rule='evenodd'/>
+        </clipPath>
+        <clipPath id='clip3'>
+            <rect y='10' width='100' height='30' transform='rotate(5)'/>
+        </clipPath>
+        <g transform='scale(2)' clip-path='url(#clip2)'>
+            <g clip-path='url(#clip3)'>
+                <rect id='rect1' width='100' height='100'/>
+            </g>
+        </g>
+    </svg>
+    ";
+
+    let tree = usvg::Tree::from_str(&svg, &usvg::Options::default()).unwrap();
+    let clip = tree.effective_clip("rect1").unwrap();
+    let path = clip.to_path(0.01).unwrap();
+
+    // The path consists of non-overlapping polygons, so a crossings count is enough.
+    let mut edges = Vec::new();
+    let mut start = None;
+    let mut prev = None;
+    for segment in path.segments() {
+        match segment {
+            usvg::tiny_skia_path::PathSegment::MoveTo(p) => {
+                start = Some(p);
+                prev = Some(p);
+            }
+            usvg::tiny_skia_path::PathSegment::LineTo(p) => {
+                edges.push((prev.unwrap(), p));
+                prev = Some(p);
+            }
+            usvg::tiny_skia_path::PathSegment::Close => edges.push((prev.unwrap(), start.unwrap())),
+            _ => unreachable!(),
+        }
+    }
+
+    let path_contains = |x: f32, y: f32| {
+        let crossings = edges
+            .iter()
+            .filter(|(p0, p1)| (p0.y > y) != (p1.y > y))
+            .filter(|(p0, p1)| x < p0.x + (p1.x - p0.x) * (y - p0.y) / (p1.y - p0.y))
+            .count();
+        crossings % 2 == 1
+    };
+
+    let mut inside = 0;
+    for y in 0..100 {
+        for x in 0..100 {
+            let (x, y) = (x as f32 * 2.0 + 0.29, y as f32 * 2.0 + 0.87);
+            assert_eq!(path_contains(x, y), clip.contains(x, y), "{} {}", x, y);
+            inside += clip.contains(x, y) as u32;
+        }
+    }
+    assert!(inside > 0);
+
+    // The `evenodd` hole.
+    assert!(!path_contains(100.0, 60.0));
+    assert!(path_contains(60.0, 60.0));
+}
+
+#[test]
+fn text_shape_inside() {
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 200 200'>
+        <rect id='rect1' x='10' y='10' width='60' height='40'/>
+        <circle id='circle1' cx='150' cy='100' r='40'/>
+        <text id='text1' font-family='Noto Sans' font-size='10'
+              style='shape-inside:url(#rect1)'>Lorem ipsum dolor sit amet, consectetur adipiscing elit</text>
+        <text id='text2' font-family='Noto Sans' font-size='10' text-anchor='middle'
+              style='shape-inside:url(#circle1);shape-padding:5'>Lorem ipsum dolor sit amet</text>
+        <circle id='circle2' cx='100' cy='100' r='50'/>
+        <text id='text3' font-family='Noto Sans' font-size='20'
+              style='shape-inside:url(#circle2)'>ab WWWWW cd ef gh</text>
+        <circle id='circle3' cx='100' cy='100' r='66'/>
+        <text id='text4' font-family='Noto Sans' font-size='20'
+              style='shape-inside:url(#circle3)'>ab WWWWWWW cd ef gh</text>
+    </svg>
+    ";
+
+    let opt = usvg::Options {
+        fontdb: GLOBAL_FONTDB.clone(),
+        ..usvg::Options::default()
+    };
+    let tree = usvg::Tree::from_str(&svg, &opt).unwrap();
+
+    let chunks = |id| match tree.node_by_id(id).unwrap() {
+        usvg::Node::Text(ref text) => text
+            .chunks()
+            .iter()
+            .map(|c| {
+                let round = |n: Option<f32>| (n.unwrap() * 100.0).round() / 100.0;
+                (c.text().to_string(), round(c.x()), round(c.y()))
+            })
+            .collect::<Vec<_>>(),
+        _ => unreachable!(),
+    };
+
+    // Lines are broken at spaces. Text that doesn't fit is removed.
+    assert_eq!(
+        chunks("text1"),
+        vec![
+            ("Lorem".to_string(), 10.0, 20.69),
+            ("ipsum dolor".to_string(), 10.0, 34.31),
+        ]
+    );
+
+    // The first line is too narrow, therefore skipped.
+    assert_eq!(
+        chunks("text2"),
+        vec![
+            ("Lorem".to_string(), 150.0, 89.31),
+            ("ipsum dolor".to_string(), 150.0, 102.93),
+            ("sit amet".to_string(), 150.0, 116.55),
+        ]
+    );
+
+    // A word that is wider than any line, but narrower than the shape,
+    // is broken instead of pushing the rest of the text out.
+    // In `text3`, there is no room for the third line.
+    assert_eq!(
+        chunks("text3"),
+        vec![
+            ("ab".to_string(), 55.53, 98.62),
+            ("WWWW".to_string(), 61.4, 125.86),
+        ]
+    );
+    assert_eq!(
+        chunks("text4"),
+        vec![
+            ("ab".to_string(), 46.67, 82.62),
+            ("WWWWWW".to_string(), 35.96, 109.86),
+            ("W cd ef gh".to_string(), 49.97, 137.1),
+        ]
+    );
+}
+
+#[test]
+fn fit_size() {
+    use usvg::tiny_skia_path::IntSize;
+    use usvg::FitSize;
+
+    let size = usvg::Size::from_wh(200.0, 100.0).unwrap();
+    let to = usvg::Size::from_wh(100.0, 100.0).unwrap();
+    assert_eq!(
+        size.fit_to_width(400.0),
+        Some((usvg::Size::from_wh(400.0, 200.0).unwrap(), 2.0))
+    );
+    assert_eq!(
+        size.fit_to_height(50.0),
+        Some((usvg::Size::from_wh(100.0, 50.0).unwrap(), 0.5))
+    );
+    assert_eq!(size.fit_to_width(0.0), None);
+    assert_eq!(
+        size.fit_inside(to),
+        (usvg::Size::from_wh(100.0, 50.0).unwrap(), 0.5)
+    );
+    assert_eq!(
+        size.fit_outside(to),
+        (usvg::Size::from_wh(200.0, 100.0).unwrap(), 1.0)
+    );
+
+    let size = IntSize::from_wh(3, 4).unwrap();
+    let to = IntSize::from_wh(30, 30).unwrap();
+    // The height is rounded up.
+    assert_eq!(
+        size.fit_to_width(10),
+        Some((IntSize::from_wh(10, 14).unwrap(), 10.0 / 3.0))
+    );
+    assert_eq!(
+        size.fit_to_height(8),
+        Some((IntSize::from_wh(6, 8).unwrap(), 2.0))
+    );
+    assert_eq!(size.fit_to_height(0), None);
+    assert_eq!(
+        size.fit_inside(to),
+        (IntSize::from_wh(23, 30).unwrap(), 7.5)
+    );
+    assert_eq!(
+        size.fit_outside(to),
+        (IntSize::from_wh(30, 40).unwrap(), 10.0)
+    );
+}
+
+#[test]
+fn text_fallback_families() {
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 200 200'>
+        <text id='text1' x='10' y='50' font-family='Noto Sans'>A&#x1F600;</text>
+    </svg>
+    ";
+
+    let fallback_family = |families: &[&str]| {
+        let opt = usvg::Options {
+            fontdb: GLOBAL_FONTDB.clone(),
+            font_resolver: usvg::FontResolver {
+                select_fallback: usvg::FontResolver::families_fallback_selector(
+                    families.iter().map(|s| s.to_string()).collect(),
+                ),
+                ..usvg::FontResolver::default()
+            },
+            ..usvg::Options::default()
+        };
+
+        let tree = usvg::Tree::from_str(&svg, &opt).unwrap();
+        let usvg::Node::Text(ref text) = tree.node_by_id("text1").unwrap() else {
+            unreachable!()
+        };
+
+        let glyphs = &text.layouted()[0].positioned_glyphs;
+        assert_eq!(glyphs.len(), 2);
+        let face = tree.fontdb().face(glyphs[1].font).unwrap();
+        face.families[0].0.clone()
+    };
+
+    assert_eq!(
+        fallback_family(&["Twitter Color Emoji", "Noto Color Emoji"]),
+        "Twitter Color Emoji"
+    );
+    assert_eq!(
+        fallback_family(&["Noto Color Emoji", "Twitter Color Emoji"]),
+        "Noto Color Emoji"
+    );
+    // Families without the character are skipped.
+    assert_eq!(
+        fallback_family(&["Noto Serif", "Noto Color Emoji"]),
+        "Noto Color Emoji"
+    );
+    // Unknown families are skipped as well, and then the whole database is searched.
+    let family = fallback_family(&["Unknown"]);
+    assert!(family.contains("Emoji"), "{}", family);
+}
+
+#[test]
+fn text_span_fonts() {
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 200 200'>
+        <text id='text1' x='10' y='50' font-family='Noto Sans'>
+            A&#x1F600;B<tspan font-family='Noto Serif'>C</tspan>
+        </text>
+    </svg>
+    ";
+
+    let opt = usvg::Options {
+        fontdb: GLOBAL_FONTDB.clone(),
+        ..usvg::Options::default()
+    };
+    let tree = usvg::Tree::from_str(&svg, &opt).unwrap();
+    let usvg::Node::Text(ref text) = tree.node_by_id("text1").unwrap() else {
+        unreachable!()
+    };
+
+    let fonts: Vec<_> = text
+        .layouted()
+        .iter()
+        .flat_map(|span| span.fonts())
+        .map(|id| {
+            let face = tree.fontdb().face(id).unwrap();
+            let path = match face.source {
+                usvg::fontdb::Source::File(ref path) => path.clone(),
+                _ => unreachable!(),
+            };
+            let file_name = path.file_name().unwrap().to_str().unwrap().to_string();
+            (file_name, face.index, face.post_script_name.clone())
+        })
+        .collect();
+
+    assert_eq!(fonts.len(), 3);
+    assert_eq!(
+        fonts[0],
+        (
+            "NotoSans-Regular.ttf".to_string(),
+            0,
+            "NotoSans-Regular".to_string()
+        )
+    );
+    // A fallback.
+    assert!(fonts[1].0.contains("Emoji"), "{:?}", fonts[1]);
+    assert_eq!(fonts[2].0, "NotoSerif-Regular.ttf");
+}
+
+#[test]
+fn text_font_size_adjust() {
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 200 200'>
+        <text id='text1' x='10' y='100' font-size='100' font-size-adjust='0.5'>
+            <tspan font-family='Noto Sans'>x</tspan><tspan font-family='Source Sans Pro'>x</tspan>
+        </text>
+        <text id='text2' x='10' y='100' font-size='100'>
+            <tspan font-family='Noto Sans'>x</tspan><tspan font-family='Source Sans Pro'>x</tspan>
+        </text>
+    </svg>
+    ";
+
+    let opt = usvg::Options {
+        fontdb: GLOBAL_FONTDB.clone(),
+        ..usvg::Options::default()
+    };
+    let tree = usvg::Tree::from_str(&svg, &opt).unwrap();
+
+    // Returns the height of each `x`, which is roughly the x-height.
+    let heights = |id: &str| -> Vec<f32> {
+        let usvg::Node::Text(ref text) = tree.node_by_id(id).unwrap() else {
+            unreachable!()
+        };
+
+        text.flattened()
+            .children()
+            .iter()
+            .map(|node| node.bounding_box().height())
+            .collect()
+    };
+
+    let adjusted = heights("text1");
+    assert_eq!(adjusted.len(), 2);
+    assert!((adjusted[0] - 50.0).abs() < 1.5, "{:?}", adjusted);
+    assert!((adjusted[1] - 50.0).abs() < 1.5, "{:?}", adjusted);
+
+    let normal = heights("text2");
+    assert_eq!(normal.len(), 2);
+    assert!((normal[0] - normal[1]).abs() > 3.0, "{:?}", normal);
+
+    let usvg::Node::Text(ref text) = tree.node_by_id("text1").unwrap() else {
+        unreachable!()
+    };
+    assert_eq!(text.chunks()[0].spans()[0].font_size_adjust(), Some(0.5));
+}
+
+#[test]
+fn text_monochrome_emoji() {
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 200 200'>
+        <text id='text1' x='10' y='50' font-size='40' fill='red'>
+            <tspan font-family='Noto Color Emoji COLR'>&#x1F436;</tspan>
+            <tspan font-family='Twitter Color Emoji'>&#x1F600;</tspan>
+        </text>
+    </svg>
+    ";
+
+    // Returns fills of all paths in a flattened text.
+    fn fills(tree: &usvg::Tree) -> Vec<usvg::Paint> {
+        fn collect(group: &usvg::Group, fills: &mut Vec<usvg::Paint>) {
+            for node in group.children() {
+                match node {
+                    usvg::Node::Group(ref group) => collect(group, fills),
+                    usvg::Node::Path(ref path) => {
+                        fills.extend(path.fill().map(|fill| fill.paint().clone()))
+                    }
+                    _ => unreachable!(),
+                }
+            }
+        }
+
+        let usvg::Node::Text(ref text) = tree.node_by_id("text1").unwrap() else {
+            unreachable!()
+        };
+
+        let mut fills = Vec::new();
+        collect(text.flattened(), &mut fills);
+        fills
+    }
+
+    let red = usvg::Paint::Color(usvg::Color::new_rgb(255, 0, 0));
+
+    let opt = usvg::Options {
+        fontdb: GLOBAL_FONTDB.clone(),
+        ..usvg::Options::default()
+    };
+    let tree = usvg::Tree::from_str(&svg, &opt).unwrap();
+    assert!(fills(&tree).iter().any(|paint| *paint != red));
+
+    let opt = usvg::Options {
+        fontdb: GLOBAL_FONTDB.clone(),
+        emoji_rendering: usvg::EmojiRendering::Monochrome,
+        ..usvg::Options::default()
+    };
+    let tree = usvg::Tree::from_str(&svg, &opt).unwrap();
+    let fills = fills(&tree);
+    // Glyph layers are merged into a single path per span.
+    assert_eq!(fills.len(), 2);
+    assert!(fills.iter().all(|paint| *paint == red));
+}
+
+#[test]
+fn text_combining_mark_fallback() {
+    // Noto Sans has a combining macron, but not the base character.
+    // And the fallback font doesn't have the first character.
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 200 200'>
+        <text id='text1' x='10' y='50' font-family='Noto Sans'>&#x436;&#x915;&#x304;</text>
+    </svg>
+    ";
+
+    let opt = usvg::Options {
+        fontdb: GLOBAL_FONTDB.clone(),
+        ..usvg::Options::default()
+    };
+    let tree = usvg::Tree::from_str(&svg, &opt).unwrap();
+    let usvg::Node::Text(ref text) = tree.node_by_id("text1").unwrap() else {
+        unreachable!()
+    };
+
+    let family = |id| tree.fontdb().face(id).unwrap().families[0].0.clone();
+    let glyphs = &text.layouted()[0].positioned_glyphs;
+    assert_eq!(glyphs.len(), 3);
+    assert_eq!(family(glyphs[0].font), "Noto Sans");
+    // The whole cluster must be shaped using the fallback font.
+    assert_eq!(family(glyphs[1].font), "Noto Sans Devanagari");
+    assert_eq!(family(glyphs[2].font), "Noto Sans Devanagari");
+}
+
+#[test]
+fn text_flattened_span_children() {
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 200 200'>
+        <text id='text1' x='10' y='50' font-family='Noto Sans'>
+            Hello <tspan fill='red' text-decoration='underline'>world</tspan>
+            <tspan x='10' y='100'>again</tspan>
+        </text>
+    </svg>
+    ";
+
+    let opt = usvg::Options {
+        fontdb: GLOBAL_FONTDB.clone(),
+        ..usvg::Options::default()
+    };
+    let tree = usvg::Tree::from_str(&svg, &opt).unwrap();
+    let usvg::Node::Text(ref text) = tree.node_by_id("text1").unwrap() else {
+        unreachable!()
+    };
+
+    let spans: Vec<_> = text
+        .layouted()
+        .iter()
+        .map(|span| {
+            let source = &text.chunks()[span.chunk_index].spans()[span.span_index];
+            let chunk_text = text.chunks()[span.chunk_index].text();
+            (
+                span.chunk_index,
+                &chunk_text[source.start()..source.end()],
+                span.flattened_children.clone(),
+            )
+        })
+        .collect();
+
+    assert_eq!(
+        spans,
+        vec![
+            (0, "Hello ", 0..1),
+            // An underline and glyphs.
+            (0, "world", 1..3),
+            // No outlines.
+            (0, " ", 3..3),
+            (1, "again", 3..4),
+        ]
+    );
+
+    let red = usvg::Paint::Color(usvg::Color::new_rgb(255, 0, 0));
+    for node in &text.flattened().children()[1..3] {
+        let usvg::Node::Path(ref path) = node else {
+            unreachable!()
+        };
+        assert_eq!(path.fill().unwrap().paint(), &red);
+    }
+    assert_eq!(text.flattened().children().len(), 4);
+}
+
+#[test]
+fn text_strict_fonts() {
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 200 200'>
+        <text id='text1' x='10' y='50' font-family='Missing Font'>Text</text>
+        <text id='text2' x='10' y='100' font-family='Noto Sans'>
+            Text<tspan>&#x1F600;</tspan>
+        </text>
+        <text id='text3' x='10' y='150' font-family='Missing Font, serif'>Text</text>
+    </svg>
+    ";
+
+    // Without the strict mode, fonts are substituted.
+    let opt = usvg::Options {
+        fontdb: GLOBAL_FONTDB.clone(),
+        ..usvg::Options::default()
+    };
+    let tree = usvg::Tree::from_str(&svg, &opt).unwrap();
+    assert!(tree.node_by_id("text1").is_some());
+
+    let diagnostics = usvg::Diagnostics::new();
+    let opt = usvg::Options {
+        fontdb: GLOBAL_FONTDB.clone(),
+        strict_fonts: true,
+        diagnostics: Some(&diagnostics),
+        ..usvg::Options::default()
+    };
+    let tree = usvg::Tree::from_str(&svg, &opt).unwrap();
+
+    assert!(tree.node_by_id("text1").is_none());
+
+    let usvg::Node::Text(ref text) = tree.node_by_id("text2").unwrap() else {
+        unreachable!()
+    };
+    assert_eq!(text.layouted().len(), 1);
+    assert_eq!(text.layouted()[0].fonts().len(), 1);
+
+    // A generic family is allowed.
+    assert!(tree.node_by_id("text3").is_some());
+
+    let list: Vec<_> = diagnostics
+        .take()
+        .iter()
+        .map(|d| (d.feature().to_string(), d.element_id().to_string()))
+        .collect();
+    assert_eq!(
+        list,
+        [
+            ("\"Missing Font\"".to_string(), "text1".to_string()),
+            ("U+1F600".to_string(), "text2".to_string()),
+        ]
+    );
+
+    // A custom font selector is still used.
+    let selected = std::sync::atomic::AtomicBool::new(false);
+    let default_selector = usvg::FontResolver::default_font_selector();
+    let opt = usvg::Options {
+        fontdb: GLOBAL_FONTDB.clone(),
+        strict_fonts: true,
+        font_resolver: usvg::FontResolver {
+            select_font: Box::new(|font, fontdb| {
+                selected.store(true, std::sync::atomic::Ordering::Relaxed);
+                default_selector(font, fontdb)
+            }),
+            ..usvg::FontResolver::default()
+        },
+        ..usvg::Options::default()
+    };
+    let tree = usvg::Tree::from_str(&svg, &opt).unwrap();
+    assert!(tree.node_by_id("text2").is_some());
+    assert!(selected.load(std::sync::atomic::Ordering::Relaxed));
+}
+
+#[test]
+fn text_glyph_cache() {
+    let svg1 = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 200 200'>
+        <text x='10' y='50' font-family='Noto Sans'>Hello</text>
+    </svg>
+    ";
+    let svg2 = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 200 200'>
+        <text x='10' y='50' font-family='Noto Sans'>hell</text>
+    </svg>
+    ";
+
+    let cache = usvg::GlyphCache::new();
+    let opt = usvg::Options {
+        fontdb: GLOBAL_FONTDB.clone(),
+        glyph_cache: Some(&cache),
+        ..usvg::Options::default()
+    };
+
+    usvg::Tree::from_str(&svg1, &opt).unwrap();
+    assert_eq!(cache.len(), 4);
+
+    // Only the new glyph is outlined.
+    let tree = usvg::Tree::from_str(&svg2, &opt).unwrap();
+    assert_eq!(cache.len(), 5);
+
+    let opt = usvg::Options {
+        fontdb: GLOBAL_FONTDB.clone(),
+        ..usvg::Options::default()
+    };
+    let uncached_tree = usvg::Tree::from_str(&svg2, &opt).unwrap();
+    let write_opt = usvg::WriteOptions::default();
+    assert_eq!(
+        tree.to_string(&write_opt),
+        uncached_tree.to_string(&write_opt)
+    );
+
+    cache.clear();
+    assert!(cache.is_empty());
+}
+
+#[test]
+fn inherit_keyword() {
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 200 200'>
+        <style>.inherit { fill: inherit; stroke-width: inherit }</style>
+        <g fill='green' stroke='black' stroke-width='5'>
+            <g>
+                <rect id='rect1' width='10' height='10' fill='inherit' stroke-width='inherit'/>
+                <rect id='rect2' width='10' height='10' style='fill:inherit; stroke-width:inherit'/>
+                <rect id='rect3' width='10' height='10' class='inherit'/>
+            </g>
+        </g>
+    </svg>
+    ";
+
+    let tree = usvg::Tree::from_str(&svg, &usvg::Options::default()).unwrap();
+    for id in ["rect1", "rect2", "rect3"] {
+        let usvg::Node::Path(ref path) = tree.node_by_id(id).unwrap() else {
+            unreachable!()
+        };
+
+        assert_eq!(
+            path.fill().unwrap().paint(),
+            &usvg::Paint::Color(Color::new_rgb(0, 128, 0)),
+            "{}",
+            id
+        );
+        assert_eq!(path.stroke().unwrap().width().get(), 5.0, "{}", id);
+    }
+}
+
+#[test]
+fn text_inherit_keyword() {
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 200 200'>
+        <g font-family='Noto Serif' font-size='20'>
+            <g font-size='2em'>
+                <text id='text1' y='50' font-family='inherit' font-size='inherit'>Text</text>
+            </g>
+            <g font-size='50%'>
+                <text id='text2' y='50' style='font-family:inherit; font-size:inherit'>Text</text>
+            </g>
+        </g>
+    </svg>
+    ";
+
+    let opt = usvg::Options {
+        fontdb: GLOBAL_FONTDB.clone(),
+        ..usvg::Options::default()
+    };
+    let tree = usvg::Tree::from_str(&svg, &opt).unwrap();
+
+    let span = |id: &str| {
+        let usvg::Node::Text(ref text) = tree.node_by_id(id).unwrap() else {
+            unreachable!()
+        };
+
+        text.chunks()[0].spans()[0].clone()
+    };
+
+    let span1 = span("text1");
+    assert_eq!(
+        span1.font().families(),
+        &[usvg::FontFamily::Named("Noto Serif".to_string())]
+    );
+    // Relative units must be resolved only once.
+    assert_eq!(span1.font_size().get(), 40.0);
+
+    let span2 = span("text2");
+    assert_eq!(
+        span2.font().families(),
+        &[usvg::FontFamily::Named("Noto Serif".to_string())]
+    );
+    assert_eq!(span2.font_size().get(), 10.0);
+}
@@ -203,3 +203,57 @@ fn preserve_text_with_complex_text_decoration() {
 fn preserve_text_with_nested_baseline_shift() {
     resave_with_text("preserve-text-with-nested-baseline-shift");
 }
+
+#[test]
+fn fully_static_output() {
+    let input_svg = "
+    <svg viewBox='0 0 200 200' xmlns='http://www.w3.org/2000/svg'
+         xmlns:xlink='http://www.w3.org/1999/xlink'>
+        <style>.red { fill: red }</style>
+        <marker id='marker1' markerWidth='10' markerHeight='10'>
+            <circle cx='5' cy='5' r='5'/>
+        </marker>
+        <linearGradient id='lg1' x1='0' x2='1'>
+            <stop offset='0' stop-color='green'/>
+            <stop offset='1' stop-color='blue'/>
+        </linearGradient>
+        <symbol id='symbol1'>
+            <rect class='red' width='10' height='10'/>
+        </symbol>
+        <switch>
+            <text x='20' y='50' font-family='Noto Sans' font-size='24'>Text</text>
+        </switch>
+        <path d='M 20 100 L 100 100' stroke='url(#lg1)' marker-end='url(#marker1)'/>
+        <use xlink:href='#symbol1' x='150' y='150'/>
+        <svg x='150' y='20' width='20' height='20'>
+            <rect width='20' height='20' fill='url(#lg1)'/>
+        </svg>
+    </svg>
+    ";
+
+    let opt = usvg::Options {
+        fontdb: GLOBAL_FONTDB.clone(),
+        ..Default::default()
+    };
+    let tree = usvg::Tree::from_str(input_svg, &opt).unwrap();
+    let output_svg = tree.to_string(&usvg::WriteOptions::default());
+
+    for name in [
+        "<text",
+        "<marker",
+        "<use",
+        "<symbol",
+        "<switch",
+        "<style",
+        "class=",
+        "objectBoundingBox",
+    ] {
+        assert!(!output_svg.contains(name), "{}", name);
+    }
+    assert_eq!(output_svg.matches("<svg").count(), 1);
+
+    // Nothing else is needed to render the output, therefore it can be parsed
+    // without fonts and would not change anymore.
+    let tree2 = usvg::Tree::from_str(&output_svg, &usvg::Options::default()).unwrap();
+    assert_eq!(tree2.to_string(&usvg::WriteOptions::default()), output_svg);
+}